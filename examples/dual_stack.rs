@@ -0,0 +1,41 @@
+//! Serves ``/ping`` over UDP and TCP on one port, accepting both IPv4 and IPv6 clients by
+//! binding ``[::]`` dual-stack, e.g. ``cargo run --example dual_stack --features socket2 9000``.
+//! Try it with ``oscsend 127.0.0.1:9000 /ping`` and ``oscsend [::1]:9000 /ping``.
+
+use std::{env, thread};
+
+use osc::{
+    errors::Error,
+    server::{OscServer, TcpOscServer},
+    socket_builder::ConnectionBuilder,
+    Arg,
+};
+
+/// The largest packet the servers can receive, the most a UDP datagram can hold.
+const MAX_PACKET_SIZE: usize = 65536;
+
+fn main() -> Result<(), Error> {
+    let port: u16 = env::args()
+        .nth(1)
+        .and_then(|port| port.parse().ok())
+        .unwrap_or(9000);
+    let builder = ConnectionBuilder::new().with_only_v6(false);
+
+    let mut udp = OscServer::from_socket(
+        builder.udp_listener(("::", port)).map_err(Error::Socket)?,
+        MAX_PACKET_SIZE,
+    )
+    .add_route("/ping", |_, _| Some(vec![Arg::Str("pong".into())]))?;
+    let tcp = TcpOscServer::from_listener(
+        builder.tcp_listener(("::", port)).map_err(Error::Socket)?,
+        MAX_PACKET_SIZE,
+    )
+    .add_route("/ping", |_, _| Some(vec![Arg::Str("pong".into())]))?;
+    println!("Listening on {} over UDP and TCP", udp.local_addr()?);
+
+    thread::scope(|scope| {
+        let tcp = scope.spawn(|| tcp.start());
+        udp.start()?;
+        tcp.join().expect("TCP server panicked")
+    })
+}
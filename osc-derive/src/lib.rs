@@ -0,0 +1,106 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Fields, LitStr};
+
+/// Implements ``osc::convert::ToOsc`` for a struct, sending it to the address given by
+/// ``#[osc(address = "...")]`` with each field as an argument, in order. Each field's type must
+/// be ``Clone`` and convert into an ``osc::Arg``.
+#[proc_macro_derive(ToOsc, attributes(osc))]
+pub fn derive_to_osc(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    to_osc(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Implements ``osc::convert::FromOsc`` for a struct, reading each field from the argument at
+/// its position. Each field's type must implement ``TryFrom<osc::Arg, Error = osc::errors::Error>``.
+#[proc_macro_derive(FromOsc, attributes(osc))]
+pub fn derive_from_osc(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    from_osc(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn to_osc(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let Some(address) = address(input)? else {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "ToOsc needs an #[osc(address = \"...\")] attribute",
+        ));
+    };
+    let fields = fields(input)?;
+    let args = fields
+        .members()
+        .map(|member| quote!(::core::convert::Into::<::osc::Arg>::into(::core::clone::Clone::clone(&self.#member))));
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::osc::convert::ToOsc for #name #ty_generics #where_clause {
+            fn to_osc(&self) -> ::osc::OscMessage {
+                let args: ::osc::Args = ::core::iter::IntoIterator::into_iter([#(#args),*]).collect();
+                ::osc::OscMessage::new(#address, args)
+            }
+        }
+    })
+}
+
+fn from_osc(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    // The address is only used by ``ToOsc``, but check the attribute is well formed anyway
+    address(input)?;
+    let fields = fields(input)?;
+    let count = fields.len();
+    let values = (0..count).map(|index| quote!(::osc::OscMessage::arg(message, #index)?));
+    let body = match fields {
+        Fields::Named(struct_fields) => {
+            let names = struct_fields.named.iter().map(|field| &field.ident);
+            quote!(Self { #(#names: #values),* })
+        }
+        Fields::Unnamed(_) => quote!(Self(#(#values),*)),
+        Fields::Unit => quote!(Self),
+    };
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::osc::convert::FromOsc for #name #ty_generics #where_clause {
+            fn from_osc(
+                message: &::osc::OscMessage,
+            ) -> ::core::result::Result<Self, ::osc::errors::Error> {
+                ::osc::convert::expect_args(message, #count)?;
+                ::core::result::Result::Ok(#body)
+            }
+        }
+    })
+}
+
+/// Returns the address from the ``#[osc(address = "...")]`` attribute, if there is one.
+fn address(input: &DeriveInput) -> syn::Result<Option<LitStr>> {
+    let mut address = None;
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("osc"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("address") {
+                address = Some(meta.value()?.parse()?);
+                Ok(())
+            } else {
+                Err(meta.error("expected `address`"))
+            }
+        })?;
+    }
+    Ok(address)
+}
+
+fn fields(input: &DeriveInput) -> syn::Result<&Fields> {
+    match &input.data {
+        Data::Struct(data) => Ok(&data.fields),
+        _ => Err(syn::Error::new_spanned(
+            &input.ident,
+            "OSC messages can only be derived for structs",
+        )),
+    }
+}
@@ -0,0 +1,124 @@
+use std::{fmt::Display, net::IpAddr, str::FromStr};
+
+use crate::errors::Error;
+
+/// A range of IP addresses in CIDR notation, such as ``192.168.1.0/24``. A single address is a
+/// range with the full prefix length.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct IpRange {
+    address: IpAddr,
+    prefix: u8,
+}
+
+impl IpRange {
+    /// Creates the range of addresses sharing the first ``prefix`` bits of ``address``.
+    ///
+    /// # Errors
+    /// Will return ``Error::Malformed`` if ``prefix`` is longer than ``address`` (32 bits for IPv4,
+    /// 128 for IPv6).
+    pub fn new(address: IpAddr, prefix: u8) -> Result<Self, Error> {
+        if prefix > max_prefix(address) {
+            return Err(Error::Malformed(format!("{address}/{prefix}")));
+        }
+        Ok(Self { address, prefix })
+    }
+
+    /// Returns whether ``ip`` is within the range. IPv4 and IPv6 ranges never contain addresses of
+    /// the other version, except that IPv4-mapped IPv6 addresses are treated as IPv4.
+    #[must_use]
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        let ip = ip.to_canonical();
+        match (self.address, ip) {
+            (IpAddr::V4(range), IpAddr::V4(ip)) => {
+                let mask = u32::MAX
+                    .checked_shl(32 - u32::from(self.prefix))
+                    .unwrap_or(0);
+                u32::from(range) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(range), IpAddr::V6(ip)) => {
+                let mask = u128::MAX
+                    .checked_shl(128 - u32::from(self.prefix))
+                    .unwrap_or(0);
+                u128::from(range) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+fn max_prefix(address: IpAddr) -> u8 {
+    match address {
+        IpAddr::V4(_) => 32,
+        IpAddr::V6(_) => 128,
+    }
+}
+
+impl From<IpAddr> for IpRange {
+    fn from(value: IpAddr) -> Self {
+        Self {
+            address: value,
+            prefix: max_prefix(value),
+        }
+    }
+}
+
+impl FromStr for IpRange {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let malformed = || Error::Malformed(format!("IP range {s:?}"));
+        match s.split_once('/') {
+            Some((address, prefix)) => Self::new(
+                address.parse().map_err(|_| malformed())?,
+                prefix.parse().map_err(|_| malformed())?,
+            ),
+            None => Ok(Self::from(s.parse::<IpAddr>().map_err(|_| malformed())?)),
+        }
+    }
+}
+
+impl Display for IpRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("{}/{}", self.address, self.prefix))
+    }
+}
+
+/// Decides which source addresses a server accepts packets from.
+///
+/// An address is denied if it is in any ``deny`` range. Otherwise, it is allowed if there are no
+/// ``allow`` ranges, or it is in one of them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AccessList {
+    allow: Vec<IpRange>,
+    deny: Vec<IpRange>,
+}
+
+impl AccessList {
+    /// Creates an ``AccessList`` which allows every address.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allows ``range``. Once any range is allowed, addresses outside the allowed ranges are
+    /// denied.
+    #[must_use]
+    pub fn allow(mut self, range: impl Into<IpRange>) -> Self {
+        self.allow.push(range.into());
+        self
+    }
+
+    /// Denies ``range``, even if it is also allowed.
+    #[must_use]
+    pub fn deny(mut self, range: impl Into<IpRange>) -> Self {
+        self.deny.push(range.into());
+        self
+    }
+
+    /// Returns whether packets from ``ip`` are accepted.
+    #[must_use]
+    pub fn permits(&self, ip: IpAddr) -> bool {
+        !self.deny.iter().any(|range| range.contains(ip))
+            && (self.allow.is_empty() || self.allow.iter().any(|range| range.contains(ip)))
+    }
+}
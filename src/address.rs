@@ -0,0 +1,202 @@
+use std::{fmt::Display, str::FromStr};
+
+use crate::errors::Error;
+
+/// Characters which may not appear in an OSC address, as they have special meaning in address
+/// patterns (or, for ``/``, separate the parts of the address).
+pub const FORBIDDEN_CHARACTERS: [char; 10] = [' ', '#', '*', ',', '/', '?', '[', ']', '{', '}'];
+
+/// A validated OSC address, such as ``/ch/01/mix``: a ``/`` followed by non-empty parts separated
+/// by ``/``, none of which contain any of the ``FORBIDDEN_CHARACTERS``.
+///
+/// Messages keep their address as a plain ``String``, since a received message's address is a
+/// pattern which may use the wildcard characters forbidden here.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct OscAddress(String);
+
+impl OscAddress {
+    /// Creates an ``OscAddress``, checking that ``address`` is valid.
+    ///
+    /// # Errors
+    /// Will return ``Error::Address`` if ``address`` does not start with ``/``, has an empty part,
+    /// or contains a forbidden character.
+    pub fn new(address: impl Into<String>) -> Result<Self, Error> {
+        let address = address.into();
+        let Some(parts) = address.strip_prefix('/') else {
+            return Err(Error::Address(format!(
+                "{address:?} does not start with '/'"
+            )));
+        };
+        for part in parts.split('/') {
+            check_segment(&address, part)?;
+        }
+        Ok(Self(address))
+    }
+
+    /// Creates an ``OscAddress`` from its parts, e.g. ``["ch", "01", "mix"]`` for ``/ch/01/mix``.
+    ///
+    /// # Errors
+    /// Will return ``Error::Address`` if there are no parts, or a part is empty or contains a
+    /// forbidden character (including ``/``).
+    pub fn from_segments<I, S>(segments: I) -> Result<Self, Error>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut address = String::new();
+        for segment in segments {
+            address.push('/');
+            address.push_str(segment.as_ref());
+        }
+        if address.is_empty() {
+            return Err(Error::Address(
+                "An address needs at least one part".to_string(),
+            ));
+        }
+        Self::new(address)
+    }
+
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns the ``/``-separated parts of the address.
+    pub fn segments(&self) -> impl Iterator<Item = &str> {
+        self.0[1..].split('/')
+    }
+
+    /// Creates an ``OscAddress`` without checking it, for ``osc_addr!``, which already has.
+    #[doc(hidden)]
+    #[must_use]
+    pub fn new_unchecked(address: &str) -> Self {
+        Self(address.to_string())
+    }
+}
+
+/// Creates an ``OscAddress`` from a constant string, checking it at compile time, so e.g.
+/// ``osc_addr!("/ch/01/fader")`` can't fail at runtime, while ``osc_addr!("/ch/01 fader")`` fails
+/// to compile. The address may be any constant ``&str`` expression, such as a ``const`` or
+/// ``concat!``.
+#[macro_export]
+macro_rules! osc_addr {
+    ($address:expr) => {{
+        const ADDRESS: &str = $address;
+        const _: () = $crate::address::assert_valid(ADDRESS);
+        $crate::address::OscAddress::new_unchecked(ADDRESS)
+    }};
+}
+
+/// Panics unless ``address`` is a valid ``OscAddress``, so ``osc_addr!`` can check addresses at
+/// compile time. Every forbidden character is ASCII, so checking bytes is enough.
+#[doc(hidden)]
+pub const fn assert_valid(address: &str) {
+    let bytes = address.as_bytes();
+    assert!(
+        !bytes.is_empty() && bytes[0] == b'/',
+        "OSC address does not start with '/'"
+    );
+    let mut part_length = 0;
+    let mut i = 1;
+    while i < bytes.len() {
+        if bytes[i] == b'/' {
+            assert!(part_length > 0, "OSC address has an empty part");
+            part_length = 0;
+        } else {
+            assert!(
+                !matches!(
+                    bytes[i],
+                    b' ' | b'#' | b'*' | b',' | b'?' | b'[' | b']' | b'{' | b'}'
+                ),
+                "OSC address contains a forbidden character"
+            );
+            part_length += 1;
+        }
+        i += 1;
+    }
+    assert!(part_length > 0, "OSC address has an empty part");
+}
+
+/// Checks that ``address`` is a valid ``OscAddress``, describing every problem found rather than
+/// only the first, with the byte position of each forbidden character.
+pub(crate) fn check_address(address: &str) -> Result<(), Error> {
+    let mut problems = Vec::new();
+    if !address.starts_with('/') {
+        problems.push("does not start with '/'".to_string());
+    }
+    if address.split('/').skip(1).any(str::is_empty) {
+        problems.push("has an empty part".to_string());
+    }
+    let forbidden: Vec<_> = address
+        .char_indices()
+        .filter(|(_, c)| *c != '/' && FORBIDDEN_CHARACTERS.contains(c))
+        .map(|(i, c)| format!("{c:?} at {i}"))
+        .collect();
+    if !forbidden.is_empty() {
+        problems.push(format!(
+            "contains forbidden characters {}",
+            forbidden.join(", ")
+        ));
+    }
+    if problems.is_empty() {
+        return Ok(());
+    }
+    Err(Error::Address(format!(
+        "{address:?} {}",
+        problems.join("; ")
+    )))
+}
+
+fn check_segment(address: &str, segment: &str) -> Result<(), Error> {
+    if segment.is_empty() {
+        return Err(Error::Address(format!("{address:?} has an empty part")));
+    }
+    if let Some(c) = segment.chars().find(|c| FORBIDDEN_CHARACTERS.contains(c)) {
+        return Err(Error::Address(format!(
+            "{address:?} contains forbidden character {c:?}"
+        )));
+    }
+    Ok(())
+}
+
+impl Display for OscAddress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl AsRef<str> for OscAddress {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromStr for OscAddress {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(s)
+    }
+}
+
+impl TryFrom<&str> for OscAddress {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Self::new(value)
+    }
+}
+
+impl TryFrom<String> for OscAddress {
+    type Error = Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::new(value)
+    }
+}
+
+impl From<OscAddress> for String {
+    fn from(value: OscAddress) -> Self {
+        value.0
+    }
+}
@@ -0,0 +1,148 @@
+use std::{
+    collections::VecDeque,
+    io::ErrorKind,
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
+
+use crate::{async_sockets::AsyncConnection, errors::Error, OscMessage};
+
+/// An OSC client whose I/O can be awaited inside an async application rather than blocking a
+/// thread, e.g. ``AsyncOscClient<tokio::net::UdpSocket>``, or
+/// ``AsyncOscClient<AsyncFramedTcpStream<smol::net::TcpStream>>`` for TCP. It behaves like
+/// ``OscClient``, but its timeout only applies to ``wait_for``.
+#[allow(clippy::module_name_repetitions)]
+pub struct AsyncOscClient<C: AsyncConnection> {
+    connection: C,
+    message_queue: VecDeque<OscMessage>,
+    timeout: Duration,
+    buffer: Vec<u8>,
+}
+
+impl<C: AsyncConnection> AsyncOscClient<C> {
+    /// Creates a new ``AsyncOscClient``, listening at ``client_address``, and connected to
+    /// ``remote_address``. ``buffer_size`` dictates the maximum size message that the client can
+    /// receive, and ``timeout`` how long ``wait_for`` waits (1 second if ``None``).
+    ///
+    /// # Errors
+    /// If the connection cannot be made, this function will return an ``Error::Socket``.
+    pub async fn new(
+        client_address: SocketAddr,
+        remote_address: SocketAddr,
+        buffer_size: usize,
+        timeout: Option<Duration>,
+    ) -> Result<Self, Error> {
+        let connection = C::new(client_address, remote_address)
+            .await
+            .map_err(Error::Socket)?;
+        Ok(Self::from_connection(connection, buffer_size, timeout))
+    }
+
+    /// Creates a new ``AsyncOscClient`` over an existing ``connection``. Other arguments are as
+    /// for ``new``.
+    pub fn from_connection(connection: C, buffer_size: usize, timeout: Option<Duration>) -> Self {
+        Self {
+            connection,
+            message_queue: VecDeque::new(),
+            timeout: timeout.unwrap_or(Duration::from_secs(1)),
+            buffer: vec![0; buffer_size],
+        }
+    }
+
+    /// Sends ``message`` over the client's connection.
+    ///
+    /// # Errors
+    /// Will return ``Err`` if ``message.build`` fails, or an ``Error::Socket`` if the connection
+    /// fails to send ``message``.
+    pub async fn send(&mut self, message: &OscMessage) -> Result<usize, Error> {
+        self.send_bytes(&message.build()?).await
+    }
+
+    /// Sends raw bytes, e.g. to a device which doesn't understand standard OSC.
+    ///
+    /// # Errors
+    /// Will return an ``Error::Socket`` if sending the data fails.
+    pub async fn send_bytes(&mut self, bytes: &[u8]) -> Result<usize, Error> {
+        self.connection.send(bytes).await.map_err(Error::Socket)
+    }
+
+    /// Waits to receive a message, and parses it into an ``OscMessage``.
+    ///
+    /// # Errors
+    /// Will return an ``Error::Socket`` if receiving fails. Will also error if
+    /// ``OscMessage::parse_bytes`` fails.
+    pub async fn recv(&mut self) -> Result<OscMessage, Error> {
+        let length = self
+            .connection
+            .recv(&mut self.buffer)
+            .await
+            .map_err(Error::Socket)?;
+        OscMessage::parse_bytes(&self.buffer[..length])
+    }
+
+    /// Waits to receive a message meant for ``addr``, for up to the client's timeout.
+    ///
+    /// # Errors
+    /// See ``wait_for_until``.
+    #[allow(clippy::needless_pass_by_value)]
+    pub async fn wait_for(&mut self, addr: impl ToString) -> Result<OscMessage, Error> {
+        let deadline = Instant::now() + self.timeout;
+        self.wait_for_until(addr, deadline).await
+    }
+
+    /// Waits to receive a message meant for ``addr``, for up to ``timeout`` instead of the
+    /// client's timeout.
+    ///
+    /// # Errors
+    /// See ``wait_for_until``.
+    #[allow(clippy::needless_pass_by_value)]
+    pub async fn wait_for_timeout(
+        &mut self,
+        addr: impl ToString,
+        timeout: Duration,
+    ) -> Result<OscMessage, Error> {
+        self.wait_for_until(addr, Instant::now() + timeout).await
+    }
+
+    /// Waits to receive a message meant for ``addr``, giving up once ``deadline`` has passed.
+    /// Messages for other addresses received in the meantime are queued for later calls.
+    ///
+    /// # Errors
+    /// Will return ``Err(Error::Socket(io::Error.kind() == ErrorKind::TimedOut))`` if ``deadline``
+    /// passes before a matching message arrives, or any other error from ``recv``.
+    #[allow(clippy::needless_pass_by_value)]
+    pub async fn wait_for_until(
+        &mut self,
+        addr: impl ToString,
+        deadline: Instant,
+    ) -> Result<OscMessage, Error> {
+        let addr = addr.to_string();
+        if let Some(i) = self.message_queue.iter().position(|m| m.address == addr) {
+            if let Some(msg) = self.message_queue.remove(i) {
+                return Ok(msg);
+            }
+        }
+
+        let start = Instant::now();
+        loop {
+            let length = match self.connection.recv_until(&mut self.buffer, deadline).await {
+                Ok(length) => length,
+                Err(e) if e.kind() == ErrorKind::TimedOut => {
+                    return Err(Error::Socket(std::io::Error::new(
+                        ErrorKind::TimedOut,
+                        format!(
+                            "Waiting for data timed out after {} seconds",
+                            start.elapsed().as_secs_f32()
+                        ),
+                    )))
+                }
+                Err(e) => return Err(Error::Socket(e)),
+            };
+            let msg = OscMessage::parse_bytes(&self.buffer[..length])?;
+            if msg.address == addr {
+                return Ok(msg);
+            }
+            self.message_queue.push_back(msg);
+        }
+    }
+}
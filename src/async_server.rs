@@ -0,0 +1,170 @@
+use std::{future::Future, net::SocketAddr, pin::Pin, sync::Arc};
+
+use tokio::net::{ToSocketAddrs, UdpSocket};
+
+use crate::{
+    address::OscAddress,
+    errors::Error,
+    handler::IntoReplies,
+    pattern::OscPattern,
+    router::Router,
+    schedule::Schedule,
+    server::{default_error_reply, ErrorReply},
+    OscMessage, OscPacket,
+};
+
+type BoxFuture = Pin<Box<dyn Future<Output = Result<Vec<OscMessage>, Error>> + Send>>;
+
+/// Handles a message sent to a route like ``Handler``, but asynchronously. It is given its own
+/// copies of the matched address and the message, so the future it returns can outlive the call.
+pub type AsyncHandler = Box<dyn Fn(OscAddress, OscMessage) -> BoxFuture + Send + Sync>;
+
+/// An OSC server over a ``tokio`` UDP socket, for use inside an async application.
+///
+/// Routes behave as they do for ``OscServer``, but each packet is dispatched on its own task, so
+/// a slow handler doesn't hold up the others.
+#[allow(clippy::module_name_repetitions)]
+pub struct AsyncOscServer {
+    socket: UdpSocket,
+    routes: Routes,
+    buffer: Vec<u8>,
+}
+
+struct Routes {
+    router: Router<AsyncHandler>,
+    error_reply: ErrorReply,
+}
+
+impl AsyncOscServer {
+    /// Creates a new ``AsyncOscServer``, listening at ``address``. ``buffer_size`` dictates the
+    /// maximum size packet that the server can receive.
+    ///
+    /// # Errors
+    /// If the socket cannot be bound, this function will return an ``Error::Socket``.
+    pub async fn new<A: ToSocketAddrs>(address: A, buffer_size: usize) -> Result<Self, Error> {
+        Ok(Self {
+            socket: UdpSocket::bind(address).await.map_err(Error::Socket)?,
+            routes: Routes {
+                router: Router::new(),
+                error_reply: Box::new(|address, error| Some(default_error_reply(address, error))),
+            },
+            buffer: vec![0; buffer_size],
+        })
+    }
+
+    /// Registers the async ``handler`` for messages sent to any address matching ``pattern``.
+    /// Its output can be any ``IntoReplies``, as for ``OscServer::add_route``.
+    ///
+    /// # Errors
+    /// Will return ``Error::Address`` if ``pattern`` is not a valid ``OscPattern``, or
+    /// ``Error::DuplicateRoute`` if a route is already registered for it.
+    pub fn add_route<P, F, Fut, R>(mut self, pattern: P, handler: F) -> Result<Self, Error>
+    where
+        P: TryInto<OscPattern>,
+        Error: From<P::Error>,
+        F: Fn(OscAddress, OscMessage) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = R> + Send + 'static,
+        R: IntoReplies,
+    {
+        let handler = move |address: OscAddress, msg: OscMessage| -> BoxFuture {
+            let replies = handler(address.clone(), msg);
+            Box::pin(async move { replies.await.into_replies(&address) })
+        };
+        self.routes
+            .router
+            .insert_route(pattern, Box::new(handler))?;
+        Ok(self)
+    }
+
+    /// See ``OscServer::with_error_reply``.
+    #[must_use]
+    pub fn with_error_reply<F>(mut self, error_reply: F) -> Self
+    where
+        F: Fn(&OscAddress, &Error) -> Option<OscMessage> + Send + Sync + 'static,
+    {
+        self.routes.error_reply = Box::new(error_reply);
+        self
+    }
+
+    /// Returns the address the server is listening at.
+    ///
+    /// # Errors
+    /// Will return an ``Error::Socket`` if the address cannot be read from the socket.
+    pub fn local_addr(&self) -> Result<SocketAddr, Error> {
+        self.socket.local_addr().map_err(Error::Socket)
+    }
+
+    /// Receives packets forever, spawning a task on the current ``tokio`` runtime to dispatch
+    /// each one. Packets that fail to parse are ignored, as are replies which cannot be sent.
+    /// Bundles timetagged in the future are held as ``OscServer::start`` describes, which needs the
+    /// runtime's time driver enabled.
+    ///
+    /// # Errors
+    /// Will return an ``Error::Socket`` if receiving a packet fails.
+    pub async fn start(self) -> Result<(), Error> {
+        let socket = Arc::new(self.socket);
+        let routes = Arc::new(self.routes);
+        let mut buffer = self.buffer;
+        loop {
+            let (length, source) = socket.recv_from(&mut buffer).await.map_err(Error::Socket)?;
+            let Ok(packet) = OscPacket::parse_bytes(&buffer[..length]) else {
+                continue;
+            };
+            let socket = Arc::clone(&socket);
+            let routes = Arc::clone(&routes);
+            tokio::spawn(async move {
+                // Bundles timetagged in the future are held by this task until they are due
+                let mut schedule = Schedule::new();
+                let mut due: Vec<_> = schedule.defer(packet, &(), &[]).into_iter().collect();
+                loop {
+                    for packet in due {
+                        for reply in routes.replies(packet).await {
+                            if let Ok(reply) = reply.build() {
+                                let _ = socket.send_to(&reply, source).await;
+                            }
+                        }
+                    }
+                    let Some(wait) = schedule.until_next() else {
+                        break;
+                    };
+                    tokio::time::sleep(wait).await;
+                    due = schedule
+                        .take_due(&[])
+                        .into_iter()
+                        .map(|(packet, ())| packet)
+                        .collect();
+                }
+            });
+        }
+    }
+}
+
+impl Routes {
+    /// Dispatches the messages in ``packet`` in order, returning the handlers' replies.
+    async fn replies(&self, packet: OscPacket) -> Vec<OscMessage> {
+        let mut messages = Vec::new();
+        let mut packets = vec![packet];
+        while let Some(packet) = packets.pop() {
+            match packet {
+                OscPacket::Message(msg) => messages.push(msg),
+                OscPacket::Bundle(bundle) => packets.extend(bundle.content.into_iter().rev()),
+            }
+        }
+
+        let mut replies = Vec::new();
+        for msg in messages {
+            let pending: Vec<_> = self
+                .router
+                .matching(&msg.address)
+                .map(|(address, handler)| (address.clone(), handler(address, msg.clone())))
+                .collect();
+            for (address, handler_replies) in pending {
+                match handler_replies.await {
+                    Ok(mut handler_replies) => replies.append(&mut handler_replies),
+                    Err(e) => replies.extend((self.error_reply)(&address, &e)),
+                }
+            }
+        }
+        replies
+    }
+}
@@ -0,0 +1,271 @@
+use std::{future::Future, net::SocketAddr, time::Instant};
+
+#[cfg(any(feature = "async-std", feature = "smol"))]
+use crate::sockets::{copy_frame, frame, take_frame};
+
+/// An async version of ``Connection``, so ``AsyncOscClient`` can run on any async runtime.
+/// Implementations are provided for the UDP sockets of ``tokio``, ``async-std`` and ``smol``, and
+/// for their TCP streams wrapped in ``AsyncFramedTcpStream``, each behind the feature of the same
+/// name.
+pub trait AsyncConnection
+where
+    Self: Sized,
+{
+    /// Creates a new ``impl AsyncConnection`` from ``local_address`` to ``remote_address``.
+    /// Connections with no local address of their own, like TCP streams, ignore it.
+    ///
+    /// # Errors
+    /// If creating the new ``impl AsyncConnection`` fails, return Err
+    fn new(
+        local_address: SocketAddr,
+        remote_address: SocketAddr,
+    ) -> impl Future<Output = std::io::Result<Self>> + Send;
+    /// Sends ``buf`` over the ``impl AsyncConnection``, returning the size of the data sent.
+    ///
+    /// # Errors
+    /// If sending data fails, return Err
+    fn send(&mut self, buf: &[u8]) -> impl Future<Output = std::io::Result<usize>> + Send;
+    /// Waits to receive data into ``buf`` over the ``impl AsyncConnection``, returning the size of
+    /// the data received.
+    ///
+    /// # Errors
+    /// If receiving data fails, return Err
+    fn recv(&mut self, buf: &mut [u8]) -> impl Future<Output = std::io::Result<usize>> + Send;
+    /// Like ``recv``, but gives up once ``deadline`` has passed, using the runtime's timer.
+    ///
+    /// # Errors
+    /// If ``deadline`` passes first, return ``Err(io::Error.kind() == ErrorKind::TimedOut)``.
+    /// If receiving data fails, return Err
+    fn recv_until(
+        &mut self,
+        buf: &mut [u8],
+        deadline: Instant,
+    ) -> impl Future<Output = std::io::Result<usize>> + Send;
+}
+
+/// An async TCP stream using OSC 1.0 stream framing, like ``FramedTcpStream``, so each ``recv``
+/// yields exactly one packet. ``S`` is the runtime's ``TcpStream``.
+#[cfg(any(feature = "tokio", feature = "async-std", feature = "smol"))]
+pub struct AsyncFramedTcpStream<S> {
+    stream: S,
+    pending: Vec<u8>,
+}
+
+#[cfg(any(feature = "tokio", feature = "async-std", feature = "smol"))]
+impl<S> AsyncFramedTcpStream<S> {
+    /// Wraps an already connected stream.
+    #[must_use]
+    pub fn from_stream(stream: S) -> Self {
+        Self {
+            stream,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Returns the underlying stream, discarding any partially received packet.
+    #[must_use]
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+}
+
+#[cfg(any(feature = "tokio", feature = "async-std", feature = "smol"))]
+fn timed_out() -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::TimedOut, "Receiving timed out")
+}
+
+#[cfg(any(feature = "tokio", feature = "async-std", feature = "smol"))]
+fn closed() -> std::io::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::UnexpectedEof,
+        "Connection closed by peer",
+    )
+}
+
+#[cfg(feature = "tokio")]
+mod tokio_impl {
+    use std::{net::SocketAddr, time::Instant};
+
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::{TcpStream, UdpSocket},
+    };
+
+    use super::{closed, timed_out, AsyncConnection, AsyncFramedTcpStream};
+    use crate::sockets::{copy_frame, frame, take_frame};
+
+    impl AsyncConnection for UdpSocket {
+        async fn new(
+            local_address: SocketAddr,
+            remote_address: SocketAddr,
+        ) -> std::io::Result<Self> {
+            let socket = UdpSocket::bind(local_address).await?;
+            socket.connect(remote_address).await?;
+            Ok(socket)
+        }
+
+        async fn send(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            UdpSocket::send(self, buf).await
+        }
+
+        async fn recv(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            UdpSocket::recv(self, buf).await
+        }
+
+        async fn recv_until(
+            &mut self,
+            buf: &mut [u8],
+            deadline: Instant,
+        ) -> std::io::Result<usize> {
+            tokio::time::timeout_at(deadline.into(), UdpSocket::recv(self, buf))
+                .await
+                .unwrap_or_else(|_| Err(timed_out()))
+        }
+    }
+
+    impl AsyncConnection for AsyncFramedTcpStream<TcpStream> {
+        async fn new(_: SocketAddr, remote_address: SocketAddr) -> std::io::Result<Self> {
+            TcpStream::connect(remote_address)
+                .await
+                .map(Self::from_stream)
+        }
+
+        async fn send(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.stream.write_all(&frame(buf)?).await?;
+            Ok(buf.len())
+        }
+
+        async fn recv(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let mut chunk = [0; 4096];
+            loop {
+                if let Some(frame) = take_frame(&mut self.pending, buf.len())? {
+                    return copy_frame(&frame, buf);
+                }
+                match self.stream.read(&mut chunk).await? {
+                    0 => return Err(closed()),
+                    n => self.pending.extend_from_slice(&chunk[..n]),
+                }
+            }
+        }
+
+        async fn recv_until(
+            &mut self,
+            buf: &mut [u8],
+            deadline: Instant,
+        ) -> std::io::Result<usize> {
+            // Partially received packets stay in ``pending``, so giving up partway loses nothing
+            tokio::time::timeout_at(deadline.into(), self.recv(buf))
+                .await
+                .unwrap_or_else(|_| Err(timed_out()))
+        }
+    }
+}
+
+/// Implements ``AsyncConnection`` for a runtime whose sockets use the ``futures`` I/O traits.
+/// ``$timeout`` gives up on a future at a deadline, using the runtime's timer.
+#[cfg(any(feature = "async-std", feature = "smol"))]
+macro_rules! futures_io_impl {
+    ($udp:ty, $tcp:ty, $read:path, $write:path, $timeout:path) => {
+        impl AsyncConnection for $udp {
+            async fn new(
+                local_address: SocketAddr,
+                remote_address: SocketAddr,
+            ) -> std::io::Result<Self> {
+                let socket = <$udp>::bind(local_address).await?;
+                socket.connect(remote_address).await?;
+                Ok(socket)
+            }
+
+            async fn send(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                <$udp>::send(self, buf).await
+            }
+
+            async fn recv(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                <$udp>::recv(self, buf).await
+            }
+
+            async fn recv_until(
+                &mut self,
+                buf: &mut [u8],
+                deadline: Instant,
+            ) -> std::io::Result<usize> {
+                $timeout(deadline, <$udp>::recv(self, buf)).await
+            }
+        }
+
+        impl AsyncConnection for AsyncFramedTcpStream<$tcp> {
+            async fn new(_: SocketAddr, remote_address: SocketAddr) -> std::io::Result<Self> {
+                <$tcp>::connect(remote_address).await.map(Self::from_stream)
+            }
+
+            async fn send(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                use $write as _;
+                self.stream.write_all(&frame(buf)?).await?;
+                Ok(buf.len())
+            }
+
+            async fn recv(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                use $read as _;
+                let mut chunk = [0; 4096];
+                loop {
+                    if let Some(frame) = take_frame(&mut self.pending, buf.len())? {
+                        return copy_frame(&frame, buf);
+                    }
+                    match self.stream.read(&mut chunk).await? {
+                        0 => return Err(closed()),
+                        n => self.pending.extend_from_slice(&chunk[..n]),
+                    }
+                }
+            }
+
+            async fn recv_until(
+                &mut self,
+                buf: &mut [u8],
+                deadline: Instant,
+            ) -> std::io::Result<usize> {
+                $timeout(deadline, self.recv(buf)).await
+            }
+        }
+    };
+}
+
+#[cfg(feature = "async-std")]
+async fn async_std_timeout(
+    deadline: Instant,
+    future: impl Future<Output = std::io::Result<usize>>,
+) -> std::io::Result<usize> {
+    let remaining = deadline.saturating_duration_since(Instant::now());
+    async_std::future::timeout(remaining, future)
+        .await
+        .unwrap_or_else(|_| Err(timed_out()))
+}
+
+#[cfg(feature = "async-std")]
+futures_io_impl!(
+    async_std::net::UdpSocket,
+    async_std::net::TcpStream,
+    async_std::io::ReadExt,
+    async_std::io::WriteExt,
+    async_std_timeout
+);
+
+#[cfg(feature = "smol")]
+async fn smol_timeout(
+    deadline: Instant,
+    future: impl Future<Output = std::io::Result<usize>>,
+) -> std::io::Result<usize> {
+    smol::future::or(future, async move {
+        smol::Timer::at(deadline).await;
+        Err(timed_out())
+    })
+    .await
+}
+
+#[cfg(feature = "smol")]
+futures_io_impl!(
+    smol::net::UdpSocket,
+    smol::net::TcpStream,
+    smol::io::AsyncReadExt,
+    smol::io::AsyncWriteExt,
+    smol_timeout
+);
@@ -0,0 +1,101 @@
+use std::time::{Duration, Instant};
+
+use crate::{session::Session, Arg, OscMessage};
+
+/// Where senders send the secret to authenticate.
+pub const AUTH_ADDRESS: &str = "/auth";
+/// Where a sender which hasn't authenticated is told to, if enabled.
+pub const AUTH_REQUIRED_ADDRESS: &str = "/auth/required";
+/// Where a sender which has just authenticated is told so.
+pub const AUTH_OK_ADDRESS: &str = "/auth/ok";
+
+/// Requires senders to authenticate with a shared secret before their messages are handled, e.g.
+/// so only the show's own control surfaces can drive a server on a venue network. Give it to a
+/// server with ``OscServer::with_auth``.
+///
+/// A sender authenticates by sending the secret as an ``Arg::Str`` to ``/auth``, which replies
+/// on ``/auth/ok``. Until then, its messages are dropped, and answered with ``/auth/required`` if
+/// enabled with ``with_required_reply``. Whether a sender has authenticated is kept in its
+/// ``Session``, so it lasts until the expiry given to ``with_expiry``, or the session ends.
+///
+/// The secret is sent in the clear, so this keeps out misconfigured and casual senders rather
+/// than attackers who can see the traffic.
+#[derive(Debug, Clone)]
+pub struct SharedSecretAuth {
+    secret: String,
+    expiry: Option<Duration>,
+    required_reply: bool,
+}
+
+/// Stored in a sender's ``Session`` once it has authenticated.
+struct Authenticated {
+    until: Option<Instant>,
+}
+
+impl SharedSecretAuth {
+    /// Creates an authenticator accepting ``secret``. Senders stay authenticated until their
+    /// session ends, and aren't told when they need to authenticate.
+    #[must_use]
+    pub fn new(secret: impl Into<String>) -> Self {
+        Self {
+            secret: secret.into(),
+            expiry: None,
+            required_reply: false,
+        }
+    }
+
+    /// Makes senders authenticate again once ``expiry`` has passed since they last did.
+    #[must_use]
+    pub fn with_expiry(mut self, expiry: Duration) -> Self {
+        self.expiry = Some(expiry);
+        self
+    }
+
+    /// Replies to each message dropped because its sender hasn't authenticated with an empty
+    /// message to ``/auth/required``, or stops if ``enabled`` is ``false``.
+    #[must_use]
+    pub fn with_required_reply(mut self, enabled: bool) -> Self {
+        self.required_reply = enabled;
+        self
+    }
+
+    /// Returns whether the sender of ``session`` has authenticated, and it hasn't expired.
+    #[must_use]
+    pub fn is_authenticated(&self, session: &Session) -> bool {
+        session
+            .get::<Authenticated>()
+            .is_some_and(|auth| auth.until.is_none_or(|until| Instant::now() < until))
+    }
+
+    /// Handles a message to ``/auth``, authenticating the sender if it carries the secret.
+    pub(crate) fn login(&self, msg: &OscMessage, session: &mut Session) -> Vec<OscMessage> {
+        let accepted = matches!(msg.args.first(), Some(Arg::Str(secret)) if self.accepts(secret));
+        if !accepted {
+            session.remove::<Authenticated>();
+            return self.rejection().into_iter().collect();
+        }
+        session.insert(Authenticated {
+            until: self.expiry.map(|expiry| Instant::now() + expiry),
+        });
+        vec![OscMessage::new(AUTH_OK_ADDRESS, vec![])]
+    }
+
+    /// Returns the reply to a message from a sender which hasn't authenticated, if any.
+    pub(crate) fn rejection(&self) -> Option<OscMessage> {
+        self.required_reply
+            .then(|| OscMessage::new(AUTH_REQUIRED_ADDRESS, vec![]))
+    }
+
+    /// Compares ``secret`` with the secret in a time independent of where they differ, so it
+    /// can't be guessed a byte at a time.
+    fn accepts(&self, secret: &str) -> bool {
+        let expected = self.secret.as_bytes();
+        let secret = secret.as_bytes();
+        expected.len() == secret.len()
+            && expected
+                .iter()
+                .zip(secret)
+                .fold(0, |differences, (a, b)| differences | (a ^ b))
+                == 0
+    }
+}
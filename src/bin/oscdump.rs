@@ -0,0 +1,51 @@
+//! Prints every OSC message arriving on a UDP port, with its sender, e.g. ``oscdump 9000``.
+//! Messages are printed in ``OscMessage``'s text form, and packets which can't be parsed are
+//! reported on stderr.
+
+use std::{env, process::ExitCode, sync::mpsc, thread};
+
+use osc::server::{OscServer, ServerEvent};
+
+const USAGE: &str = "Usage: oscdump PORT | ADDRESS:PORT";
+
+/// The largest packet the server can receive, the most a UDP datagram can hold.
+const MAX_PACKET_SIZE: usize = 65536;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let [address] = &args[..] else {
+        eprintln!("{USAGE}");
+        return ExitCode::FAILURE;
+    };
+    let address = match address.parse::<u16>() {
+        Ok(port) => format!("0.0.0.0:{port}"),
+        Err(_) => address.clone(),
+    };
+    match dump(&address) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("oscdump: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn dump(address: &str) -> Result<(), osc::errors::Error> {
+    let (events, received) = mpsc::channel();
+    let mut server = OscServer::new(address, MAX_PACKET_SIZE)?.with_events(events);
+    eprintln!("Listening on {}", server.local_addr()?);
+    thread::spawn(move || {
+        for event in received {
+            match event {
+                ServerEvent::MessageReceived { source, message } => {
+                    println!("{source} {message}");
+                }
+                ServerEvent::ParseError { source, error } => {
+                    eprintln!("{source} sent an unreadable packet: {error}");
+                }
+                _ => {}
+            }
+        }
+    });
+    server.start()
+}
@@ -0,0 +1,65 @@
+//! Sends one OSC message over UDP, e.g. ``oscsend 192.168.1.20:10023 /ch/01/mix/fader f 0.75``.
+//! Arguments are given as type tags followed by values, as ``OscMessage``'s text form.
+
+use std::{
+    env,
+    net::{SocketAddr, ToSocketAddrs, UdpSocket},
+    process::ExitCode,
+};
+
+use osc::{client::OscClient, OscMessage};
+
+const USAGE: &str = "Usage: oscsend HOST:PORT ADDRESS [TAG [VALUE]]...";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let [remote, message @ ..] = &args[..] else {
+        eprintln!("{USAGE}");
+        return ExitCode::FAILURE;
+    };
+    if message.is_empty() {
+        eprintln!("{USAGE}");
+        return ExitCode::FAILURE;
+    }
+    match send(remote, message) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("oscsend: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn send(remote: &str, message: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let text: Vec<String> = message.iter().map(|arg| quote(arg)).collect();
+    let message: OscMessage = text.join(" ").parse()?;
+    let remote: SocketAddr = remote
+        .to_socket_addrs()?
+        .next()
+        .ok_or("no address found for the host")?;
+    let local = if remote.is_ipv6() {
+        "[::]:0"
+    } else {
+        "0.0.0.0:0"
+    };
+    let mut client = OscClient::<UdpSocket>::new(local, remote, 0, None)?;
+    client.send(&message)?;
+    Ok(())
+}
+
+/// Quotes a command line argument, which the shell has already split, so the message's text
+/// form reads it as one token.
+fn quote(arg: &str) -> String {
+    if !arg.is_empty() && !arg.contains(|c: char| c.is_whitespace() || matches!(c, '"' | '\\')) {
+        return arg.to_string();
+    }
+    let mut quoted = String::from('"');
+    for c in arg.chars() {
+        if matches!(c, '"' | '\\') {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
@@ -0,0 +1,221 @@
+use std::{
+    io::ErrorKind,
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    bundle::OscBundle, errors::Error, lock, pattern::OscPattern, router::Router,
+    sockets::Connection, OscMessage, OscPacket,
+};
+
+/// Which way packets travel through an ``OscBridge``.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BridgeDirection {
+    /// From the first connection given to ``OscBridge::new`` to the second.
+    AToB,
+    /// From the second connection to the first.
+    BToA,
+}
+
+/// What happens to packets travelling one way through a bridge.
+#[derive(Default)]
+struct Rules {
+    /// Only messages matching one of these are forwarded, if there are any.
+    filter: Router<()>,
+    /// Each address starting with the first prefix has it replaced by the second.
+    rewrite: Option<(String, String)>,
+}
+
+impl Rules {
+    fn is_empty(&self) -> bool {
+        self.filter.routes().next().is_none() && self.rewrite.is_none()
+    }
+
+    /// Applies the rules to ``packet``, returning ``None`` if nothing in it is forwarded.
+    fn apply(&self, packet: OscPacket) -> Option<OscPacket> {
+        match packet {
+            OscPacket::Message(msg) => self.apply_message(msg).map(OscPacket::Message),
+            OscPacket::Bundle(bundle) => {
+                let content: Vec<_> = bundle
+                    .content
+                    .into_iter()
+                    .filter_map(|packet| self.apply(packet))
+                    .collect();
+                (!content.is_empty())
+                    .then(|| OscPacket::Bundle(OscBundle::new(bundle.timetag, content)))
+            }
+        }
+    }
+
+    fn apply_message(&self, mut msg: OscMessage) -> Option<OscMessage> {
+        let filtered = self.filter.routes().next().is_some();
+        if filtered && self.filter.matching(&msg.address).next().is_none() {
+            return None;
+        }
+        if let Some((from, to)) = &self.rewrite {
+            if let Some(rest) = msg.address.strip_prefix(from.as_str()) {
+                msg.address = format!("{to}{rest}");
+            }
+        }
+        Some(msg)
+    }
+}
+
+/// Forwards packets between two connections of any kind, e.g. to expose UDP-only gear to clients
+/// which can only speak TCP or WebSocket. Each direction can be filtered to the addresses it
+/// should carry, and can rewrite an address prefix, e.g. ``/mixer`` on one side to nothing on the
+/// other.
+///
+/// Packets travelling a direction without any rules are forwarded byte-for-byte, even if they
+/// can't be parsed. Otherwise each packet is parsed, the rules applied to each message (including
+/// those in bundles), and the result rebuilt; packets which can't be parsed, and bundles left
+/// empty by filtering, are dropped.
+pub struct OscBridge<A: Connection, B: Connection> {
+    a: A,
+    b: B,
+    a_to_b: Arc<Mutex<Rules>>,
+    b_to_a: Arc<Mutex<Rules>>,
+    buffer: Vec<u8>,
+}
+
+impl<A: Connection, B: Connection> OscBridge<A, B> {
+    /// Bridges ``a`` and ``b``. ``buffer_size`` dictates the largest packet that can be forwarded
+    /// either way.
+    #[must_use]
+    pub fn new(a: A, b: B, buffer_size: usize) -> Self {
+        Self {
+            a,
+            b,
+            a_to_b: Arc::default(),
+            b_to_a: Arc::default(),
+            buffer: vec![0; buffer_size],
+        }
+    }
+
+    /// Only forwards messages travelling ``direction`` whose address matches ``pattern``, or any
+    /// other pattern added this way.
+    ///
+    /// # Errors
+    /// Will return ``Error::Address`` if ``pattern`` is not a valid ``OscPattern``, or
+    /// ``Error::DuplicateRoute`` if it was already added for ``direction``.
+    pub fn with_filter<P>(self, direction: BridgeDirection, pattern: P) -> Result<Self, Error>
+    where
+        P: TryInto<OscPattern>,
+        Error: From<P::Error>,
+    {
+        lock(self.rules(direction))
+            .filter
+            .insert_route(pattern, ())?;
+        Ok(self)
+    }
+
+    /// Replaces the ``from`` prefix of addresses travelling ``direction`` with ``to``, e.g.
+    /// ``with_rewrite(BridgeDirection::BToA, "/mixer", "")`` so TCP clients sending to
+    /// ``/mixer/ch/01/fader`` reach ``/ch/01/fader`` on a mixer which knows nothing of the
+    /// prefix. Addresses without the prefix are left as they are. Filters see the address before
+    /// it's rewritten.
+    #[must_use]
+    pub fn with_rewrite(self, direction: BridgeDirection, from: &str, to: &str) -> Self {
+        lock(self.rules(direction)).rewrite = Some((from.to_string(), to.to_string()));
+        self
+    }
+
+    fn rules(&self, direction: BridgeDirection) -> &Mutex<Rules> {
+        match direction {
+            BridgeDirection::AToB => &self.a_to_b,
+            BridgeDirection::BToA => &self.b_to_a,
+        }
+    }
+
+    /// Receives a packet travelling ``direction`` and forwards it, so the bridge can be driven
+    /// from another event loop, e.g. with both connections non-blocking. Returns whether a packet
+    /// was received, whether or not it was forwarded.
+    ///
+    /// # Errors
+    /// Will return an ``Error::Socket`` if receiving fails, other than because no packet arrived
+    /// before the read timeout, or if forwarding fails.
+    pub fn forward(&mut self, direction: BridgeDirection) -> Result<bool, Error> {
+        match direction {
+            BridgeDirection::AToB => {
+                forward(&mut self.a, &mut self.b, &self.a_to_b, &mut self.buffer)
+            }
+            BridgeDirection::BToA => {
+                forward(&mut self.b, &mut self.a, &self.b_to_a, &mut self.buffer)
+            }
+        }
+    }
+
+    /// Forwards packets both ways forever: from ``b`` to ``a`` on a background thread, using
+    /// clones of the connections made with ``try_clone``, and from ``a`` to ``b`` on this one.
+    /// The background thread stops if forwarding fails.
+    ///
+    /// # Errors
+    /// Will return an ``Error::Socket`` if either connection can't be cloned, or if receiving
+    /// from ``a`` or sending to ``b`` fails.
+    #[cfg(not(target_family = "wasm"))]
+    pub fn start(mut self) -> Result<(), Error>
+    where
+        A: Send + 'static,
+        B: Send + 'static,
+    {
+        let mut from = self.b.try_clone().map_err(Error::Socket)?;
+        let mut to = self.a.try_clone().map_err(Error::Socket)?;
+        let rules = self.b_to_a.clone();
+        let mut buffer = vec![0; self.buffer.len()];
+        std::thread::spawn(
+            move || {
+                while forward(&mut from, &mut to, &rules, &mut buffer).is_ok() {}
+            },
+        );
+        loop {
+            forward(&mut self.a, &mut self.b, &self.a_to_b, &mut self.buffer)?;
+        }
+    }
+
+    /// Returns the connections.
+    #[must_use]
+    pub fn into_inner(self) -> (A, B) {
+        (self.a, self.b)
+    }
+}
+
+/// Receives a packet from ``from`` and sends it to ``to`` after applying ``rules``, returning
+/// whether one was received.
+fn forward<F: Connection, T: Connection>(
+    from: &mut F,
+    to: &mut T,
+    rules: &Mutex<Rules>,
+    buffer: &mut [u8],
+) -> Result<bool, Error> {
+    let size = match from.recv(buffer) {
+        Ok(size) => size,
+        // A connected UDP socket reports the remote refusing an earlier send here
+        Err(e)
+            if matches!(
+                e.kind(),
+                ErrorKind::WouldBlock
+                    | ErrorKind::TimedOut
+                    | ErrorKind::ConnectionRefused
+                    | ErrorKind::ConnectionReset
+            ) =>
+        {
+            return Ok(false)
+        }
+        Err(e) => return Err(Error::Socket(e)),
+    };
+    let packet = &buffer[..size];
+    let rules = lock(rules);
+    if rules.is_empty() {
+        to.send(packet).map_err(Error::Socket)?;
+        return Ok(true);
+    }
+    let Some(packet) = OscPacket::parse_bytes(packet)
+        .ok()
+        .and_then(|packet| rules.apply(packet))
+    else {
+        return Ok(true);
+    };
+    drop(rules);
+    to.send(&packet.build()?).map_err(Error::Socket)?;
+    Ok(true)
+}
@@ -0,0 +1,190 @@
+use crate::{
+    bundle::OscBundle, errors::Error, time::OscTime, Arg, Args, OscMessage, OscPacket, Rgba,
+};
+
+/// Builds an ``OscMessage`` one argument at a time, e.g.
+/// ``OscMessage::builder("/ch/01/mix").int(1).float(0.5).build()``.
+#[derive(Debug, Clone)]
+#[allow(clippy::module_name_repetitions)]
+pub struct OscMessageBuilder {
+    address: String,
+    args: Args,
+}
+
+impl OscMessageBuilder {
+    #[must_use]
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn new(address: impl ToString) -> Self {
+        Self {
+            address: address.to_string(),
+            args: Args::new(),
+        }
+    }
+
+    /// Appends ``arg``, or any value which converts into one.
+    #[must_use]
+    pub fn arg(mut self, arg: impl Into<Arg>) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    #[must_use]
+    pub fn int(self, value: i32) -> Self {
+        self.arg(Arg::Int(value))
+    }
+
+    #[must_use]
+    pub fn int64(self, value: i64) -> Self {
+        self.arg(Arg::Int64(value))
+    }
+
+    #[must_use]
+    pub fn float(self, value: f32) -> Self {
+        self.arg(Arg::Float(value))
+    }
+
+    #[must_use]
+    pub fn double(self, value: f64) -> Self {
+        self.arg(Arg::Double(value))
+    }
+
+    #[must_use]
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn string(self, value: impl ToString) -> Self {
+        self.arg(Arg::Str(value.to_string()))
+    }
+
+    #[must_use]
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn symbol(self, value: impl ToString) -> Self {
+        self.arg(Arg::Symbol(value.to_string()))
+    }
+
+    #[must_use]
+    pub fn blob(self, value: impl Into<Vec<u8>>) -> Self {
+        self.arg(value.into())
+    }
+
+    #[must_use]
+    pub fn bool(self, value: bool) -> Self {
+        self.arg(Arg::Bool(value))
+    }
+
+    #[must_use]
+    pub fn char(self, value: char) -> Self {
+        self.arg(Arg::Char(value))
+    }
+
+    #[must_use]
+    pub fn color(self, value: Rgba) -> Self {
+        self.arg(Arg::Color(value))
+    }
+
+    #[must_use]
+    pub fn time(self, value: OscTime) -> Self {
+        self.arg(Arg::Time(value))
+    }
+
+    #[must_use]
+    pub fn nil(self) -> Self {
+        self.arg(Arg::Nil)
+    }
+
+    #[must_use]
+    pub fn infinitum(self) -> Self {
+        self.arg(Arg::Infinitum)
+    }
+
+    #[must_use]
+    pub fn array(self, items: Vec<Arg>) -> Self {
+        self.arg(Arg::Array(items))
+    }
+
+    /// Returns the message built so far.
+    #[must_use]
+    pub fn build(self) -> OscMessage {
+        OscMessage::new(self.address, self.args)
+    }
+}
+
+/// Builds an ``OscBundle`` one element at a time, checking that every nested bundle's timetag is
+/// no earlier than the one enclosing it, as the spec requires, e.g.
+/// ``OscBundle::builder(at).message(msg).bundle(OscBundle::builder(later).message(other))``.
+#[derive(Clone)]
+#[allow(clippy::module_name_repetitions)]
+pub struct OscBundleBuilder {
+    timetag: OscTime,
+    content: Vec<OscPacket>,
+}
+
+impl OscBundleBuilder {
+    #[must_use]
+    pub fn new(timetag: OscTime) -> Self {
+        Self {
+            timetag,
+            content: Vec::new(),
+        }
+    }
+
+    /// Appends ``message``.
+    #[must_use]
+    pub fn message(mut self, message: OscMessage) -> Self {
+        self.content.push(OscPacket::Message(message));
+        self
+    }
+
+    /// Appends the bundle ``nested`` is building.
+    #[must_use]
+    pub fn bundle(mut self, nested: OscBundleBuilder) -> Self {
+        self.content.push(OscPacket::Bundle(OscBundle::new(
+            nested.timetag,
+            nested.content,
+        )));
+        self
+    }
+
+    /// Appends ``packet``, e.g. a bundle which was received or built elsewhere. Its timetags are
+    /// checked along with the rest.
+    #[must_use]
+    pub fn packet(mut self, packet: impl Into<OscPacket>) -> Self {
+        self.content.push(packet.into());
+        self
+    }
+
+    /// Returns the bundle built so far.
+    ///
+    /// # Errors
+    /// Will return ``Error::NestedTimetag`` if a bundle, at any depth, has an earlier timetag than
+    /// the bundle it's in. ``OscTime::IMMEDIATE`` is earlier than any other time, so can only be
+    /// nested in bundles which are themselves immediate.
+    pub fn build(self) -> Result<OscBundle, Error> {
+        let bundle = OscBundle::new(self.timetag, self.content);
+        check_nesting(&bundle)?;
+        Ok(bundle)
+    }
+
+    /// Returns the bundle built so far, along with its encoding.
+    ///
+    /// # Errors
+    /// See ``build`` and ``OscBundle::build``.
+    pub fn build_encoded(self) -> Result<(OscBundle, Vec<u8>), Error> {
+        let bundle = self.build()?;
+        let bytes = bundle.build()?;
+        Ok((bundle, bytes))
+    }
+}
+
+fn check_nesting(bundle: &OscBundle) -> Result<(), Error> {
+    for packet in &bundle.content {
+        if let OscPacket::Bundle(nested) = packet {
+            if nested.timetag < bundle.timetag {
+                return Err(Error::NestedTimetag {
+                    timetag: nested.timetag,
+                    enclosing: bundle.timetag,
+                });
+            }
+            check_nesting(nested)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,214 @@
+use crate::{
+    builder::OscBundleBuilder,
+    check_limit, copy_blob,
+    errors::{Error, ParseError, ParseSection},
+    parse::ParseOptions,
+    scan_into_byte_array,
+    time::OscTime,
+    Blob, OscMessage, OscPacket,
+};
+
+const BUNDLE_TAG: &[u8; 8] = b"#bundle\0";
+
+/// A group of messages and bundles which the receiver should act on together, at the time given
+/// by ``timetag``.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OscBundle {
+    pub timetag: OscTime,
+    pub content: Vec<OscPacket>,
+}
+
+impl OscBundle {
+    #[must_use]
+    pub fn new(timetag: OscTime, content: Vec<OscPacket>) -> Self {
+        Self { timetag, content }
+    }
+
+    /// Starts building a bundle to be acted on at ``timetag``, adding messages and nested bundles
+    /// one at a time. See ``OscBundleBuilder``.
+    #[must_use]
+    pub fn builder(timetag: OscTime) -> OscBundleBuilder {
+        OscBundleBuilder::new(timetag)
+    }
+
+    /// Returns whether ``data`` starts with the ``#bundle`` tag that marks an encoded bundle.
+    #[must_use]
+    pub fn is_bundle(data: &[u8]) -> bool {
+        data.starts_with(BUNDLE_TAG)
+    }
+
+    /// Builds a byte-vec out of ``self``, so that it can be sent over a ``Connection``.
+    ///
+    /// # Errors
+    /// Will return any error from building the contained elements (see ``OscMessage::build``).
+    /// ## ``Error::DataLength``
+    /// If an encoded element is longer than ``i32::MAX`` bytes.
+    pub fn build(&self) -> Result<Vec<u8>, Error> {
+        let mut bundle = Vec::new();
+        self.build_into(&mut bundle)?;
+        Ok(bundle)
+    }
+
+    /// Appends the encoded bundle to ``buf``, returning its size. Nested bundles and messages
+    /// are encoded straight into ``buf``, with each element's size patched in after it, so even
+    /// a large bundle only needs the one buffer. On error, ``buf`` is left as it was.
+    ///
+    /// # Errors
+    /// See ``build``.
+    pub fn build_into(&self, buf: &mut Vec<u8>) -> Result<usize, Error> {
+        let start = buf.len();
+        let res = self.encode_into(buf);
+        if res.is_err() {
+            buf.truncate(start);
+        }
+        res.map(|()| buf.len() - start)
+    }
+
+    fn encode_into(&self, buf: &mut Vec<u8>) -> Result<(), Error> {
+        buf.extend_from_slice(BUNDLE_TAG);
+        buf.extend_from_slice(&u64::from(self.timetag).to_be_bytes());
+
+        for element in &self.content {
+            let size_at = buf.len();
+            buf.extend_from_slice(&[0; 4]);
+            let size = element.build_into(buf)?;
+            let size =
+                i32::try_from(size).map_err(|_| Error::DataLength(i32::MAX as usize, size))?;
+            buf[size_at..size_at + 4].copy_from_slice(&size.to_be_bytes());
+        }
+        Ok(())
+    }
+
+    /// Returns the size ``self`` encodes to, without building it. See
+    /// ``OscMessage::encoded_len``.
+    ///
+    /// # Errors
+    /// See ``build``.
+    pub fn encoded_len(&self) -> Result<usize, Error> {
+        let mut size = BUNDLE_TAG.len() + 8;
+        for element in &self.content {
+            let element = element.encoded_len()?;
+            i32::try_from(element).map_err(|_| Error::DataLength(i32::MAX as usize, element))?;
+            size += 4 + element;
+        }
+        Ok(size)
+    }
+
+    /// Transforms ``data`` into an ``OscBundle``
+    ///
+    /// # Errors
+    /// If ``data`` is not 4-byte aligned, will return ``Error::Alignment``.
+    /// If ``data`` does not start with ``#bundle``, will return ``Error::Malformed``.
+    /// If ``data`` ends before the timetag, or before the end of an element, will return
+    /// ``Error::DataLength``.
+    /// If an element states a negative or unaligned size, will return ``Error::BlobSize``.
+    /// Will also return any error from parsing the contained elements (see
+    /// ``OscPacket::parse_bytes``).
+    /// Each error is wrapped in an ``Error::Parse`` saying where in ``data`` it occurred.
+    pub fn parse_bytes(data: &[u8]) -> Result<Self, Error> {
+        Self::parse_bytes_with(data, &ParseOptions::default())
+    }
+
+    /// Transforms ``data`` into an ``OscBundle``, enforcing ``options`` on it and the contained
+    /// elements. Elements must be aligned, whatever ``options.require_alignment`` is.
+    ///
+    /// # Errors
+    /// See ``parse_bytes`` and ``OscPacket::parse_bytes_with``.
+    pub fn parse_bytes_with(data: &[u8], options: &ParseOptions) -> Result<Self, Error> {
+        Self::parse_blobs_with(data, options, &copy_blob)
+    }
+
+    /// Transforms ``data`` into an ``OscBundle`` as ``parse_bytes_with`` does, but with each
+    /// blob a slice of ``data`` rather than a copy.
+    ///
+    /// # Errors
+    /// See ``parse_bytes_with``.
+    #[cfg(feature = "bytes")]
+    pub fn parse_shared(data: &bytes::Bytes, options: &ParseOptions) -> Result<Self, Error> {
+        Self::parse_blobs_with(data, options, &|contents| data.slice_ref(contents))
+    }
+
+    /// Transforms ``data`` into an ``OscBundle`` as ``parse_bytes_with`` does, making each blob
+    /// from its contents with ``blob``.
+    pub(crate) fn parse_blobs_with(
+        data: &[u8],
+        options: &ParseOptions,
+        blob: &dyn Fn(&[u8]) -> Blob,
+    ) -> Result<Self, Error> {
+        Self::parse_nested(data, options, blob, 1)
+    }
+
+    /// Parses a bundle as ``parse_blobs_with`` does, where ``depth`` is how many bundles deep it
+    /// is, counting itself.
+    fn parse_nested(
+        data: &[u8],
+        options: &ParseOptions,
+        blob: &dyn Fn(&[u8]) -> Blob,
+        depth: usize,
+    ) -> Result<Self, Error> {
+        let in_packet = |error| ParseError::wrap(error, ParseSection::Packet, 0, data);
+        if options.require_alignment && !data.len().is_multiple_of(4) {
+            return Err(in_packet(Error::Alignment(data.len(), 4)));
+        }
+        if !Self::is_bundle(data) {
+            return Err(in_packet(Error::Malformed("OSC bundle tag".to_string())));
+        }
+        check_limit(depth, options.max_depth, |_, limit| Error::NestingTooDeep {
+            limit,
+        })
+        .map_err(in_packet)?;
+
+        let mut i = BUNDLE_TAG.len();
+        let mut timetag = [0; 8];
+        scan_into_byte_array(&mut timetag, &mut i, data).map_err(|error| {
+            ParseError::wrap(error, ParseSection::Timetag, BUNDLE_TAG.len(), data)
+        })?;
+
+        let mut content = Vec::new();
+        let mut four_bytes = [0; 4];
+        while i < data.len() {
+            let start = i;
+            let in_element =
+                |error| ParseError::wrap(error, ParseSection::Element(content.len()), start, data);
+            scan_into_byte_array(&mut four_bytes, &mut i, data).map_err(in_element)?;
+            let size = i32::from_be_bytes(four_bytes);
+            let size = usize::try_from(size)
+                .ok()
+                .filter(|size| size.is_multiple_of(4))
+                .ok_or(Error::BlobSize(size))
+                .map_err(in_element)?;
+            let element = data
+                .get(i..i + size)
+                .ok_or(Error::DataLength(size, data.len() - i))
+                .map_err(in_element)?;
+            i += size;
+
+            let element = if Self::is_bundle(element) {
+                Self::parse_nested(element, options, blob, depth + 1).map(OscPacket::Bundle)
+            } else {
+                OscMessage::parse_blobs_with(element, options, blob).map(OscPacket::Message)
+            }
+            .map_err(in_element)?;
+            content.push(element);
+        }
+
+        Ok(Self::new(u64::from_be_bytes(timetag).into(), content))
+    }
+}
+
+/// Appends every message in ``packet`` to ``messages`` in order, unpacking nested bundles.
+#[cfg_attr(
+    not(any(feature = "client", all(feature = "server", feature = "udp"))),
+    allow(dead_code)
+)]
+pub(crate) fn flatten_into(packet: OscPacket, messages: &mut impl Extend<OscMessage>) {
+    match packet {
+        OscPacket::Message(msg) => messages.extend(Some(msg)),
+        OscPacket::Bundle(bundle) => {
+            for packet in bundle.content {
+                flatten_into(packet, messages);
+            }
+        }
+    }
+}
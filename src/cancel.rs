@@ -0,0 +1,34 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+/// A flag which another thread can set to abort blocking waits promptly, e.g. to stop an
+/// ``OscClient`` waiting for a reply when the application closes. Clones share the flag, so keep
+/// one to cancel with and give another to the client with ``OscClient::set_cancel_flag``.
+///
+/// Once cancelled, a flag stays cancelled, failing every wait which checks it, until ``reset``.
+#[derive(Debug, Clone, Default)]
+pub struct CancelFlag(Arc<AtomicBool>);
+
+impl CancelFlag {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancels every wait checking the flag, now and until it is ``reset``.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Clears the flag, so waits block as usual again.
+    pub fn reset(&self) {
+        self.0.store(false, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,104 @@
+use std::{
+    cell::RefCell,
+    collections::VecDeque,
+    io::{self, ErrorKind},
+    net::ToSocketAddrs,
+    rc::Rc,
+    time::Duration,
+};
+
+use crate::sockets::{copy_frame, Connection};
+
+/// Sends one packet over the underlying channel.
+type Outbox = Rc<RefCell<dyn FnMut(&[u8]) -> io::Result<()>>>;
+
+/// A ``Connection`` over a message-based channel the application drives through callbacks, such
+/// as a browser ``WebSocket`` or WebRTC data channel in wasm32, so a browser control surface can
+/// use the same ``OscClient`` API as native code. Each packet is sent as one binary message by
+/// the ``send`` callback given to ``new``, and each binary message the channel receives should
+/// be passed to ``ChannelInbox::push``, e.g. from the socket's ``onmessage`` handler.
+///
+/// A browser can't block waiting for data, so ``recv`` never blocks: it takes the oldest
+/// received packet, or returns an error of kind ``io::ErrorKind::WouldBlock`` if there is none,
+/// and read timeouts are ignored. Poll the client from the event loop, e.g. with ``try_iter``,
+/// rather than calling methods which wait until a deadline. The channel isn't ``Send``, so the
+/// client's background-thread features are unavailable, as they would be in a browser anyway.
+#[derive(Clone)]
+pub struct MessageChannel {
+    inbox: ChannelInbox,
+    outbox: Outbox,
+}
+
+/// The receiving end of a ``MessageChannel``, which the channel's message handler pushes each
+/// received packet into. Clones push to the same channel.
+#[derive(Clone, Default)]
+pub struct ChannelInbox(Rc<RefCell<VecDeque<Vec<u8>>>>);
+
+impl ChannelInbox {
+    /// Queues ``packet`` to be received by the ``MessageChannel``.
+    pub fn push(&self, packet: impl Into<Vec<u8>>) {
+        self.0.borrow_mut().push_back(packet.into());
+    }
+}
+
+impl MessageChannel {
+    /// Creates a channel which sends each packet with ``send``, returning it with the inbox
+    /// which received packets should be pushed into.
+    pub fn new(send: impl FnMut(&[u8]) -> io::Result<()> + 'static) -> (Self, ChannelInbox) {
+        let inbox = ChannelInbox::default();
+        let channel = Self {
+            inbox: inbox.clone(),
+            outbox: Rc::new(RefCell::new(send)),
+        };
+        (channel, inbox)
+    }
+
+    /// Returns the inbox which received packets should be pushed into.
+    #[must_use]
+    pub fn inbox(&self) -> ChannelInbox {
+        self.inbox.clone()
+    }
+}
+
+impl Connection for MessageChannel {
+    /// Message channels can't be opened by address, so this always returns an error. Use
+    /// ``MessageChannel::new`` and ``OscClient::from_connection``.
+    fn new<A: ToSocketAddrs, B: ToSocketAddrs>(_: A, _: B) -> io::Result<Self> {
+        Err(io::Error::new(
+            ErrorKind::Unsupported,
+            "Message channels can't be opened by address",
+        ))
+    }
+
+    fn send(&mut self, buf: &[u8]) -> io::Result<usize> {
+        (self.outbox.borrow_mut())(buf)?;
+        Ok(buf.len())
+    }
+
+    fn recv(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let packet = self.inbox.0.borrow_mut().pop_front();
+        match packet {
+            Some(packet) => copy_frame(&packet, buf),
+            None => Err(ErrorKind::WouldBlock.into()),
+        }
+    }
+
+    fn set_read_timeout(&self, _: Option<Duration>) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn set_nonblocking(&self, _: bool) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn try_clone(&self) -> io::Result<Self> {
+        Ok(self.clone())
+    }
+
+    fn peek(&self, buf: &mut [u8]) -> io::Result<usize> {
+        match self.inbox.0.borrow().front() {
+            Some(packet) => copy_frame(packet, buf),
+            None => Err(ErrorKind::WouldBlock.into()),
+        }
+    }
+}
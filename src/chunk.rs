@@ -0,0 +1,227 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::atomic::{AtomicI32, Ordering},
+    time::{Duration, Instant},
+};
+
+use crate::{copy_blob, errors::Error, Arg, OscMessage};
+
+/// A datagram limit which fits most Ethernet networks: a 1500 byte MTU, less IP and UDP headers.
+pub const DEFAULT_DATAGRAM_SIZE: usize = 1472;
+
+/// The size of a chunk message, less its address and chunk: the type tags and the transfer id,
+/// index, count, and blob size.
+const CHUNK_OVERHEAD: usize = 8 + 4 * 4;
+
+/// How many transfers a ``ChunkAssembler`` holds at once, unless set with
+/// ``ChunkAssembler::with_max_transfers``.
+const DEFAULT_MAX_TRANSFERS: usize = 64;
+
+static NEXT_TRANSFER_ID: AtomicI32 = AtomicI32::new(0);
+
+/// Returns an id for a new transfer, unique within this process, to tell its chunks apart from
+/// those of other transfers to the same address.
+pub fn next_transfer_id() -> i32 {
+    NEXT_TRANSFER_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Splits ``blob`` into messages to ``address`` which each fit in ``max_size`` bytes, so a blob
+/// too large for one datagram, e.g. a firmware image, can be sent over UDP. Each message has the
+/// arguments ``id``, the chunk's index, the number of chunks, and the chunk as a blob, and a
+/// ``ChunkAssembler`` puts them back together on the receiving side. An empty blob takes one
+/// empty chunk.
+///
+/// # Errors
+/// Will return ``Error::MessageTooLarge`` if even an empty chunk doesn't fit in ``max_size``, or
+/// ``Error::DataLength`` if ``blob`` needs more than ``i32::MAX`` chunks.
+pub fn split(
+    address: &str,
+    id: i32,
+    blob: &[u8],
+    max_size: usize,
+) -> Result<Vec<OscMessage>, Error> {
+    let overhead = address.len() + 4 - address.len() % 4 + CHUNK_OVERHEAD;
+    let chunk_size = max_size.saturating_sub(overhead) / 4 * 4;
+    if chunk_size == 0 {
+        return Err(Error::MessageTooLarge {
+            size: overhead + 4,
+            limit: max_size,
+        });
+    }
+    let chunks: Vec<_> = if blob.is_empty() {
+        vec![blob]
+    } else {
+        blob.chunks(chunk_size).collect()
+    };
+    let count = i32::try_from(chunks.len())
+        .map_err(|_| Error::DataLength(i32::MAX as usize, chunks.len()))?;
+    Ok((0..count)
+        .zip(chunks)
+        .map(|(index, chunk)| {
+            OscMessage::new(
+                address,
+                vec![
+                    Arg::Int(id),
+                    Arg::Int(index),
+                    Arg::Int(count),
+                    Arg::Blob(copy_blob(chunk)),
+                ],
+            )
+        })
+        .collect())
+}
+
+/// Puts blobs split by ``split`` back together from their chunk messages, which may arrive in
+/// any order. Transfers which stop receiving chunks for ``timeout`` are dropped, so lost chunks
+/// don't hold memory forever. Chunks are keyed by address and transfer id, so use one assembler
+/// per sender if several may send to the same address.
+pub struct ChunkAssembler {
+    transfers: HashMap<(String, i32), Transfer>,
+    timeout: Duration,
+    max_len: Option<usize>,
+    max_transfers: usize,
+    /// No transfer expires before this, so they needn't be checked until then.
+    next_expiry: Option<Instant>,
+}
+
+struct Transfer {
+    /// The chunks received so far, by index, so memory grows with what has arrived rather than
+    /// the count a sender claims.
+    chunks: BTreeMap<usize, Vec<u8>>,
+    count: usize,
+    len: usize,
+    last_chunk: Instant,
+}
+
+impl ChunkAssembler {
+    #[must_use]
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            transfers: HashMap::new(),
+            timeout,
+            max_len: None,
+            max_transfers: DEFAULT_MAX_TRANSFERS,
+            next_expiry: None,
+        }
+    }
+
+    /// Refuses transfers which grow beyond ``max_len`` bytes, dropping what was received of them,
+    /// so a sender can't exhaust memory.
+    #[must_use]
+    pub fn with_max_len(mut self, max_len: usize) -> Self {
+        self.max_len = Some(max_len);
+        self
+    }
+
+    /// Holds at most ``max_transfers`` partly received transfers, 64 by default, so a sender
+    /// can't exhaust memory by starting transfers it never finishes. When a chunk starts another,
+    /// the transfer which has gone longest without a chunk is dropped to make room.
+    #[must_use]
+    pub fn with_max_transfers(mut self, max_transfers: usize) -> Self {
+        self.max_transfers = max_transfers;
+        self
+    }
+
+    /// Returns whether ``message`` has the arguments of a chunk message.
+    #[must_use]
+    pub fn is_chunk(message: &OscMessage) -> bool {
+        matches!(
+            &message.args[..],
+            [Arg::Int(_), Arg::Int(_), Arg::Int(_), Arg::Blob(_)]
+        )
+    }
+
+    /// Adds the chunk ``message`` to its transfer, returning the address and the whole blob once
+    /// every chunk has arrived. Repeated chunks are ignored.
+    ///
+    /// # Errors
+    /// Will return ``Error::Malformed`` if ``message`` isn't a chunk message, or its index or
+    /// count don't fit its transfer. Will return ``Error::TransferTooLarge`` if the transfer
+    /// exceeds ``max_len``.
+    pub fn push(&mut self, message: &OscMessage) -> Result<Option<(String, Vec<u8>)>, Error> {
+        let now = Instant::now();
+        if self.next_expiry.is_some_and(|at| now >= at) {
+            self.drop_expired(now);
+        }
+
+        let malformed = || Error::Malformed("OSC chunk".to_string());
+        let [Arg::Int(id), Arg::Int(index), Arg::Int(count), Arg::Blob(chunk)] = &message.args[..]
+        else {
+            return Err(malformed());
+        };
+        let index = usize::try_from(*index).map_err(|_| malformed())?;
+        let count = usize::try_from(*count).map_err(|_| malformed())?;
+        if index >= count {
+            return Err(malformed());
+        }
+
+        let key = (message.address.clone(), *id);
+        if !self.transfers.contains_key(&key) && self.transfers.len() >= self.max_transfers {
+            let stalest = self
+                .transfers
+                .iter()
+                .min_by_key(|(_, transfer)| transfer.last_chunk)
+                .map(|(key, _)| key.clone());
+            if let Some(stalest) = stalest {
+                self.transfers.remove(&stalest);
+            }
+        }
+        // Chunks only push expiry later, so the earliest a new transfer could expire is enough
+        if let Some(expiry) = now.checked_add(self.timeout) {
+            self.next_expiry.get_or_insert(expiry);
+        }
+        let transfer = self
+            .transfers
+            .entry(key.clone())
+            .or_insert_with(|| Transfer {
+                chunks: BTreeMap::new(),
+                count,
+                len: 0,
+                last_chunk: now,
+            });
+        if transfer.count != count {
+            return Err(malformed());
+        }
+        transfer.last_chunk = now;
+        if transfer.chunks.contains_key(&index) {
+            return Ok(None);
+        }
+        transfer.len += chunk.len();
+        if let Some(limit) = self.max_len.filter(|&max_len| transfer.len > max_len) {
+            let size = transfer.len;
+            self.transfers.remove(&key);
+            return Err(Error::TransferTooLarge { size, limit });
+        }
+        transfer.chunks.insert(index, chunk[..].to_vec());
+        if transfer.chunks.len() < count {
+            return Ok(None);
+        }
+
+        let Some(transfer) = self.transfers.remove(&key) else {
+            return Ok(None);
+        };
+        let mut blob = Vec::with_capacity(transfer.len);
+        for chunk in transfer.chunks.into_values() {
+            blob.extend_from_slice(&chunk);
+        }
+        Ok(Some((key.0, blob)))
+    }
+
+    /// Drops the transfers which haven't received a chunk for the timeout, and works out when the
+    /// next one will expire.
+    fn drop_expired(&mut self, now: Instant) {
+        self.transfers
+            .retain(|_, transfer| now.duration_since(transfer.last_chunk) < self.timeout);
+        self.next_expiry = self
+            .transfers
+            .values()
+            .filter_map(|transfer| transfer.last_chunk.checked_add(self.timeout))
+            .min();
+    }
+
+    /// Returns how many transfers are partly received.
+    #[must_use]
+    pub fn pending(&self) -> usize {
+        self.transfers.len()
+    }
+}
@@ -1,25 +1,178 @@
 use std::{
-    collections::VecDeque,
-    io::ErrorKind,
-    net::ToSocketAddrs,
-    time::{Duration, Instant},
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, VecDeque},
+    io::{self, ErrorKind},
+    net::{SocketAddr, ToSocketAddrs},
+    sync::{mpsc, Arc},
+    thread,
+    time::{Duration, Instant, SystemTime},
 };
 
-use crate::{errors::Error, sockets::Connection, OscMessage};
+#[cfg(feature = "udp")]
+use std::net::{Ipv4Addr, UdpSocket};
+
+pub use crate::async_client::AsyncOscClient;
+#[cfg(feature = "chunking")]
+use crate::chunk;
+use crate::{
+    acl::AccessList,
+    address::OscAddress,
+    bundle::{flatten_into, OscBundle},
+    cancel::CancelFlag,
+    errors::Error,
+    message_ref::OscMessageRef,
+    pattern::OscPattern,
+    pool::BufferPool,
+    ramp::Ramp,
+    recording::{Observers, RawTap, Recorder},
+    router::Router,
+    schema::OscSchema,
+    sequence::{SequenceStats, SequenceStatus, SequenceTracker, Sequencer},
+    sockets::Connection,
+    sync::{channel, lock, Mutex, Receiver, RecvTimeoutError, Sender},
+    time::{ClockEstimate, OscTime},
+    Arg, Args, OscMessage, OscPacket,
+};
+
+/// Where clock sync pings are sent, and answered. See ``OscClient::estimate_clock``.
+const CLOCK_ADDRESS: &str = "/osc/clock";
+
+/// How long a cancellable wait blocks at a time before checking its ``CancelFlag``.
+const CANCEL_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// An ``OscClient`` over UDP, the transport most OSC devices expect.
+#[cfg(feature = "udp")]
+pub type UdpOscClient = OscClient<UdpSocket>;
+
+/// An ``OscClient`` over TCP, using OSC 1.0 length-prefix framing (see ``FramedTcpStream``) so
+/// message boundaries survive the byte stream.
+#[cfg(feature = "tcp")]
+pub type TcpOscClient = OscClient<crate::sockets::FramedTcpStream>;
+
+/// How an ``OscClient`` reconnects when its connection drops, e.g. because the device rebooted.
+/// The delay before each attempt is ``multiplier`` times the last, up to ``max_delay``. See
+/// ``OscClient::set_reconnect``.
+#[derive(Debug, Clone)]
+pub struct Reconnect {
+    /// How many attempts to make before giving up, or ``None`` to keep trying forever.
+    pub attempts: Option<u32>,
+    /// Delay before the first attempt.
+    pub initial_delay: Duration,
+    pub multiplier: f64,
+    pub max_delay: Duration,
+}
+
+impl Default for Reconnect {
+    fn default() -> Self {
+        Self {
+            attempts: Some(5),
+            initial_delay: Duration::from_millis(100),
+            multiplier: 2.0,
+            max_delay: Duration::from_secs(5),
+        }
+    }
+}
+
+/// Which message an ``OscClient`` drops when a message arrives while its queue is full. See
+/// ``OscClient::set_queue_capacity``.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Eviction {
+    /// Drop the oldest queued message to make room.
+    #[default]
+    DropOldest,
+    /// Drop the message which just arrived.
+    DropNewest,
+}
+
+/// A snapshot of a client's counters, for monitoring the link to a device. See
+/// ``OscClient::stats``.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClientStats {
+    pub packets_sent: u64,
+    pub bytes_sent: u64,
+    /// Packets received, whether or not they could be parsed.
+    pub packets_received: u64,
+    pub bytes_received: u64,
+    /// Received packets which could not be parsed.
+    pub parse_failures: u64,
+    /// Packets dropped because their source isn't permitted by the client's ``AccessList``.
+    pub packets_rejected: u64,
+    /// Batched messages replaced by a newer one to the same address before being sent. See
+    /// ``OscClient::set_coalescing``.
+    pub messages_coalesced: u64,
+    /// Number of replies whose round-trip time was measured.
+    pub round_trips: u64,
+    pub last_rtt: Option<Duration>,
+    pub min_rtt: Option<Duration>,
+    pub max_rtt: Option<Duration>,
+    /// Sum of every measured round-trip time, for ``mean_rtt``.
+    pub total_rtt: Duration,
+}
+
+impl ClientStats {
+    /// Returns the mean round-trip time, or ``None`` if none has been measured.
+    #[must_use]
+    pub fn mean_rtt(&self) -> Option<Duration> {
+        let round_trips = u32::try_from(self.round_trips).ok().filter(|&n| n > 0)?;
+        Some(self.total_rtt / round_trips)
+    }
+
+    fn round_trip(&mut self, rtt: Duration) {
+        self.round_trips += 1;
+        self.last_rtt = Some(rtt);
+        self.min_rtt = Some(self.min_rtt.map_or(rtt, |min| min.min(rtt)));
+        self.max_rtt = Some(self.max_rtt.map_or(rtt, |max| max.max(rtt)));
+        self.total_rtt += rtt;
+    }
+}
+
+/// Called with a message the client received unprompted (e.g. meter updates or parameter
+/// feedback) and the concrete address it was matched at. See ``OscClient::subscribe``.
+pub type Subscription = Box<dyn FnMut(&OscAddress, &OscMessage) + Send>;
 
 #[allow(clippy::module_name_repetitions)]
 #[derive(Clone)]
 pub struct OscClient<C: Connection> {
     connection: C,
+    remote_address: Vec<SocketAddr>,
+    reconnect: Option<Reconnect>,
     message_queue: VecDeque<OscMessage>,
-    timeout_secs: f32,
+    /// Messages from a received bundle which ``recv`` hasn't returned yet.
+    unpacked: VecDeque<OscMessage>,
+    flatten_bundles: bool,
+    sequence: Option<(Sequencer, SequenceTracker)>,
+    /// The most recent arguments received at each address, if caching them.
+    latest: Option<HashMap<String, Args>>,
+    access: AccessList,
+    cancel: Option<CancelFlag>,
+    schema: Option<OscSchema>,
+    queue_capacity: Option<usize>,
+    eviction: Eviction,
+    read_timeout: Option<Duration>,
+    timeout: Duration,
+    /// Whether the connection was put in non-blocking mode with ``set_nonblocking``.
+    nonblocking: bool,
     buffer: Vec<u8>,
+    max_buffer_size: Option<usize>,
+    max_datagram_size: Option<usize>,
+    pool: BufferPool,
+    batch_window: Option<Duration>,
+    batch: Vec<OscMessage>,
+    batch_started: Option<Instant>,
+    coalesce: bool,
+    subscriptions: Arc<Mutex<Router<Subscription>>>,
+    keepalive: Option<Sender<()>>,
+    scheduled: Option<Sender<(Instant, Vec<u8>)>>,
+    stats: ClientStats,
+    last_sent: Option<Instant>,
+    observers: Observers,
 }
 
 impl<C: Connection> OscClient<C> {
     /// Creates a new ``OscClient``, listening at ``client_address``, and connected to
     /// ``remote_address``. ``buffer_size`` dictates the maximum size message that the client can
-    /// receive (See ``recv`` docs).
+    /// receive (See ``recv`` docs), and ``timeout`` how long reads may block, which is also how
+    /// long ``wait_for`` waits (1 second if ``None``).
     ///
     /// # Errors
     /// If the connection cannot be made, or the read timeout cannot be set, this function will
@@ -28,72 +181,480 @@ impl<C: Connection> OscClient<C> {
         client_address: A,
         remote_address: B,
         buffer_size: usize,
-        timeout_secs: Option<f32>,
+        read_timeout: Option<Duration>,
+    ) -> Result<Self, Error> {
+        let remote_address: Vec<_> = remote_address
+            .to_socket_addrs()
+            .map_err(Error::Socket)?
+            .collect();
+        let connection = C::new(client_address, &remote_address[..]).map_err(Error::Socket)?;
+        Self::from_parts(connection, remote_address, buffer_size, read_timeout)
+    }
+
+    /// Creates an ``OscClient`` over an existing ``connection``, e.g. a ``TlsConnection`` with a
+    /// custom configuration. Other arguments are as for ``new``. The client doesn't know the
+    /// remote address, so it can't reconnect (see ``set_reconnect``) until one is set with
+    /// ``set_remote_address``.
+    ///
+    /// # Errors
+    /// If the read timeout cannot be set, this function will return an ``Error::Socket``.
+    pub fn from_connection(
+        connection: C,
+        buffer_size: usize,
+        read_timeout: Option<Duration>,
+    ) -> Result<Self, Error> {
+        Self::from_parts(connection, Vec::new(), buffer_size, read_timeout)
+    }
+
+    fn from_parts(
+        connection: C,
+        remote_address: Vec<SocketAddr>,
+        buffer_size: usize,
+        read_timeout: Option<Duration>,
     ) -> Result<Self, Error> {
-        let connection = C::new(client_address, remote_address).map_err(Error::Socket)?;
         connection
-            .set_read_timeout(timeout_secs.map(Duration::from_secs_f32))
+            .set_read_timeout(read_timeout)
             .map_err(Error::Socket)?;
         Ok(Self {
             connection,
+            remote_address,
+            reconnect: None,
             message_queue: VecDeque::new(),
-            timeout_secs: timeout_secs.unwrap_or(1.0),
+            unpacked: VecDeque::new(),
+            flatten_bundles: false,
+            sequence: None,
+            latest: None,
+            access: AccessList::new(),
+            cancel: None,
+            schema: None,
+            queue_capacity: None,
+            eviction: Eviction::default(),
+            read_timeout,
+            timeout: read_timeout.unwrap_or(Duration::from_secs(1)),
+            nonblocking: false,
             buffer: vec![0; buffer_size],
+            max_buffer_size: None,
+            max_datagram_size: None,
+            pool: BufferPool::default(),
+            batch_window: None,
+            batch: Vec::new(),
+            batch_started: None,
+            coalesce: false,
+            subscriptions: Arc::default(),
+            keepalive: None,
+            scheduled: None,
+            stats: ClientStats::default(),
+            last_sent: None,
+            observers: Observers::default(),
         })
     }
 
     /// Sends ``message`` over client's underlying connection.
     ///
+    /// When batching (see ``set_batching``), ``message`` is queued instead, and this returns 0
+    /// unless the batching window has passed, in which case the batch is sent and the size of it
+    /// returned.
+    ///
     /// # Errors
     /// Will return ``Err`` if ``message.build`` (see relevant docs), or if the connection fails
-    /// to send ``message``, will return an ``Error::Socket``
+    /// to send ``message``, will return an ``Error::Socket``. If ``message`` is larger than the
+    /// datagram limit (see ``set_max_datagram_size``), will return ``Error::MessageTooLarge``.
+    /// If it doesn't match the schema (see ``set_schema``), will return ``Error::Schema``.
     pub fn send(&mut self, messsage: &OscMessage) -> Result<usize, Error> {
-        self.connection
-            .send(&messsage.build()?)
-            .map_err(Error::Socket)
+        if let Some(schema) = &self.schema {
+            schema.check(messsage)?;
+        }
+        if let Some((sequencer, _)) = &mut self.sequence {
+            let mut messsage = messsage.clone();
+            sequencer.stamp(&mut messsage);
+            return self.send_message(&messsage);
+        }
+        self.send_message(messsage)
+    }
+
+    fn send_message(&mut self, messsage: &OscMessage) -> Result<usize, Error> {
+        let Some(window) = self.batch_window else {
+            let pool = self.pool.clone();
+            let mut bytes = pool.take();
+            messsage.build_into(&mut bytes)?;
+            return self.send_bytes(&bytes);
+        };
+        let started = *self.batch_started.get_or_insert_with(Instant::now);
+        let queued = self
+            .batch
+            .iter_mut()
+            .find(|queued| self.coalesce && queued.address == messsage.address);
+        match queued {
+            Some(queued) => {
+                queued.clone_from(messsage);
+                self.stats.messages_coalesced += 1;
+            }
+            None => self.batch.push(messsage.clone()),
+        }
+        if started.elapsed() >= window {
+            return self.flush();
+        }
+        Ok(0)
+    }
+
+    /// Sends ``messages`` together in one bundle, which the receiver should act on at
+    /// ``timetag`` (or straight away, for ``OscTime::IMMEDIATE``). Any batched messages are
+    /// sent first.
+    ///
+    /// # Errors
+    /// Will return ``Err`` if building the bundle fails (see ``OscBundle::build``), or an
+    /// ``Error::Socket`` if the connection fails to send it.
+    pub fn send_bundle(
+        &mut self,
+        messages: &[OscMessage],
+        timetag: OscTime,
+    ) -> Result<usize, Error> {
+        if let Some(schema) = &self.schema {
+            messages
+                .iter()
+                .try_for_each(|message| schema.check(message))?;
+        }
+        self.flush()?;
+        let content = messages
+            .iter()
+            .cloned()
+            .map(|mut message| {
+                if let Some((sequencer, _)) = &mut self.sequence {
+                    sequencer.stamp(&mut message);
+                }
+                OscPacket::Message(message)
+            })
+            .collect();
+        self.send_packet(&OscBundle::new(timetag, content).into())
+    }
+
+    /// Sends ``message`` at ``at`` from a background thread, e.g. to line up a cue's parameter
+    /// changes in advance. Messages due at the same time are sent in the order they were
+    /// scheduled. Unlike sending a bundle with a future timetag, this doesn't rely on the device
+    /// to hold the message.
+    ///
+    /// The thread sends over a clone of the connection made with ``try_clone``, ignoring failed
+    /// sends, and is started by the first scheduled send. Dropping the client, or calling
+    /// ``cancel_scheduled``, cancels sends which are not yet due.
+    ///
+    /// # Errors
+    /// Will return ``Err`` if building ``message`` fails, or an ``Error::Socket`` if cloning the
+    /// connection fails.
+    #[cfg(not(target_family = "wasm"))]
+    pub fn send_at(&mut self, message: &OscMessage, at: Instant) -> Result<(), Error>
+    where
+        C: Send + 'static,
+    {
+        let bytes = message.build()?;
+        self.check_datagram_size(bytes.len())?;
+        if self.scheduled.is_none() {
+            let connection = self.connection.try_clone().map_err(Error::Socket)?;
+            let (schedule, scheduled) = channel();
+            let observers = self.observers.clone();
+            let peer = self.remote_address.first().copied();
+            thread::spawn(move || send_scheduled(connection, &scheduled, &observers, peer));
+            self.scheduled = Some(schedule);
+        }
+        if let Some(schedule) = &self.scheduled {
+            // The thread only stops once this sender is dropped.
+            let _ = schedule.send((at, bytes));
+        }
+        Ok(())
+    }
+
+    /// Sends ``message`` once ``delay`` has passed. See ``send_at``.
+    ///
+    /// # Errors
+    /// See ``send_at``.
+    #[cfg(not(target_family = "wasm"))]
+    pub fn send_after(&mut self, message: &OscMessage, delay: Duration) -> Result<(), Error>
+    where
+        C: Send + 'static,
+    {
+        self.send_at(message, Instant::now() + delay)
+    }
+
+    /// Schedules every message of ``ramp`` with ``send_at``, starting now, e.g. to fade a fader
+    /// out without a timing loop of your own. ``cancel_scheduled`` stops the fade where it is.
+    ///
+    /// # Errors
+    /// See ``send_at``. Messages scheduled before an error are still sent.
+    #[cfg(not(target_family = "wasm"))]
+    pub fn send_ramp(&mut self, ramp: &Ramp) -> Result<(), Error>
+    where
+        C: Send + 'static,
+    {
+        let start = Instant::now();
+        for (offset, message) in ramp.steps() {
+            self.send_at(&message, start + offset)?;
+        }
+        Ok(())
+    }
+
+    /// Cancels every send scheduled by ``send_at`` which is not yet due.
+    pub fn cancel_scheduled(&mut self) {
+        self.scheduled = None;
+    }
+
+    /// Sends each of ``messages`` as for ``send``, e.g. to recall a scene's hundreds of
+    /// parameters, returning the result of sending each. A failure doesn't stop the rest from
+    /// being sent. Use ``send_bundle`` to send them in one packet instead, or ``set_batching`` to
+    /// coalesce them into as few packets as the batching window allows.
+    pub fn send_all(&mut self, messages: &[OscMessage]) -> Vec<Result<usize, Error>> {
+        messages.iter().map(|message| self.send(message)).collect()
+    }
+
+    /// Coalesces messages passed to ``send`` into bundles, sending each batch once ``window``
+    /// has passed since its first message was queued, e.g. so that setting many parameters each
+    /// frame takes one packet rather than one per parameter. ``None`` stops batching, sending any
+    /// messages already queued.
+    ///
+    /// Batches are only sent by calls on the client: ``send``, ``flush``, and calls that wait
+    /// for a reply, which flush first so the request goes out.
+    ///
+    /// # Errors
+    /// Will return an ``Error::Socket`` if stopping batching fails to send the queued messages.
+    pub fn set_batching(&mut self, window: Option<Duration>) -> Result<(), Error> {
+        self.coalesce = false;
+        self.batch_window = window;
+        if window.is_none() {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Batches messages as ``set_batching`` does, but keeps only the newest message queued for
+    /// each address, e.g. so a fader being dragged sends its latest level once per ``window``
+    /// rather than every intermediate value. A newer message takes the place of the older one in
+    /// the batch, and is counted in ``ClientStats::messages_coalesced``. ``None`` stops
+    /// batching, sending any messages already queued, and ``set_batching`` stops coalescing.
+    ///
+    /// Messages are compared by address alone, so don't coalesce messages whose arguments
+    /// select what they change, such as ``/set "gain" 0.5``.
+    ///
+    /// # Errors
+    /// Will return an ``Error::Socket`` if stopping fails to send the queued messages.
+    pub fn set_coalescing(&mut self, window: Option<Duration>) -> Result<(), Error> {
+        self.set_batching(window)?;
+        self.coalesce = window.is_some();
+        Ok(())
+    }
+
+    /// Sends any messages queued by batching straight away, as a single bundle (or as a plain
+    /// message, if only one is queued). Returns the size of the data sent.
+    ///
+    /// # Errors
+    /// Will return ``Err`` if building the batch fails, or an ``Error::Socket`` if the
+    /// connection fails to send it. The batch is dropped either way.
+    pub fn flush(&mut self) -> Result<usize, Error> {
+        self.batch_started = None;
+        let packet = match self.batch.len() {
+            0 => return Ok(0),
+            1 => OscPacket::Message(self.batch.remove(0)),
+            _ => OscPacket::Bundle(OscBundle::new(
+                OscTime::IMMEDIATE,
+                self.batch.drain(..).map(OscPacket::Message).collect(),
+            )),
+        };
+        self.send_packet(&packet)
+    }
+
+    /// Encodes ``packet`` into a buffer from the pool and sends it.
+    fn send_packet(&mut self, packet: &OscPacket) -> Result<usize, Error> {
+        let pool = self.pool.clone();
+        let mut bytes = pool.take();
+        packet.build_into(&mut bytes)?;
+        self.send_bytes(&bytes)
+    }
+
+    /// Sends ``blob`` to ``address`` split into chunks which each fit the datagram limit (see
+    /// ``set_max_datagram_size``, or ``chunk::DEFAULT_DATAGRAM_SIZE`` if there is none), for a
+    /// ``ChunkAssembler`` to put back together. Returns the total size sent. Batched messages are
+    /// sent first, and the chunks are never batched.
+    ///
+    /// # Errors
+    /// Will return any error from ``chunk::split``, or an ``Error::Socket`` if sending a chunk
+    /// fails, in which case the chunks after it aren't sent.
+    #[cfg(feature = "chunking")]
+    pub fn send_chunked(&mut self, address: &str, blob: &[u8]) -> Result<usize, Error> {
+        self.flush()?;
+        let limit = self
+            .max_datagram_size
+            .unwrap_or(chunk::DEFAULT_DATAGRAM_SIZE);
+        let pool = self.pool.clone();
+        let mut size = 0;
+        for message in chunk::split(address, chunk::next_transfer_id(), blob, limit)? {
+            let mut bytes = pool.take();
+            message.build_into(&mut bytes)?;
+            size += self.send_bytes(&bytes)?;
+        }
+        Ok(size)
     }
 
     /// Sends raw bytes. This function may be useful if your target does not implement standard
     /// OSC, and so would not understand/respond to regular ``send``.
     ///
     /// # Errors
-    /// Will return an ``Error::Socket`` if sending the data fails.
+    /// Will return an ``Error::Socket`` if sending the data fails, or
+    /// ``Error::MessageTooLarge`` if it's larger than the datagram limit (see
+    /// ``set_max_datagram_size``).
     pub fn send_bytes(&mut self, bytes: &[u8]) -> Result<usize, Error> {
-        self.connection.send(bytes).map_err(Error::Socket)
+        self.check_datagram_size(bytes.len())?;
+        let size = match self.connection.send(bytes) {
+            Err(e) if self.reconnect.is_some() && is_disconnect(&e) => {
+                self.reconnect()?;
+                self.connection.send(bytes).map_err(Error::Socket)?
+            }
+            res => res.map_err(Error::Socket)?,
+        };
+        self.record_sent(size);
+        self.observers
+            .sent(bytes, self.remote_address.first().copied());
+        Ok(size)
+    }
+
+    /// Returns ``Error::MessageTooLarge`` if a packet of ``size`` bytes exceeds
+    /// ``self.max_datagram_size``.
+    fn check_datagram_size(&self, size: usize) -> Result<(), Error> {
+        match self.max_datagram_size {
+            Some(limit) if size > limit => Err(Error::MessageTooLarge { size, limit }),
+            _ => Ok(()),
+        }
+    }
+
+    fn record_sent(&mut self, size: usize) {
+        self.stats.packets_sent += 1;
+        self.stats.bytes_sent += size as u64;
+        self.last_sent = Some(Instant::now());
+    }
+
+    /// Parses the ``size`` bytes just received into the buffer. If ``flatten``, the messages in a
+    /// bundle are added to ``self.unpacked`` instead, and the first returned.
+    fn parse_received(&mut self, size: usize, flatten: bool) -> Result<Option<OscMessage>, Error> {
+        if flatten {
+            let packet = self.count_received(size, OscPacket::parse_bytes(&self.buffer[..size]))?;
+            flatten_into(packet, &mut self.unpacked);
+            Ok(self.unpacked.pop_front())
+        } else {
+            self.count_received(size, OscMessage::parse_bytes(&self.buffer[..size]))
+                .map(Some)
+        }
+    }
+
+    /// Counts a received packet of ``size`` bytes, and whether it was ``parsed``, in the stats.
+    fn count_received<T>(&mut self, size: usize, parsed: Result<T, Error>) -> Result<T, Error> {
+        self.stats.packets_received += 1;
+        self.stats.bytes_received += size as u64;
+        if parsed.is_err() {
+            self.stats.parse_failures += 1;
+        }
+        parsed
     }
 
     // This returns "Error: Resource temporarily unavailable" if `buf` cannot
     // fit the message
-    /// Receives data and parses it into an ``OscMessage``
+    /// Receives data and parses it into an ``OscMessage``. Any batched messages are sent first.
     ///
     /// # Errors
     /// If no data is ready to be received, or ``self.buffer`` is too small to contain the full
-    /// message, this function will return an ``Error::Socket`` containing an error of kind
-    /// ``io::ErrorKind::WouldBlock``.
+    /// message (and can't grow, see ``set_max_buffer_size``), this function will return an
+    /// ``Error::Socket`` containing an error of kind ``io::ErrorKind::WouldBlock``.
     /// Will also error if ``OscMessage::parse_bytes`` fails. See ``parse_bytes`` docs.
     pub fn recv(&mut self) -> Result<OscMessage, Error> {
-        self.connection
-            .recv(&mut self.buffer)
-            .map_err(Error::Socket)?;
-        OscMessage::parse_bytes(&self.buffer)
+        loop {
+            let mut msg = if let Some(msg) = self.unpacked.pop_front() {
+                msg
+            } else {
+                let size = self.receive()?;
+                // An empty bundle holds no message to return
+                let Some(msg) = self.parse_received(size, self.flatten_bundles)? else {
+                    continue;
+                };
+                msg
+            };
+            let status = match &mut self.sequence {
+                Some((_, tracker)) => tracker.check(&mut msg),
+                None => SequenceStatus::Unsequenced,
+            };
+            if status != SequenceStatus::Duplicate {
+                if let Some(latest) = &mut self.latest {
+                    latest.insert(msg.address.clone(), msg.args.clone());
+                }
+                return Ok(msg);
+            }
+        }
+    }
+
+    /// Receives a packet as ``recv`` does, returning its raw bytes without parsing them, e.g. for
+    /// a device which doesn't send standard OSC. The length of the slice is the size of the
+    /// packet.
+    ///
+    /// # Errors
+    /// Will return an ``Error::Socket`` if receiving fails. See ``recv``.
+    pub fn recv_bytes(&mut self) -> Result<&[u8], Error> {
+        let size = self.receive()?;
+        self.stats.packets_received += 1;
+        self.stats.bytes_received += size as u64;
+        Ok(&self.buffer[..size])
+    }
+
+    /// Receives a message as ``recv`` does, but parses it without copying into an
+    /// ``OscMessageRef`` borrowing the client's buffer, which must be dropped before the next
+    /// receive.
+    ///
+    /// # Errors
+    /// See ``recv``.
+    pub fn recv_ref(&mut self) -> Result<OscMessageRef<'_>, Error> {
+        let size = self.receive()?;
+        self.stats.packets_received += 1;
+        self.stats.bytes_received += size as u64;
+        let res = OscMessageRef::parse_bytes(&self.buffer[..size]);
+        if res.is_err() {
+            self.stats.parse_failures += 1;
+        }
+        res
+    }
+
+    /// Sends any batched messages, then receives a packet into ``self.buffer``, returning its
+    /// size.
+    fn receive(&mut self) -> Result<usize, Error> {
+        self.flush()?;
+        self.grow_buffer();
+        loop {
+            match self.connection.recv_with_source(&mut self.buffer) {
+                Ok((_, Some(source))) if !self.access.permits(source.ip()) => {
+                    self.stats.packets_rejected += 1;
+                }
+                Ok((size, source)) => {
+                    let peer = source.or_else(|| self.remote_address.first().copied());
+                    self.observers.received(&self.buffer[..size], peer);
+                    return Ok(size);
+                }
+                Err(e) if self.reconnect.is_some() && is_disconnect(&e) => {
+                    self.reconnect()?;
+                    return Err(Error::Socket(e));
+                }
+                Err(e) => return Err(Error::Socket(e)),
+            }
+        }
     }
 
     fn handle_waiting_errors(
         &mut self,
         res: Result<OscMessage, Error>,
-        addr: &impl ToString,
+        wanted: &mut impl FnMut(&OscMessage) -> bool,
     ) -> Result<Option<OscMessage>, Error> {
         match res {
             Ok(msg) => {
-                if msg.address == addr.to_string() {
+                if wanted(&msg) {
                     return Ok(Some(msg));
                 }
 
-                self.message_queue.push_back(msg);
+                self.enqueue(msg);
                 Ok(None)
             }
             Err(Error::Socket(e)) => match e.kind() {
-                ErrorKind::WouldBlock => Ok(None),
+                ErrorKind::WouldBlock | ErrorKind::TimedOut => Ok(None),
                 _ => Err(Error::Socket(e)),
             },
             Err(e) => Err(e),
@@ -102,40 +663,812 @@ impl<C: Connection> OscClient<C> {
 
     /// Wait to receive data meant for ``addr``.
     ///
+    /// Equivalent to ``wait_for_until(addr, Instant::now() + timeout)``, where ``timeout`` is the
+    /// timeout the client was created with (or 1 second if it was created without one).
+    ///
     /// # Errors
-    /// Will return ``Err(Error::Socket(io::Error.kind() == ErrorKind::TimedOut))`` if waiting for
-    /// data takes longer than ``self.timeout_secs``
-    /// Will also return ``Err(Error::Socket)`` if the call to ``connection.recv`` returns an error
-    /// other than ``io::Error::WouldBlock``
+    /// See ``wait_for_until``.
     #[allow(clippy::needless_pass_by_value)]
     pub fn wait_for(&mut self, addr: impl ToString) -> Result<OscMessage, Error> {
-        for i in 0..self.message_queue.len() {
-            if self.message_queue[i].address == addr.to_string() {
-                let msg = unsafe { self.message_queue.remove(i).unwrap_unchecked() };
-                return Ok(msg);
-            }
-        }
+        let deadline = Instant::now() + self.timeout;
+        self.wait_for_until(addr, deadline)
+    }
+
+    /// Wait to receive data meant for ``addr``, for up to ``timeout`` instead of the client's
+    /// timeout, e.g. for a reply which takes longer than usual, like loading a scene.
+    ///
+    /// # Errors
+    /// See ``wait_for_until``.
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn wait_for_timeout(
+        &mut self,
+        addr: impl ToString,
+        timeout: Duration,
+    ) -> Result<OscMessage, Error> {
+        self.wait_for_until(addr, Instant::now() + timeout)
+    }
+
+    /// Wait to receive data meant for ``addr``, giving up once ``deadline`` has passed. Messages
+    /// for other addresses received in the meantime are queued for later calls.
+    ///
+    /// The connection's read timeout is set to the time remaining before ``deadline`` on every
+    /// iteration, so the wait blocks in the socket rather than spinning, and is restored
+    /// afterwards.
+    ///
+    /// # Errors
+    /// Will return ``Err(Error::Socket(io::Error.kind() == ErrorKind::TimedOut))`` if ``deadline``
+    /// passes before a matching message arrives.
+    /// Will also return ``Err(Error::Socket)`` if the call to ``connection.recv`` returns an error
+    /// other than ``io::Error::WouldBlock``, or if the read timeout cannot be set.
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn wait_for_until(
+        &mut self,
+        addr: impl ToString,
+        deadline: Instant,
+    ) -> Result<OscMessage, Error> {
+        let addr = addr.to_string();
+        self.wait_for_where_until(|msg| msg.address == addr, deadline)
+    }
+
+    /// Wait to receive data at any address matching the OSC address pattern ``pattern``, e.g.
+    /// ``/ch/*/mix/fader`` for a device which replies on an address including the channel.
+    /// Otherwise behaves as ``wait_for``.
+    ///
+    /// # Errors
+    /// Will return ``Error::Address`` if ``pattern`` is not a valid ``OscPattern``. Otherwise,
+    /// see ``wait_for_until``.
+    pub fn wait_for_matching<P>(&mut self, pattern: P) -> Result<OscMessage, Error>
+    where
+        P: TryInto<OscPattern>,
+        Error: From<P::Error>,
+    {
+        let pattern = pattern.try_into()?;
+        self.wait_for_where(|msg| crate::pattern::matches(pattern.as_str(), &msg.address))
+    }
+
+    /// Wait to receive a message for which ``predicate`` returns ``true``, e.g. one carrying a
+    /// particular argument. Otherwise behaves as ``wait_for``.
+    ///
+    /// # Errors
+    /// See ``wait_for_until``.
+    pub fn wait_for_where<F>(&mut self, predicate: F) -> Result<OscMessage, Error>
+    where
+        F: FnMut(&OscMessage) -> bool,
+    {
+        let deadline = Instant::now() + self.timeout;
+        self.wait_for_where_until(predicate, deadline)
+    }
 
-        let rec = self.recv();
-        if let Some(msg) = self.handle_waiting_errors(rec, &addr)? {
+    /// Like ``wait_for_where``, but giving up once ``deadline`` has passed, as for
+    /// ``wait_for_until``.
+    ///
+    /// # Errors
+    /// See ``wait_for_until``.
+    pub fn wait_for_where_until<F>(
+        &mut self,
+        mut predicate: F,
+        deadline: Instant,
+    ) -> Result<OscMessage, Error>
+    where
+        F: FnMut(&OscMessage) -> bool,
+    {
+        if let Some(msg) = self
+            .message_queue
+            .iter()
+            .position(&mut predicate)
+            .and_then(|i| self.message_queue.remove(i))
+        {
             return Ok(msg);
         }
+        self.receive_until(predicate, deadline)
+    }
+
+    /// Sends ``message`` and waits up to the client's timeout for the reply to it, which is
+    /// expected at the same address (as with most devices' queries). Unrelated messages received
+    /// in the meantime are queued for later calls.
+    ///
+    /// Only messages received after sending count as the reply, so a message which had already
+    /// arrived for the address (e.g. an earlier, unsolicited update) isn't mistaken for it.
+    /// Replies to earlier messages which are still in flight can't be told apart, though.
+    ///
+    /// # Errors
+    /// Will return any error from ``send``, or as for ``wait_for_until`` if no reply arrives in
+    /// time.
+    pub fn call(&mut self, message: &OscMessage) -> Result<OscMessage, Error> {
+        self.call_expecting(message, &message.address)
+    }
+
+    /// Like ``call``, but for devices which reply at a different address, ``reply_addr``.
+    ///
+    /// # Errors
+    /// See ``call``.
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn call_expecting(
+        &mut self,
+        message: &OscMessage,
+        reply_addr: impl ToString,
+    ) -> Result<OscMessage, Error> {
+        let deadline = Instant::now() + self.timeout;
+        let reply_addr = reply_addr.to_string();
+        self.queue_received()?;
+        self.send(message)?;
+        self.receive_until(|msg| msg.address == reply_addr, deadline)
+    }
 
-        let loop_start = Instant::now();
+    /// Sends ``message`` until an acknowledgement arrives at ``ack_addr``, returning it, for
+    /// devices on lossy networks (such as busy venue Wi-Fi) which acknowledge what they're sent.
+    ///
+    /// After each send, waits for the acknowledgement for ``backoff``, doubling each time, and
+    /// gives up after ``retries`` resends. As for ``call``, only messages received after the first
+    /// send count as the acknowledgement.
+    ///
+    /// # Errors
+    /// Will return ``Err(Error::Socket(io::Error.kind() == ErrorKind::TimedOut))`` if no
+    /// acknowledgement arrives after the last attempt, or any error from ``send``.
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn send_reliable(
+        &mut self,
+        message: &OscMessage,
+        ack_addr: impl ToString,
+        retries: u32,
+        backoff: Duration,
+    ) -> Result<OscMessage, Error> {
+        let ack_addr = ack_addr.to_string();
+        let mut wait = backoff;
+        self.queue_received()?;
+        let mut attempt = 0;
         loop {
-            let rec = self.recv();
-            if let Some(msg) = self.handle_waiting_errors(rec, &addr)? {
-                return Ok(msg);
+            self.send(message)?;
+            match self.receive_until(|msg| msg.address == ack_addr, Instant::now() + wait) {
+                Err(Error::Socket(e)) if e.kind() == ErrorKind::TimedOut && attempt < retries => {
+                    attempt += 1;
+                    wait = wait.saturating_mul(2);
+                }
+                res => return res,
             }
+        }
+    }
 
-            let duration = loop_start.elapsed().as_secs_f32();
-            if duration >= self.timeout_secs {
-                return Err(Error::Socket(std::io::Error::new(
+    /// Estimates the remote's clock relative to ours by sending ``samples`` timetagged pings to
+    /// ``/osc/clock``, as answered by a server with ``with_clock_sync``, and keeping the one with
+    /// the shortest round trip, whose timings were least disturbed by the network. Each ping
+    /// waits up to the client's timeout for its reply.
+    ///
+    /// # Errors
+    /// Will return ``Err(Error::Socket(io::Error.kind() == ErrorKind::TimedOut))`` if a reply
+    /// doesn't arrive in time, ``Error::Malformed`` if it doesn't carry three timetags, or any
+    /// error from ``send``.
+    pub fn estimate_clock(&mut self, samples: usize) -> Result<ClockEstimate, Error> {
+        self.queue_received()?;
+        let mut best = self.clock_sample()?;
+        for _ in 1..samples {
+            let estimate = self.clock_sample()?;
+            if estimate.round_trip < best.round_trip {
+                best = estimate;
+            }
+        }
+        Ok(best)
+    }
+
+    /// Sends one ping for ``estimate_clock`` and estimates the clock from its reply.
+    fn clock_sample(&mut self) -> Result<ClockEstimate, Error> {
+        let deadline = Instant::now() + self.timeout;
+        let sent = OscTime::from(SystemTime::now());
+        self.send(&OscMessage::new(CLOCK_ADDRESS, vec![Arg::Time(sent)]))?;
+        let reply = self.receive_until(
+            |msg| {
+                msg.address == CLOCK_ADDRESS
+                    && matches!(msg.args.first(), Some(Arg::Time(time)) if *time == sent)
+            },
+            deadline,
+        )?;
+        let received = OscTime::from(SystemTime::now());
+        let [_, Arg::Time(remote_received), Arg::Time(remote_sent), ..] = reply.args[..] else {
+            return Err(Error::Malformed("Clock sync reply".to_string()));
+        };
+        Ok(ClockEstimate::from_exchange(
+            sent,
+            remote_received,
+            remote_sent,
+            received,
+        ))
+    }
+
+    /// Returns the messages queued by ``wait_for`` and similar calls while they waited for
+    /// another address, oldest first.
+    pub fn pending(&self) -> impl Iterator<Item = &OscMessage> {
+        self.message_queue.iter()
+    }
+
+    /// Removes and returns every queued message, oldest first, without receiving any more. See
+    /// ``try_iter`` to receive messages which have already arrived too.
+    pub fn drain_pending(&mut self) -> impl Iterator<Item = OscMessage> + '_ {
+        self.message_queue.drain(..)
+    }
+
+    /// Limits the queue to ``capacity`` messages, or lifts the limit if it is ``None``, as by
+    /// default, so a chatty device can't grow it without bound. When a message arrives while the
+    /// queue is full, ``eviction`` decides which is dropped. Messages beyond a new capacity are
+    /// dropped straight away.
+    pub fn set_queue_capacity(&mut self, capacity: Option<usize>, eviction: Eviction) {
+        self.queue_capacity = capacity;
+        self.eviction = eviction;
+        if let Some(capacity) = capacity {
+            let excess = self.message_queue.len().saturating_sub(capacity);
+            match eviction {
+                Eviction::DropOldest => drop(self.message_queue.drain(..excess)),
+                Eviction::DropNewest => self.message_queue.truncate(capacity),
+            }
+        }
+    }
+
+    /// Returns the oldest queued message, or else a message which has already arrived, without
+    /// waiting. Returns ``None`` if there is neither.
+    ///
+    /// # Errors
+    /// Will return an ``Error::Socket`` if receiving fails, or an error from
+    /// ``OscMessage::parse_bytes`` if the message can't be parsed.
+    pub fn try_recv(&mut self) -> Result<Option<OscMessage>, Error> {
+        if let Some(msg) = self.message_queue.pop_front() {
+            return Ok(Some(msg));
+        }
+        self.connection
+            .set_nonblocking(true)
+            .map_err(Error::Socket)?;
+        let res = match self.recv() {
+            Ok(msg) => Ok(Some(msg)),
+            Err(Error::Socket(e)) if e.kind() == ErrorKind::WouldBlock => Ok(None),
+            Err(e) => Err(e),
+        };
+        self.connection
+            .set_nonblocking(self.nonblocking)
+            .map_err(Error::Socket)?;
+        res
+    }
+
+    /// Queues ``msg`` for later calls, dropping a message if the queue is full.
+    fn enqueue(&mut self, msg: OscMessage) {
+        let full = self
+            .queue_capacity
+            .is_some_and(|capacity| self.message_queue.len() >= capacity);
+        if full {
+            if self.eviction == Eviction::DropNewest || self.queue_capacity == Some(0) {
+                return;
+            }
+            self.message_queue.pop_front();
+        }
+        self.message_queue.push_back(msg);
+    }
+
+    /// Returns every message which has already arrived, queued or not, without waiting for more,
+    /// e.g. to poll for messages once per frame of a render loop. The messages are removed from
+    /// the queue as the iterator yields them.
+    ///
+    /// # Errors
+    /// Will return an ``Error::Socket`` if receiving fails, or an error from
+    /// ``OscMessage::parse_bytes`` if a message can't be parsed. Messages received before the
+    /// error stay queued for the next call.
+    pub fn try_iter(&mut self) -> Result<impl Iterator<Item = OscMessage> + '_, Error> {
+        self.queue_received()?;
+        Ok(self.message_queue.drain(..))
+    }
+
+    /// Queues every message which has already arrived, without waiting for more.
+    fn queue_received(&mut self) -> Result<(), Error> {
+        self.connection
+            .set_nonblocking(true)
+            .map_err(Error::Socket)?;
+        let res = loop {
+            match self.recv() {
+                Ok(msg) => self.enqueue(msg),
+                Err(Error::Socket(e)) if e.kind() == ErrorKind::WouldBlock => break Ok(()),
+                Err(e) => break Err(e),
+            }
+        };
+        self.connection
+            .set_nonblocking(self.nonblocking)
+            .map_err(Error::Socket)?;
+        res
+    }
+
+    /// Receives messages until one ``wanted`` returns ``true`` for arrives, ignoring the queue.
+    fn receive_until(
+        &mut self,
+        mut wanted: impl FnMut(&OscMessage) -> bool,
+        deadline: Instant,
+    ) -> Result<OscMessage, Error> {
+        let start = Instant::now();
+        let res = loop {
+            if self.cancel.as_ref().is_some_and(CancelFlag::is_cancelled) {
+                break Err(Error::Socket(std::io::Error::new(
+                    ErrorKind::Interrupted,
+                    "Waiting for data was cancelled",
+                )));
+            }
+            let mut remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                break Err(Error::Socket(std::io::Error::new(
                     ErrorKind::TimedOut,
-                    format!("Waiting for data timed out after {duration} seconds"),
+                    format!(
+                        "Waiting for data timed out after {} seconds",
+                        start.elapsed().as_secs_f32()
+                    ),
                 )));
             }
+            if self.cancel.is_some() {
+                remaining = remaining.min(CANCEL_POLL_INTERVAL);
+            }
+            if let Err(e) = self.connection.set_read_timeout(Some(remaining)) {
+                break Err(Error::Socket(e));
+            }
+
+            let rec = self.recv();
+            match self.handle_waiting_errors(rec, &mut wanted) {
+                Ok(Some(msg)) => {
+                    if let Some(sent) = self.last_sent {
+                        self.stats.round_trip(sent.elapsed());
+                    }
+                    break Ok(msg);
+                }
+                Ok(None) => {}
+                Err(e) => break Err(e),
+            }
+        };
+
+        self.connection
+            .set_read_timeout(self.read_timeout)
+            .map_err(Error::Socket)?;
+        res
+    }
+
+    /// Collects a burst of messages meant for ``addr``, such as a device answering one query with
+    /// a message per channel.
+    ///
+    /// Waits up to the client's timeout for the first matching message, then keeps collecting
+    /// until no further matching message arrives within ``quiet_period``, or the timeout passes.
+    /// Matching messages already in the queue are included first.
+    ///
+    /// # Errors
+    /// Will return ``Err(Error::Socket(io::Error.kind() == ErrorKind::TimedOut))`` if no matching
+    /// message arrives before the timeout. Other errors are as for ``wait_for_until``.
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn recv_all_for(
+        &mut self,
+        addr: impl ToString,
+        quiet_period: Duration,
+    ) -> Result<Vec<OscMessage>, Error> {
+        let addr = addr.to_string();
+        let deadline = Instant::now() + self.timeout;
+        let mut msgs = vec![self.wait_for_until(&addr, deadline)?];
+
+        loop {
+            let until = deadline.min(Instant::now() + quiet_period);
+            match self.wait_for_until(&addr, until) {
+                Ok(msg) => msgs.push(msg),
+                Err(Error::Socket(e)) if e.kind() == ErrorKind::TimedOut => return Ok(msgs),
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Registers ``callback`` for messages received at any address matching ``pattern``, e.g.
+    /// ``"/meters/*"``. Subscribed messages are only handed to callbacks by ``dispatch_pending``
+    /// and ``run_dispatch``; until then they wait in the queue like any other message.
+    ///
+    /// Clones made with ``clone`` share their subscriptions, while ``try_clone`` starts without
+    /// any.
+    ///
+    /// # Errors
+    /// Will return ``Error::Address`` if ``pattern`` is not a valid ``OscPattern``, or
+    /// ``Error::DuplicateRoute`` if a callback is already registered for it.
+    pub fn subscribe<P, F>(&mut self, pattern: P, callback: F) -> Result<(), Error>
+    where
+        P: TryInto<OscPattern>,
+        Error: From<P::Error>,
+        F: FnMut(&OscAddress, &OscMessage) + Send + 'static,
+    {
+        lock(&self.subscriptions).insert_route(pattern, Box::new(callback))
+    }
+
+    /// Removes the callback registered for ``pattern``, returning whether there was one.
+    pub fn unsubscribe(&mut self, pattern: &str) -> bool {
+        lock(&self.subscriptions).remove_route(pattern).is_some()
+    }
+
+    /// Hands every message which has already arrived, queued or not, to the callbacks subscribed
+    /// to its address, without waiting for more. Messages no callback is subscribed to are left in
+    /// the queue for ``wait_for``.
+    ///
+    /// Returns the number of messages dispatched.
+    ///
+    /// # Errors
+    /// Will return an ``Error::Socket`` if receiving fails, or an error from
+    /// ``OscMessage::parse_bytes`` if a message can't be parsed.
+    pub fn dispatch_pending(&mut self) -> Result<usize, Error> {
+        self.queue_received()?;
+        let mut dispatched = 0;
+        let mut unmatched = VecDeque::new();
+        for msg in std::mem::take(&mut self.message_queue) {
+            if self.dispatch(&msg) {
+                dispatched += 1;
+            } else {
+                unmatched.push_back(msg);
+            }
         }
+        self.message_queue = unmatched;
+        Ok(dispatched)
+    }
+
+    /// Dispatches messages to their subscribed callbacks forever, as ``dispatch_pending`` does,
+    /// waiting for each to arrive. Messages no callback is subscribed to are dropped, so this is
+    /// usually run on its own client, e.g. one made with ``try_clone``.
+    ///
+    /// # Errors
+    /// Will return an ``Error::Socket`` if receiving fails, or an error from
+    /// ``OscMessage::parse_bytes`` if a message can't be parsed.
+    pub fn run_dispatch(&mut self) -> Result<(), Error> {
+        self.dispatch_pending()?;
+        self.message_queue.clear();
+        loop {
+            match self.recv() {
+                Ok(msg) => {
+                    self.dispatch(&msg);
+                }
+                Err(Error::Socket(e))
+                    if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Calls every callback subscribed to ``msg``'s address, returning whether there were any.
+    fn dispatch(&self, msg: &OscMessage) -> bool {
+        let mut subscriptions = lock(&self.subscriptions);
+        let mut matched = false;
+        for (address, callback) in subscriptions.matching_mut(&msg.address) {
+            callback(&address, msg);
+            matched = true;
+        }
+        matched
+    }
+
+    /// Returns a snapshot of the client's counters: packets and bytes sent and received, parse
+    /// failures, and round-trip times.
+    ///
+    /// A round trip is measured whenever ``wait_for``, ``call`` or similar receive the message
+    /// they wait for, from the most recent send to its arrival. Messages which were already
+    /// queued aren't measured.
+    #[must_use]
+    pub fn stats(&self) -> ClientStats {
+        self.stats.clone()
+    }
+
+    /// Returns the client's local address, e.g. to learn which port it was given when created
+    /// with port 0.
+    ///
+    /// # Errors
+    /// Will return an ``Error::Socket`` if the ``Connection`` can't report it.
+    pub fn local_addr(&self) -> Result<SocketAddr, Error> {
+        self.connection.local_addr().map_err(Error::Socket)
+    }
+
+    /// Returns the remote address the client is connected to.
+    ///
+    /// # Errors
+    /// Will return an ``Error::Socket`` if the client isn't connected (e.g. it is
+    /// ``unconnected``), or the ``Connection`` can't report it.
+    pub fn peer_addr(&self) -> Result<SocketAddr, Error> {
+        self.connection.peer_addr().map_err(Error::Socket)
+    }
+
+    /// Points the client at ``remote_address`` instead, e.g. when the user changes the device's
+    /// IP. UDP clients keep their local address, while TCP clients open a new stream. Queued
+    /// messages from the old remote are discarded, but batched messages are sent to the new one.
+    ///
+    /// # Errors
+    /// Will return an ``Error::Socket`` if connecting fails, or if the ``Connection`` can't
+    /// change its remote address.
+    pub fn set_remote_address<A: ToSocketAddrs>(&mut self, remote_address: A) -> Result<(), Error> {
+        let remote_address: Vec<_> = remote_address
+            .to_socket_addrs()
+            .map_err(Error::Socket)?
+            .collect();
+        self.connection
+            .connect(&remote_address[..])
+            .map_err(Error::Socket)?;
+        self.remote_address = remote_address;
+        self.connection
+            .set_read_timeout(self.read_timeout)
+            .map_err(Error::Socket)?;
+        self.connection
+            .set_nonblocking(self.nonblocking)
+            .map_err(Error::Socket)?;
+        self.message_queue.clear();
+        Ok(())
+    }
+
+    /// Reconnects according to ``reconnect`` when the connection drops (the stream is closed or
+    /// reset, as when a device reboots), or stops reconnecting if it is ``None``, as by default.
+    ///
+    /// A send which finds the connection dropped is sent again once reconnected. A receive
+    /// reconnects too, but still returns the error, since any reply due on the old connection is
+    /// lost. Clones made with ``try_clone`` don't reconnect. While reconnecting, the calling
+    /// thread sleeps between attempts.
+    pub fn set_reconnect(&mut self, reconnect: Option<Reconnect>) {
+        self.reconnect = reconnect;
+    }
+
+    /// Sends ``message`` now and then every ``interval`` from a background thread, e.g.
+    /// ``/xremote`` every 9 seconds to keep receiving updates from a Behringer X-Air. ``None``
+    /// stops any keepalive, as does dropping the client (and any clones of it made with
+    /// ``clone``).
+    ///
+    /// The thread sends over a clone of the connection made with ``try_clone``, and stops if a
+    /// send fails, so keepalives are best suited to UDP devices.
+    ///
+    /// # Errors
+    /// Will return ``Err`` if building ``message`` fails, or an ``Error::Socket`` if sending it
+    /// or cloning the connection fails.
+    #[cfg(not(target_family = "wasm"))]
+    pub fn set_keepalive(&mut self, keepalive: Option<(OscMessage, Duration)>) -> Result<(), Error>
+    where
+        C: Send + 'static,
+    {
+        self.keepalive = None;
+        let Some((message, interval)) = keepalive else {
+            return Ok(());
+        };
+        let bytes = message.build()?;
+        let mut connection = self.connection.try_clone().map_err(Error::Socket)?;
+        self.send_bytes(&bytes)?;
+        let (stop, stopped) = channel();
+        let observers = self.observers.clone();
+        let peer = self.remote_address.first().copied();
+        thread::spawn(move || {
+            while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(interval) {
+                if connection.send(&bytes).is_err() {
+                    return;
+                }
+                observers.sent(&bytes, peer);
+            }
+        });
+        self.keepalive = Some(stop);
+        Ok(())
+    }
+
+    /// Tries to reconnect to the remote address, as configured by ``set_reconnect``.
+    fn reconnect(&mut self) -> Result<(), Error> {
+        let Some(policy) = self.reconnect.clone() else {
+            return Ok(());
+        };
+        let mut delay = policy.initial_delay;
+        let mut attempt = 0;
+        loop {
+            thread::sleep(delay);
+            attempt += 1;
+            match self.connection.connect(&self.remote_address[..]) {
+                Ok(()) => break,
+                Err(e) if policy.attempts.is_some_and(|attempts| attempt >= attempts) => {
+                    return Err(Error::Socket(e));
+                }
+                Err(_) => delay = delay.mul_f64(policy.multiplier).min(policy.max_delay),
+            }
+        }
+        self.connection
+            .set_read_timeout(self.read_timeout)
+            .map_err(Error::Socket)?;
+        self.connection
+            .set_nonblocking(self.nonblocking)
+            .map_err(Error::Socket)
+    }
+
+    /// Puts the connection in non-blocking mode if ``nonblocking``, so ``recv`` and the other
+    /// receiving methods return an ``Error::Socket`` of kind ``WouldBlock`` rather than wait for a
+    /// message. ``try_recv`` and ``try_iter`` never wait, and leave the mode as it was.
+    ///
+    /// # Errors
+    /// Will return an ``Error::Socket`` if the mode can't be set.
+    pub fn set_nonblocking(&mut self, nonblocking: bool) -> Result<(), Error> {
+        self.connection
+            .set_nonblocking(nonblocking)
+            .map_err(Error::Socket)?;
+        self.nonblocking = nonblocking;
+        Ok(())
+    }
+
+    /// Sets the maximum size message that the client can receive (See ``recv`` docs).
+    pub fn set_buffer_size(&mut self, buffer_size: usize) {
+        self.buffer.resize(buffer_size, 0);
+    }
+
+    /// Encodes sent messages in buffers from ``pool``, e.g. to share one pool between several
+    /// clients. Each client has its own pool by default, which its ``try_clone``s share.
+    pub fn set_buffer_pool(&mut self, pool: BufferPool) {
+        self.pool = pool;
+    }
+
+    /// Returns the pool sent messages are encoded in.
+    pub fn buffer_pool(&self) -> &BufferPool {
+        &self.pool
+    }
+
+    /// Limits the size of packets the client sends, so a packet which would be fragmented or
+    /// dropped on the way (e.g. one larger than the path MTU, less IP and UDP headers: 1472
+    /// bytes on most Ethernet networks) is refused with ``Error::MessageTooLarge`` instead.
+    /// ``None``, the default, leaves it to the OS. Use ``OscMessage::encoded_len`` to check a
+    /// message before sending it.
+    pub fn set_max_datagram_size(&mut self, max_datagram_size: Option<usize>) {
+        self.max_datagram_size = max_datagram_size;
+    }
+
+    /// Unpacks bundles the client receives, so ``recv``, ``wait_for``, and everything built on
+    /// them see each message in a bundle (including those in nested bundles) in order, as if it
+    /// had arrived on its own. Otherwise, as by default, a bundle fails to parse as a message.
+    /// Timetags are ignored, so messages are returned as soon as their bundle arrives.
+    /// ``recv_bytes`` and ``recv_ref`` still return whole packets.
+    pub fn set_flatten_bundles(&mut self, flatten: bool) {
+        self.flatten_bundles = flatten;
+    }
+
+    /// Remembers the most recent arguments received at each address, for ``latest``, or stops and
+    /// forgets them if ``enabled`` is ``false``. Every message the client receives is cached as
+    /// it arrives, including those queued by ``wait_for`` and similar calls.
+    pub fn set_latest_cache(&mut self, enabled: bool) {
+        let latest = self.latest.take();
+        self.latest = enabled.then(|| latest.unwrap_or_default());
+    }
+
+    /// Makes every wait, such as ``wait_for``, ``call``, and ``send_reliable``, fail promptly
+    /// with an ``Error::Socket`` of kind ``io::ErrorKind::Interrupted`` once ``cancel`` is
+    /// cancelled, e.g. from another thread on shutdown. Waits check it at least every 50ms.
+    pub fn set_cancel_flag(&mut self, cancel: CancelFlag) {
+        self.cancel = Some(cancel);
+    }
+
+    /// Checks each message sent by ``send`` and ``send_bundle`` against ``schema``, failing with
+    /// ``Error::Schema`` instead of sending one which doesn't match, e.g. to catch a fader sent
+    /// as an ``Arg::Int`` during development. Other ways of sending aren't checked.
+    pub fn set_schema(&mut self, schema: OscSchema) {
+        self.schema = Some(schema);
+    }
+
+    /// Only accepts packets from sources permitted by ``access``, e.g. to ignore other devices
+    /// sending to the same port of an ``unconnected`` client. Other packets are dropped before
+    /// being parsed, and counted in ``ClientStats::packets_rejected``. Connections which can't
+    /// tell where a packet came from, such as TCP streams, aren't filtered.
+    pub fn set_access_list(&mut self, access: AccessList) {
+        self.access = access;
+    }
+
+    /// Returns the most recent arguments received at ``address``, e.g. ``/meters/1``, so a UI can
+    /// read the current state between polls. Returns ``None`` if nothing has been received there
+    /// since caching was enabled with ``set_latest_cache``, or it isn't enabled.
+    #[must_use]
+    pub fn latest(&self, address: &str) -> Option<&[Arg]> {
+        self.latest.as_ref()?.get(address).map(|args| &args[..])
+    }
+
+    /// Stamps each message sent by ``send`` and ``send_bundle`` with a sequence number (see
+    /// ``Sequencer``), and checks those on received messages, removing them and dropping
+    /// duplicates, or stops if ``enabled`` is ``false``. Enabling starts counting afresh. The
+    /// device must do the same, so this is mostly for links between two programs using this
+    /// crate, e.g. to measure how lossy an installation's wireless network is with
+    /// ``sequence_stats``. ``try_clone``s start without sequence numbers.
+    pub fn set_sequence_numbers(&mut self, enabled: bool) {
+        self.sequence = enabled.then(Default::default);
+    }
+
+    /// Returns the counts of lost, duplicated, and late messages seen since sequence numbers were
+    /// enabled, or ``None`` if they aren't. See ``set_sequence_numbers``.
+    #[must_use]
+    pub fn sequence_stats(&self) -> Option<SequenceStats> {
+        self.sequence.as_ref().map(|(_, tracker)| tracker.stats())
+    }
+
+    /// Records every packet the client sends and receives with ``recorder``, or stops recording
+    /// if ``None``. Keepalives and sends scheduled by ``send_at`` are recorded by the threads
+    /// which send them, so only if the recorder was set before the first of them started.
+    pub fn set_recorder(&mut self, recorder: Option<Recorder>) {
+        self.observers.recorder = recorder;
+    }
+
+    /// Calls ``tap`` with the exact bytes of every packet the client sends, once it's sent, and
+    /// the remote address, e.g. for a protocol debugger. Replaces any earlier tap. As with
+    /// ``set_recorder``, background sends only see a tap set before they started.
+    pub fn on_raw_send<F>(&mut self, tap: F)
+    where
+        F: Fn(&[u8], Option<SocketAddr>) + Send + Sync + 'static,
+    {
+        self.observers.raw_send = Some(Arc::new(tap) as RawTap);
+    }
+
+    /// Calls ``tap`` with the exact bytes of every packet the client receives, before they're
+    /// parsed, and the address they came from, if known. Replaces any earlier tap.
+    pub fn on_raw_recv<F>(&mut self, tap: F)
+    where
+        F: Fn(&[u8], Option<SocketAddr>) + Send + Sync + 'static,
+    {
+        self.observers.raw_recv = Some(Arc::new(tap) as RawTap);
+    }
+
+    /// Lets the receive buffer grow, doubling up to ``max_buffer_size``, when a packet doesn't
+    /// fit, rather than the packet being truncated or failing to be received. ``None``, the
+    /// default, keeps the buffer at a fixed size.
+    ///
+    /// Each packet is peeked at before it is received, so this only works for connections which
+    /// support ``Connection::peek``, e.g. ``UdpSocket``.
+    pub fn set_max_buffer_size(&mut self, max_buffer_size: Option<usize>) {
+        self.max_buffer_size = max_buffer_size;
+    }
+
+    /// Peeks at the next packet, doubling the buffer until it fits or reaches
+    /// ``self.max_buffer_size``. Errors are left for the receive which follows.
+    fn grow_buffer(&mut self) {
+        let Some(max) = self.max_buffer_size else {
+            return;
+        };
+        while self.buffer.len() < max {
+            match self.connection.peek(&mut self.buffer) {
+                // A full buffer may mean the packet was truncated
+                Ok(size) if size >= self.buffer.len() => {}
+                Err(e) if is_truncated(&e) => {}
+                _ => return,
+            }
+            let size = (self.buffer.len() * 2).clamp(1, max);
+            self.buffer.resize(size, 0);
+        }
+    }
+
+    /// Sets how long reads may block, and so how long ``wait_for`` waits (1 second if ``None``),
+    /// as for ``new``.
+    ///
+    /// # Errors
+    /// Will return an ``Error::Socket`` if the read timeout can't be set, e.g. if it is zero.
+    pub fn set_read_timeout(&mut self, read_timeout: Option<Duration>) -> Result<(), Error> {
+        self.connection
+            .set_read_timeout(read_timeout)
+            .map_err(Error::Socket)?;
+        self.read_timeout = read_timeout;
+        self.timeout = read_timeout.unwrap_or(Duration::from_secs(1));
+        Ok(())
+    }
+
+    /// Receives messages on a background thread, using a clone of the connection made with
+    /// ``try_clone``, and returns a channel they are delivered to as they arrive. Messages that
+    /// fail to parse are skipped.
+    ///
+    /// The thread competes with this client for incoming messages, so once it is running this
+    /// client should only be used for sending. The thread stops when receiving fails, or when a
+    /// message arrives after the ``Receiver`` has been dropped.
+    ///
+    /// The channel is always a ``std`` one, even with the ``parking_lot`` feature, so enabling
+    /// it doesn't break code using the ``Receiver``.
+    ///
+    /// # Errors
+    /// Will return ``Err(Error::Socket)`` if the connection can't be cloned.
+    #[cfg(not(target_family = "wasm"))]
+    pub fn spawn_receiver(&self) -> Result<mpsc::Receiver<OscMessage>, Error>
+    where
+        C: Send + 'static,
+    {
+        let mut receiver = self.try_clone()?;
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || loop {
+            match receiver.recv() {
+                Ok(msg) => {
+                    if tx.send(msg).is_err() {
+                        return;
+                    }
+                }
+                Err(Error::Socket(e))
+                    if !matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) =>
+                {
+                    return;
+                }
+                Err(_) => {}
+            }
+        });
+        Ok(rx)
     }
 
     /// Attempts to clone the ``XAirClient``
@@ -146,9 +1479,183 @@ impl<C: Connection> OscClient<C> {
     pub fn try_clone(&self) -> Result<Self, Error> {
         Ok(Self {
             connection: self.connection.try_clone().map_err(Error::Socket)?,
+            remote_address: self.remote_address.clone(),
+            reconnect: None,
             message_queue: VecDeque::new(),
-            timeout_secs: self.timeout_secs,
+            unpacked: VecDeque::new(),
+            flatten_bundles: self.flatten_bundles,
+            sequence: None,
+            latest: self.latest.as_ref().map(|_| HashMap::new()),
+            access: self.access.clone(),
+            cancel: self.cancel.clone(),
+            schema: self.schema.clone(),
+            queue_capacity: self.queue_capacity,
+            eviction: self.eviction,
+            read_timeout: self.read_timeout,
+            timeout: self.timeout,
+            nonblocking: self.nonblocking,
             buffer: vec![0; self.buffer.len()],
+            max_buffer_size: self.max_buffer_size,
+            max_datagram_size: self.max_datagram_size,
+            pool: self.pool.clone(),
+            batch_window: self.batch_window,
+            batch: Vec::new(),
+            batch_started: None,
+            coalesce: self.coalesce,
+            subscriptions: Arc::default(),
+            keepalive: None,
+            scheduled: None,
+            stats: ClientStats::default(),
+            last_sent: None,
+            observers: self.observers.clone(),
         })
     }
 }
+
+#[cfg(all(feature = "udp", not(target_family = "wasm")))]
+impl OscClient<UdpSocket> {
+    /// Creates an ``OscClient`` listening at ``client_address`` without a remote address, so one
+    /// socket can talk to several devices: use ``send_to`` to address each message, and
+    /// ``recv_from`` to learn who sent each reply. ``wait_for`` and similar calls accept messages
+    /// from any device, but ``send`` fails until a remote address is set with
+    /// ``set_remote_address``. Other arguments are as for ``new``.
+    ///
+    /// # Errors
+    /// If the socket cannot be bound, or the read timeout cannot be set, this function will
+    /// return an ``Error::Socket``.
+    pub fn unconnected<A: ToSocketAddrs>(
+        client_address: A,
+        buffer_size: usize,
+        read_timeout: Option<Duration>,
+    ) -> Result<Self, Error> {
+        let socket = UdpSocket::bind(client_address).map_err(Error::Socket)?;
+        Self::from_parts(socket, Vec::new(), buffer_size, read_timeout)
+    }
+
+    /// Sends ``message`` to ``address``, rather than the client's remote address. Batched messages
+    /// are not sent.
+    ///
+    /// # Errors
+    /// Will return ``Err`` if ``message.build`` fails, or an ``Error::Socket`` if sending fails.
+    pub fn send_to<A: ToSocketAddrs>(
+        &mut self,
+        message: &OscMessage,
+        address: A,
+    ) -> Result<usize, Error> {
+        let mut bytes = self.pool.take();
+        message.build_into(&mut bytes)?;
+        self.check_datagram_size(bytes.len())?;
+        let address: Vec<_> = address.to_socket_addrs().map_err(Error::Socket)?.collect();
+        let size = self
+            .connection
+            .send_to(&bytes, &address[..])
+            .map_err(Error::Socket)?;
+        self.observers.sent(&bytes, address.first().copied());
+        drop(bytes);
+        self.record_sent(size);
+        Ok(size)
+    }
+
+    /// Sets whether the socket may send to broadcast addresses. See ``send_broadcast``.
+    ///
+    /// # Errors
+    /// Will return an ``Error::Socket`` if the option can't be set.
+    pub fn set_broadcast(&self, broadcast: bool) -> Result<(), Error> {
+        self.connection
+            .set_broadcast(broadcast)
+            .map_err(Error::Socket)
+    }
+
+    /// Broadcasts ``message`` to ``port`` on every device on the local network, e.g. to discover
+    /// OSC devices, enabling broadcast on the socket first. Replies can then be collected with
+    /// ``recv_from``, so this is usually used with an ``unconnected`` client.
+    ///
+    /// # Errors
+    /// Will return ``Err`` if ``message.build`` fails, or an ``Error::Socket`` if enabling
+    /// broadcast or sending fails.
+    pub fn send_broadcast(&mut self, message: &OscMessage, port: u16) -> Result<usize, Error> {
+        self.set_broadcast(true)?;
+        self.send_to(message, (Ipv4Addr::BROADCAST, port))
+    }
+
+    /// Receives a message as ``recv`` does, along with the address of the device which sent it.
+    /// Bundles aren't unpacked, even with ``set_flatten_bundles``.
+    ///
+    /// # Errors
+    /// See ``recv``.
+    pub fn recv_from(&mut self) -> Result<(OscMessage, SocketAddr), Error> {
+        self.flush()?;
+        self.grow_buffer();
+        let (size, source) = loop {
+            let (size, source) = self
+                .connection
+                .recv_from(&mut self.buffer)
+                .map_err(Error::Socket)?;
+            if self.access.permits(source.ip()) {
+                break (size, source);
+            }
+            self.stats.packets_rejected += 1;
+        };
+        self.observers.received(&self.buffer[..size], Some(source));
+        let msg = self.count_received(size, OscMessage::parse_bytes(&self.buffer[..size]))?;
+        Ok((msg, source))
+    }
+}
+
+/// Sends packets from ``scheduled`` over ``connection`` once they are due, until the channel
+/// disconnects.
+#[cfg(not(target_family = "wasm"))]
+fn send_scheduled<C: Connection>(
+    mut connection: C,
+    scheduled: &Receiver<(Instant, Vec<u8>)>,
+    observers: &Observers,
+    peer: Option<SocketAddr>,
+) {
+    let mut due: BinaryHeap<Reverse<(Instant, u64, Vec<u8>)>> = BinaryHeap::new();
+    let mut order = 0_u64;
+    loop {
+        let received = match due.peek() {
+            Some(Reverse((at, _, _))) => {
+                scheduled.recv_timeout(at.saturating_duration_since(Instant::now()))
+            }
+            None => scheduled.recv().map_err(|_| RecvTimeoutError::Disconnected),
+        };
+        match received {
+            Ok((at, bytes)) => {
+                due.push(Reverse((at, order, bytes)));
+                order += 1;
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => return,
+        }
+        while due
+            .peek()
+            .is_some_and(|Reverse((at, _, _))| *at <= Instant::now())
+        {
+            if let Some(Reverse((_, _, bytes))) = due.pop() {
+                if connection.send(&bytes).is_ok() {
+                    observers.sent(&bytes, peer);
+                }
+            }
+        }
+    }
+}
+
+/// Returns whether ``error`` means the connection has dropped, rather than a read timing out or a
+/// bad packet.
+fn is_disconnect(error: &io::Error) -> bool {
+    matches!(
+        error.kind(),
+        ErrorKind::BrokenPipe
+            | ErrorKind::ConnectionReset
+            | ErrorKind::ConnectionAborted
+            | ErrorKind::NotConnected
+            | ErrorKind::UnexpectedEof
+    )
+}
+
+/// Returns whether ``error`` means a datagram was too big for the buffer, which Windows reports
+/// as ``WSAEMSGSIZE`` rather than truncating it.
+fn is_truncated(error: &io::Error) -> bool {
+    cfg!(windows) && error.raw_os_error() == Some(10040)
+}
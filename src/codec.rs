@@ -0,0 +1,147 @@
+use std::io::ErrorKind;
+
+use tokio_util::{
+    bytes::{Buf, BufMut, BytesMut},
+    codec::{Decoder, Encoder},
+};
+
+use crate::{
+    errors::Error,
+    sockets::{slip_decode, slip_encode, slip_end},
+    OscMessage, OscPacket,
+};
+
+/// How an ``OscCodec`` separates packets in a byte stream.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Framing {
+    /// Each packet is prefixed with its length as a big-endian ``u32``, as in OSC 1.0 and
+    /// ``FramedTcpStream``.
+    #[default]
+    LengthPrefixed,
+    /// Each packet is sent between SLIP ``END`` bytes, as in OSC 1.1 and ``SlipFramed``, e.g. for
+    /// serial links.
+    Slip,
+}
+
+/// A ``tokio_util`` codec for OSC packets over a byte stream, so a ``TcpStream``, serial port, or
+/// anything else ``AsyncRead + AsyncWrite`` can be wrapped in ``Framed`` to send and receive
+/// ``OscPacket``s, e.g. ``Framed::new(stream, OscCodec::slip())``.
+///
+/// Errors are ``io::Error``s, with those from building or parsing a packet wrapped in one of kind
+/// ``ErrorKind::InvalidData``.
+#[derive(Debug, Clone, Default)]
+pub struct OscCodec {
+    framing: Framing,
+    max_length: Option<usize>,
+}
+
+impl OscCodec {
+    #[must_use]
+    pub fn new(framing: Framing) -> Self {
+        Self {
+            framing,
+            max_length: None,
+        }
+    }
+
+    /// Creates a codec with ``Framing::LengthPrefixed``.
+    #[must_use]
+    pub fn length_prefixed() -> Self {
+        Self::new(Framing::LengthPrefixed)
+    }
+
+    /// Creates a codec with ``Framing::Slip``.
+    #[must_use]
+    pub fn slip() -> Self {
+        Self::new(Framing::Slip)
+    }
+
+    /// Rejects incoming packets larger than ``max_length`` bytes with an ``Error::MessageTooLarge``,
+    /// rather than buffering them however large they claim to be.
+    #[must_use]
+    pub fn with_max_length(mut self, max_length: usize) -> Self {
+        self.max_length = Some(max_length);
+        self
+    }
+
+    #[must_use]
+    pub fn framing(&self) -> Framing {
+        self.framing
+    }
+
+    fn check_length(&self, size: usize) -> std::io::Result<()> {
+        match self.max_length {
+            Some(limit) if size > limit => Err(invalid(Error::MessageTooLarge { size, limit })),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn invalid(e: Error) -> std::io::Error {
+    std::io::Error::new(ErrorKind::InvalidData, e)
+}
+
+impl Decoder for OscCodec {
+    type Item = OscPacket;
+    type Error = std::io::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        let packet = match self.framing {
+            Framing::LengthPrefixed => {
+                let Some(header) = src.get(..4) else {
+                    return Ok(None);
+                };
+                // The slice is 4 bytes long
+                let length = u32::from_be_bytes(header.try_into().unwrap()) as usize;
+                self.check_length(length)?;
+                if src.len() < 4 + length {
+                    src.reserve(4 + length - src.len());
+                    return Ok(None);
+                }
+                src.advance(4);
+                src.split_to(length).to_vec()
+            }
+            Framing::Slip => loop {
+                let Some(end) = slip_end(src) else {
+                    self.check_length(src.len())?;
+                    return Ok(None);
+                };
+                let frame = src.split_to(end);
+                src.advance(1);
+                // Empty frames are the double END between packets.
+                if !frame.is_empty() {
+                    break slip_decode(&frame);
+                }
+            },
+        };
+        OscPacket::parse_bytes(&packet).map(Some).map_err(invalid)
+    }
+}
+
+impl Encoder<OscPacket> for OscCodec {
+    type Error = std::io::Error;
+
+    fn encode(&mut self, item: OscPacket, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        let packet = item.build().map_err(invalid)?;
+        match self.framing {
+            Framing::LengthPrefixed => {
+                let length = u32::try_from(packet.len()).map_err(|_| {
+                    std::io::Error::new(ErrorKind::InvalidInput, "Packet too large to frame")
+                })?;
+                dst.reserve(4 + packet.len());
+                dst.put_u32(length);
+                dst.put_slice(&packet);
+            }
+            Framing::Slip => dst.put_slice(&slip_encode(&packet)),
+        }
+        Ok(())
+    }
+}
+
+impl Encoder<OscMessage> for OscCodec {
+    type Error = std::io::Error;
+
+    fn encode(&mut self, item: OscMessage, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        self.encode(OscPacket::Message(item), dst)
+    }
+}
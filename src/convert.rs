@@ -0,0 +1,67 @@
+use crate::{errors::Error, Arg, OscMessage};
+
+#[cfg(feature = "derive")]
+pub use osc_derive::{FromOsc, ToOsc};
+
+/// A type which converts into an ``OscMessage``, e.g. a typed request to a device. With the
+/// ``derive`` feature, ``#[derive(ToOsc)]`` implements it for a struct with an
+/// ``#[osc(address = "...")]`` attribute, making each field an argument, in order.
+pub trait ToOsc {
+    fn to_osc(&self) -> OscMessage;
+}
+
+/// A type which can be read from an ``OscMessage``, e.g. a typed response from a device. With
+/// the ``derive`` feature, ``#[derive(FromOsc)]`` implements it for a struct, reading each field
+/// from the argument at its position with ``TryFrom<Arg>``. The address isn't checked, since a
+/// route has already matched it. Tuples of up to 8 argument types implement it too, e.g.
+/// ``(i32, f32)`` for a message with an int and a float, as well as ``TryFrom<&OscMessage>``, so
+/// a handler can read its arguments with ``let (channel, level): (i32, f32) = msg.try_into()?``.
+pub trait FromOsc: Sized {
+    /// Reads ``Self`` from ``message``.
+    ///
+    /// # Errors
+    /// Will return ``Err`` if ``message`` doesn't hold the arguments ``Self`` needs.
+    fn from_osc(message: &OscMessage) -> Result<Self, Error>;
+}
+
+/// Checks that ``message`` has ``count`` arguments, for ``#[derive(FromOsc)]``.
+///
+/// # Errors
+/// Will return ``Error::DataLength`` if it doesn't.
+#[doc(hidden)]
+pub fn expect_args(message: &OscMessage, count: usize) -> Result<(), Error> {
+    if message.args.len() == count {
+        Ok(())
+    } else {
+        Err(Error::DataLength(count, message.args.len()))
+    }
+}
+
+macro_rules! tuple_from_osc {
+    ($count:literal; $($index:tt $arg:ident),*) => {
+        impl<$($arg: TryFrom<Arg, Error = Error>),*> FromOsc for ($($arg,)*) {
+            fn from_osc(message: &OscMessage) -> Result<Self, Error> {
+                expect_args(message, $count)?;
+                Ok(($(message.arg::<$arg>($index)?,)*))
+            }
+        }
+
+        impl<$($arg: TryFrom<Arg, Error = Error>),*> TryFrom<&OscMessage> for ($($arg,)*) {
+            type Error = Error;
+
+            fn try_from(message: &OscMessage) -> Result<Self, Self::Error> {
+                Self::from_osc(message)
+            }
+        }
+    };
+}
+
+tuple_from_osc!(0;);
+tuple_from_osc!(1; 0 A);
+tuple_from_osc!(2; 0 A, 1 B);
+tuple_from_osc!(3; 0 A, 1 B, 2 C);
+tuple_from_osc!(4; 0 A, 1 B, 2 C, 3 D);
+tuple_from_osc!(5; 0 A, 1 B, 2 C, 3 D, 4 E);
+tuple_from_osc!(6; 0 A, 1 B, 2 C, 3 D, 4 E, 5 F);
+tuple_from_osc!(7; 0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G);
+tuple_from_osc!(8; 0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H);
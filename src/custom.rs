@@ -0,0 +1,73 @@
+use std::{any::Any, collections::HashMap, fmt::Debug, sync::Arc};
+
+use crate::{errors::Error, is_standard_tag};
+
+/// The value of an argument with an application-defined type tag, e.g. a console's proprietary
+/// extension, carried by ``Arg::Custom``. Register a decoder for its tag in a
+/// ``TypeTagRegistry`` to parse it.
+pub trait CustomArg: Any + Debug + Send + Sync {
+    /// The type tag the value is sent with.
+    fn tag(&self) -> char;
+    /// Encodes the value's data, which must be padded to a multiple of 4 bytes.
+    ///
+    /// # Errors
+    /// Will return Err if the value can't be encoded.
+    fn encode(&self) -> Result<Vec<u8>, Error>;
+}
+
+impl dyn CustomArg {
+    /// Returns the value as a ``T``, if that is its type.
+    #[must_use]
+    pub fn downcast_ref<T: CustomArg>(&self) -> Option<&T> {
+        (self as &dyn Any).downcast_ref()
+    }
+}
+
+/// Decodes the data of a custom argument from the start of the slice, which holds the rest of
+/// the message, returning the value and how many bytes it took up (a multiple of 4).
+pub type Decoder = Arc<dyn Fn(&[u8]) -> Result<(Arc<dyn CustomArg>, usize), Error> + Send + Sync>;
+
+/// Decoders for custom type tags, used when parsing with ``ParseOptions::custom_tags``.
+#[derive(Clone, Default)]
+pub struct TypeTagRegistry {
+    decoders: HashMap<char, Decoder>,
+}
+
+impl TypeTagRegistry {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses arguments with type tag ``tag`` using ``decode``, replacing any decoder already
+    /// registered for it.
+    ///
+    /// # Errors
+    /// Will return ``Error::Malformed`` if ``tag`` is a standard type tag, or ``,``, ``[``, or
+    /// ``]``.
+    pub fn register<F>(&mut self, tag: char, decode: F) -> Result<(), Error>
+    where
+        F: Fn(&[u8]) -> Result<(Arc<dyn CustomArg>, usize), Error> + Send + Sync + 'static,
+    {
+        if is_standard_tag(tag) || matches!(tag, ',' | '[' | ']') {
+            return Err(Error::Malformed(format!("Custom type tag {tag}")));
+        }
+        self.decoders.insert(tag, Arc::new(decode));
+        Ok(())
+    }
+
+    /// Stops parsing arguments with type tag ``tag``, returning whether a decoder was registered.
+    pub fn unregister(&mut self, tag: char) -> bool {
+        self.decoders.remove(&tag).is_some()
+    }
+
+    pub(crate) fn get(&self, tag: char) -> Option<&Decoder> {
+        self.decoders.get(&tag)
+    }
+}
+
+impl Debug for TypeTagRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.decoders.keys()).finish()
+    }
+}
@@ -0,0 +1,191 @@
+use std::fmt::Write;
+
+use crate::{
+    bundle::OscBundle, copy_blob, parse::ParseOptions, scan_arg, scan_string, time::OscTime, Arg,
+};
+
+/// How many bytes are shown on each line of a dump.
+const BYTES_PER_LINE: usize = 16;
+
+/// Renders ``packet`` as a hexdump annotated with what each span of it holds: a message's
+/// address, type tags, and each argument's decoded value, or a bundle's timetag and the size of
+/// each element, followed by the element. Each line starts with the offset of its first byte.
+/// Where the packet can't be read, the rest of it is shown with the error, e.g. to see why a
+/// console won't parse a message.
+///
+/// ```text
+/// 0000  2f 66 61 64 65 72 00 00                           address "/fader"
+/// 0008  2c 66 00 00                                       type tags ",f"
+/// 000c  3f 40 00 00                                       argument 0 (f) Float(0.75)
+/// ```
+#[must_use]
+pub fn dump(packet: &[u8]) -> String {
+    let mut out = String::new();
+    dump_packet(&mut out, packet, 0, packet.len(), 0);
+    out
+}
+
+fn dump_packet(out: &mut String, data: &[u8], start: usize, end: usize, depth: usize) {
+    if OscBundle::is_bundle(&data[start..end]) {
+        dump_bundle(out, data, start, end, depth);
+    } else {
+        dump_message(out, data, start, end, depth);
+    }
+}
+
+fn dump_bundle(out: &mut String, data: &[u8], start: usize, end: usize, depth: usize) {
+    span(out, data, start, start + 8, depth, "bundle");
+    let mut idx = start + 8;
+    let Some(timetag) = data.get(idx..idx + 8).filter(|_| idx + 8 <= end) else {
+        span(out, data, idx, end, depth, "error: truncated timetag");
+        return;
+    };
+    let timetag = OscTime::from(u64::from_be_bytes(timetag.try_into().unwrap_or_default()));
+    let label = if timetag == OscTime::IMMEDIATE {
+        "timetag immediately".to_string()
+    } else {
+        format!("timetag {timetag:?}")
+    };
+    span(out, data, idx, idx + 8, depth, &label);
+    idx += 8;
+    let mut index = 0;
+    while idx < end {
+        let Some(size) = data.get(idx..idx + 4).filter(|_| idx + 4 <= end) else {
+            span(out, data, idx, end, depth, "error: truncated element size");
+            return;
+        };
+        let size = i32::from_be_bytes(size.try_into().unwrap_or_default());
+        let element_end = usize::try_from(size).ok().map(|size| idx + 4 + size);
+        let Some(element_end) = element_end.filter(|element_end| *element_end <= end) else {
+            span(
+                out,
+                data,
+                idx,
+                end,
+                depth,
+                &format!("error: element size {size}"),
+            );
+            return;
+        };
+        span(
+            out,
+            data,
+            idx,
+            idx + 4,
+            depth,
+            &format!("element {index} size {size}"),
+        );
+        dump_packet(out, data, idx + 4, element_end, depth + 1);
+        idx = element_end;
+        index += 1;
+    }
+}
+
+fn dump_message(out: &mut String, data: &[u8], start: usize, end: usize, depth: usize) {
+    let message = &data[start..end];
+    let mut idx = 0;
+    let address = scan_string(&mut idx, message);
+    idx = idx.min(message.len());
+    match address {
+        Ok(address) => span(
+            out,
+            data,
+            start,
+            start + idx,
+            depth,
+            &format!("address {address:?}"),
+        ),
+        Err(e) => return span(out, data, start, end, depth, &format!("error: address {e}")),
+    }
+    if idx == message.len() {
+        return;
+    }
+    let tags_start = idx;
+    let tags = match scan_string(&mut idx, message) {
+        Ok(tags) if tags.starts_with(',') => tags,
+        Ok(_) => {
+            let label = "error: type tags don't start with ','";
+            return span(out, data, start + tags_start, end, depth, label);
+        }
+        Err(e) => {
+            let label = format!("error: type tags {e}");
+            return span(out, data, start + tags_start, end, depth, &label);
+        }
+    };
+    idx = idx.min(message.len());
+    span(
+        out,
+        data,
+        start + tags_start,
+        start + idx,
+        depth,
+        &format!("type tags {tags:?}"),
+    );
+
+    let options = ParseOptions::default();
+    let mut index = 0;
+    for tag in tags.chars().skip(1) {
+        match tag {
+            // Array delimiters have no data
+            '[' => span(out, data, start + idx, start + idx, depth, "array start"),
+            ']' => span(out, data, start + idx, start + idx, depth, "array end"),
+            _ => {
+                let arg_start = idx;
+                match scan_arg(tag, &mut idx, message, &options, &|b| copy_blob(b)) {
+                    Ok(arg) => {
+                        // The bytes are shown already
+                        let value = match arg {
+                            Arg::Blob(blob) => format!("Blob of {} bytes", blob.len()),
+                            arg => format!("{arg:?}"),
+                        };
+                        let label = format!("argument {index} ({tag}) {value}");
+                        span(out, data, start + arg_start, start + idx, depth, &label);
+                    }
+                    Err(e) => {
+                        let label = format!("error: argument {index} ({tag}) {e}");
+                        return span(out, data, start + arg_start, end, depth, &label);
+                    }
+                }
+                index += 1;
+            }
+        }
+    }
+    if idx < message.len() {
+        span(out, data, start + idx, end, depth, "unused bytes");
+    }
+}
+
+/// Writes the bytes of ``data`` from ``start`` to ``end`` (clamped to ``data``), with ``label``
+/// on the first line, indented by ``depth``.
+fn span(out: &mut String, data: &[u8], start: usize, end: usize, depth: usize, label: &str) {
+    let end = end.min(data.len());
+    let start = start.min(end);
+    let bytes = &data[start..end];
+    let mut lines = bytes.chunks(BYTES_PER_LINE);
+    let first = lines.next().unwrap_or_default();
+    line(
+        out,
+        start,
+        first,
+        &format!("{:indent$}{label}", "", indent = depth * 2),
+    );
+    for (i, chunk) in lines.enumerate() {
+        line(out, start + (i + 1) * BYTES_PER_LINE, chunk, "");
+    }
+}
+
+fn line(out: &mut String, offset: usize, bytes: &[u8], label: &str) {
+    let mut hex = String::with_capacity(BYTES_PER_LINE * 3 + 1);
+    for (i, b) in bytes.iter().enumerate() {
+        if i == BYTES_PER_LINE / 2 {
+            hex.push(' ');
+        }
+        let _ = write!(hex, "{b:02x} ");
+    }
+    let line = format!(
+        "{offset:04x}  {hex:width$} {label}",
+        width = BYTES_PER_LINE * 3 + 1
+    );
+    out.push_str(line.trim_end());
+    out.push('\n');
+}
@@ -1,6 +1,11 @@
-use std::{char, fmt::Display};
+use std::{char, convert::Infallible, fmt::Display, io::ErrorKind};
 
+use crate::time::OscTime;
+
+/// Errors from building, parsing, sending, and receiving OSC. New variants may be added, so match
+/// with a wildcard arm, or use the ``is_*`` methods to tell common cases apart.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum Error {
     Utf8(String),
     DataLength(usize, usize),
@@ -10,12 +15,71 @@ pub enum Error {
     Malformed(String),
     Socket(std::io::Error),
     BlobSize(i32),
+    TimeRange(OscTime),
+    Address(String),
+    DuplicateRoute(String),
+    TemplateArg(usize),
+    /// Where parsing a packet failed, wrapping the error.
+    Parse(Box<ParseError>),
+    /// A packet was larger than the client's datagram limit, so wasn't sent.
+    MessageTooLarge {
+        size: usize,
+        limit: usize,
+    },
+    /// A bundle was nested in one with a later timetag, which the spec forbids.
+    NestedTimetag {
+        timetag: OscTime,
+        enclosing: OscTime,
+    },
+    /// A blob was longer than ``ParseOptions::max_blob_len``.
+    BlobTooLarge {
+        size: usize,
+        limit: usize,
+    },
+    /// An address, string, or symbol was longer than ``ParseOptions::max_string_len``.
+    StringTooLong {
+        length: usize,
+        limit: usize,
+    },
+    /// A message had more arguments than ``ParseOptions::max_args``.
+    TooManyArgs {
+        count: usize,
+        limit: usize,
+    },
+    /// Bundles or arrays were nested deeper than ``ParseOptions::max_depth``.
+    NestingTooDeep {
+        limit: usize,
+    },
+    /// A message didn't match the signature an ``OscSchema`` expects at its address.
+    Schema {
+        address: String,
+        reason: String,
+    },
+    /// The sender isn't permitted to read or write the parameter at this address, as set with
+    /// ``OscServer::with_access_level`` and ``with_write_access``.
+    AccessDenied(String),
+    /// A string, blob, or argument list didn't fit the capacity of an ``OscMessageFixed``.
+    CapacityExceeded {
+        size: usize,
+        capacity: usize,
+    },
+    /// A chunked transfer was larger than ``ChunkAssembler::with_max_len``.
+    TransferTooLarge {
+        size: usize,
+        limit: usize,
+    },
+    /// The handler for a route panicked with this message, and the panic was caught, as set with
+    /// ``OscServer::with_panic_isolation``.
+    HandlerPanicked(String),
 }
 
 impl Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         use Error::{
-            Alignment, BlobSize, DataLength, Malformed, NoData, Socket, UnrecognisedTypeTag, Utf8,
+            AccessDenied, Address, Alignment, BlobSize, BlobTooLarge, CapacityExceeded, DataLength,
+            DuplicateRoute, HandlerPanicked, Malformed, MessageTooLarge, NestedTimetag,
+            NestingTooDeep, NoData, Parse, Schema, Socket, StringTooLong, TemplateArg, TimeRange,
+            TooManyArgs, TransferTooLarge, UnrecognisedTypeTag, Utf8,
         };
         match self {
             Utf8(s) => f.write_fmt(format_args!("{s} not valid utf-8")),
@@ -23,7 +87,7 @@ impl Display for Error {
                 "Expected {expected} elements, received {received}",
             )),
             NoData(expected) => {
-                f.write_fmt(format_args!("Expected {expected} elememts, received none",))
+                f.write_fmt(format_args!("Expected {expected} elememts, received none"))
             }
             UnrecognisedTypeTag(tag) => f.write_fmt(format_args!("Unrecognised type tag: {tag}")),
             Alignment(length, expected_alignment) => f.write_fmt(format_args!(
@@ -34,8 +98,179 @@ impl Display for Error {
             BlobSize(size) => f.write_fmt(format_args!(
                 "Blob size invalid, found {size}, expected size >= 0 && size % 4 == 0"
             )),
+            TimeRange(time) => f.write_fmt(format_args!(
+                "Timetag {time:?} cannot be represented as a SystemTime"
+            )),
+            Address(s) => f.write_fmt(format_args!("Invalid OSC address: {s}")),
+            DuplicateRoute(pattern) => {
+                f.write_fmt(format_args!("A route is already registered for {pattern}"))
+            }
+            TemplateArg(index) => f.write_fmt(format_args!(
+                "Argument {index} of the template can't be replaced in place"
+            )),
+            Parse(e) => e.fmt(f),
+            MessageTooLarge { size, limit } => f.write_fmt(format_args!(
+                "Packet of {size} bytes exceeds the datagram limit of {limit} bytes"
+            )),
+            NestedTimetag { timetag, enclosing } => f.write_fmt(format_args!(
+                "Nested bundle's timetag {timetag:?} is earlier than its enclosing bundle's \
+                 {enclosing:?}"
+            )),
+            BlobTooLarge { size, limit } => f.write_fmt(format_args!(
+                "Blob of {size} bytes exceeds the parser's limit of {limit} bytes"
+            )),
+            StringTooLong { length, limit } => f.write_fmt(format_args!(
+                "String of {length} bytes exceeds the parser's limit of {limit} bytes"
+            )),
+            TooManyArgs { count, limit } => f.write_fmt(format_args!(
+                "{count} arguments exceeds the parser's limit of {limit}"
+            )),
+            NestingTooDeep { limit } => f.write_fmt(format_args!(
+                "Bundles or arrays nested deeper than the parser's limit of {limit}"
+            )),
+            Schema { address, reason } => f.write_fmt(format_args!(
+                "Message to {address} doesn't match its schema: {reason}"
+            )),
+            AccessDenied(address) => f.write_fmt(format_args!("Access to {address} denied")),
+            CapacityExceeded { size, capacity } => f.write_fmt(format_args!(
+                "{size} exceeds the fixed capacity of {capacity}"
+            )),
+            TransferTooLarge { size, limit } => f.write_fmt(format_args!(
+                "Chunked transfer of {size} bytes exceeds the limit of {limit} bytes"
+            )),
+            HandlerPanicked(message) => f.write_fmt(format_args!("Handler panicked: {message}")),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Parse(e) => Some(&e.error),
+            _ => None,
+        }
+    }
+}
+
+impl Error {
+    /// Returns the error beneath any ``Error::Parse`` context, e.g. to check whether parsing
+    /// failed with ``Error::DataLength`` because a packet was incomplete.
+    #[must_use]
+    pub fn cause(&self) -> &Error {
+        match self {
+            Error::Parse(e) => e.error.cause(),
+            _ => self,
+        }
+    }
+
+    /// Returns whether waiting for data timed out. As read timeouts are reported as
+    /// ``io::ErrorKind::WouldBlock`` on some platforms, that counts as a timeout too.
+    #[must_use]
+    pub fn is_timeout(&self) -> bool {
+        matches!(
+            self,
+            Error::Socket(e) if matches!(e.kind(), ErrorKind::TimedOut | ErrorKind::WouldBlock)
+        )
+    }
+
+    /// Returns whether a non-blocking call found no data ready.
+    #[must_use]
+    pub fn is_would_block(&self) -> bool {
+        matches!(self, Error::Socket(e) if e.kind() == ErrorKind::WouldBlock)
+    }
+
+    /// Returns whether the error came from parsing a malformed or unsupported packet, rather
+    /// than from the socket.
+    #[must_use]
+    pub fn is_parse(&self) -> bool {
+        matches!(
+            self,
+            Error::Parse(_)
+                | Error::Utf8(_)
+                | Error::DataLength(..)
+                | Error::NoData(_)
+                | Error::UnrecognisedTypeTag(_)
+                | Error::Alignment(..)
+                | Error::Malformed(_)
+                | Error::BlobSize(_)
+                | Error::BlobTooLarge { .. }
+                | Error::StringTooLong { .. }
+                | Error::TooManyArgs { .. }
+                | Error::NestingTooDeep { .. }
+        )
+    }
+}
+
+/// The part of a packet being parsed when an error occurred.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseSection {
+    /// The packet as a whole, e.g. its length.
+    Packet,
+    Address,
+    TypeTags,
+    /// The data of the argument at this index.
+    Arg(usize),
+    /// The timetag of a bundle.
+    Timetag,
+    /// The element of a bundle at this index.
+    Element(usize),
+}
+
+impl Display for ParseSection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseSection::Packet => f.write_str("packet"),
+            ParseSection::Address => f.write_str("address"),
+            ParseSection::TypeTags => f.write_str("type tags"),
+            ParseSection::Arg(index) => f.write_fmt(format_args!("argument {index}")),
+            ParseSection::Timetag => f.write_str("bundle timetag"),
+            ParseSection::Element(index) => f.write_fmt(format_args!("bundle element {index}")),
         }
     }
 }
 
-impl std::error::Error for Error {}
+/// Where parsing a packet failed, for diagnosing malformed packets from a device.
+#[derive(Debug)]
+#[allow(clippy::module_name_repetitions)]
+pub struct ParseError {
+    pub section: ParseSection,
+    /// The offset of the start of ``section`` in the packet.
+    pub offset: usize,
+    /// Up to 16 bytes of the packet, from ``offset``.
+    pub bytes: Vec<u8>,
+    pub error: Error,
+}
+
+impl ParseError {
+    /// Wraps ``error`` with the ``section`` of ``data`` starting at ``offset``.
+    pub(crate) fn wrap(error: Error, section: ParseSection, offset: usize, data: &[u8]) -> Error {
+        let bytes = data.get(offset..).unwrap_or_default();
+        Error::Parse(Box::new(Self {
+            section,
+            offset,
+            bytes: bytes[..bytes.len().min(16)].to_vec(),
+            error,
+        }))
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!(
+            "{}, in {} at byte {} ({:02x?})",
+            self.error, self.section, self.offset, self.bytes
+        ))
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(value: std::io::Error) -> Self {
+        Error::Socket(value)
+    }
+}
+
+impl From<Infallible> for Error {
+    fn from(value: Infallible) -> Self {
+        match value {}
+    }
+}
@@ -0,0 +1,404 @@
+use crate::{errors::Error, scan_bytes, time::OscTime, Arg, Args, OscMessage, Rgba};
+
+/// Up to ``LEN`` bytes stored inline, the contents of a ``FixedArg::Str`` or ``FixedArg::Blob``.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedBytes<const LEN: usize> {
+    bytes: [u8; LEN],
+    len: usize,
+}
+
+impl<const LEN: usize> FixedBytes<LEN> {
+    /// Copies ``bytes``.
+    ///
+    /// # Errors
+    /// Will return ``Error::CapacityExceeded`` if ``bytes`` is longer than ``LEN``.
+    pub fn new(bytes: &[u8]) -> Result<Self, Error> {
+        if bytes.len() > LEN {
+            return Err(Error::CapacityExceeded {
+                size: bytes.len(),
+                capacity: LEN,
+            });
+        }
+        let mut fixed = Self {
+            bytes: [0; LEN],
+            len: bytes.len(),
+        };
+        fixed.bytes[..bytes.len()].copy_from_slice(bytes);
+        Ok(fixed)
+    }
+
+    #[must_use]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+
+    /// Returns the contents as a string, or ``None`` if they aren't valid utf-8. Strings
+    /// created with ``FixedArg::str`` or parsed from a message always are.
+    #[must_use]
+    pub fn as_str(&self) -> Option<&str> {
+        std::str::from_utf8(self.as_bytes()).ok()
+    }
+}
+
+/// An ``Arg`` whose strings and blobs are stored inline, in up to ``STR_LEN`` bytes. Arrays and
+/// custom type tags aren't supported.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FixedArg<const STR_LEN: usize> {
+    Int(i32),
+    Float(f32),
+    Str(FixedBytes<STR_LEN>),
+    Blob(FixedBytes<STR_LEN>),
+    Time(OscTime),
+    Int64(i64),
+    Double(f64),
+    Bool(bool),
+    Nil,
+    Infinitum,
+    Char(char),
+    Color(Rgba),
+    Midi {
+        port: u8,
+        status: u8,
+        data1: u8,
+        data2: u8,
+    },
+    Symbol(FixedBytes<STR_LEN>),
+}
+
+impl<const STR_LEN: usize> FixedArg<STR_LEN> {
+    /// Creates a ``Str`` holding ``s``.
+    ///
+    /// # Errors
+    /// Will return ``Error::CapacityExceeded`` if ``s`` is longer than ``STR_LEN`` bytes.
+    pub fn str(s: &str) -> Result<Self, Error> {
+        FixedBytes::new(s.as_bytes()).map(Self::Str)
+    }
+
+    /// Creates a ``Blob`` holding ``bytes``.
+    ///
+    /// # Errors
+    /// Will return ``Error::CapacityExceeded`` if ``bytes`` is longer than ``STR_LEN``.
+    pub fn blob(bytes: &[u8]) -> Result<Self, Error> {
+        FixedBytes::new(bytes).map(Self::Blob)
+    }
+
+    fn tag(&self) -> u8 {
+        match self {
+            Self::Int(_) => b'i',
+            Self::Float(_) => b'f',
+            Self::Str(_) => b's',
+            Self::Blob(_) => b'b',
+            Self::Time(_) => b't',
+            Self::Int64(_) => b'h',
+            Self::Double(_) => b'd',
+            Self::Bool(true) => b'T',
+            Self::Bool(false) => b'F',
+            Self::Nil => b'N',
+            Self::Infinitum => b'I',
+            Self::Char(_) => b'c',
+            Self::Color(_) => b'r',
+            Self::Midi { .. } => b'm',
+            Self::Symbol(_) => b'S',
+        }
+    }
+
+    /// Returns the size of the argument's data.
+    fn data_len(&self) -> usize {
+        match self {
+            Self::Int(_) | Self::Float(_) | Self::Char(_) | Self::Color(_) | Self::Midi { .. } => 4,
+            Self::Time(_) | Self::Int64(_) | Self::Double(_) => 8,
+            Self::Str(s) | Self::Symbol(s) => s.len + 4 - s.len % 4,
+            Self::Blob(b) => 4 + b.len.next_multiple_of(4),
+            Self::Bool(_) | Self::Nil | Self::Infinitum => 0,
+        }
+    }
+
+    /// Copies the argument into an owned ``Arg``.
+    #[must_use]
+    pub fn to_owned(&self) -> Arg {
+        match self {
+            Self::Int(i) => Arg::Int(*i),
+            Self::Float(f) => Arg::Float(*f),
+            Self::Str(s) => Arg::Str(String::from_utf8_lossy(s.as_bytes()).into_owned()),
+            Self::Blob(b) => Arg::Blob(crate::copy_blob(b.as_bytes())),
+            Self::Time(t) => Arg::Time(*t),
+            Self::Int64(h) => Arg::Int64(*h),
+            Self::Double(d) => Arg::Double(*d),
+            Self::Bool(b) => Arg::Bool(*b),
+            Self::Nil => Arg::Nil,
+            Self::Infinitum => Arg::Infinitum,
+            Self::Char(c) => Arg::Char(*c),
+            Self::Color(c) => Arg::Color(*c),
+            Self::Midi {
+                port,
+                status,
+                data1,
+                data2,
+            } => Arg::Midi {
+                port: *port,
+                status: *status,
+                data1: *data1,
+                data2: *data2,
+            },
+            Self::Symbol(s) => Arg::Symbol(String::from_utf8_lossy(s.as_bytes()).into_owned()),
+        }
+    }
+}
+
+impl<const STR_LEN: usize> TryFrom<&Arg> for FixedArg<STR_LEN> {
+    type Error = Error;
+
+    fn try_from(value: &Arg) -> Result<Self, Self::Error> {
+        Ok(match value {
+            Arg::Int(i) => Self::Int(*i),
+            Arg::Float(f) => Self::Float(*f),
+            Arg::Str(s) => Self::str(s)?,
+            Arg::Blob(b) => Self::blob(b)?,
+            Arg::Time(t) => Self::Time(*t),
+            Arg::Int64(h) => Self::Int64(*h),
+            Arg::Double(d) => Self::Double(*d),
+            Arg::Bool(b) => Self::Bool(*b),
+            Arg::Nil => Self::Nil,
+            Arg::Infinitum => Self::Infinitum,
+            Arg::Char(c) => Self::Char(*c),
+            Arg::Color(c) => Self::Color(*c),
+            Arg::Midi {
+                port,
+                status,
+                data1,
+                data2,
+            } => Self::Midi {
+                port: *port,
+                status: *status,
+                data1: *data1,
+                data2: *data2,
+            },
+            Arg::Symbol(s) => Self::Symbol(FixedBytes::new(s.as_bytes())?),
+            Arg::Array(_) | Arg::Unknown(..) | Arg::Custom(_) => {
+                return Err(Error::UnrecognisedTypeTag(crate::arg_char_repr(value)))
+            }
+        })
+    }
+}
+
+/// An ``OscMessage`` of up to ``N_ARGS`` arguments, whose address, strings, and blobs are each
+/// up to ``STR_LEN`` bytes, stored entirely inline, e.g. for microcontrollers without a heap.
+/// It's encoded into and parsed from a caller-provided ``&[u8]``, so building, sending, and
+/// receiving messages never allocates. Only errors do, to describe what went wrong.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OscMessageFixed<const N_ARGS: usize, const STR_LEN: usize> {
+    address: FixedBytes<STR_LEN>,
+    args: [FixedArg<STR_LEN>; N_ARGS],
+    len: usize,
+}
+
+impl<const N_ARGS: usize, const STR_LEN: usize> OscMessageFixed<N_ARGS, STR_LEN> {
+    /// Creates a message to ``address`` with no arguments.
+    ///
+    /// # Errors
+    /// Will return ``Error::CapacityExceeded`` if ``address`` is longer than ``STR_LEN`` bytes.
+    pub fn new(address: &str) -> Result<Self, Error> {
+        Ok(Self {
+            address: FixedBytes::new(address.as_bytes())?,
+            args: [FixedArg::Nil; N_ARGS],
+            len: 0,
+        })
+    }
+
+    #[must_use]
+    pub fn address(&self) -> &str {
+        self.address.as_str().unwrap_or_default()
+    }
+
+    #[must_use]
+    pub fn args(&self) -> &[FixedArg<STR_LEN>] {
+        &self.args[..self.len]
+    }
+
+    /// Appends ``arg``.
+    ///
+    /// # Errors
+    /// Will return ``Error::CapacityExceeded`` if the message already has ``N_ARGS`` arguments.
+    pub fn push(&mut self, arg: FixedArg<STR_LEN>) -> Result<(), Error> {
+        let slot = self.args.get_mut(self.len).ok_or(Error::CapacityExceeded {
+            size: N_ARGS + 1,
+            capacity: N_ARGS,
+        })?;
+        *slot = arg;
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Returns the size ``self`` encodes to.
+    #[must_use]
+    pub fn encoded_len(&self) -> usize {
+        let tags_len = 1 + self.len;
+        let data_len: usize = self.args().iter().map(FixedArg::data_len).sum();
+        self.address.len + 4 - self.address.len % 4 + tags_len + 4 - tags_len % 4 + data_len
+    }
+
+    /// Writes the encoded message to the start of ``buf``, returning its size.
+    ///
+    /// # Errors
+    /// Will return ``Error::DataLength`` if ``buf`` is shorter than ``encoded_len``, in which
+    /// case nothing is written.
+    pub fn encode_into(&self, buf: &mut [u8]) -> Result<usize, Error> {
+        let size = self.encoded_len();
+        if buf.len() < size {
+            return Err(Error::DataLength(size, buf.len()));
+        }
+        buf[..size].fill(0);
+        let mut idx = 0;
+        let mut write = |bytes: &[u8]| {
+            buf[idx..idx + bytes.len()].copy_from_slice(bytes);
+            idx += bytes.len();
+        };
+
+        write(self.address.as_bytes());
+        idx += 4 - idx % 4;
+        let tags_start = idx;
+        buf[idx] = b',';
+        for (i, arg) in self.args().iter().enumerate() {
+            buf[idx + 1 + i] = arg.tag();
+        }
+        idx += 1 + self.len;
+        idx += 4 - (idx - tags_start) % 4;
+
+        for arg in self.args() {
+            let mut write = |bytes: &[u8]| {
+                buf[idx..idx + bytes.len()].copy_from_slice(bytes);
+                idx += bytes.len();
+            };
+            match arg {
+                FixedArg::Int(i) => write(&i.to_be_bytes()),
+                FixedArg::Float(f) => write(&f.to_be_bytes()),
+                FixedArg::Str(s) | FixedArg::Symbol(s) => {
+                    write(s.as_bytes());
+                    idx += 4 - idx % 4;
+                }
+                FixedArg::Blob(b) => {
+                    // Checked against ``STR_LEN`` when the blob was created
+                    #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+                    write(&(b.len as i32).to_be_bytes());
+                    write(b.as_bytes());
+                    idx = idx.next_multiple_of(4);
+                }
+                FixedArg::Time(t) => write(&u64::from(*t).to_be_bytes()),
+                FixedArg::Int64(h) => write(&h.to_be_bytes()),
+                FixedArg::Double(d) => write(&d.to_be_bytes()),
+                FixedArg::Char(c) => write(&u32::from(*c).to_be_bytes()),
+                FixedArg::Color(c) => write(&<[u8; 4]>::from(*c)),
+                FixedArg::Midi {
+                    port,
+                    status,
+                    data1,
+                    data2,
+                } => write(&[*port, *status, *data1, *data2]),
+                FixedArg::Bool(_) | FixedArg::Nil | FixedArg::Infinitum => {}
+            }
+        }
+        Ok(size)
+    }
+
+    /// Parses ``data`` into a message, copying its address, strings, and blobs inline.
+    ///
+    /// # Errors
+    /// See ``OscMessage::parse_bytes``. Will also return ``Error::CapacityExceeded`` if the
+    /// message has more than ``N_ARGS`` arguments, or a string or blob longer than ``STR_LEN``
+    /// bytes, and ``Error::UnrecognisedTypeTag`` for arrays. Unlike ``parse_bytes``, errors aren't
+    /// wrapped in an ``Error::Parse``.
+    pub fn parse_bytes(data: &[u8]) -> Result<Self, Error> {
+        if !data.len().is_multiple_of(4) {
+            return Err(Error::Alignment(data.len(), 4));
+        }
+        let mut idx = 0;
+        let address = scan_str(&mut idx, data, "OSC address")?;
+        let mut msg = Self::new(address)?;
+        let tags = scan_str(&mut idx, data, "OSC argument type tags")?;
+        let tags = match tags.strip_prefix(',') {
+            Some(tags) => tags,
+            None if tags.is_empty() => tags,
+            None => return Err(Error::Malformed("OSC argument type tags".to_string())),
+        };
+        for tag in tags.chars() {
+            let arg = match tag {
+                'i' => FixedArg::Int(i32::from_be_bytes(scan_bytes(&mut idx, data)?)),
+                'f' => FixedArg::Float(f32::from_be_bytes(scan_bytes(&mut idx, data)?)),
+                's' => FixedArg::str(scan_str(&mut idx, data, "OSC string")?)?,
+                'S' => FixedArg::Symbol(FixedBytes::new(
+                    scan_str(&mut idx, data, "OSC string")?.as_bytes(),
+                )?),
+                'b' => {
+                    let blob_size = i32::from_be_bytes(scan_bytes(&mut idx, data)?);
+                    let length =
+                        usize::try_from(blob_size).map_err(|_| Error::BlobSize(blob_size))?;
+                    let rest = data.get(idx..).unwrap_or_default();
+                    let blob = rest
+                        .get(..length)
+                        .ok_or(Error::DataLength(length, rest.len()))?;
+                    idx = (idx + length).next_multiple_of(4);
+                    FixedArg::blob(blob)?
+                }
+                't' => FixedArg::Time(u64::from_be_bytes(scan_bytes(&mut idx, data)?).into()),
+                'h' => FixedArg::Int64(i64::from_be_bytes(scan_bytes(&mut idx, data)?)),
+                'd' => FixedArg::Double(f64::from_be_bytes(scan_bytes(&mut idx, data)?)),
+                'T' => FixedArg::Bool(true),
+                'F' => FixedArg::Bool(false),
+                'N' => FixedArg::Nil,
+                'I' => FixedArg::Infinitum,
+                'c' => FixedArg::Char(
+                    char::from_u32(u32::from_be_bytes(scan_bytes(&mut idx, data)?))
+                        .ok_or_else(|| Error::Malformed("OSC char".to_string()))?,
+                ),
+                'r' => FixedArg::Color(scan_bytes::<4>(&mut idx, data)?.into()),
+                'm' => {
+                    let [port, status, data1, data2] = scan_bytes(&mut idx, data)?;
+                    FixedArg::Midi {
+                        port,
+                        status,
+                        data1,
+                        data2,
+                    }
+                }
+                _ => return Err(Error::UnrecognisedTypeTag(tag)),
+            };
+            msg.push(arg)?;
+        }
+        Ok(msg)
+    }
+
+    /// Copies the message into an owned ``OscMessage``.
+    #[must_use]
+    pub fn to_owned(&self) -> OscMessage {
+        OscMessage::new(
+            self.address(),
+            self.args().iter().map(FixedArg::to_owned).collect::<Args>(),
+        )
+    }
+}
+
+impl<const N_ARGS: usize, const STR_LEN: usize> TryFrom<&OscMessage>
+    for OscMessageFixed<N_ARGS, STR_LEN>
+{
+    type Error = Error;
+
+    fn try_from(value: &OscMessage) -> Result<Self, Self::Error> {
+        let mut msg = Self::new(&value.address)?;
+        for arg in &value.args {
+            msg.push(arg.try_into()?)?;
+        }
+        Ok(msg)
+    }
+}
+
+/// Reads a null-terminated, padded string from ``data`` at ``idx``, advancing ``idx`` past it.
+fn scan_str<'a>(idx: &mut usize, data: &'a [u8], what: &str) -> Result<&'a str, Error> {
+    let rest = data.get(*idx..).unwrap_or_default();
+    let length = rest
+        .iter()
+        .position(|b| *b == 0)
+        .ok_or(Error::DataLength(rest.len() + 1, rest.len()))?;
+    *idx += length;
+    *idx += 4 - (*idx % 4);
+    std::str::from_utf8(&rest[..length]).map_err(|_| Error::Utf8(what.to_string()))
+}
@@ -0,0 +1,105 @@
+use crate::{address::OscAddress, errors::Error, Arg, OscMessage};
+
+/// What a handler can return to reply to a message. Replies are sent back to the message's sender.
+pub trait IntoReplies {
+    /// Converts ``self`` into the replies to a message received at ``address``.
+    ///
+    /// # Errors
+    /// Will return the handler's error, if it failed.
+    fn into_replies(self, address: &OscAddress) -> Result<Vec<OscMessage>, Error>;
+}
+
+/// No reply.
+impl IntoReplies for () {
+    fn into_replies(self, _: &OscAddress) -> Result<Vec<OscMessage>, Error> {
+        Ok(Vec::new())
+    }
+}
+
+/// ``Some(args)`` replies with ``args`` at the address the message was received at.
+impl IntoReplies for Option<Vec<Arg>> {
+    fn into_replies(self, address: &OscAddress) -> Result<Vec<OscMessage>, Error> {
+        Ok(self
+            .map(|args| vec![OscMessage::new(address, args)])
+            .unwrap_or_default())
+    }
+}
+
+impl IntoReplies for OscMessage {
+    fn into_replies(self, _: &OscAddress) -> Result<Vec<OscMessage>, Error> {
+        Ok(vec![self])
+    }
+}
+
+impl IntoReplies for Option<OscMessage> {
+    fn into_replies(self, _: &OscAddress) -> Result<Vec<OscMessage>, Error> {
+        Ok(self.into_iter().collect())
+    }
+}
+
+impl IntoReplies for Vec<OscMessage> {
+    fn into_replies(self, _: &OscAddress) -> Result<Vec<OscMessage>, Error> {
+        Ok(self)
+    }
+}
+
+/// ``Err(e)`` is passed on to the server, which replies with its error reply instead.
+impl<R: IntoReplies> IntoReplies for Result<R, Error> {
+    fn into_replies(self, address: &OscAddress) -> Result<Vec<OscMessage>, Error> {
+        self?.into_replies(address)
+    }
+}
+
+/// A handler taking typed arguments, such as ``|ch: i32, level: f32| ...``, which are extracted
+/// from a message's arguments with their ``TryFrom<Arg>`` implementations.
+///
+/// Implemented for ``FnMut`` closures of up to 8 arguments which return ``IntoReplies``.
+pub trait TypedHandler<Args>: Send + 'static {
+    /// Extracts the handler's arguments from ``args`` and calls it, returning its replies to a
+    /// message received at ``address``.
+    ///
+    /// # Errors
+    /// Will return ``Error::Malformed`` if there are the wrong number of arguments, or one of them
+    /// has the wrong type, or the handler's own error if it failed.
+    fn call(&mut self, address: &OscAddress, args: Vec<Arg>) -> Result<Vec<OscMessage>, Error>;
+}
+
+macro_rules! impl_typed_handler {
+    ($count:literal $(, $arg:ident)*) => {
+        impl<Func, Ret, $($arg,)*> TypedHandler<($($arg,)*)> for Func
+        where
+            Func: FnMut($($arg),*) -> Ret + Send + 'static,
+            Ret: IntoReplies,
+            $($arg: TryFrom<Arg, Error = Error>,)*
+        {
+            #[allow(non_snake_case)]
+            fn call(
+                &mut self,
+                address: &OscAddress,
+                args: Vec<Arg>,
+            ) -> Result<Vec<OscMessage>, Error> {
+                if args.len() != $count {
+                    return Err(Error::Malformed(format!(
+                        "Expected {} arguments, received {}",
+                        $count,
+                        args.len()
+                    )));
+                }
+                #[allow(unused_mut, unused_variables)]
+                let mut args = args.into_iter();
+                $(let $arg = $arg::try_from(args.next().expect("length checked above"))?;)*
+                self($($arg),*).into_replies(address)
+            }
+        }
+    };
+}
+
+impl_typed_handler!(0);
+impl_typed_handler!(1, A);
+impl_typed_handler!(2, A, B);
+impl_typed_handler!(3, A, B, C);
+impl_typed_handler!(4, A, B, C, D);
+impl_typed_handler!(5, A, B, C, D, E);
+impl_typed_handler!(6, A, B, C, D, E, F);
+impl_typed_handler!(7, A, B, C, D, E, F, G);
+impl_typed_handler!(8, A, B, C, D, E, F, G, H);
@@ -1,16 +1,226 @@
+#[cfg(all(feature = "server", not(any(feature = "udp", feature = "tcp"))))]
+compile_error!("The server feature needs a transport: enable udp, tcp, or both");
+
+pub mod acl;
+pub mod address;
+#[cfg(feature = "client")]
+mod async_client;
+#[cfg(all(feature = "tokio", feature = "server", feature = "udp"))]
+mod async_server;
+pub mod async_sockets;
+#[cfg(feature = "server")]
+pub mod auth;
+pub mod bridge;
+pub mod builder;
+pub mod bundle;
+pub mod cancel;
+pub mod channel;
+#[cfg(feature = "chunking")]
+pub mod chunk;
+#[cfg(feature = "client")]
 pub mod client;
+#[cfg(feature = "tokio")]
+pub mod codec;
+pub mod convert;
+pub mod custom;
+pub mod debug;
 pub mod errors;
+pub mod fixed;
+#[cfg(feature = "server")]
+pub mod handler;
+pub mod limit;
+pub mod message_ref;
+#[cfg(feature = "midir")]
+pub mod midi;
+#[cfg(all(feature = "mio", feature = "server", feature = "udp", feature = "tcp"))]
+pub mod mio_server;
+#[cfg(all(feature = "client", not(target_family = "wasm")))]
+pub mod mux;
+pub mod namespace;
+#[cfg(feature = "oscquery")]
+pub mod oscquery;
+pub mod parse;
+pub mod pattern;
+#[cfg(all(feature = "server", feature = "udp"))]
+pub mod peer;
+pub mod pool;
+#[cfg(feature = "quic")]
+pub mod quic;
+pub mod ramp;
+#[cfg(feature = "udp")]
+pub mod receiver;
+pub mod recording;
+pub mod router;
+#[cfg(feature = "server")]
+mod schedule;
+pub mod schema;
+#[cfg(feature = "client")]
+pub mod selector;
+pub mod sender;
+pub mod sequence;
+#[cfg(feature = "server")]
+pub mod server;
+pub mod service;
+#[cfg(feature = "server")]
+pub mod session;
+#[cfg(feature = "shm")]
+pub mod shm;
+#[cfg(all(feature = "socket2", any(feature = "udp", feature = "tcp")))]
+pub mod socket_builder;
 pub mod sockets;
+pub mod state;
+#[cfg(feature = "client")]
+mod sync;
+pub mod taper;
+pub mod template;
+#[cfg(feature = "testing")]
+pub mod testing;
+mod text;
+pub mod time;
+#[cfg(feature = "rustls")]
+pub mod tls;
+
+use std::{
+    io::Write,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    time::{Duration, Instant, SystemTime},
+};
+
+use builder::OscMessageBuilder;
+use bundle::OscBundle;
+use convert::FromOsc;
+use custom::CustomArg;
+use errors::{Error, ParseError, ParseSection};
+use parse::ParseOptions;
+use time::OscTime;
 
-use errors::Error;
+/// The contents of an ``Arg::Blob``. With the ``bytes`` feature, this is a reference-counted
+/// ``bytes::Bytes``, so blobs parsed with ``OscPacket::parse_shared`` share the packet's memory
+/// rather than being copied out of it.
+#[cfg(feature = "bytes")]
+pub type Blob = bytes::Bytes;
+/// The contents of an ``Arg::Blob``. With the ``bytes`` feature, this is a reference-counted
+/// ``bytes::Bytes``, so blobs parsed with ``OscPacket::parse_shared`` share the packet's memory
+/// rather than being copied out of it.
+#[cfg(not(feature = "bytes"))]
+pub type Blob = Vec<u8>;
 
+/// With the ``serde`` feature, an ``Arg`` is represented by its variant name in snake case and
+/// its value, e.g. ``{"type": "int", "value": 1}`` in JSON, or ``{"type": "nil"}`` for one with
+/// no value.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(tag = "type", content = "value", rename_all = "snake_case")
+)]
 pub enum Arg {
     // Core OSC Type Tags
     Int(i32),
     Float(f32),
     Str(String),
-    Blob(Vec<u8>),
+    Blob(Blob),
+    Time(OscTime),
+    Int64(i64),
+    Double(f64),
+    Bool(bool),
+    Nil,
+    Infinitum,
+    Char(char),
+    Color(Rgba),
+    /// A 4-byte MIDI message: port id, status byte, and two data bytes.
+    Midi {
+        port: u8,
+        status: u8,
+        data1: u8,
+        data2: u8,
+    },
+    /// A group of arguments, delimited by the ``[`` and ``]`` type tags.
+    Array(Vec<Arg>),
+    /// An alternate string type, encoded like ``Str`` but with the ``S`` type tag.
+    Symbol(String),
+    /// An argument with a type tag this crate doesn't know, and its raw data, kept when parsing
+    /// with ``ParseOptions::preserve_unknown_tags`` so the message can be passed on unchanged.
+    /// The size of unknown data can't be known, so the first unknown argument holds all of the
+    /// remaining data, and the arguments after it none.
+    Unknown(char, Vec<u8>),
+    /// An argument with an application-defined type tag. See ``TypeTagRegistry``. Custom
+    /// arguments can't be serialized with ``serde``.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    Custom(Arc<dyn CustomArg>),
+}
+
+/// A 32-bit RGBA color, as carried by the ``r`` type tag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rgba {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+impl From<[u8; 4]> for Rgba {
+    fn from([r, g, b, a]: [u8; 4]) -> Self {
+        Self { r, g, b, a }
+    }
+}
+
+impl From<Rgba> for [u8; 4] {
+    fn from(value: Rgba) -> Self {
+        [value.r, value.g, value.b, value.a]
+    }
+}
+
+/// Interprets ``value`` as ``0xRRGGBBAA``.
+impl From<u32> for Rgba {
+    fn from(value: u32) -> Self {
+        value.to_be_bytes().into()
+    }
+}
+
+impl From<Rgba> for u32 {
+    fn from(value: Rgba) -> Self {
+        u32::from_be_bytes(value.into())
+    }
+}
+
+/// A ``Float`` in ``0.0..=1.0``, the range many control surfaces use for faders and knobs. Read
+/// one with ``msg.arg::<Normalized>(0)?`` to reject values outside it.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
+pub struct Normalized(f32);
+
+impl Normalized {
+    /// # Errors
+    /// Will return ``Error::Malformed`` if ``value`` isn't in ``0.0..=1.0``.
+    pub fn new(value: f32) -> Result<Self, Error> {
+        if (0.0..=1.0).contains(&value) {
+            Ok(Self(value))
+        } else {
+            Err(Error::Malformed(format!(
+                "{value} is not in the normalized range (0..=1)"
+            )))
+        }
+    }
+
+    #[must_use]
+    pub fn get(self) -> f32 {
+        self.0
+    }
+}
+
+impl TryFrom<f32> for Normalized {
+    type Error = Error;
+
+    fn try_from(value: f32) -> Result<Self, Self::Error> {
+        Self::new(value)
+    }
+}
+
+impl From<Normalized> for f32 {
+    fn from(value: Normalized) -> Self {
+        value.0
+    }
 }
 
 impl TryFrom<Arg> for i32 {
@@ -34,10 +244,8 @@ impl TryFrom<Arg> for f32 {
 
     fn try_from(value: Arg) -> Result<Self, Self::Error> {
         match value {
-            Arg::Float(f) if (0.0..=1.0).contains(&f) => Ok(f),
-            _ => Err(Error::Malformed(format!(
-                "{value:?} is not Float or is not in the valid OSC range (0..=1)"
-            ))),
+            Arg::Float(f) if f.is_finite() => Ok(f),
+            _ => Err(Error::Malformed(format!("{value:?} is not a finite f32"))),
         }
     }
 }
@@ -46,6 +254,119 @@ impl From<f32> for Arg {
         Arg::Float(value)
     }
 }
+impl TryFrom<Arg> for Normalized {
+    type Error = Error;
+
+    fn try_from(value: Arg) -> Result<Self, Self::Error> {
+        match value {
+            Arg::Float(f) => Self::new(f),
+            _ => Err(Error::Malformed(format!("{value:?} is not Float"))),
+        }
+    }
+}
+impl From<Normalized> for Arg {
+    fn from(value: Normalized) -> Self {
+        Arg::Float(value.0)
+    }
+}
+impl TryFrom<Arg> for i64 {
+    type Error = Error;
+
+    fn try_from(value: Arg) -> Result<Self, Self::Error> {
+        match value {
+            Arg::Int64(h) => Ok(h),
+            Arg::Int(i) => Ok(i.into()),
+            _ => Err(Error::Malformed(format!("{value:?} is not i64"))),
+        }
+    }
+}
+impl From<i64> for Arg {
+    fn from(value: i64) -> Self {
+        Arg::Int64(value)
+    }
+}
+impl TryFrom<Arg> for f64 {
+    type Error = Error;
+
+    fn try_from(value: Arg) -> Result<Self, Self::Error> {
+        match value {
+            Arg::Double(d) => Ok(d),
+            _ => Err(Error::Malformed(format!("{value:?} is not f64"))),
+        }
+    }
+}
+impl From<f64> for Arg {
+    fn from(value: f64) -> Self {
+        Arg::Double(value)
+    }
+}
+impl TryFrom<Arg> for bool {
+    type Error = Error;
+
+    fn try_from(value: Arg) -> Result<Self, Self::Error> {
+        match value {
+            Arg::Bool(b) => Ok(b),
+            _ => Err(Error::Malformed(format!("{value:?} is not bool"))),
+        }
+    }
+}
+impl From<bool> for Arg {
+    fn from(value: bool) -> Self {
+        Arg::Bool(value)
+    }
+}
+impl TryFrom<Arg> for char {
+    type Error = Error;
+
+    fn try_from(value: Arg) -> Result<Self, Self::Error> {
+        match value {
+            Arg::Char(c) => Ok(c),
+            _ => Err(Error::Malformed(format!("{value:?} is not char"))),
+        }
+    }
+}
+impl From<char> for Arg {
+    fn from(value: char) -> Self {
+        Arg::Char(value)
+    }
+}
+impl TryFrom<Arg> for Rgba {
+    type Error = Error;
+
+    fn try_from(value: Arg) -> Result<Self, Self::Error> {
+        match value {
+            Arg::Color(c) => Ok(c),
+            _ => Err(Error::Malformed(format!("{value:?} is not Rgba"))),
+        }
+    }
+}
+impl From<Rgba> for Arg {
+    fn from(value: Rgba) -> Self {
+        Arg::Color(value)
+    }
+}
+impl TryFrom<Arg> for Vec<Arg> {
+    type Error = Error;
+
+    fn try_from(value: Arg) -> Result<Self, Self::Error> {
+        match value {
+            Arg::Array(a) => Ok(a),
+            _ => Err(Error::Malformed(format!("{value:?} is not Array"))),
+        }
+    }
+}
+/// Collects arguments into an ``Array``.
+impl FromIterator<Arg> for Arg {
+    fn from_iter<I: IntoIterator<Item = Arg>>(iter: I) -> Self {
+        Arg::Array(iter.into_iter().collect())
+    }
+}
+
+impl From<Vec<Arg>> for Arg {
+    fn from(value: Vec<Arg>) -> Self {
+        Arg::Array(value)
+    }
+}
 impl TryFrom<Arg> for String {
     type Error = Error;
 
@@ -61,12 +382,19 @@ impl From<String> for Arg {
         Arg::Str(value)
     }
 }
+impl From<&str> for Arg {
+    fn from(value: &str) -> Self {
+        Arg::Str(value.to_string())
+    }
+}
 impl TryFrom<Arg> for Vec<u8> {
     type Error = Error;
 
+    // With the ``bytes`` feature, ``Blob`` isn't a ``Vec``
+    #[allow(clippy::useless_conversion)]
     fn try_from(value: Arg) -> Result<Self, Self::Error> {
         match value {
-            Arg::Blob(b) => Ok(b),
+            Arg::Blob(b) => Ok(b.into()),
             _ => Err(Error::Malformed(format!("{value:?} is not Vec<u8>"))),
         }
     }
@@ -77,41 +405,231 @@ impl<const N: usize> TryFrom<Arg> for [u8; N] {
 
     fn try_from(value: Arg) -> Result<Self, Self::Error> {
         match value {
-            Arg::Blob(b) if b.len() == N => Ok(b.try_into().unwrap()),
+            Arg::Blob(b) if b.len() == N => Ok(b[..].try_into().unwrap()),
             _ => Err(Error::Malformed(format!("{value:?} is not [u8; {N}]"))),
         }
     }
 }
 
 impl From<Vec<u8>> for Arg {
+    // With the ``bytes`` feature, ``Blob`` isn't a ``Vec``
+    #[allow(clippy::useless_conversion)]
     fn from(value: Vec<u8>) -> Self {
+        Arg::Blob(value.into())
+    }
+}
+
+impl From<&[u8]> for Arg {
+    fn from(value: &[u8]) -> Self {
+        Arg::Blob(copy_blob(value))
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl TryFrom<Arg> for bytes::Bytes {
+    type Error = Error;
+
+    fn try_from(value: Arg) -> Result<Self, Self::Error> {
+        match value {
+            Arg::Blob(b) => Ok(b),
+            _ => Err(Error::Malformed(format!("{value:?} is not Bytes"))),
+        }
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl From<bytes::Bytes> for Arg {
+    fn from(value: bytes::Bytes) -> Self {
         Arg::Blob(value)
     }
 }
+
+impl From<u8> for Arg {
+    fn from(value: u8) -> Self {
+        Arg::Int(value.into())
+    }
+}
+
+impl From<u16> for Arg {
+    fn from(value: u16) -> Self {
+        Arg::Int(value.into())
+    }
+}
+
+/// Converts an unsigned integer into ``$variant``, failing if it doesn't fit.
+macro_rules! unsigned_into_arg {
+    ($ty:ty, $variant:ident, $inner:ty) => {
+        impl TryFrom<$ty> for Arg {
+            type Error = Error;
+
+            fn try_from(value: $ty) -> Result<Self, Self::Error> {
+                <$inner>::try_from(value)
+                    .map(Arg::$variant)
+                    .map_err(|_| Error::Malformed(format!("{value} as {}", stringify!($variant))))
+            }
+        }
+    };
+}
+
+unsigned_into_arg!(u32, Int, i32);
+unsigned_into_arg!(u64, Int64, i64);
+unsigned_into_arg!(usize, Int64, i64);
+
+/// Converts an ``Int`` or ``Int64`` into an unsigned integer, failing if it doesn't fit.
+macro_rules! unsigned_from_arg {
+    ($($ty:ty),*) => {
+        $(
+            impl TryFrom<Arg> for $ty {
+                type Error = Error;
+
+                fn try_from(value: Arg) -> Result<Self, Self::Error> {
+                    let int = match value {
+                        Arg::Int(i) => Self::try_from(i).ok(),
+                        Arg::Int64(h) => Self::try_from(h).ok(),
+                        _ => None,
+                    };
+                    int.ok_or_else(|| {
+                        Error::Malformed(format!("{value:?} is not {}", stringify!($ty)))
+                    })
+                }
+            }
+        )*
+    };
+}
+
+unsigned_from_arg!(u8, u16, u32, u64, usize);
+
+/// ``None`` is sent as ``Nil``, so an optional parameter can be left unset without a sentinel
+/// value.
+impl<T: Into<Arg>> From<Option<T>> for Arg {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Arg::Nil, Into::into)
+    }
+}
+
+/// ``Nil`` reads as ``None``, and anything else as ``Some`` of ``T``.
+impl<T: TryFrom<Arg, Error = Error>> TryFrom<Arg> for Option<T> {
+    type Error = Error;
+
+    fn try_from(value: Arg) -> Result<Self, Self::Error> {
+        match value {
+            Arg::Nil => Ok(None),
+            _ => T::try_from(value).map(Some),
+        }
+    }
+}
+
+impl TryFrom<Arg> for OscTime {
+    type Error = Error;
+
+    fn try_from(value: Arg) -> Result<Self, Self::Error> {
+        match value {
+            Arg::Time(t) => Ok(t),
+            _ => Err(Error::Malformed(format!("{value:?} is not OscTime"))),
+        }
+    }
+}
+
+impl From<OscTime> for Arg {
+    fn from(value: OscTime) -> Self {
+        Arg::Time(value)
+    }
+}
+
+/// A ``Time`` is read as the ``SystemTime`` it stands for, in the 1900-2036 NTP era.
+impl TryFrom<Arg> for SystemTime {
+    type Error = Error;
+
+    fn try_from(value: Arg) -> Result<Self, Self::Error> {
+        OscTime::try_from(value)?.try_into()
+    }
+}
+
+/// A ``SystemTime`` is sent as a ``Time``.
+impl From<SystemTime> for Arg {
+    fn from(value: SystemTime) -> Self {
+        Arg::Time(value.into())
+    }
+}
+
+/// An ``Instant`` is sent as a ``Time``, placed relative to the current system time. See
+/// ``OscTime``'s ``From<Instant>``.
+impl From<Instant> for Arg {
+    fn from(value: Instant) -> Self {
+        Arg::Time(value.into())
+    }
+}
+
+/// A ``Float`` or ``Double`` is read as a ``Duration`` of that many seconds.
+impl TryFrom<Arg> for Duration {
+    type Error = Error;
+
+    fn try_from(value: Arg) -> Result<Self, Self::Error> {
+        let duration = match value {
+            Arg::Float(f) => Duration::try_from_secs_f32(f).ok(),
+            Arg::Double(d) => Duration::try_from_secs_f64(d).ok(),
+            _ => None,
+        };
+        duration.ok_or_else(|| Error::Malformed(format!("{value:?} is not Duration")))
+    }
+}
+
+/// A ``Duration`` is sent as a ``Float`` of seconds, the usual type for fade and delay times. Use
+/// ``Arg::Double(duration.as_secs_f64())`` if the receiver needs more precision.
+impl From<Duration> for Arg {
+    fn from(value: Duration) -> Self {
+        Arg::Float(value.as_secs_f32())
+    }
+}
 fn arg_char_repr(arg: &Arg) -> char {
-    use self::Arg::{Blob, Float, Int, Str};
+    use self::Arg::{
+        Array, Blob, Bool, Char, Color, Custom, Double, Float, Infinitum, Int, Int64, Midi, Nil,
+        Str, Symbol, Time, Unknown,
+    };
     match arg {
         Int(_) => 'i',
         Float(_) => 'f',
         Str(_) => 's',
         Blob(_) => 'b',
+        Time(_) => 't',
+        Int64(_) => 'h',
+        Double(_) => 'd',
+        Bool(true) => 'T',
+        Bool(false) => 'F',
+        Nil => 'N',
+        Infinitum => 'I',
+        Char(_) => 'c',
+        Color(_) => 'r',
+        Midi { .. } => 'm',
+        Array(_) => '[',
+        Symbol(_) => 'S',
+        Unknown(tag, _) => *tag,
+        Custom(value) => value.tag(),
     }
 }
 
-fn type_tag_to_default_arg(tag: char) -> Result<Arg, Error> {
-    match tag {
-        'i' => Ok(Arg::Int(0)),
-        'f' => Ok(Arg::Float(0.0)),
-        's' => Ok(Arg::Str(String::new())),
-        'b' => Ok(Arg::Blob(Vec::new())),
-        _ => Err(Error::UnrecognisedTypeTag(tag)),
+pub(crate) fn write_type_tags(arg: &Arg, tags: &mut String) {
+    tags.push(arg_char_repr(arg));
+    if let Arg::Array(items) = arg {
+        for item in items {
+            write_type_tags(item, tags);
+        }
+        tags.push(']');
     }
 }
 
-fn write_string(arg: &str) -> Vec<u8> {
+/// Returns whether ``tag`` is one of the type tags this crate parses itself.
+pub(crate) fn is_standard_tag(tag: char) -> bool {
+    matches!(
+        tag,
+        'i' | 'f' | 's' | 'b' | 't' | 'h' | 'd' | 'T' | 'F' | 'N' | 'I' | 'c' | 'r' | 'S' | 'm'
+    )
+}
+
+pub(crate) fn write_string(arg: &str) -> Vec<u8> {
     let mut bytes = arg.as_bytes().to_vec();
     bytes.append(&mut vec![b'\0'; 4 - (arg.len() % 4)]);
-    assert!(bytes.len() % 4 == 0);
+    assert!(bytes.len().is_multiple_of(4));
     bytes
 }
 
@@ -120,87 +638,515 @@ fn write_blob(mut arg: Vec<u8>) -> Result<Vec<u8>, Error> {
         .map_err(|_| Error::DataLength(i32::MAX as usize, arg.len())))?
     .to_be_bytes()
     .to_vec();
-    arg.append(&mut vec![b'\0'; 4 - (arg.len() % 4)]);
-    assert!(arg.len() % 4 == 0);
+    // Unlike strings, blobs need no terminator, so only pad if unaligned
+    arg.append(&mut vec![b'\0'; (4 - (arg.len() % 4)) % 4]);
+    assert!(arg.len().is_multiple_of(4));
     size_bytes.append(&mut arg);
     Ok(size_bytes)
 }
 
-fn write_arg(arg: &Arg) -> Result<Vec<u8>, Error> {
-    use self::Arg::{Blob, Float, Int, Str};
+pub(crate) fn write_arg(arg: &Arg) -> Result<Vec<u8>, Error> {
+    use self::Arg::{
+        Array, Blob, Bool, Char, Color, Custom, Double, Float, Infinitum, Int, Int64, Midi, Nil,
+        Str, Symbol, Time, Unknown,
+    };
     Ok(match arg {
         Float(f) => f.to_be_bytes().to_vec(),
-        // Double(d) => d.to_be_bytes().to_vec(),
+        Double(d) => d.to_be_bytes().to_vec(),
         Int(i) => i.to_be_bytes().to_vec(),
-        // Int64(h) => h.to_be_bytes().to_vec(),
-        Str(s) => write_string(s),
-        Blob(b) => write_blob(b.clone())?,
+        Int64(h) => h.to_be_bytes().to_vec(),
+        Str(s) | Symbol(s) => write_string(s),
+        Blob(b) => write_blob(b[..].to_vec())?,
+        Time(t) => u64::from(*t).to_be_bytes().to_vec(),
+        Char(c) => u32::from(*c).to_be_bytes().to_vec(),
+        Color(c) => <[u8; 4]>::from(*c).to_vec(),
+        Midi {
+            port,
+            status,
+            data1,
+            data2,
+        } => vec![*port, *status, *data1, *data2],
+        Array(items) => {
+            let mut bytes = Vec::new();
+            for item in items {
+                bytes.append(&mut write_arg(item)?);
+            }
+            bytes
+        }
+        Unknown(_, data) => data.clone(),
+        Custom(value) => value.encode()?,
+        // These are encoded entirely by their type tag
+        Bool(_) | Nil | Infinitum => Vec::new(),
     })
 }
 
+/// Returns the size of a string of ``size`` bytes once null terminated and padded.
+fn padded_len(size: usize) -> usize {
+    size + 4 - size % 4
+}
+
+/// Returns the size of the type tags of ``arg``, as ``encode_type_tags`` writes them.
+fn type_tags_len(arg: &Arg) -> usize {
+    match arg {
+        Arg::Array(items) => 2 + items.iter().map(type_tags_len).sum::<usize>(),
+        _ => arg_char_repr(arg).len_utf8(),
+    }
+}
+
+/// Returns the size of the data of ``arg``, as ``encode_arg`` writes it.
+fn arg_len(arg: &Arg) -> Result<usize, Error> {
+    use self::Arg::{
+        Array, Blob, Bool, Char, Color, Custom, Double, Float, Infinitum, Int, Int64, Midi, Nil,
+        Str, Symbol, Time, Unknown,
+    };
+    Ok(match arg {
+        Int(_) | Float(_) | Char(_) | Color(_) | Midi { .. } => 4,
+        Time(_) | Int64(_) | Double(_) => 8,
+        Str(s) | Symbol(s) => padded_len(s.len()),
+        Blob(b) => {
+            i32::try_from(b.len()).map_err(|_| Error::DataLength(i32::MAX as usize, b.len()))?;
+            4 + b.len().next_multiple_of(4)
+        }
+        Array(items) => {
+            let mut size = 0;
+            for item in items {
+                size += arg_len(item)?;
+            }
+            size
+        }
+        Unknown(_, data) => data.len(),
+        Custom(value) => value.encode()?.len(),
+        // These are encoded entirely by their type tag
+        Bool(_) | Nil | Infinitum => 0,
+    })
+}
+
+/// Writes the null terminator and padding after ``size`` bytes of a string, returning how many
+/// bytes were written.
+fn pad<W: Write>(size: usize, writer: &mut W) -> Result<usize, Error> {
+    let padding = 4 - (size % 4);
+    writer
+        .write_all(&[0; 4][..padding])
+        .map_err(Error::Socket)?;
+    Ok(padding)
+}
+
+fn encode_string<W: Write>(arg: &str, writer: &mut W) -> Result<usize, Error> {
+    writer.write_all(arg.as_bytes()).map_err(Error::Socket)?;
+    Ok(arg.len() + pad(arg.len(), writer)?)
+}
+
+fn encode_type_tags<W: Write>(arg: &Arg, writer: &mut W) -> Result<usize, Error> {
+    let mut tag = [0; 4];
+    let tag = arg_char_repr(arg).encode_utf8(&mut tag);
+    writer.write_all(tag.as_bytes()).map_err(Error::Socket)?;
+    let mut size = tag.len();
+    if let Arg::Array(items) = arg {
+        for item in items {
+            size += encode_type_tags(item, writer)?;
+        }
+        writer.write_all(b"]").map_err(Error::Socket)?;
+        size += 1;
+    }
+    Ok(size)
+}
+
+/// Writes the data of ``arg`` to ``writer``, as ``write_arg`` encodes it, returning its size.
+fn encode_arg<W: Write>(arg: &Arg, writer: &mut W) -> Result<usize, Error> {
+    use self::Arg::{
+        Array, Blob, Bool, Char, Color, Custom, Double, Float, Infinitum, Int, Int64, Midi, Nil,
+        Str, Symbol, Time, Unknown,
+    };
+    let mut write = |bytes: &[u8]| {
+        writer.write_all(bytes).map_err(Error::Socket)?;
+        Ok(bytes.len())
+    };
+    match arg {
+        Float(f) => write(&f.to_be_bytes()),
+        Double(d) => write(&d.to_be_bytes()),
+        Int(i) => write(&i.to_be_bytes()),
+        Int64(h) => write(&h.to_be_bytes()),
+        Str(s) | Symbol(s) => encode_string(s, writer),
+        Blob(b) => {
+            let size = i32::try_from(b.len())
+                .map_err(|_| Error::DataLength(i32::MAX as usize, b.len()))?;
+            let padding = (4 - (b.len() % 4)) % 4;
+            Ok(write(&size.to_be_bytes())? + write(b)? + write(&[0; 4][..padding])?)
+        }
+        Time(t) => write(&u64::from(*t).to_be_bytes()),
+        Char(c) => write(&u32::from(*c).to_be_bytes()),
+        Color(c) => write(&<[u8; 4]>::from(*c)),
+        Midi {
+            port,
+            status,
+            data1,
+            data2,
+        } => write(&[*port, *status, *data1, *data2]),
+        Array(items) => {
+            let mut size = 0;
+            for item in items {
+                size += encode_arg(item, writer)?;
+            }
+            Ok(size)
+        }
+        Unknown(_, data) => write(data),
+        Custom(value) => write(&value.encode()?),
+        // These are encoded entirely by their type tag
+        Bool(_) | Nil | Infinitum => Ok(0),
+    }
+}
+
 fn scan_into_byte_array(arr: &mut [u8], idx: &mut usize, data: &[u8]) -> Result<(), Error> {
     let length = arr.len();
     for item in &mut *arr {
-        *item = *data
-            .get(*idx)
-            .ok_or_else(|| Error::DataLength(length, *idx))?;
+        *item = *data.get(*idx).ok_or(Error::DataLength(length, *idx))?;
         *idx += 1;
     }
     Ok(())
 }
 
-#[derive(Clone)]
+/// Reads ``N`` bytes from ``data`` at ``idx``, advancing ``idx`` past them.
+pub(crate) fn scan_bytes<const N: usize>(idx: &mut usize, data: &[u8]) -> Result<[u8; N], Error> {
+    let mut bytes = [0; N];
+    scan_into_byte_array(&mut bytes, idx, data)?;
+    Ok(bytes)
+}
+
+pub(crate) fn scan_string(idx: &mut usize, data: &[u8]) -> Result<String, Error> {
+    let mut string = Vec::new();
+    while *idx < data.len() {
+        if data[*idx] != 0 {
+            string.push(data[*idx]);
+        } else {
+            break;
+        }
+        *idx += 1;
+    }
+    *idx += 4 - (*idx % 4);
+    String::from_utf8(string).map_err(|_| Error::Utf8("OSC string".to_string()))
+}
+
+/// Locks ``mutex``. A handler panicking doesn't leave the server (or anything else sharing state
+/// with it) in an invalid state, so poisoning is ignored.
+pub(crate) fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Copies ``contents`` into a new ``Blob``.
+// With the ``bytes`` feature, ``Blob`` isn't a ``Vec``
+#[allow(clippy::useless_conversion)]
+pub(crate) fn copy_blob(contents: &[u8]) -> Blob {
+    contents.to_vec().into()
+}
+
+/// Returns the error made by ``exceeded`` from ``value`` and ``limit`` if ``value`` exceeds
+/// ``limit``.
+pub(crate) fn check_limit(
+    value: usize,
+    limit: Option<usize>,
+    exceeded: fn(usize, usize) -> Error,
+) -> Result<(), Error> {
+    match limit {
+        Some(limit) if value > limit => Err(exceeded(value, limit)),
+        _ => Ok(()),
+    }
+}
+
+fn string_too_long(length: usize, limit: usize) -> Error {
+    Error::StringTooLong { length, limit }
+}
+
+/// Reads the args for the type tags in ``tags`` from ``data`` at ``idx``, recursing into arrays.
+/// ``depth`` is how many arrays deep this is, where the content of an array must end with a
+/// ``]``. Errors in
+/// top-level args are wrapped in an ``Error::Parse``. If ``options.preserve_unknown_tags``, the
+/// first unknown top-level tag takes the rest of ``data``, and every tag after it becomes an empty
+/// ``Arg::Unknown``, rather than an error.
+fn scan_args<V: Default + Extend<Arg>>(
+    tags: &mut std::str::Chars<'_>,
+    depth: usize,
+    idx: &mut usize,
+    data: &[u8],
+    options: &ParseOptions,
+    blob: &dyn Fn(&[u8]) -> Blob,
+) -> Result<V, Error> {
+    let nested = depth > 0;
+    let mut args = V::default();
+    let mut index = 0;
+    while let Some(tag) = tags.next() {
+        let start = *idx;
+        let arg = match tag {
+            '[' => check_limit(depth + 1, options.max_depth, |_, limit| {
+                Error::NestingTooDeep { limit }
+            })
+            .and_then(|()| scan_args(tags, depth + 1, idx, data, options, blob))
+            .map(Arg::Array),
+            ']' if nested => return Ok(args),
+            _ => scan_arg(tag, idx, data, options, blob),
+        };
+        match arg {
+            Ok(arg) => args.extend([arg]),
+            Err(Error::UnrecognisedTypeTag(unknown))
+                if unknown == tag && !nested && options.preserve_unknown_tags =>
+            {
+                let rest = data.get(start..).unwrap_or_default();
+                args.extend([Arg::Unknown(tag, rest.to_vec())]);
+                args.extend(tags.map(|tag| Arg::Unknown(tag, Vec::new())));
+                *idx = start.max(data.len());
+                return Ok(args);
+            }
+            Err(error) if nested => return Err(error),
+            Err(error) => {
+                let section = ParseSection::Arg(index);
+                return Err(ParseError::wrap(error, section, start, data));
+            }
+        }
+        index += 1;
+    }
+
+    if nested {
+        return Err(Error::Malformed("OSC array type tags".to_string()));
+    }
+    Ok(args)
+}
+
+/// Reads the value of the arg with type tag ``tag`` from ``data`` at ``idx``, advancing ``idx``
+/// past it. Tags in ``options.custom_tags`` are read by their decoder, and blobs by ``blob``.
+pub(crate) fn scan_arg(
+    tag: char,
+    idx: &mut usize,
+    data: &[u8],
+    options: &ParseOptions,
+    blob: &dyn Fn(&[u8]) -> Blob,
+) -> Result<Arg, Error> {
+    Ok(match tag {
+        'i' => Arg::Int(i32::from_be_bytes(scan_bytes(idx, data)?)),
+        'f' => Arg::Float(f32::from_be_bytes(scan_bytes(idx, data)?)),
+        's' => {
+            let string = scan_string(idx, data)?;
+            check_limit(string.len(), options.max_string_len, string_too_long)?;
+            Arg::Str(string)
+        }
+        'S' => {
+            let symbol = scan_string(idx, data)?;
+            check_limit(symbol.len(), options.max_string_len, string_too_long)?;
+            Arg::Symbol(symbol)
+        }
+        'b' => {
+            let blob_size = i32::from_be_bytes(scan_bytes(idx, data)?);
+            let blob_size = usize::try_from(blob_size).map_err(|_| Error::BlobSize(blob_size))?;
+            check_limit(blob_size, options.max_blob_len, |size, limit| {
+                Error::BlobTooLarge { size, limit }
+            })?;
+            let rest = data.get(*idx..).unwrap_or_default();
+            let contents = rest
+                .get(..blob_size)
+                .ok_or(Error::DataLength(blob_size, rest.len()))?;
+            *idx += blob_size;
+            *idx += (4 - (*idx % 4)) % 4;
+            Arg::Blob(blob(contents))
+        }
+        't' => Arg::Time(u64::from_be_bytes(scan_bytes(idx, data)?).into()),
+        'h' => Arg::Int64(i64::from_be_bytes(scan_bytes(idx, data)?)),
+        'd' => Arg::Double(f64::from_be_bytes(scan_bytes(idx, data)?)),
+        'T' => Arg::Bool(true),
+        'F' => Arg::Bool(false),
+        'N' => Arg::Nil,
+        'I' => Arg::Infinitum,
+        'c' => Arg::Char(
+            char::from_u32(u32::from_be_bytes(scan_bytes(idx, data)?))
+                .ok_or_else(|| Error::Malformed("OSC char".to_string()))?,
+        ),
+        'r' => Arg::Color(scan_bytes::<4>(idx, data)?.into()),
+        'm' => {
+            let [port, status, data1, data2] = scan_bytes(idx, data)?;
+            Arg::Midi {
+                port,
+                status,
+                data1,
+                data2,
+            }
+        }
+        _ => {
+            let decode = options
+                .custom_tags
+                .get(tag)
+                .ok_or(Error::UnrecognisedTypeTag(tag))?;
+            let (value, size) = decode(data.get(*idx..).unwrap_or_default())?;
+            *idx += size;
+            Arg::Custom(value)
+        }
+    })
+}
+
+/// The arguments of an ``OscMessage``. With the ``smallvec`` feature, up to 4 are stored inline
+/// rather than on the heap, which covers most messages.
+#[cfg(feature = "smallvec")]
+pub type Args = smallvec::SmallVec<[Arg; 4]>;
+/// The arguments of an ``OscMessage``. With the ``smallvec`` feature, up to 4 are stored inline
+/// rather than on the heap, which covers most messages.
+#[cfg(not(feature = "smallvec"))]
+pub type Args = Vec<Arg>;
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct OscMessage {
     pub address: String,
-    pub args: Vec<Arg>,
+    pub args: Args,
+}
+
+/// Appends each argument, e.g. to add a computed sequence to a message built by hand.
+impl<T: Into<Arg>> Extend<T> for OscMessage {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.args.extend(iter.into_iter().map(Into::into));
+    }
 }
 
 impl OscMessage {
     #[allow(clippy::needless_pass_by_value)]
-    pub fn new(address: impl ToString, args: Vec<Arg>) -> Self {
+    pub fn new(address: impl ToString, args: impl Into<Args>) -> Self {
+        Self {
+            address: address.to_string(),
+            args: args.into(),
+        }
+    }
+
+    /// Creates a message to ``address`` with an argument converted from each of ``args``, e.g.
+    /// ``OscMessage::with_args("/levels", levels)`` for a ``Vec<f32>`` of channel levels.
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn with_args<T: Into<Arg>>(
+        address: impl ToString,
+        args: impl IntoIterator<Item = T>,
+    ) -> Self {
         Self {
             address: address.to_string(),
-            args,
+            args: args.into_iter().map(Into::into).collect(),
         }
     }
 
+    /// Starts building a message to ``address``, adding arguments one at a time. See
+    /// ``OscMessageBuilder``.
+    #[must_use]
+    pub fn builder(address: impl ToString) -> OscMessageBuilder {
+        OscMessageBuilder::new(address)
+    }
+
+    /// Returns the argument at ``index`` as a ``T``, e.g. ``msg.arg::<f32>(0)?`` for a fader
+    /// level.
+    ///
+    /// # Errors
+    /// Will return ``Error::DataLength`` if there is no argument at ``index``, or
+    /// ``Error::Malformed`` if it isn't a ``T``.
+    pub fn arg<T: TryFrom<Arg, Error = Error>>(&self, index: usize) -> Result<T, Error> {
+        let arg = self
+            .args
+            .get(index)
+            .ok_or(Error::DataLength(index + 1, self.args.len()))?;
+        T::try_from(arg.clone())
+    }
+
+    /// Returns the first argument as a ``T``. See ``arg``.
+    ///
+    /// # Errors
+    /// See ``arg``.
+    pub fn first<T: TryFrom<Arg, Error = Error>>(&self) -> Result<T, Error> {
+        self.arg(0)
+    }
+
+    /// Returns every argument as a ``T``, e.g. ``msg.args_as::<(i32, f32, String)>()?``, or any
+    /// other type implementing ``FromOsc``.
+    ///
+    /// # Errors
+    /// For a tuple, will return ``Error::DataLength`` if the message doesn't have one argument
+    /// per element, or ``Error::Malformed`` if one is the wrong type. Otherwise, any error from
+    /// ``T::from_osc``.
+    pub fn args_as<T: FromOsc>(&self) -> Result<T, Error> {
+        T::from_osc(self)
+    }
+
+    /// Appends ``arg``, or any value which converts into one, e.g.
+    /// ``OscMessage::new("/fader", vec![]).with_arg(0.5)``.
+    #[must_use]
+    pub fn with_arg(mut self, arg: impl Into<Arg>) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
     /// Builds a byte-vec out of ``self``, so that it can be sent over
     /// a ``Connection``.
     ///
     /// # Errors
-    /// ## ``Error::Utf8``
-    /// If the type tags are not valid utf-8
-    ///
     /// ## ``Error::DataLength``
     /// If ``self`` contains an ``Arg::Blob`` which is of length > ``i32::MAX``.
     pub fn build(&self) -> Result<Vec<u8>, Error> {
-        let mut msg: Vec<u8> = Vec::new();
-
-        msg.append(&mut write_string(&self.address));
+        let mut msg = Vec::new();
+        self.build_into(&mut msg)?;
+        Ok(msg)
+    }
 
-        let mut message_arg_types = ",".to_string();
+    /// Builds the message as ``build`` does, but only if its address is a valid ``OscAddress``,
+    /// since many receivers reject addresses containing spaces, ``#``, or wildcards. A message
+    /// sent to an address pattern (e.g. ``/ch/*/mute``) should use ``build`` instead.
+    ///
+    /// # Errors
+    /// Will return ``Error::Address`` describing what is wrong with the address, including each
+    /// forbidden character and its byte position, e.g. ``"/ch 1/fader#" contains forbidden
+    /// characters ' ' at 3, '#' at 11``. Otherwise, see ``build``.
+    pub fn build_strict(&self) -> Result<Vec<u8>, Error> {
+        address::check_address(&self.address)?;
+        self.build()
+    }
 
-        if self.args.is_empty() {
-            message_arg_types = String::from_utf8(write_string(
-                &String::from_utf8(message_arg_types.into_bytes())
-                    .map_err(|_| Error::Utf8("Message OSC Type Tags".to_string()))?,
-            ))
-            .map_err(|_| Error::Utf8("Message OSC Type Tags".to_string()))?;
-            msg.append(&mut message_arg_types.into_bytes());
-            return Ok(msg);
+    /// Appends the encoded message to ``buf``, returning its size, so one buffer can be reused
+    /// across sends. On error, ``buf`` is left as it was.
+    ///
+    /// # Errors
+    /// See ``build``.
+    pub fn build_into(&self, buf: &mut Vec<u8>) -> Result<usize, Error> {
+        let start = buf.len();
+        let res = self.write_to(buf);
+        if res.is_err() {
+            buf.truncate(start);
         }
+        res
+    }
 
-        let mut message_arguments = Vec::new();
+    /// Writes the encoded message to ``writer`` piece by piece, without building it in memory
+    /// first, returning its size. Wrap unbuffered writers (e.g. a ``TcpStream``) in an
+    /// ``io::BufWriter``.
+    ///
+    /// # Errors
+    /// See ``build``. Will also return an ``Error::Socket`` if writing fails. On error, part of
+    /// the message may have been written.
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<usize, Error> {
+        let mut size = encode_string(&self.address, writer)?;
 
+        writer.write_all(b",").map_err(Error::Socket)?;
+        let mut tags_size = 1;
         for arg in &self.args {
-            message_arg_types.push(arg_char_repr(arg));
-            message_arguments.append(&mut write_arg(arg)?);
+            tags_size += encode_type_tags(arg, writer)?;
         }
+        size += tags_size + pad(tags_size, writer)?;
 
-        msg.append(&mut write_string(&message_arg_types));
-        msg.append(&mut message_arguments);
+        for arg in &self.args {
+            size += encode_arg(arg, writer)?;
+        }
+        Ok(size)
+    }
 
-        Ok(msg)
+    /// Returns the exact size ``self`` encodes to, without building it, e.g. to pre-allocate a
+    /// buffer, or check it fits in a datagram before sending. Only custom args
+    /// (``Arg::Custom``) are encoded to find their size.
+    ///
+    /// # Errors
+    /// See ``build``.
+    pub fn encoded_len(&self) -> Result<usize, Error> {
+        let tags_len = 1 + self.args.iter().map(type_tags_len).sum::<usize>();
+        let mut size = padded_len(self.address.len()) + padded_len(tags_len);
+        for arg in &self.args {
+            size += arg_len(arg)?;
+        }
+        Ok(size)
     }
 
     /// Transforms ``data`` into an ``OscMessage``
@@ -214,12 +1160,123 @@ impl OscMessage {
     /// suggest), will return ``Error::DataLength``
     /// If ``data`` contains a blob that states its size is negative, will return
     /// ``Error::BlobSize``.
+    /// Each error is wrapped in an ``Error::Parse`` saying where in ``data`` it occurred (see
+    /// ``Error::cause``).
     pub fn parse_bytes(data: &[u8]) -> Result<Self, Error> {
-        if data.len() % 4 != 0 {
+        Self::parse_bytes_with(data, &ParseOptions::default())
+    }
+
+    /// Transforms ``data`` into an ``OscMessage``, enforcing ``options``.
+    ///
+    /// # Errors
+    /// See ``parse_bytes``. Alignment is only checked if ``options.require_alignment``.
+    /// If the message is followed by more data, and ``options.reject_trailing``, will return
+    /// ``Error::DataLength``.
+    /// If the message exceeds a limit in ``options``, will return ``Error::StringTooLong``,
+    /// ``Error::BlobTooLarge``, ``Error::TooManyArgs``, or ``Error::NestingTooDeep`` (for nested
+    /// arrays). As with the errors above, these are wrapped in an ``Error::Parse``, so match on
+    /// ``error.cause()`` to tell them apart.
+    pub fn parse_bytes_with(data: &[u8], options: &ParseOptions) -> Result<Self, Error> {
+        Self::parse_blobs_with(data, options, &copy_blob)
+    }
+
+    /// Transforms ``data`` into an ``OscMessage`` as ``parse_bytes_with`` does, but with each
+    /// blob a slice of ``data`` rather than a copy.
+    ///
+    /// # Errors
+    /// See ``parse_bytes_with``.
+    #[cfg(feature = "bytes")]
+    pub fn parse_shared(data: &bytes::Bytes, options: &ParseOptions) -> Result<Self, Error> {
+        Self::parse_blobs_with(data, options, &|contents| data.slice_ref(contents))
+    }
+
+    /// Returns ``self`` as JSON, e.g. for a web dashboard which can't speak binary OSC. The
+    /// message is an object with an ``address`` string and an ``args`` array. Each argument is an
+    /// object with a ``type`` and, unless it has none (``nil`` and ``infinitum``), a ``value``:
+    ///
+    /// | ``type`` | ``value`` |
+    /// |---|---|
+    /// | ``int``, ``int64``, ``float``, ``double`` | a number |
+    /// | ``str``, ``symbol``, ``char`` | a string |
+    /// | ``bool`` | ``true`` or ``false`` |
+    /// | ``blob`` | an array of bytes, as numbers |
+    /// | ``time`` | an object with ``seconds`` and ``fractional`` numbers, as in ``OscTime`` |
+    /// | ``color`` | an object with ``r``, ``g``, ``b`` and ``a`` numbers |
+    /// | ``midi`` | an object with ``port``, ``status``, ``data1`` and ``data2`` numbers |
+    /// | ``array`` | an array of arguments |
+    /// | ``unknown`` | an array of the type tag, as a string, and its data, as in ``blob`` |
+    ///
+    /// e.g. ``{"address":"/ch/01/fader","args":[{"type":"float","value":0.75}]}``.
+    ///
+    /// # Errors
+    /// Will return ``Error::Malformed`` if ``self`` holds an ``Arg::Custom``, which has no JSON
+    /// form.
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> Result<String, Error> {
+        serde_json::to_string(self).map_err(|e| Error::Malformed(format!("JSON message ({e})")))
+    }
+
+    /// Reads a message from JSON in the form ``to_json`` writes.
+    ///
+    /// # Errors
+    /// Will return ``Error::Malformed`` if ``json`` isn't a message in that form.
+    #[cfg(feature = "json")]
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        serde_json::from_str(json).map_err(|e| Error::Malformed(format!("JSON message ({e})")))
+    }
+
+    /// Transforms ``data`` into an ``OscMessage`` as ``parse_bytes_with`` does, making each blob
+    /// from its contents with ``blob``.
+    pub(crate) fn parse_blobs_with(
+        data: &[u8],
+        options: &ParseOptions,
+        blob: &dyn Fn(&[u8]) -> Blob,
+    ) -> Result<Self, Error> {
+        if options.require_alignment && !data.len().is_multiple_of(4) {
             // All valid OSC data has a length multiple of 32, so error if not.
-            return Err(Error::Alignment(data.len(), 4));
+            let error = Error::Alignment(data.len(), 4);
+            let offset = data.len() - data.len() % 4;
+            return Err(ParseError::wrap(error, ParseSection::Packet, offset, data));
+        }
+        let (msg, consumed) = Self::parse_from(data, options, blob)?;
+        if options.reject_trailing && consumed < data.len() {
+            let error = Error::DataLength(consumed, data.len());
+            return Err(ParseError::wrap(
+                error,
+                ParseSection::Packet,
+                consumed,
+                data,
+            ));
         }
+        Ok(msg)
+    }
 
+    /// Parses the message at the start of ``data``, returning it with the number of bytes it
+    /// took up, so several messages can be taken from one stream buffer, keeping the rest for
+    /// the next read. ``data`` need not be 4-byte aligned.
+    ///
+    /// # Errors
+    /// Will return an error whose ``Error::cause`` is ``Error::DataLength`` if ``data`` ends
+    /// before the message does, e.g. because the rest hasn't arrived yet. Will also return any
+    /// other error from ``parse_bytes``.
+    pub fn parse_prefix(data: &[u8]) -> Result<(Self, usize), Error> {
+        let (msg, consumed) = Self::parse_from(data, &ParseOptions::default(), &copy_blob)?;
+        if consumed > data.len() {
+            let error = Error::DataLength(consumed, data.len());
+            return Err(ParseError::wrap(error, ParseSection::Packet, 0, data));
+        }
+        Ok((msg, consumed))
+    }
+
+    /// Parses the message at the start of ``data``, returning it with the index just past its
+    /// end, which may be beyond the end of ``data`` if a string is unterminated. Errors are
+    /// wrapped in an ``Error::Parse`` saying where they occurred.
+    fn parse_from(
+        data: &[u8],
+        options: &ParseOptions,
+        blob: &dyn Fn(&[u8]) -> Blob,
+    ) -> Result<(Self, usize), Error> {
+        let in_address = |error| ParseError::wrap(error, ParseSection::Address, 0, data);
         let mut curr_datagram = Vec::new();
         let mut i: usize = 0;
 
@@ -234,12 +1291,19 @@ impl OscMessage {
 
         let address: String = match String::from_utf8(std::mem::take(&mut curr_datagram)) {
             Ok(s) => s,
-            Err(_) => return Err(Error::Utf8("OSC address".to_string())),
+            Err(_) => return Err(in_address(Error::Utf8("OSC address".to_string()))),
         };
+        check_limit(address.len(), options.max_string_len, string_too_long).map_err(in_address)?;
 
         // Skip to the next part, which is always 32bit/4 byte aligned
         i += 4 - (i % 4);
 
+        if options.allow_missing_type_tags && data.get(i) != Some(&b',') {
+            return Ok((Self::new(address, Args::new()), i));
+        }
+
+        let tags_start = i;
+        let in_tags = |error| ParseError::wrap(error, ParseSection::TypeTags, tags_start, data);
         while i < data.len() {
             if data[i] != 0 {
                 curr_datagram.push(data[i]);
@@ -252,64 +1316,123 @@ impl OscMessage {
         i += 4 - (i % 4);
 
         let Ok(mut arg_types_str) = String::from_utf8(std::mem::take(&mut curr_datagram)) else {
-            return Err(Error::Utf8("OSC argument type tags".to_string()));
+            return Err(in_tags(Error::Utf8("OSC argument type tags".to_string())));
         };
 
         if !arg_types_str.is_empty() && arg_types_str.remove(0) != ',' {
-            return Err(Error::Malformed("OSC argument type tags".to_string()));
-        }
-
-        // Prepare args vec by scanning argument types
-        let mut args: Vec<Arg> = Vec::new();
-        for arg_tag in arg_types_str.chars() {
-            args.push(type_tag_to_default_arg(arg_tag)?);
-        }
-
-        let mut four_bytes = [0; 4];
-        if !args.is_empty() {
-            for arg in &mut args {
-                use self::Arg::{Blob, Float, Int, Str};
-                match arg {
-                    Int(_) => {
-                        scan_into_byte_array(&mut four_bytes, &mut i, data)?;
-                        *arg = Int(i32::from_be_bytes(four_bytes));
-                    }
-                    Float(_) => {
-                        scan_into_byte_array(&mut four_bytes, &mut i, data)?;
-                        *arg = Float(f32::from_be_bytes(four_bytes));
-                    }
-                    Str(_) => {
-                        while i < data.len() {
-                            if data[i] != 0 {
-                                curr_datagram.push(data[i]);
-                            } else {
-                                break;
-                            }
-                            i += 1;
-                        }
-                        i += 4 - (i % 4);
-                        match String::from_utf8(std::mem::take(&mut curr_datagram)) {
-                            Ok(s) => *arg = Str(s),
-                            Err(_) => {
-                                return Err(Error::Utf8("OSC string".to_string()));
-                            }
-                        }
-                    }
-                    Blob(_) => {
-                        scan_into_byte_array(&mut four_bytes, &mut i, data)?;
-                        let blob_size = i32::from_be_bytes(four_bytes);
-                        let mut blob = vec![
-                            0;
-                            (usize::try_from(blob_size)
-                                .map_err(|_| { Error::BlobSize(blob_size) }))?
-                        ];
-                        scan_into_byte_array(&mut blob, &mut i, data)?;
-                        *arg = Blob(blob);
-                    }
-                }
-            }
+            return Err(in_tags(Error::Malformed(
+                "OSC argument type tags".to_string(),
+            )));
+        }
+
+        let arg_count = arg_types_str.chars().filter(|tag| *tag != ']').count();
+        check_limit(arg_count, options.max_args, |count, limit| {
+            Error::TooManyArgs { count, limit }
+        })
+        .map_err(in_tags)?;
+
+        let args: Args = scan_args(&mut arg_types_str.chars(), 0, &mut i, data, options, blob)?;
+        Ok((Self::new(address, args), i))
+    }
+}
+
+/// Either kind of OSC packet that can arrive on a connection. With the ``serde`` feature, its
+/// kind is given by a ``type`` of ``message`` or ``bundle``.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", rename_all = "snake_case"))]
+pub enum OscPacket {
+    Message(OscMessage),
+    Bundle(OscBundle),
+}
+
+impl OscPacket {
+    /// Builds a byte-vec out of ``self``. See ``OscMessage::build`` and ``OscBundle::build``.
+    ///
+    /// # Errors
+    /// Will return any error from building the message or bundle.
+    pub fn build(&self) -> Result<Vec<u8>, Error> {
+        match self {
+            OscPacket::Message(msg) => msg.build(),
+            OscPacket::Bundle(bundle) => bundle.build(),
+        }
+    }
+
+    /// Appends the encoded packet to ``buf``, returning its size. See
+    /// ``OscMessage::build_into`` and ``OscBundle::build_into``.
+    ///
+    /// # Errors
+    /// Will return any error from building the message or bundle.
+    pub fn build_into(&self, buf: &mut Vec<u8>) -> Result<usize, Error> {
+        match self {
+            OscPacket::Message(msg) => msg.build_into(buf),
+            OscPacket::Bundle(bundle) => bundle.build_into(buf),
+        }
+    }
+
+    /// Returns the size ``self`` encodes to. See ``OscMessage::encoded_len``.
+    ///
+    /// # Errors
+    /// Will return any error from building the message or bundle.
+    pub fn encoded_len(&self) -> Result<usize, Error> {
+        match self {
+            OscPacket::Message(msg) => msg.encoded_len(),
+            OscPacket::Bundle(bundle) => bundle.encoded_len(),
         }
+    }
+
+    /// Transforms ``data`` into an ``OscPacket``, parsing it as a bundle if it starts with
+    /// ``#bundle``, or as a message otherwise.
+    ///
+    /// # Errors
+    /// Will return any error from ``OscMessage::parse_bytes`` or ``OscBundle::parse_bytes``.
+    pub fn parse_bytes(data: &[u8]) -> Result<Self, Error> {
+        Self::parse_bytes_with(data, &ParseOptions::default())
+    }
+
+    /// Transforms ``data`` into an ``OscPacket`` as ``parse_bytes`` does, enforcing ``options``.
+    ///
+    /// # Errors
+    /// Will return any error from ``OscMessage::parse_bytes_with`` or
+    /// ``OscBundle::parse_bytes_with``.
+    pub fn parse_bytes_with(data: &[u8], options: &ParseOptions) -> Result<Self, Error> {
+        Self::parse_blobs_with(data, options, &copy_blob)
+    }
+
+    /// Transforms ``data`` into an ``OscPacket`` as ``parse_bytes_with`` does, but with each
+    /// blob a reference-counted slice of ``data`` rather than a copy, e.g. for large audio or
+    /// firmware blobs.
+    ///
+    /// # Errors
+    /// See ``parse_bytes_with``.
+    #[cfg(feature = "bytes")]
+    pub fn parse_shared(data: &bytes::Bytes, options: &ParseOptions) -> Result<Self, Error> {
+        Self::parse_blobs_with(data, options, &|contents| data.slice_ref(contents))
+    }
+
+    /// Transforms ``data`` into an ``OscPacket`` as ``parse_bytes_with`` does, making each blob
+    /// from its contents with ``blob``.
+    pub(crate) fn parse_blobs_with(
+        data: &[u8],
+        options: &ParseOptions,
+        blob: &dyn Fn(&[u8]) -> Blob,
+    ) -> Result<Self, Error> {
+        if OscBundle::is_bundle(data) {
+            OscBundle::parse_blobs_with(data, options, blob).map(OscPacket::Bundle)
+        } else {
+            OscMessage::parse_blobs_with(data, options, blob).map(OscPacket::Message)
+        }
+    }
+}
+
+impl From<OscMessage> for OscPacket {
+    fn from(value: OscMessage) -> Self {
+        OscPacket::Message(value)
+    }
+}
 
-        Ok(Self::new(address, args))
+impl From<OscBundle> for OscPacket {
+    fn from(value: OscBundle) -> Self {
+        OscPacket::Bundle(value)
     }
 }
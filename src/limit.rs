@@ -0,0 +1,142 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, SystemTime},
+};
+
+use crate::{bundle::OscBundle, pattern::OscPattern, OscMessage, OscPacket};
+
+/// How often messages to a route may be handled, set with ``OscServer::with_rate_limit``. Each
+/// address the route matches is limited separately, so moving ``/ch/01/fader`` doesn't hold up
+/// ``/ch/02/fader``.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateLimit {
+    /// Handles a message at most once per interval. A message arriving sooner is held until the
+    /// interval is up, replacing any message already held, so the latest value always arrives.
+    Throttle(Duration),
+    /// Holds every message until none has arrived for the interval, then handles the latest.
+    Debounce(Duration),
+}
+
+/// The messages held back by rate limits. ``T`` is whatever the server needs to know to send the
+/// replies, as for ``Schedule``.
+#[cfg_attr(not(feature = "server"), allow(dead_code))]
+pub(crate) struct Limiter<T> {
+    windows: HashMap<String, Window<T>>,
+}
+
+#[cfg_attr(not(feature = "server"), allow(dead_code))]
+struct Window<T> {
+    limit: RateLimit,
+    /// When the window closes, releasing ``held``.
+    until: SystemTime,
+    held: Option<(OscMessage, T)>,
+}
+
+#[cfg_attr(not(feature = "server"), allow(dead_code))]
+impl<T: Clone> Limiter<T> {
+    pub(crate) fn new() -> Self {
+        Self {
+            windows: HashMap::new(),
+        }
+    }
+
+    /// Removes the messages in ``packet`` which ``limits`` hold back, returning whatever should be
+    /// dispatched now.
+    pub(crate) fn filter(
+        &mut self,
+        packet: OscPacket,
+        origin: &T,
+        limits: &[(OscPattern, RateLimit)],
+    ) -> Option<OscPacket> {
+        if limits.is_empty() {
+            return Some(packet);
+        }
+        match packet {
+            OscPacket::Message(msg) => self.admit(msg, origin, limits).map(OscPacket::Message),
+            OscPacket::Bundle(bundle) => {
+                let content: Vec<_> = bundle
+                    .content
+                    .into_iter()
+                    .filter_map(|packet| self.filter(packet, origin, limits))
+                    .collect();
+                (!content.is_empty())
+                    .then(|| OscPacket::Bundle(OscBundle::new(bundle.timetag, content)))
+            }
+        }
+    }
+
+    fn admit(
+        &mut self,
+        msg: OscMessage,
+        origin: &T,
+        limits: &[(OscPattern, RateLimit)],
+    ) -> Option<OscMessage> {
+        let Some(&(_, limit)) = limits
+            .iter()
+            .find(|(pattern, _)| crate::pattern::matches(pattern.as_str(), &msg.address))
+        else {
+            return Some(msg);
+        };
+        let now = SystemTime::now();
+        match limit {
+            RateLimit::Throttle(interval) => match self.windows.get_mut(&msg.address) {
+                Some(window) if window.until > now => {
+                    window.held = Some((msg, origin.clone()));
+                    None
+                }
+                _ => {
+                    let window = Window {
+                        limit,
+                        until: now + interval,
+                        held: None,
+                    };
+                    self.windows.insert(msg.address.clone(), window);
+                    Some(msg)
+                }
+            },
+            RateLimit::Debounce(interval) => {
+                let address = msg.address.clone();
+                let window = Window {
+                    limit,
+                    until: now + interval,
+                    held: Some((msg, origin.clone())),
+                };
+                self.windows.insert(address, window);
+                None
+            }
+        }
+    }
+
+    /// Returns when the next held message is released, if any are held.
+    pub(crate) fn next_release(&self) -> Option<SystemTime> {
+        self.windows
+            .values()
+            .filter(|window| window.held.is_some())
+            .map(|window| window.until)
+            .min()
+    }
+
+    /// Removes and returns the held messages whose windows have closed.
+    pub(crate) fn take_released(&mut self) -> Vec<(OscMessage, T)> {
+        let now = SystemTime::now();
+        let mut released = Vec::new();
+        self.windows.retain(|_, window| {
+            if window.until > now {
+                return true;
+            }
+            let Some(held) = window.held.take() else {
+                return false;
+            };
+            released.push(held);
+            match window.limit {
+                // The released message starts a new window, so the next is still limited
+                RateLimit::Throttle(interval) => {
+                    window.until = now + interval;
+                    true
+                }
+                RateLimit::Debounce(_) => false,
+            }
+        });
+        released
+    }
+}
@@ -0,0 +1,209 @@
+use std::str::Chars;
+
+use crate::{
+    copy_blob,
+    errors::{Error, ParseError, ParseSection},
+    parse::DEFAULT_MAX_DEPTH,
+    scan_bytes,
+    time::OscTime,
+    Arg, Args, OscMessage, Rgba,
+};
+
+/// An ``Arg`` whose strings and blobs borrow from the buffer it was parsed from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArgRef<'a> {
+    Int(i32),
+    Float(f32),
+    Str(&'a str),
+    Blob(&'a [u8]),
+    Time(OscTime),
+    Int64(i64),
+    Double(f64),
+    Bool(bool),
+    Nil,
+    Infinitum,
+    Char(char),
+    Color(Rgba),
+    Midi {
+        port: u8,
+        status: u8,
+        data1: u8,
+        data2: u8,
+    },
+    Array(Vec<ArgRef<'a>>),
+    Symbol(&'a str),
+}
+
+impl ArgRef<'_> {
+    /// Copies the argument into an owned ``Arg``.
+    #[must_use]
+    pub fn to_owned(&self) -> Arg {
+        match self {
+            ArgRef::Int(i) => Arg::Int(*i),
+            ArgRef::Float(f) => Arg::Float(*f),
+            ArgRef::Str(s) => Arg::Str((*s).to_string()),
+            ArgRef::Blob(b) => Arg::Blob(copy_blob(b)),
+            ArgRef::Time(t) => Arg::Time(*t),
+            ArgRef::Int64(h) => Arg::Int64(*h),
+            ArgRef::Double(d) => Arg::Double(*d),
+            ArgRef::Bool(b) => Arg::Bool(*b),
+            ArgRef::Nil => Arg::Nil,
+            ArgRef::Infinitum => Arg::Infinitum,
+            ArgRef::Char(c) => Arg::Char(*c),
+            ArgRef::Color(c) => Arg::Color(*c),
+            ArgRef::Midi {
+                port,
+                status,
+                data1,
+                data2,
+            } => Arg::Midi {
+                port: *port,
+                status: *status,
+                data1: *data1,
+                data2: *data2,
+            },
+            ArgRef::Array(items) => Arg::Array(items.iter().map(ArgRef::to_owned).collect()),
+            ArgRef::Symbol(s) => Arg::Symbol((*s).to_string()),
+        }
+    }
+}
+
+/// An ``OscMessage`` parsed without copying, whose address, strings, and blobs are slices of the
+/// received data, e.g. for handling meter updates many times a second. Use ``to_owned`` to keep
+/// it beyond the buffer's lifetime.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OscMessageRef<'a> {
+    pub address: &'a str,
+    pub args: Vec<ArgRef<'a>>,
+}
+
+impl<'a> OscMessageRef<'a> {
+    /// Parses ``data`` into an ``OscMessageRef`` borrowing from it.
+    ///
+    /// # Errors
+    /// See ``OscMessage::parse_bytes``.
+    pub fn parse_bytes(data: &'a [u8]) -> Result<Self, Error> {
+        if !data.len().is_multiple_of(4) {
+            let error = Error::Alignment(data.len(), 4);
+            let offset = data.len() - data.len() % 4;
+            return Err(ParseError::wrap(error, ParseSection::Packet, offset, data));
+        }
+
+        let mut i = 0;
+        let address = scan_str(&mut i, data, "OSC address")
+            .map_err(|error| ParseError::wrap(error, ParseSection::Address, 0, data))?;
+        let tags_start = i;
+        let in_tags = |error| ParseError::wrap(error, ParseSection::TypeTags, tags_start, data);
+        let tags = scan_str(&mut i, data, "OSC argument type tags").map_err(in_tags)?;
+        let tags = match tags.strip_prefix(',') {
+            Some(tags) => tags,
+            None if tags.is_empty() => tags,
+            None => {
+                let error = Error::Malformed("OSC argument type tags".to_string());
+                return Err(in_tags(error));
+            }
+        };
+        let args = scan_args(&mut tags.chars(), 0, &mut i, data)?;
+        Ok(Self { address, args })
+    }
+
+    /// Copies the message into an owned ``OscMessage``.
+    #[must_use]
+    pub fn to_owned(&self) -> OscMessage {
+        OscMessage::new(
+            self.address,
+            self.args.iter().map(ArgRef::to_owned).collect::<Args>(),
+        )
+    }
+}
+
+/// Reads a null-terminated, padded string from ``data`` at ``idx``, advancing ``idx`` past it.
+fn scan_str<'a>(idx: &mut usize, data: &'a [u8], what: &str) -> Result<&'a str, Error> {
+    let rest = data.get(*idx..).unwrap_or_default();
+    let length = rest.iter().position(|b| *b == 0).unwrap_or(rest.len());
+    *idx += length;
+    *idx += 4 - (*idx % 4);
+    std::str::from_utf8(&rest[..length]).map_err(|_| Error::Utf8(what.to_string()))
+}
+
+/// Reads the args for the type tags in ``tags`` from ``data`` at ``idx``, recursing into arrays
+/// up to ``ParseOptions``'s default depth. ``depth`` is how many arrays deep this is, where the
+/// content of an array must end with a ``]``. Errors in top-level args are wrapped in an
+/// ``Error::Parse``.
+fn scan_args<'a>(
+    tags: &mut Chars<'_>,
+    depth: usize,
+    idx: &mut usize,
+    data: &'a [u8],
+) -> Result<Vec<ArgRef<'a>>, Error> {
+    let nested = depth > 0;
+    let mut args = Vec::new();
+    while let Some(tag) = tags.next() {
+        let start = *idx;
+        let arg = match tag {
+            '[' if depth >= DEFAULT_MAX_DEPTH => Err(Error::NestingTooDeep {
+                limit: DEFAULT_MAX_DEPTH,
+            }),
+            '[' => scan_args(tags, depth + 1, idx, data).map(ArgRef::Array),
+            ']' if nested => return Ok(args),
+            _ => scan_arg(tag, idx, data),
+        };
+        match arg {
+            Ok(arg) => args.push(arg),
+            Err(error) if nested => return Err(error),
+            Err(error) => {
+                let section = ParseSection::Arg(args.len());
+                return Err(ParseError::wrap(error, section, start, data));
+            }
+        }
+    }
+
+    if nested {
+        return Err(Error::Malformed("OSC array type tags".to_string()));
+    }
+    Ok(args)
+}
+
+/// Reads the value of the arg with type tag ``tag`` from ``data`` at ``idx``, advancing ``idx``
+/// past it.
+fn scan_arg<'a>(tag: char, idx: &mut usize, data: &'a [u8]) -> Result<ArgRef<'a>, Error> {
+    Ok(match tag {
+        'i' => ArgRef::Int(i32::from_be_bytes(scan_bytes(idx, data)?)),
+        'f' => ArgRef::Float(f32::from_be_bytes(scan_bytes(idx, data)?)),
+        's' => ArgRef::Str(scan_str(idx, data, "OSC string")?),
+        'S' => ArgRef::Symbol(scan_str(idx, data, "OSC string")?),
+        'b' => {
+            let blob_size = i32::from_be_bytes(scan_bytes(idx, data)?);
+            let length = usize::try_from(blob_size).map_err(|_| Error::BlobSize(blob_size))?;
+            let rest = data.get(*idx..).unwrap_or_default();
+            let blob = rest
+                .get(..length)
+                .ok_or(Error::DataLength(length, rest.len()))?;
+            *idx += length;
+            *idx += (4 - (*idx % 4)) % 4;
+            ArgRef::Blob(blob)
+        }
+        't' => ArgRef::Time(u64::from_be_bytes(scan_bytes(idx, data)?).into()),
+        'h' => ArgRef::Int64(i64::from_be_bytes(scan_bytes(idx, data)?)),
+        'd' => ArgRef::Double(f64::from_be_bytes(scan_bytes(idx, data)?)),
+        'T' => ArgRef::Bool(true),
+        'F' => ArgRef::Bool(false),
+        'N' => ArgRef::Nil,
+        'I' => ArgRef::Infinitum,
+        'c' => ArgRef::Char(
+            char::from_u32(u32::from_be_bytes(scan_bytes(idx, data)?))
+                .ok_or_else(|| Error::Malformed("OSC char".to_string()))?,
+        ),
+        'r' => ArgRef::Color(scan_bytes::<4>(idx, data)?.into()),
+        'm' => {
+            let [port, status, data1, data2] = scan_bytes(idx, data)?;
+            ArgRef::Midi {
+                port,
+                status,
+                data1,
+                data2,
+            }
+        }
+        _ => return Err(Error::UnrecognisedTypeTag(tag)),
+    })
+}
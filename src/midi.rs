@@ -0,0 +1,318 @@
+use std::{
+    collections::HashMap,
+    io::{self, ErrorKind},
+    sync::Arc,
+};
+
+use midir::{MidiInput, MidiInputConnection, MidiOutput, MidiOutputConnection};
+
+use crate::{errors::Error, lock, sockets::Connection, Arg, OscMessage};
+
+/// The name this crate's MIDI ports are given, where the backend names them.
+const CLIENT_NAME: &str = "osc";
+
+/// A MIDI channel voice message which can be mapped to and from OSC. Channels are ``0..16``.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MidiEvent {
+    NoteOn {
+        channel: u8,
+        note: u8,
+        velocity: u8,
+    },
+    NoteOff {
+        channel: u8,
+        note: u8,
+        velocity: u8,
+    },
+    ControlChange {
+        channel: u8,
+        controller: u8,
+        value: u8,
+    },
+    /// A 14-bit bend, centred on 8192.
+    PitchBend {
+        channel: u8,
+        value: u16,
+    },
+}
+
+impl MidiEvent {
+    /// Reads the event from a raw MIDI message, or returns ``None`` if it's of another kind. A
+    /// note on with zero velocity is read as a note off, as many devices send them that way.
+    #[must_use]
+    pub fn parse(bytes: &[u8]) -> Option<Self> {
+        let [status, data1, data2] = *bytes.get(..3)? else {
+            return None;
+        };
+        let channel = status & 0x0f;
+        Some(match status & 0xf0 {
+            0x80 => Self::NoteOff {
+                channel,
+                note: data1,
+                velocity: data2,
+            },
+            0x90 if data2 == 0 => Self::NoteOff {
+                channel,
+                note: data1,
+                velocity: 0,
+            },
+            0x90 => Self::NoteOn {
+                channel,
+                note: data1,
+                velocity: data2,
+            },
+            0xb0 => Self::ControlChange {
+                channel,
+                controller: data1,
+                value: data2,
+            },
+            0xe0 => Self::PitchBend {
+                channel,
+                value: u16::from(data1 & 0x7f) | u16::from(data2 & 0x7f) << 7,
+            },
+            _ => return None,
+        })
+    }
+
+    /// Returns the raw MIDI message.
+    #[must_use]
+    pub fn to_bytes(self) -> [u8; 3] {
+        match self {
+            Self::NoteOn {
+                channel,
+                note,
+                velocity,
+            } => [0x90 | channel & 0x0f, note & 0x7f, velocity & 0x7f],
+            Self::NoteOff {
+                channel,
+                note,
+                velocity,
+            } => [0x80 | channel & 0x0f, note & 0x7f, velocity & 0x7f],
+            Self::ControlChange {
+                channel,
+                controller,
+                value,
+            } => [0xb0 | channel & 0x0f, controller & 0x7f, value & 0x7f],
+            #[allow(clippy::cast_possible_truncation)]
+            Self::PitchBend { channel, value } => [
+                0xe0 | channel & 0x0f,
+                (value & 0x7f) as u8,
+                (value >> 7 & 0x7f) as u8,
+            ],
+        }
+    }
+}
+
+/// What a MIDI control is mapped to, keyed by channel and note or controller number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Control {
+    Note(u8, u8),
+    Controller(u8, u8),
+    PitchBend(u8),
+}
+
+/// Which OSC address each MIDI control is sent to, and back. Values are sent as a ``Float`` in
+/// ``0.0..=1.0``: a note's velocity (``0.0`` for note off), a controller's value, or the pitch
+/// bend (``0.5`` being centred), e.g. ``MidiMap::new().control(0, 7, "/ch/01/mix/fader")``.
+#[derive(Debug, Clone, Default)]
+pub struct MidiMap {
+    addresses: HashMap<Control, String>,
+    controls: HashMap<String, Control>,
+}
+
+impl MidiMap {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Maps ``note`` on ``channel`` to ``address``.
+    #[must_use]
+    pub fn note(self, channel: u8, note: u8, address: &str) -> Self {
+        self.map(Control::Note(channel, note), address.to_string())
+    }
+
+    /// Maps ``controller`` on ``channel`` to ``address``.
+    #[must_use]
+    pub fn control(self, channel: u8, controller: u8, address: &str) -> Self {
+        self.map(
+            Control::Controller(channel, controller),
+            address.to_string(),
+        )
+    }
+
+    /// Maps the pitch bend on ``channel`` to ``address``.
+    #[must_use]
+    pub fn pitch_bend(self, channel: u8, address: &str) -> Self {
+        self.map(Control::PitchBend(channel), address.to_string())
+    }
+
+    fn map(mut self, control: Control, address: String) -> Self {
+        self.addresses.insert(control, address.clone());
+        self.controls.insert(address, control);
+        self
+    }
+
+    /// Returns the OSC message for ``event``, or ``None`` if its control isn't mapped.
+    #[must_use]
+    pub fn to_osc(&self, event: MidiEvent) -> Option<OscMessage> {
+        let (control, value) = match event {
+            MidiEvent::NoteOn {
+                channel,
+                note,
+                velocity,
+            } => (Control::Note(channel, note), f32::from(velocity) / 127.0),
+            MidiEvent::NoteOff { channel, note, .. } => (Control::Note(channel, note), 0.0),
+            MidiEvent::ControlChange {
+                channel,
+                controller,
+                value,
+            } => (
+                Control::Controller(channel, controller),
+                f32::from(value) / 127.0,
+            ),
+            MidiEvent::PitchBend { channel, value } => {
+                (Control::PitchBend(channel), f32::from(value) / 16383.0)
+            }
+        };
+        let address = self.addresses.get(&control)?;
+        Some(OscMessage::new(address, vec![Arg::Float(value)]))
+    }
+
+    /// Returns the MIDI event for ``msg``, or ``None`` if its address isn't mapped or its first
+    /// argument isn't a number. Values outside ``0.0..=1.0`` are clamped.
+    #[must_use]
+    pub fn to_midi(&self, msg: &OscMessage) -> Option<MidiEvent> {
+        let control = self.controls.get(&msg.address)?;
+        let value = match msg.args.first()? {
+            Arg::Float(f) => f64::from(*f),
+            Arg::Double(d) => *d,
+            Arg::Int(i) => f64::from(*i),
+            _ => return None,
+        }
+        .clamp(0.0, 1.0);
+        // Clamped above, so the scaled values fit
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let scale = |max: f64| (value * max).round() as u16;
+        #[allow(clippy::cast_possible_truncation)]
+        Some(match *control {
+            Control::Note(channel, note) if value > 0.0 => MidiEvent::NoteOn {
+                channel,
+                note,
+                velocity: scale(127.0).max(1) as u8,
+            },
+            Control::Note(channel, note) => MidiEvent::NoteOff {
+                channel,
+                note,
+                velocity: 0,
+            },
+            Control::Controller(channel, controller) => MidiEvent::ControlChange {
+                channel,
+                controller,
+                value: scale(127.0) as u8,
+            },
+            Control::PitchBend(channel) => MidiEvent::PitchBend {
+                channel,
+                value: scale(16383.0),
+            },
+        })
+    }
+}
+
+/// Bridges MIDI devices and OSC through a ``MidiMap``: mapped events arriving on a MIDI input
+/// are sent as OSC over a ``Connection``, and mapped OSC messages given to ``send`` are played on
+/// a MIDI output, e.g. from an ``OscServer`` route. Ports are chosen by the first whose name
+/// contains the given text, e.g. ``"nanoKONTROL"``.
+pub struct MidiBridge {
+    map: Arc<MidiMap>,
+    input: Option<MidiInputConnection<()>>,
+    output: Option<MidiOutputConnection>,
+}
+
+impl MidiBridge {
+    #[must_use]
+    pub fn new(map: MidiMap) -> Self {
+        Self {
+            map: Arc::new(map),
+            input: None,
+            output: None,
+        }
+    }
+
+    /// Listens on the MIDI input whose name contains ``port``, sending each mapped event over
+    /// ``connection`` as it arrives. Unmapped events, and sends which fail, are ignored.
+    ///
+    /// # Errors
+    /// Will return an ``Error::Socket`` of kind ``io::ErrorKind::NotFound`` if there is no such
+    /// port, or any other kind if it can't be opened.
+    pub fn with_input<C>(mut self, port: &str, connection: C) -> Result<Self, Error>
+    where
+        C: Connection + Send + 'static,
+    {
+        let midi = MidiInput::new(CLIENT_NAME).map_err(midi_error)?;
+        let found = midi
+            .ports()
+            .into_iter()
+            .find(|p| midi.port_name(p).is_ok_and(|name| name.contains(port)))
+            .ok_or_else(|| not_found(port))?;
+        let map = self.map.clone();
+        let connection = std::sync::Mutex::new(connection);
+        let callback = move |_: u64, bytes: &[u8], (): &mut ()| {
+            let Some(msg) = MidiEvent::parse(bytes).and_then(|event| map.to_osc(event)) else {
+                return;
+            };
+            if let Ok(bytes) = msg.build() {
+                let _ = lock(&connection).send(&bytes);
+            }
+        };
+        let input = midi
+            .connect(&found, CLIENT_NAME, callback, ())
+            .map_err(midi_error)?;
+        self.input = Some(input);
+        Ok(self)
+    }
+
+    /// Opens the MIDI output whose name contains ``port``, which ``send`` plays to.
+    ///
+    /// # Errors
+    /// See ``with_input``.
+    pub fn with_output(mut self, port: &str) -> Result<Self, Error> {
+        let midi = MidiOutput::new(CLIENT_NAME).map_err(midi_error)?;
+        let found = midi
+            .ports()
+            .into_iter()
+            .find(|p| midi.port_name(p).is_ok_and(|name| name.contains(port)))
+            .ok_or_else(|| not_found(port))?;
+        self.output = Some(midi.connect(&found, CLIENT_NAME).map_err(midi_error)?);
+        Ok(self)
+    }
+
+    /// Plays ``msg`` on the MIDI output if its address is mapped, returning whether it was.
+    ///
+    /// # Errors
+    /// Will return an ``Error::Socket`` if there is no output (see ``with_output``), or sending
+    /// to it fails.
+    pub fn send(&mut self, msg: &OscMessage) -> Result<bool, Error> {
+        let Some(event) = self.map.to_midi(msg) else {
+            return Ok(false);
+        };
+        let output = self.output.as_mut().ok_or_else(|| {
+            Error::Socket(io::Error::new(ErrorKind::NotConnected, "No MIDI output"))
+        })?;
+        output.send(&event.to_bytes()).map_err(midi_error)?;
+        Ok(true)
+    }
+}
+
+fn not_found(port: &str) -> Error {
+    Error::Socket(io::Error::new(
+        ErrorKind::NotFound,
+        format!("No MIDI port named {port:?}"),
+    ))
+}
+
+/// Some of midir's errors hold the port they failed to open, which can't be sent between threads,
+/// so only the message is kept.
+fn midi_error(error: impl std::fmt::Display) -> Error {
+    Error::Socket(io::Error::other(error.to_string()))
+}
@@ -0,0 +1,400 @@
+use std::{
+    collections::HashMap,
+    io::{ErrorKind, Read, Write},
+    net::{SocketAddr, ToSocketAddrs},
+    thread,
+    time::{Duration, Instant},
+};
+
+use mio::{
+    net::{TcpListener, TcpStream, UdpSocket},
+    Events, Interest, Poll, Registry, Token,
+};
+
+use crate::{
+    acl::AccessList,
+    errors::Error,
+    schedule::Schedule,
+    server::{dispatcher_builders, Dispatcher, SHUTDOWN_POLL_INTERVAL},
+    session::Session,
+    sockets::{frame, take_frame},
+    OscPacket,
+};
+
+/// An OSC server which multiplexes any number of UDP sockets, TCP listeners, and TCP clients on a
+/// single thread using ``mio`` readiness events, for installations with many controllers.
+///
+/// TCP streams use OSC 1.0 stream framing (see ``FramedTcpStream``). Routes behave exactly as they
+/// do for ``OscServer``, but handlers are called on the polling thread, so they should be quick.
+#[allow(clippy::module_name_repetitions)]
+pub struct MioOscServer {
+    poll: Poll,
+    events: Events,
+    sources: HashMap<Token, Source>,
+    next_token: usize,
+    dispatcher: Dispatcher,
+    buffer: Vec<u8>,
+    schedule: Schedule<(Token, SocketAddr)>,
+}
+
+enum Source {
+    Udp(UdpSocket),
+    Listener(TcpListener),
+    Client(Client),
+}
+
+/// A connected TCP client, with its partially received packets and unsent replies.
+struct Client {
+    stream: TcpStream,
+    peer: SocketAddr,
+    pending: Vec<u8>,
+    outgoing: Vec<u8>,
+}
+
+impl MioOscServer {
+    /// Creates a new ``MioOscServer`` with no sockets. ``buffer_size`` dictates the maximum size
+    /// packet that can be received over UDP, and over TCP, where a client sending a larger packet
+    /// is disconnected as soon as its length arrives.
+    ///
+    /// # Errors
+    /// If the poll instance cannot be created, this function will return an ``Error::Socket``.
+    pub fn new(buffer_size: usize) -> Result<Self, Error> {
+        Ok(Self {
+            poll: Poll::new().map_err(Error::Socket)?,
+            events: Events::with_capacity(1024),
+            sources: HashMap::new(),
+            next_token: 0,
+            dispatcher: Dispatcher::new(),
+            buffer: vec![0; buffer_size],
+            schedule: Schedule::new(),
+        })
+    }
+
+    /// Listens for UDP packets at ``address``, returning the address actually bound.
+    ///
+    /// # Errors
+    /// If the socket cannot be bound or registered, this function will return an
+    /// ``Error::Socket``.
+    pub fn bind_udp<A: ToSocketAddrs>(&mut self, address: A) -> Result<SocketAddr, Error> {
+        let socket = std::net::UdpSocket::bind(address).map_err(Error::Socket)?;
+        socket.set_nonblocking(true).map_err(Error::Socket)?;
+        let mut socket = UdpSocket::from_std(socket);
+        let local = socket.local_addr().map_err(Error::Socket)?;
+        let token = self.next_token();
+        self.poll
+            .registry()
+            .register(&mut socket, token, Interest::READABLE)
+            .map_err(Error::Socket)?;
+        self.sources.insert(token, Source::Udp(socket));
+        Ok(local)
+    }
+
+    /// Accepts TCP clients at ``address``, returning the address actually bound.
+    ///
+    /// # Errors
+    /// If the listener cannot be bound or registered, this function will return an
+    /// ``Error::Socket``.
+    pub fn bind_tcp<A: ToSocketAddrs>(&mut self, address: A) -> Result<SocketAddr, Error> {
+        let listener = std::net::TcpListener::bind(address).map_err(Error::Socket)?;
+        listener.set_nonblocking(true).map_err(Error::Socket)?;
+        let mut listener = TcpListener::from_std(listener);
+        let local = listener.local_addr().map_err(Error::Socket)?;
+        let token = self.next_token();
+        self.poll
+            .registry()
+            .register(&mut listener, token, Interest::READABLE)
+            .map_err(Error::Socket)?;
+        self.sources.insert(token, Source::Listener(listener));
+        Ok(local)
+    }
+
+    dispatcher_builders!();
+
+    /// Only accepts UDP packets and TCP clients from sources permitted by ``access``. Other
+    /// packets are dropped, and other clients disconnected as soon as they are accepted.
+    #[must_use]
+    pub fn with_access_list(mut self, access: AccessList) -> Self {
+        self.dispatcher.access = access;
+        self
+    }
+
+    /// See ``TcpOscServer::on_connect``. Only TCP clients connect.
+    #[must_use]
+    pub fn on_connect<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(SocketAddr, &mut Session) + Send + Sync + 'static,
+    {
+        self.dispatcher.on_connect = Some(Box::new(hook));
+        self
+    }
+
+    /// See ``TcpOscServer::on_disconnect``.
+    #[must_use]
+    pub fn on_disconnect<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(SocketAddr, &mut Session) + Send + Sync + 'static,
+    {
+        self.dispatcher.on_disconnect = Some(Box::new(hook));
+        self
+    }
+
+    /// Returns the addresses of the currently connected TCP clients.
+    pub fn connections(&self) -> Vec<SocketAddr> {
+        self.sources
+            .values()
+            .filter_map(|source| match source {
+                Source::Client(client) => Some(client.peer),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Handles readiness events until shut down with ``ServerHandle::shutdown``. Packets that
+    /// fail to parse are ignored, and TCP clients are disconnected if their stream fails, or a
+    /// reply to them cannot be built.
+    ///
+    /// Once shut down, the server stops handling events, dispatches held bundles which become
+    /// due within the drain timeout set with ``with_drain_timeout``, and returns ``Ok``. Its
+    /// sockets stay open, so it can be started again, until it is dropped.
+    ///
+    /// # Errors
+    /// Will return an ``Error::Socket`` if polling, receiving over UDP, or accepting a client
+    /// fails.
+    pub fn start(&mut self) -> Result<(), Error> {
+        while !self.dispatcher.shutdown.is_cancelled() {
+            self.step(Some(SHUTDOWN_POLL_INTERVAL))?;
+        }
+        let deadline = Instant::now() + self.dispatcher.drain_timeout;
+        while let Some(next) = self
+            .schedule
+            .until_next()
+            .filter(|next| Instant::now() + *next <= deadline)
+        {
+            thread::sleep(next);
+            self.dispatch_due();
+        }
+        self.schedule = Schedule::new();
+        self.dispatcher.shutdown.reset();
+        Ok(())
+    }
+
+    /// Waits up to ``timeout`` (or forever, if ``None``) for readiness events and handles them, so
+    /// the server can be driven from another event loop. Bundles timetagged in the future are held
+    /// as ``OscServer::start`` describes, and the wait is cut short when one becomes due.
+    ///
+    /// # Errors
+    /// Will return an ``Error::Socket`` if polling, receiving over UDP, or accepting a client
+    /// fails.
+    pub fn step(&mut self, timeout: Option<Duration>) -> Result<(), Error> {
+        let timeout = match (timeout, self.schedule.until_next()) {
+            (Some(timeout), Some(next)) => Some(timeout.min(next)),
+            (timeout, next) => timeout.or(next),
+        };
+        match self.poll.poll(&mut self.events, timeout) {
+            Err(e) if e.kind() == ErrorKind::Interrupted => return Ok(()),
+            result => result.map_err(Error::Socket)?,
+        }
+        let ready: Vec<(Token, bool)> = self
+            .events
+            .iter()
+            .map(|event| (event.token(), event.is_read_closed() || event.is_readable()))
+            .collect();
+        for (token, readable) in ready {
+            self.ready(token, readable)?;
+        }
+        self.dispatch_due();
+        Ok(())
+    }
+
+    fn dispatch_due(&mut self) {
+        for (packet, (token, source)) in self.schedule.take_due(&self.dispatcher.limits) {
+            match self.sources.get_mut(&token) {
+                Some(Source::Udp(socket)) => send_to(socket, &self.dispatcher, &packet, source),
+                Some(Source::Client(client)) => {
+                    let open = client.queue(&self.dispatcher, &packet)
+                        && client.flush(self.poll.registry(), token);
+                    if !open {
+                        self.disconnect(token);
+                    }
+                }
+                // The client disconnected before the bundle was due
+                _ => {}
+            }
+        }
+    }
+
+    fn disconnect(&mut self, token: Token) {
+        if let Some(Source::Client(mut client)) = self.sources.remove(&token) {
+            let _ = self.poll.registry().deregister(&mut client.stream);
+            self.dispatcher.disconnected(client.peer);
+        }
+    }
+
+    fn next_token(&mut self) -> Token {
+        let token = Token(self.next_token);
+        self.next_token += 1;
+        token
+    }
+
+    fn ready(&mut self, token: Token, readable: bool) -> Result<(), Error> {
+        let mut accepted = Vec::new();
+        match self.sources.get_mut(&token) {
+            None => {}
+            Some(Source::Udp(socket)) => loop {
+                let (length, source) = match socket.recv_from(&mut self.buffer) {
+                    Ok(received) => received,
+                    Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                    Err(e) => return Err(Error::Socket(e)),
+                };
+                if !self.dispatcher.access.permits(source.ip()) {
+                    continue;
+                }
+                let Some(packet) = self.dispatcher.parse(&self.buffer[..length], source) else {
+                    continue;
+                };
+                if let Some(packet) =
+                    self.schedule
+                        .defer(packet, &(token, source), &self.dispatcher.limits)
+                {
+                    send_to(socket, &self.dispatcher, &packet, source);
+                }
+            },
+            Some(Source::Listener(listener)) => loop {
+                match listener.accept() {
+                    Ok((_, peer)) if !self.dispatcher.access.permits(peer.ip()) => {}
+                    Ok(stream) => accepted.push(stream),
+                    Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                    Err(e) => return Err(Error::Socket(e)),
+                }
+            },
+            Some(Source::Client(client)) => {
+                let open = client.ready(
+                    readable,
+                    &self.dispatcher,
+                    &mut self.schedule,
+                    token,
+                    self.buffer.len(),
+                ) && client.flush(self.poll.registry(), token);
+                if !open {
+                    self.disconnect(token);
+                }
+            }
+        }
+
+        for (mut stream, peer) in accepted {
+            self.dispatcher.connected(peer);
+            let token = self.next_token();
+            self.poll
+                .registry()
+                .register(&mut stream, token, Interest::READABLE)
+                .map_err(Error::Socket)?;
+            self.sources.insert(
+                token,
+                Source::Client(Client {
+                    stream,
+                    peer,
+                    pending: Vec::new(),
+                    outgoing: Vec::new(),
+                }),
+            );
+        }
+        Ok(())
+    }
+}
+
+impl Client {
+    /// Reads and dispatches any whole packets, scheduling bundles timetagged in the future.
+    /// Returns whether the connection is still open, which it isn't once the client sends a packet
+    /// larger than ``max_packet_size``.
+    fn ready(
+        &mut self,
+        readable: bool,
+        dispatcher: &Dispatcher,
+        schedule: &mut Schedule<(Token, SocketAddr)>,
+        token: Token,
+        max_packet_size: usize,
+    ) -> bool {
+        if !readable {
+            return true;
+        }
+        let mut chunk = [0; 4096];
+        loop {
+            match self.stream.read(&mut chunk) {
+                Ok(0) => return false,
+                Ok(n) => self.pending.extend_from_slice(&chunk[..n]),
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(_) => return false,
+            }
+        }
+        loop {
+            let packet = match take_frame(&mut self.pending, max_packet_size) {
+                Ok(Some(packet)) => packet,
+                Ok(None) => break,
+                Err(_) => return false,
+            };
+            let Some(packet) = dispatcher.parse(&packet, self.peer) else {
+                continue;
+            };
+            if let Some(packet) = schedule.defer(packet, &(token, self.peer), &dispatcher.limits) {
+                if !self.queue(dispatcher, &packet) {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    /// Dispatches ``packet`` and queues the replies to be written. Returns whether they could all
+    /// be built.
+    fn queue(&mut self, dispatcher: &Dispatcher, packet: &OscPacket) -> bool {
+        for reply in dispatcher.replies(packet, self.peer) {
+            let Ok(bytes) = dispatcher.build(&reply) else {
+                return false;
+            };
+            let Ok(framed) = frame(&bytes) else {
+                return false;
+            };
+            // Counted once queued, as the stream may take several writes to send it
+            dispatcher.sent(self.peer, reply, &bytes, bytes.len());
+            self.outgoing.extend(framed);
+        }
+        true
+    }
+
+    /// Writes as many queued replies as possible. Returns whether the connection is still open.
+    fn flush(&mut self, registry: &Registry, token: Token) -> bool {
+        while !self.outgoing.is_empty() {
+            match self.stream.write(&self.outgoing) {
+                Ok(0) => return false,
+                Ok(n) => {
+                    self.outgoing.drain(..n);
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(_) => return false,
+            }
+        }
+        let interest = if self.outgoing.is_empty() {
+            Interest::READABLE
+        } else {
+            Interest::READABLE | Interest::WRITABLE
+        };
+        registry
+            .reregister(&mut self.stream, token, interest)
+            .is_ok()
+    }
+}
+
+/// Dispatches ``packet`` and sends the replies to ``source``.
+fn send_to(socket: &UdpSocket, dispatcher: &Dispatcher, packet: &OscPacket, source: SocketAddr) {
+    for reply in dispatcher.replies(packet, source) {
+        // UDP makes no delivery guarantees, so a reply which can't be sent is dropped
+        let Ok(bytes) = dispatcher.build(&reply) else {
+            continue;
+        };
+        if let Ok(sent) = socket.send_to(&bytes, source) {
+            dispatcher.sent(source, reply, &bytes, sent);
+        }
+    }
+}
@@ -0,0 +1,273 @@
+use std::{
+    cell::Cell,
+    io::{self, ErrorKind},
+    net::{SocketAddr, ToSocketAddrs},
+    sync::{Arc, Weak},
+    thread,
+    time::Duration,
+};
+
+use crate::{
+    bundle::OscBundle,
+    client::OscClient,
+    errors::Error,
+    sockets::{copy_frame, Connection},
+    sync::{channel, lock, Mutex, Receiver, RecvTimeoutError, Sender, TryRecvError},
+};
+
+/// How long the receiving thread waits for a packet before checking whether it's still needed.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The largest packet the receiving thread can receive, the most a UDP datagram can hold.
+const MAX_PACKET_SIZE: usize = 65536;
+
+/// Decides whether a handle receives packets to an address.
+type Filter = Box<dyn Fn(&str) -> bool + Send>;
+
+/// One connection shared by several ``OscClient``s, e.g. one per device abstraction in an app,
+/// so they don't each need their own bound port and receiving thread. Each client gets a
+/// ``MuxConnection`` handle, which sends over the shared connection, and receives the packets
+/// whose address matches its filter.
+///
+/// A background thread receives every packet, started by ``new``, and passes each to the first
+/// handle whose filter matches the packet's address (a bundle's is that of its first message).
+/// Packets no handle matches are dropped. The thread stops once the ``OscMux`` and every handle
+/// have been dropped.
+pub struct OscMux<C: Connection> {
+    connection: C,
+    routes: Arc<Mutex<Vec<Route>>>,
+}
+
+struct Route {
+    filter: Filter,
+    packets: Sender<Vec<u8>>,
+    /// The handle's end of ``packets``, so the route can be removed once every clone of the
+    /// handle has been dropped.
+    handle: Weak<Mutex<Receiver<Vec<u8>>>>,
+}
+
+impl<C: Connection + Send + 'static> OscMux<C> {
+    /// Shares ``connection``, starting the thread which receives from it.
+    ///
+    /// # Errors
+    /// Will return an ``Error::Socket`` if ``connection`` can't be cloned for the thread, or its
+    /// read timeout can't be set.
+    pub fn new(connection: C) -> Result<Self, Error> {
+        let mut receiving = connection.try_clone().map_err(Error::Socket)?;
+        receiving
+            .set_read_timeout(Some(POLL_INTERVAL))
+            .map_err(Error::Socket)?;
+        let routes: Arc<Mutex<Vec<Route>>> = Arc::default();
+        let weak_routes = Arc::downgrade(&routes);
+        thread::spawn(move || receive(&mut receiving, &weak_routes));
+        Ok(Self { connection, routes })
+    }
+
+    /// Creates a connection from ``local_address`` to ``remote_address`` and shares it. See
+    /// ``new``.
+    ///
+    /// # Errors
+    /// Will return an ``Error::Socket`` if the connection can't be made, or ``new`` fails.
+    pub fn bind<A: ToSocketAddrs, B: ToSocketAddrs>(
+        local_address: A,
+        remote_address: B,
+    ) -> Result<Self, Error> {
+        Self::new(C::new(local_address, remote_address).map_err(Error::Socket)?)
+    }
+
+    /// Returns a handle which receives the packets to addresses starting with ``prefix``, e.g.
+    /// ``/mixer/`` for everything one device abstraction cares about. An empty ``prefix``
+    /// receives every packet no earlier handle matches.
+    ///
+    /// # Errors
+    /// Will return an ``Error::Socket`` if the connection can't be cloned for the handle.
+    pub fn handle(&self, prefix: &str) -> Result<MuxConnection<C>, Error> {
+        let prefix = prefix.to_string();
+        self.handle_where(move |address| address.starts_with(&prefix))
+    }
+
+    /// Returns a handle which receives the packets whose address ``filter`` matches, e.g. to
+    /// correlate replies with whichever client asked for them. Handles are tried in the order
+    /// they were created.
+    ///
+    /// # Errors
+    /// See ``handle``.
+    pub fn handle_where<F>(&self, filter: F) -> Result<MuxConnection<C>, Error>
+    where
+        F: Fn(&str) -> bool + Send + 'static,
+    {
+        let connection = self.connection.try_clone().map_err(Error::Socket)?;
+        let (sender, packets) = channel();
+        let packets = Arc::new(Mutex::new(packets));
+        lock(&self.routes).push(Route {
+            filter: Box::new(filter),
+            packets: sender,
+            handle: Arc::downgrade(&packets),
+        });
+        Ok(MuxConnection {
+            connection,
+            routes: self.routes.clone(),
+            packets,
+            read_timeout: Cell::new(None),
+            nonblocking: Cell::new(false),
+        })
+    }
+
+    /// Returns an ``OscClient`` over a handle made by ``handle``. ``buffer_size`` and
+    /// ``read_timeout`` are as for ``OscClient::new``.
+    ///
+    /// # Errors
+    /// See ``handle`` and ``OscClient::from_connection``.
+    pub fn client(
+        &self,
+        prefix: &str,
+        buffer_size: usize,
+        read_timeout: Option<Duration>,
+    ) -> Result<OscClient<MuxConnection<C>>, Error> {
+        OscClient::from_connection(self.handle(prefix)?, buffer_size, read_timeout)
+    }
+}
+
+/// Receives packets from ``connection``, passing each to the first route which matches it, until
+/// the ``OscMux`` and all its handles are dropped, or receiving fails.
+fn receive<C: Connection>(connection: &mut C, routes: &Weak<Mutex<Vec<Route>>>) {
+    let mut buffer = vec![0; MAX_PACKET_SIZE];
+    loop {
+        let res = connection.recv(&mut buffer);
+        let Some(routes) = routes.upgrade() else {
+            return;
+        };
+        let mut routes = lock(&routes);
+        routes.retain(|route| route.handle.strong_count() > 0);
+        let size = match res {
+            Ok(size) => size,
+            // A connected UDP socket reports the remote refusing an earlier send here
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    ErrorKind::WouldBlock
+                        | ErrorKind::TimedOut
+                        | ErrorKind::ConnectionRefused
+                        | ErrorKind::ConnectionReset
+                ) =>
+            {
+                continue
+            }
+            Err(_) => {
+                // Dropping the routes disconnects the handles, so they stop waiting
+                routes.clear();
+                return;
+            }
+        };
+        let packet = &buffer[..size];
+        let Some(address) = packet_address(packet) else {
+            continue;
+        };
+        if let Some(route) = routes.iter().find(|route| (route.filter)(address)) {
+            let _ = route.packets.send(packet.to_vec());
+        }
+    }
+}
+
+/// Returns the address of the message ``packet`` holds, or of the first message in it if it's a
+/// bundle.
+fn packet_address(packet: &[u8]) -> Option<&str> {
+    if OscBundle::is_bundle(packet) {
+        let size = packet.get(16..20)?;
+        let size = usize::try_from(u32::from_be_bytes(size.try_into().ok()?)).ok()?;
+        return packet_address(packet.get(20..20 + size)?);
+    }
+    let end = packet.iter().position(|b| *b == 0)?;
+    std::str::from_utf8(&packet[..end]).ok()
+}
+
+/// A handle onto a connection shared by an ``OscMux``, which receives the packets that match
+/// its filter. Clones made with ``try_clone`` share the same packets.
+pub struct MuxConnection<C: Connection> {
+    connection: C,
+    /// Keeps the receiving thread running while the handle is in use.
+    routes: Arc<Mutex<Vec<Route>>>,
+    packets: Arc<Mutex<Receiver<Vec<u8>>>>,
+    read_timeout: Cell<Option<Duration>>,
+    nonblocking: Cell<bool>,
+}
+
+impl<C: Connection + Send + 'static> Connection for MuxConnection<C> {
+    /// Creates a handle onto a new connection of its own, receiving every packet. Use
+    /// ``OscMux::handle`` to share one connection.
+    fn new<A: ToSocketAddrs, B: ToSocketAddrs>(
+        local_address: A,
+        remote_address: B,
+    ) -> io::Result<Self> {
+        OscMux::<C>::bind(local_address, remote_address)
+            .and_then(|mux| mux.handle(""))
+            .map_err(|e| match e {
+                Error::Socket(e) => e,
+                e => io::Error::other(e.to_string()),
+            })
+    }
+
+    fn send(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.connection.send(buf)
+    }
+
+    fn recv(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let packets = lock(&self.packets);
+        let packet = if self.nonblocking.get() {
+            packets.try_recv().map_err(|e| match e {
+                TryRecvError::Empty => io::Error::from(ErrorKind::WouldBlock),
+                TryRecvError::Disconnected => stopped(),
+            })?
+        } else if let Some(timeout) = self.read_timeout.get() {
+            packets.recv_timeout(timeout).map_err(|e| match e {
+                RecvTimeoutError::Timeout => io::Error::from(ErrorKind::WouldBlock),
+                RecvTimeoutError::Disconnected => stopped(),
+            })?
+        } else {
+            packets.recv().map_err(|_| stopped())?
+        };
+        copy_frame(&packet, buf)
+    }
+
+    fn set_read_timeout(&self, dur: Option<Duration>) -> io::Result<()> {
+        if dur == Some(Duration::ZERO) {
+            return Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                "Read timeout must not be zero",
+            ));
+        }
+        self.read_timeout.set(dur);
+        Ok(())
+    }
+
+    fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        self.nonblocking.set(nonblocking);
+        Ok(())
+    }
+
+    fn try_clone(&self) -> io::Result<Self> {
+        Ok(Self {
+            connection: self.connection.try_clone()?,
+            routes: self.routes.clone(),
+            packets: self.packets.clone(),
+            read_timeout: self.read_timeout.clone(),
+            nonblocking: self.nonblocking.clone(),
+        })
+    }
+
+    fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.connection.local_addr()
+    }
+
+    fn peer_addr(&self) -> io::Result<SocketAddr> {
+        self.connection.peer_addr()
+    }
+}
+
+/// The error a handle returns once the ``OscMux``'s receiving thread has stopped.
+fn stopped() -> io::Error {
+    io::Error::new(
+        ErrorKind::NotConnected,
+        "The shared connection stopped receiving",
+    )
+}
@@ -0,0 +1,217 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    address::OscAddress, errors::Error, pattern::OscPattern, write_arg, write_type_tags, Arg,
+};
+
+/// A description of the addresses a server exposes, as a tree of containers (such as ``/ch``)
+/// and the parameters within them (such as ``/ch/01/fader``), with the types and ranges of their
+/// arguments, e.g. ``OscNamespace::new().with_parameter("/ch/01/fader", Parameter::new("f"))``.
+/// Give it to a server with ``OscServer::with_namespace``.
+#[derive(Debug, Clone, Default)]
+pub struct OscNamespace {
+    root: NamespaceNode,
+}
+
+/// A container or parameter in an ``OscNamespace``.
+#[derive(Debug, Clone, Default)]
+pub struct NamespaceNode {
+    pub description: Option<String>,
+    /// The parameter at this node's address, or ``None`` if it only contains other nodes.
+    pub parameter: Option<Parameter>,
+    children: BTreeMap<String, NamespaceNode>,
+}
+
+/// The arguments a parameter takes.
+#[derive(Debug, Clone)]
+pub struct Parameter {
+    /// The OSC type tags of the arguments, e.g. ``"ff"`` for two ``Arg::Float``s.
+    pub type_tags: String,
+    /// The range of each argument, in order. Arguments without one are unrestricted.
+    pub ranges: Vec<Option<ArgRange>>,
+    pub access: Access,
+}
+
+/// The values an argument may take.
+#[derive(Debug, Clone)]
+pub enum ArgRange {
+    /// Any number from ``min`` to ``max``, inclusive. Either end may be left open.
+    Numeric { min: Option<f64>, max: Option<f64> },
+    /// Only these values, e.g. the names of a selector's options.
+    Values(Vec<Arg>),
+}
+
+/// Whether clients may read a parameter's value, set it, or both.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Access {
+    None,
+    ReadOnly,
+    WriteOnly,
+    #[default]
+    ReadWrite,
+}
+
+impl OscNamespace {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds ``parameter`` at ``address``, creating its containers as needed. A parameter already
+    /// there is replaced.
+    ///
+    /// # Errors
+    /// Will return ``Error::Address`` if ``address`` is not a valid ``OscAddress``.
+    pub fn with_parameter<A>(mut self, address: A, parameter: Parameter) -> Result<Self, Error>
+    where
+        A: TryInto<OscAddress>,
+        Error: From<A::Error>,
+    {
+        self.node_mut(&address.try_into()?).parameter = Some(parameter);
+        Ok(self)
+    }
+
+    /// Describes the container or parameter at ``address`` for people browsing the namespace,
+    /// creating the node if needed.
+    ///
+    /// # Errors
+    /// Will return ``Error::Address`` if ``address`` is not a valid ``OscAddress``.
+    pub fn with_description<A>(
+        mut self,
+        address: A,
+        description: impl Into<String>,
+    ) -> Result<Self, Error>
+    where
+        A: TryInto<OscAddress>,
+        Error: From<A::Error>,
+    {
+        self.node_mut(&address.try_into()?).description = Some(description.into());
+        Ok(self)
+    }
+
+    /// Returns the root container, ``/``.
+    #[must_use]
+    pub fn root(&self) -> &NamespaceNode {
+        &self.root
+    }
+
+    /// Returns the node at ``address``, or the root for ``/``.
+    #[must_use]
+    pub fn get(&self, address: &str) -> Option<&NamespaceNode> {
+        address
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .try_fold(&self.root, |node, segment| node.children.get(segment))
+    }
+
+    /// Returns the addresses of every parameter, in order.
+    #[must_use]
+    pub fn parameters(&self) -> Vec<String> {
+        let mut addresses = Vec::new();
+        self.root.collect_parameters("", &mut addresses);
+        addresses
+    }
+
+    /// Adds a node for each of ``patterns`` which is a plain address, leaving nodes that are
+    /// already described as they are. Patterns with wildcards can't be placed in the tree.
+    #[cfg_attr(not(feature = "server"), allow(dead_code))]
+    pub(crate) fn add_routes<'a>(&mut self, patterns: impl IntoIterator<Item = &'a OscPattern>) {
+        for pattern in patterns {
+            if let Ok(address) = OscAddress::new(pattern.as_str()) {
+                self.node_mut(&address);
+            }
+        }
+    }
+
+    fn node_mut(&mut self, address: &OscAddress) -> &mut NamespaceNode {
+        address.segments().fold(&mut self.root, |node, segment| {
+            node.children.entry(segment.to_string()).or_default()
+        })
+    }
+}
+
+impl NamespaceNode {
+    /// Returns the nodes directly within this one, by name, in order.
+    pub fn children(&self) -> impl Iterator<Item = (&str, &NamespaceNode)> {
+        self.children
+            .iter()
+            .map(|(name, child)| (name.as_str(), child))
+    }
+
+    fn collect_parameters(&self, path: &str, addresses: &mut Vec<String>) {
+        for (name, child) in &self.children {
+            let path = format!("{path}/{name}");
+            if child.parameter.is_some() {
+                addresses.push(path.clone());
+            }
+            child.collect_parameters(&path, addresses);
+        }
+    }
+}
+
+impl Parameter {
+    /// Creates a ``Parameter`` taking arguments with the OSC type tags ``type_tags``, e.g.
+    /// ``"f"`` for a single ``Arg::Float``.
+    #[must_use]
+    pub fn new(type_tags: impl Into<String>) -> Self {
+        Self {
+            type_tags: type_tags.into(),
+            ranges: Vec::new(),
+            access: Access::default(),
+        }
+    }
+
+    /// Sets the range of the next argument, so the first call sets the first argument's range,
+    /// the second the second's, and so on.
+    #[must_use]
+    pub fn with_range(mut self, range: ArgRange) -> Self {
+        self.ranges.push(Some(range));
+        self
+    }
+
+    #[must_use]
+    pub fn with_access(mut self, access: Access) -> Self {
+        self.access = access;
+        self
+    }
+}
+
+impl ArgRange {
+    /// Creates a range from ``min`` to ``max``, inclusive.
+    #[must_use]
+    pub fn new(min: f64, max: f64) -> Self {
+        Self::Numeric {
+            min: Some(min),
+            max: Some(max),
+        }
+    }
+
+    /// Returns whether ``arg`` is within the range. Only numbers can be within a
+    /// ``Numeric`` range, and ``Values`` are compared by their encoding.
+    #[must_use]
+    pub fn contains(&self, arg: &Arg) -> bool {
+        match self {
+            ArgRange::Numeric { min, max } => {
+                #[allow(clippy::cast_precision_loss)]
+                let value = match *arg {
+                    Arg::Int(i) => f64::from(i),
+                    Arg::Float(f) => f64::from(f),
+                    Arg::Int64(i) => i as f64,
+                    Arg::Double(d) => d,
+                    _ => return false,
+                };
+                min.is_none_or(|min| value >= min) && max.is_none_or(|max| value <= max)
+            }
+            ArgRange::Values(values) => values.iter().any(|value| same_arg(value, arg)),
+        }
+    }
+}
+
+/// Returns whether ``a`` and ``b`` have the same type and encoding.
+fn same_arg(a: &Arg, b: &Arg) -> bool {
+    let mut a_tags = String::new();
+    let mut b_tags = String::new();
+    write_type_tags(a, &mut a_tags);
+    write_type_tags(b, &mut b_tags);
+    a_tags == b_tags && matches!((write_arg(a), write_arg(b)), (Ok(a), Ok(b)) if a == b)
+}
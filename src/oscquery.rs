@@ -0,0 +1,254 @@
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+};
+
+use serde_json::{json, Map, Value};
+
+use crate::{
+    errors::Error,
+    namespace::{Access, ArgRange, NamespaceNode},
+    server::ServerHandle,
+    state::StateStore,
+    Arg,
+};
+
+/// The transport an ``OSCQuery`` server advertises for the OSC server it describes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OscTransport {
+    Udp,
+    Tcp,
+}
+
+/// Serves a server's ``OscNamespace`` over HTTP using the ``OSCQuery`` protocol, so editors such
+/// as Vezér and Chataigne can discover its parameters. Create one with ``OscServer::oscquery``.
+///
+/// ``GET`` on an address returns the JSON description of that node and everything within it,
+/// ``?HOST_INFO`` describes the server, and ``?ATTRIBUTE`` (e.g. ``?VALUE``) returns a single
+/// attribute. Values are read from the server's ``StateStore``, so are only reported when it
+/// records state.
+#[allow(clippy::module_name_repetitions)]
+pub struct OscQueryServer {
+    listener: TcpListener,
+    handle: ServerHandle,
+    state: StateStore,
+    osc_address: SocketAddr,
+    transport: OscTransport,
+    name: String,
+}
+
+impl OscQueryServer {
+    /// Creates a new ``OscQueryServer``, listening for HTTP requests at ``address`` and
+    /// describing the OSC server at ``osc_address``.
+    ///
+    /// # Errors
+    /// If the listener cannot be bound, this function will return an ``Error::Socket``.
+    pub fn new<A: ToSocketAddrs>(
+        address: A,
+        handle: ServerHandle,
+        state: StateStore,
+        osc_address: SocketAddr,
+        transport: OscTransport,
+    ) -> Result<Self, Error> {
+        Ok(Self {
+            listener: TcpListener::bind(address).map_err(Error::Socket)?,
+            handle,
+            state,
+            osc_address,
+            transport,
+            name: env!("CARGO_PKG_NAME").to_string(),
+        })
+    }
+
+    /// Sets the name editors show for the server. Defaults to ``osc``.
+    #[must_use]
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Returns the address the server is listening at.
+    ///
+    /// # Errors
+    /// Will return an ``Error::Socket`` if the address cannot be read from the listener.
+    pub fn local_addr(&self) -> Result<SocketAddr, Error> {
+        self.listener.local_addr().map_err(Error::Socket)
+    }
+
+    /// Answers requests forever, one at a time. Requests which fail partway through are
+    /// abandoned.
+    ///
+    /// # Errors
+    /// Will return an ``Error::Socket`` if accepting a connection fails.
+    pub fn start(&self) -> Result<(), Error> {
+        loop {
+            let (stream, _) = self.listener.accept().map_err(Error::Socket)?;
+            let _ = self.answer(stream);
+        }
+    }
+
+    fn answer(&self, stream: TcpStream) -> std::io::Result<()> {
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let mut request = String::new();
+        reader.read_line(&mut request)?;
+        // Skip the headers, which make no difference to the reply
+        let mut header = String::new();
+        while reader.read_line(&mut header)? > 2 {
+            header.clear();
+        }
+
+        let mut parts = request.split_whitespace();
+        let (status, body) = match (parts.next(), parts.next()) {
+            (Some("GET"), Some(target)) => self.respond(target),
+            (Some(_), Some(_)) => ("405 Method Not Allowed", None),
+            _ => ("400 Bad Request", None),
+        };
+        let body = body.map(|body| body.to_string()).unwrap_or_default();
+        let mut stream = stream;
+        write!(
+            stream,
+            "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+             Connection: close\r\n\r\n{body}",
+            body.len()
+        )?;
+        stream.flush()
+    }
+
+    fn respond(&self, target: &str) -> (&'static str, Option<Value>) {
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        if query == "HOST_INFO" {
+            return ("200 OK", Some(self.host_info()));
+        }
+        let namespace = self.handle.namespace();
+        let Some(node) = namespace.get(path) else {
+            return ("404 Not Found", None);
+        };
+        let path = match path.trim_end_matches('/') {
+            "" => "/",
+            path => path,
+        };
+        let mut description = self.describe(path, node);
+        if query.is_empty() {
+            return ("200 OK", Some(description));
+        }
+        match description.get_mut(query).map(Value::take) {
+            Some(value) => ("200 OK", Some(json!({ query: value }))),
+            None if ATTRIBUTES.contains(&query) => ("204 No Content", None),
+            None => ("400 Bad Request", None),
+        }
+    }
+
+    fn host_info(&self) -> Value {
+        let transport = match self.transport {
+            OscTransport::Udp => "UDP",
+            OscTransport::Tcp => "TCP",
+        };
+        json!({
+            "NAME": self.name,
+            "OSC_IP": self.osc_address.ip().to_string(),
+            "OSC_PORT": self.osc_address.port(),
+            "OSC_TRANSPORT": transport,
+            "EXTENSIONS": {
+                "ACCESS": true,
+                "VALUE": true,
+                "RANGE": true,
+                "DESCRIPTION": true,
+                "TYPE": true,
+                "CONTENTS": true,
+            },
+        })
+    }
+
+    /// Describes ``node``, at ``path``, and everything within it. Attributes the node doesn't
+    /// have are left out.
+    fn describe(&self, path: &str, node: &NamespaceNode) -> Value {
+        let mut description = Map::new();
+        description.insert("FULL_PATH".to_string(), json!(path));
+        if let Some(text) = &node.description {
+            description.insert("DESCRIPTION".to_string(), json!(text));
+        }
+        let access = node.parameter.as_ref().map_or(Access::None, |p| p.access);
+        description.insert("ACCESS".to_string(), json!(access_value(access)));
+        if let Some(parameter) = &node.parameter {
+            let ranges: Vec<_> = parameter.ranges.iter().map(|r| range(r.as_ref())).collect();
+            description.insert("TYPE".to_string(), json!(parameter.type_tags));
+            if !ranges.is_empty() {
+                description.insert("RANGE".to_string(), Value::Array(ranges));
+            }
+            if let Some(args) = self.state.get(path) {
+                let value = args.iter().map(arg_value).collect();
+                description.insert("VALUE".to_string(), Value::Array(value));
+            }
+        }
+
+        let contents: Map<String, Value> = node
+            .children()
+            .map(|(name, child)| {
+                let child_path = match path {
+                    "/" => format!("/{name}"),
+                    path => format!("{path}/{name}"),
+                };
+                (name.to_string(), self.describe(&child_path, child))
+            })
+            .collect();
+        if !contents.is_empty() {
+            description.insert("CONTENTS".to_string(), Value::Object(contents));
+        }
+        Value::Object(description)
+    }
+}
+
+/// The attributes a node may have, which can be queried individually.
+const ATTRIBUTES: [&str; 7] = [
+    "FULL_PATH",
+    "CONTENTS",
+    "DESCRIPTION",
+    "ACCESS",
+    "TYPE",
+    "RANGE",
+    "VALUE",
+];
+
+fn access_value(access: Access) -> u8 {
+    match access {
+        Access::None => 0,
+        Access::ReadOnly => 1,
+        Access::WriteOnly => 2,
+        Access::ReadWrite => 3,
+    }
+}
+
+fn range(range: Option<&ArgRange>) -> Value {
+    match range {
+        None => Value::Null,
+        Some(ArgRange::Numeric { min, max }) => json!({ "MIN": min, "MAX": max }),
+        Some(ArgRange::Values(values)) => {
+            json!({ "VALS": values.iter().map(arg_value).collect::<Vec<_>>() })
+        }
+    }
+}
+
+/// Converts ``arg`` to its ``OSCQuery`` JSON value. Types JSON can't represent are ``null``.
+fn arg_value(arg: &Arg) -> Value {
+    match arg {
+        Arg::Int(i) => json!(i),
+        Arg::Float(f) => json!(f),
+        Arg::Int64(i) => json!(i),
+        Arg::Double(d) => json!(d),
+        Arg::Str(s) | Arg::Symbol(s) => json!(s),
+        Arg::Char(c) => json!(c.to_string()),
+        Arg::Bool(b) => json!(b),
+        Arg::Color(color) => json!(format!(
+            "#{:02x}{:02x}{:02x}{:02x}",
+            color.r, color.g, color.b, color.a
+        )),
+        Arg::Array(args) => Value::Array(args.iter().map(arg_value).collect()),
+        Arg::Blob(_)
+        | Arg::Time(_)
+        | Arg::Nil
+        | Arg::Infinitum
+        | Arg::Midi { .. }
+        | Arg::Unknown(..)
+        | Arg::Custom(_) => Value::Null,
+    }
+}
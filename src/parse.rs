@@ -0,0 +1,86 @@
+use crate::custom::TypeTagRegistry;
+
+/// How deeply bundles and arrays may be nested by default.
+pub(crate) const DEFAULT_MAX_DEPTH: usize = 64;
+
+/// What ``OscPacket::parse_bytes_with`` (and the ``OscMessage`` and ``OscBundle`` equivalents)
+/// accepts, so a server exposed to an untrusted network can reject hostile packets while lab
+/// tools stay permissive. Limits which are ``None`` aren't enforced.
+///
+/// The default matches ``parse_bytes``, which only limits nesting, to 64 levels. See ``strict`` and
+/// ``lenient`` for the two extremes.
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Debug, Clone)]
+pub struct ParseOptions {
+    /// Reject packets whose length isn't a multiple of 4 bytes.
+    pub require_alignment: bool,
+    /// Reject messages followed by bytes their type tags don't account for.
+    pub reject_trailing: bool,
+    /// Accept messages without a type tag string, as sent by some old OSC implementations. The
+    /// data after the address can't be interpreted without type tags, so they are parsed as
+    /// having no arguments.
+    pub allow_missing_type_tags: bool,
+    /// Keep arguments with unknown type tags as ``Arg::Unknown`` rather than failing, e.g. so a
+    /// proxy or logger can pass on messages with exotic types unchanged.
+    pub preserve_unknown_tags: bool,
+    /// Decoders for application-defined type tags, which are parsed as ``Arg::Custom``.
+    pub custom_tags: TypeTagRegistry,
+    /// Most arguments a message may have, counting each array and each of its items.
+    pub max_args: Option<usize>,
+    /// Longest address, string, or symbol accepted, in bytes.
+    pub max_string_len: Option<usize>,
+    /// Longest blob accepted, in bytes.
+    pub max_blob_len: Option<usize>,
+    /// Most levels of bundles within bundles, or of arrays within arrays, accepted, so hostile
+    /// packets can't exhaust the stack. A bundle of messages, or a message with an array of plain
+    /// arguments, has a depth of 1.
+    pub max_depth: Option<usize>,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            require_alignment: true,
+            reject_trailing: false,
+            allow_missing_type_tags: false,
+            preserve_unknown_tags: false,
+            custom_tags: TypeTagRegistry::new(),
+            max_args: None,
+            max_string_len: None,
+            max_blob_len: None,
+            max_depth: Some(DEFAULT_MAX_DEPTH),
+        }
+    }
+}
+
+impl ParseOptions {
+    /// Options for untrusted networks: packets must be aligned without trailing data, a message
+    /// may have at most 256 arguments, 1 KiB strings, and 64 KiB blobs, and bundles and arrays
+    /// may be nested at most 8 deep.
+    #[must_use]
+    pub fn strict() -> Self {
+        Self {
+            require_alignment: true,
+            reject_trailing: true,
+            allow_missing_type_tags: false,
+            preserve_unknown_tags: false,
+            custom_tags: TypeTagRegistry::new(),
+            max_args: Some(256),
+            max_string_len: Some(1024),
+            max_blob_len: Some(65536),
+            max_depth: Some(8),
+        }
+    }
+
+    /// Options which accept anything that can be parsed, including unaligned packets from
+    /// devices which don't pad them, messages without type tags, and unknown type tags.
+    #[must_use]
+    pub fn lenient() -> Self {
+        Self {
+            require_alignment: false,
+            allow_missing_type_tags: true,
+            preserve_unknown_tags: true,
+            ..Self::default()
+        }
+    }
+}
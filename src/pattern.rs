@@ -0,0 +1,205 @@
+use std::{fmt::Display, str::FromStr};
+
+use crate::{address::OscAddress, errors::Error};
+
+/// An OSC address pattern, such as ``/ch/*/mix`` or ``/ch/{01,02}/mute``, which can be matched
+/// against addresses the same way ``OscServer`` matches incoming messages against its routes.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct OscPattern(String);
+
+impl OscPattern {
+    /// Creates an ``OscPattern``.
+    ///
+    /// # Errors
+    /// Will return ``Error::Address`` if ``pattern`` does not start with ``/``, or contains a space
+    /// or ``#``, which are not allowed even in patterns.
+    pub fn new(pattern: impl Into<String>) -> Result<Self, Error> {
+        let pattern = pattern.into();
+        if !pattern.starts_with('/') {
+            return Err(Error::Address(format!(
+                "{pattern:?} does not start with '/'"
+            )));
+        }
+        if let Some(c) = pattern.chars().find(|c| [' ', '#'].contains(c)) {
+            return Err(Error::Address(format!(
+                "{pattern:?} contains forbidden character {c:?}"
+            )));
+        }
+        Ok(Self(pattern))
+    }
+
+    /// Returns whether ``address`` matches this pattern.
+    #[must_use]
+    pub fn matches(&self, address: &OscAddress) -> bool {
+        matches(&self.0, address.as_str())
+    }
+
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Display for OscPattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl FromStr for OscPattern {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(s)
+    }
+}
+
+impl TryFrom<&str> for OscPattern {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Self::new(value)
+    }
+}
+
+impl From<OscAddress> for OscPattern {
+    fn from(value: OscAddress) -> Self {
+        Self(value.into())
+    }
+}
+
+/// The most wildcards (``?``, ``*``, and ``[...]``) an incoming address pattern may contain
+/// before it is rejected by ``is_acceptable``.
+pub(crate) const MAX_WILDCARDS: usize = 32;
+
+/// The most ``{...}`` alternatives, counted over every group, an incoming address pattern may
+/// contain before it is rejected by ``is_acceptable``.
+pub(crate) const MAX_ALTERNATIVES: usize = 64;
+
+/// Returns whether ``pattern``, sent by a peer rather than registered by the application, is
+/// simple enough to be matched against routes. Even though matching takes polynomial time, a
+/// pattern made of thousands of wildcards would still stall dispatch.
+pub(crate) fn is_acceptable(pattern: &str) -> bool {
+    let wildcards = pattern
+        .chars()
+        .filter(|c| matches!(c, '?' | '*' | '['))
+        .count();
+    let alternatives = pattern.chars().filter(|c| matches!(c, '{' | ',')).count();
+    wildcards <= MAX_WILDCARDS && alternatives <= MAX_ALTERNATIVES
+}
+
+/// Returns whether the OSC address pattern ``pattern`` matches ``address``.
+///
+/// Each ``/``-separated part of the pattern is matched against the corresponding part of the
+/// address, where ``?`` matches any single character, ``*`` matches any sequence of characters,
+/// ``[abc]``/``[a-z]`` match any listed character (or any other, when the list starts with
+/// ``!``), and ``{foo,bar}`` matches any of the comma-separated strings. Other characters match
+/// themselves.
+///
+/// Matching takes time proportional to the length of the pattern times the length of the
+/// address, however the pattern's wildcards are arranged.
+pub(crate) fn matches(pattern: &str, address: &str) -> bool {
+    let mut pattern_parts = pattern.split('/');
+    let mut address_parts = address.split('/');
+    loop {
+        match (pattern_parts.next(), address_parts.next()) {
+            (Some(pattern_part), Some(address_part)) => {
+                let pattern_part: Vec<char> = pattern_part.chars().collect();
+                let address_part: Vec<char> = address_part.chars().collect();
+                if !part_matches(&pattern_part, &address_part) {
+                    return false;
+                }
+            }
+            (None, None) => return true,
+            _ => return false,
+        }
+    }
+}
+
+/// One element of a part of a pattern.
+enum Token<'a> {
+    Char(char),
+    /// ``?``
+    Any,
+    /// ``*``
+    Star,
+    /// The contents of ``[...]``.
+    Class(&'a [char]),
+    /// The comma-separated strings of ``{...}``.
+    Alternatives(Vec<&'a [char]>),
+}
+
+/// Splits a part of a pattern into tokens, or returns ``None`` if a ``[`` or ``{`` is unclosed,
+/// in which case the part matches nothing.
+fn tokenize(mut pattern: &[char]) -> Option<Vec<Token<'_>>> {
+    let mut tokens = Vec::with_capacity(pattern.len());
+    while let Some((&c, rest)) = pattern.split_first() {
+        let (token, rest) = match c {
+            '?' => (Token::Any, rest),
+            '*' => (Token::Star, rest),
+            '[' => {
+                let end = rest.iter().position(|&c| c == ']')?;
+                (Token::Class(&rest[..end]), &rest[end + 1..])
+            }
+            '{' => {
+                let end = rest.iter().position(|&c| c == '}')?;
+                let alternatives = rest[..end].split(|&c| c == ',').collect();
+                (Token::Alternatives(alternatives), &rest[end + 1..])
+            }
+            c => (Token::Char(c), rest),
+        };
+        tokens.push(token);
+        pattern = rest;
+    }
+    Some(tokens)
+}
+
+/// Matches a part of a pattern against a part of an address. Working back from the end of the
+/// pattern, ``next[i]`` holds whether the tokens after the current one match ``part[i..]``, so
+/// each token is only tried once at each position instead of backtracking.
+fn part_matches(pattern: &[char], part: &[char]) -> bool {
+    let Some(tokens) = tokenize(pattern) else {
+        return false;
+    };
+    let mut next: Vec<bool> = (0..=part.len()).map(|i| i == part.len()).collect();
+    let mut current = vec![false; part.len() + 1];
+    for token in tokens.iter().rev() {
+        for i in (0..=part.len()).rev() {
+            let c = part.get(i).copied();
+            current[i] = match token {
+                Token::Char(expected) => c == Some(*expected) && next[i + 1],
+                Token::Any => c.is_some() && next[i + 1],
+                Token::Class(class) => c.is_some_and(|c| class_matches(class, c)) && next[i + 1],
+                // Either match nothing, or one more character and then as the same star
+                Token::Star => next[i] || (c.is_some() && current[i + 1]),
+                Token::Alternatives(alternatives) => alternatives.iter().any(|alternative| {
+                    part[i..].starts_with(alternative) && next[i + alternative.len()]
+                }),
+            };
+        }
+        std::mem::swap(&mut next, &mut current);
+    }
+    next[0]
+}
+
+/// Matches ``c`` against the contents of a ``[...]`` character class.
+fn class_matches(class: &[char], c: char) -> bool {
+    let (negated, class) = match class.split_first() {
+        Some(('!', rest)) => (true, rest),
+        _ => (false, class),
+    };
+
+    let mut found = false;
+    let mut i = 0;
+    while i < class.len() {
+        // A '-' between two characters is a range, anywhere else it is literal
+        if i + 2 < class.len() && class[i + 1] == '-' {
+            found |= (class[i]..=class[i + 2]).contains(&c);
+            i += 3;
+        } else {
+            found |= class[i] == c;
+            i += 1;
+        }
+    }
+    found != negated
+}
@@ -0,0 +1,262 @@
+use std::{
+    collections::VecDeque,
+    io::ErrorKind,
+    net::{SocketAddr, ToSocketAddrs, UdpSocket},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    address::OscAddress,
+    bundle::flatten_into,
+    errors::Error,
+    handler::{IntoReplies, TypedHandler},
+    lock,
+    pattern::OscPattern,
+    server::{into_handler, is_timeout, Dispatcher, ServerHandle},
+    OscMessage, OscPacket,
+};
+
+/// One end of a symmetric OSC relationship over a single UDP socket, where both sides send
+/// messages and handle each other's, e.g. a control surface and the mixer it drives. Routes are
+/// registered as for ``OscServer``, and their replies sent back to the peer, while messages can be
+/// sent and waited for as with ``OscClient``.
+///
+/// Messages are handled whenever the peer receives, whether from ``step``, ``start``, or while
+/// waiting in ``wait_for``: those matching a route are dispatched to it, and any matching no route
+/// are queued for ``wait_for`` and ``drain_pending``. Bundles are unpacked and their messages
+/// handled as they arrive, whatever their timetag.
+pub struct OscPeer {
+    socket: UdpSocket,
+    dispatcher: Dispatcher,
+    buffer: Vec<u8>,
+    message_queue: VecDeque<OscMessage>,
+    timeout: Duration,
+}
+
+impl OscPeer {
+    /// Creates a new ``OscPeer``, bound to ``local_address`` and connected to
+    /// ``remote_address``. ``buffer_size`` dictates the maximum size packet that the peer can
+    /// receive, and ``timeout`` how long ``wait_for`` waits (1 second if ``None``).
+    ///
+    /// # Errors
+    /// If the socket cannot be bound or connected, this function will return an
+    /// ``Error::Socket``.
+    pub fn new<A: ToSocketAddrs, B: ToSocketAddrs>(
+        local_address: A,
+        remote_address: B,
+        buffer_size: usize,
+        timeout: Option<Duration>,
+    ) -> Result<Self, Error> {
+        let socket = UdpSocket::bind(local_address).map_err(Error::Socket)?;
+        socket.connect(remote_address).map_err(Error::Socket)?;
+        Ok(Self::from_socket(socket, buffer_size, timeout))
+    }
+
+    /// Creates a new ``OscPeer`` on an already bound ``socket``, which should be connected to the
+    /// remote peer.
+    #[must_use]
+    pub fn from_socket(socket: UdpSocket, buffer_size: usize, timeout: Option<Duration>) -> Self {
+        Self {
+            socket,
+            dispatcher: Dispatcher::new(),
+            buffer: vec![0; buffer_size],
+            message_queue: VecDeque::new(),
+            timeout: timeout.unwrap_or(Duration::from_secs(1)),
+        }
+    }
+
+    /// Registers ``handler`` to be called for messages sent to any address matching ``pattern``.
+    /// See ``OscServer::add_route``.
+    ///
+    /// # Errors
+    /// Will return ``Error::Address`` if ``pattern`` is not a valid ``OscPattern``, or
+    /// ``Error::DuplicateRoute`` if a route is already registered for it.
+    pub fn add_route<P, F, R>(self, pattern: P, handler: F) -> Result<Self, Error>
+    where
+        P: TryInto<OscPattern>,
+        Error: From<P::Error>,
+        F: FnMut(&OscAddress, &OscMessage) -> R + Send + 'static,
+        R: IntoReplies,
+    {
+        lock(&self.dispatcher.router).insert_route(pattern, into_handler(handler))?;
+        Ok(self)
+    }
+
+    /// See ``OscServer::add_typed_route``.
+    ///
+    /// # Errors
+    /// Will return ``Error::Address`` if ``pattern`` is not a valid ``OscPattern``, or
+    /// ``Error::DuplicateRoute`` if a route is already registered for it.
+    pub fn add_typed_route<P, Args>(
+        self,
+        pattern: P,
+        mut handler: impl TypedHandler<Args>,
+    ) -> Result<Self, Error>
+    where
+        P: TryInto<OscPattern>,
+        Error: From<P::Error>,
+    {
+        self.add_route(pattern, move |address: &OscAddress, msg: &OscMessage| {
+            handler.call(address, msg.args[..].to_vec())
+        })
+    }
+
+    /// Returns a ``ServerHandle`` which can add and remove routes while the peer is running.
+    #[must_use]
+    pub fn handle(&self) -> ServerHandle {
+        self.dispatcher.handle()
+    }
+
+    /// Sends ``message`` to the remote peer, returning the number of bytes sent.
+    ///
+    /// # Errors
+    /// Will return any error from building the message, or an ``Error::Socket`` if sending fails.
+    pub fn send(&mut self, message: &OscMessage) -> Result<usize, Error> {
+        let bytes = message.build()?;
+        self.socket.send(&bytes).map_err(Error::Socket)
+    }
+
+    /// Waits up to the peer's timeout for a message at ``addr``, handling any other messages
+    /// which arrive meanwhile. A queued message at ``addr`` is returned straight away.
+    ///
+    /// A message at ``addr`` is returned even if it also matches a route, after being dispatched.
+    ///
+    /// # Errors
+    /// Will return ``Err(Error::Socket(io::Error.kind() == ErrorKind::TimedOut))`` if no
+    /// matching message arrives in time, or any other ``Error::Socket`` if receiving or
+    /// replying fails.
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn wait_for(&mut self, addr: impl ToString) -> Result<OscMessage, Error> {
+        let deadline = Instant::now() + self.timeout;
+        self.wait_for_until(addr, deadline)
+    }
+
+    /// Like ``wait_for``, but giving up once ``deadline`` has passed.
+    ///
+    /// # Errors
+    /// See ``wait_for``.
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn wait_for_until(
+        &mut self,
+        addr: impl ToString,
+        deadline: Instant,
+    ) -> Result<OscMessage, Error> {
+        let addr = addr.to_string();
+        let start = Instant::now();
+        loop {
+            if let Some(msg) = self
+                .message_queue
+                .iter()
+                .position(|m| m.address == addr)
+                .and_then(|i| self.message_queue.remove(i))
+            {
+                return Ok(msg);
+            }
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Err(Error::Socket(std::io::Error::new(
+                    ErrorKind::TimedOut,
+                    format!(
+                        "Waiting for data timed out after {} seconds",
+                        start.elapsed().as_secs_f32()
+                    ),
+                )));
+            }
+            self.receive(remaining, Some(&addr))?;
+        }
+    }
+
+    /// Waits up to ``timeout`` to receive a packet and handles it, so the peer can be driven from
+    /// another event loop. A ``timeout`` of zero only handles a packet which has already arrived.
+    /// Returns whether a packet was received.
+    ///
+    /// # Errors
+    /// Will return an ``Error::Socket`` if receiving a packet or sending a reply fails.
+    pub fn step(&mut self, timeout: Duration) -> Result<bool, Error> {
+        self.receive(timeout, None)
+    }
+
+    /// Receives and handles packets forever.
+    ///
+    /// # Errors
+    /// Will return an ``Error::Socket`` if receiving a packet or sending a reply fails.
+    pub fn start(&mut self) -> Result<(), Error> {
+        loop {
+            self.receive(Duration::from_secs(1), None)?;
+        }
+    }
+
+    /// Returns the messages which matched no route, oldest first.
+    pub fn pending(&self) -> impl Iterator<Item = &OscMessage> {
+        self.message_queue.iter()
+    }
+
+    /// Removes and returns every queued message, oldest first.
+    pub fn drain_pending(&mut self) -> impl Iterator<Item = OscMessage> + '_ {
+        self.message_queue.drain(..)
+    }
+
+    /// Returns the address the peer is bound to.
+    ///
+    /// # Errors
+    /// Will return an ``Error::Socket`` if the address cannot be read.
+    pub fn local_addr(&self) -> Result<SocketAddr, Error> {
+        self.socket.local_addr().map_err(Error::Socket)
+    }
+
+    /// Returns the address of the remote peer.
+    ///
+    /// # Errors
+    /// Will return an ``Error::Socket`` if the socket is not connected.
+    pub fn peer_addr(&self) -> Result<SocketAddr, Error> {
+        self.socket.peer_addr().map_err(Error::Socket)
+    }
+
+    /// Waits up to ``timeout`` for a packet and handles each message in it, queueing those which
+    /// match no route or are at ``wanted``. Returns whether a packet was received.
+    fn receive(&mut self, timeout: Duration, wanted: Option<&str>) -> Result<bool, Error> {
+        if timeout.is_zero() {
+            self.socket.set_nonblocking(true).map_err(Error::Socket)?;
+        } else {
+            self.socket.set_nonblocking(false).map_err(Error::Socket)?;
+            self.socket
+                .set_read_timeout(Some(timeout))
+                .map_err(Error::Socket)?;
+        }
+        let (length, source) = match self.socket.recv_from(&mut self.buffer) {
+            Ok(received) => received,
+            Err(e) if is_timeout(&e) => return Ok(false),
+            Err(e) => return Err(Error::Socket(e)),
+        };
+        let Some(packet) = self.dispatcher.parse(&self.buffer[..length], source) else {
+            return Ok(true);
+        };
+        let mut messages = Vec::new();
+        flatten_into(packet, &mut messages);
+        for msg in messages {
+            let routed = lock(&self.dispatcher.router)
+                .matching(&msg.address)
+                .next()
+                .is_some();
+            if routed {
+                self.dispatch(&msg, source)?;
+            }
+            if !routed || wanted == Some(msg.address.as_str()) {
+                self.message_queue.push_back(msg);
+            }
+        }
+        Ok(true)
+    }
+
+    fn dispatch(&self, msg: &OscMessage, source: SocketAddr) -> Result<(), Error> {
+        for reply in self
+            .dispatcher
+            .replies(&OscPacket::Message(msg.clone()), source)
+        {
+            let bytes = self.dispatcher.build(&reply)?;
+            let sent = self.socket.send_to(&bytes, source).map_err(Error::Socket)?;
+            self.dispatcher.sent(source, reply, &bytes, sent);
+        }
+        Ok(())
+    }
+}
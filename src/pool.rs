@@ -0,0 +1,80 @@
+use std::{
+    ops::{Deref, DerefMut},
+    sync::{Arc, Mutex},
+};
+
+use crate::lock;
+
+/// A pool of byte buffers which packets are encoded into, so sending at a high rate doesn't
+/// allocate and free a ``Vec`` per packet. Clones share the same buffers, so one pool can serve
+/// several clients, e.g. a client and its ``try_clone``s.
+#[derive(Debug, Clone)]
+pub struct BufferPool {
+    buffers: Arc<Mutex<Vec<Vec<u8>>>>,
+    max_idle: usize,
+}
+
+impl Default for BufferPool {
+    fn default() -> Self {
+        Self::new(64)
+    }
+}
+
+impl BufferPool {
+    /// Creates an empty ``BufferPool``, which keeps up to ``max_idle`` returned buffers for
+    /// reuse, freeing any beyond that.
+    #[must_use]
+    pub fn new(max_idle: usize) -> Self {
+        Self {
+            buffers: Arc::default(),
+            max_idle,
+        }
+    }
+
+    /// Takes an empty buffer from the pool, or allocates one if there is none idle. The buffer
+    /// returns to the pool when dropped.
+    #[must_use]
+    pub fn take(&self) -> PooledBuffer<'_> {
+        PooledBuffer {
+            buffer: lock(&self.buffers).pop().unwrap_or_default(),
+            pool: self,
+        }
+    }
+
+    /// Returns how many buffers are idle in the pool.
+    #[must_use]
+    pub fn idle(&self) -> usize {
+        lock(&self.buffers).len()
+    }
+}
+
+/// A buffer taken from a ``BufferPool``, which returns to it, emptied, when dropped.
+pub struct PooledBuffer<'a> {
+    buffer: Vec<u8>,
+    pool: &'a BufferPool,
+}
+
+impl Deref for PooledBuffer<'_> {
+    type Target = Vec<u8>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.buffer
+    }
+}
+
+impl DerefMut for PooledBuffer<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.buffer
+    }
+}
+
+impl Drop for PooledBuffer<'_> {
+    fn drop(&mut self) {
+        let mut buffers = lock(&self.pool.buffers);
+        if buffers.len() < self.pool.max_idle {
+            let mut buffer = std::mem::take(&mut self.buffer);
+            buffer.clear();
+            buffers.push(buffer);
+        }
+    }
+}
@@ -0,0 +1,228 @@
+use std::{
+    cell::Cell,
+    future::{poll_fn, Future},
+    io::{self, ErrorKind},
+    net::{SocketAddr, ToSocketAddrs},
+    pin::pin,
+    sync::Arc,
+    task::Poll,
+    time::Duration,
+};
+
+use quinn::{
+    crypto::rustls::QuicClientConfig, ClientConfig, Connection as QuinnConnection, Endpoint,
+};
+use tokio::runtime::{self, Runtime};
+
+use crate::{
+    sockets::{copy_frame, Connection},
+    tls::TlsConnection,
+};
+
+/// An experimental QUIC ``Connection``, for encrypted show control over the internet which
+/// survives the client's address changing. Each packet is sent as a datagram if it fits, or on
+/// its own stream otherwise.
+///
+/// QUIC is asynchronous, so each connection runs a small tokio runtime to drive it.
+/// ``Connection::new`` verifies the server's certificate against the web PKI roots, for the
+/// remote IP address. To trust other certificates, or verify a host name, use ``connect`` with a
+/// ``rustls::ClientConfig`` (see ``TlsConnection::config``), and give the connection to
+/// ``OscClient::from_connection``.
+pub struct QuicConnection {
+    runtime: Arc<Runtime>,
+    endpoint: Endpoint,
+    connection: QuinnConnection,
+    config: ClientConfig,
+    server_name: String,
+    read_timeout: Cell<Option<Duration>>,
+    nonblocking: Cell<bool>,
+}
+
+impl QuicConnection {
+    /// Connects from ``local_address`` to ``remote_address``, verifying that its certificate is
+    /// valid for ``server_name`` (a host name or IP address) according to ``config``.
+    ///
+    /// # Errors
+    /// Will return ``Err`` if ``config`` doesn't support QUIC (which requires TLS 1.3), or
+    /// binding, connecting, or the handshake fails.
+    pub fn connect<A: ToSocketAddrs, B: ToSocketAddrs>(
+        local_address: A,
+        remote_address: B,
+        server_name: &str,
+        config: Arc<rustls::ClientConfig>,
+    ) -> io::Result<Self> {
+        let runtime = runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .enable_all()
+            .build()?;
+        let local_address = first_address(local_address)?;
+        let endpoint = {
+            let _runtime = runtime.enter();
+            Endpoint::client(local_address)?
+        };
+        let config = ClientConfig::new(Arc::new(
+            QuicClientConfig::try_from(config).map_err(io::Error::other)?,
+        ));
+        let connection = handshake(&runtime, &endpoint, &config, remote_address, server_name)?;
+        Ok(Self {
+            runtime: Arc::new(runtime),
+            endpoint,
+            connection,
+            config,
+            server_name: server_name.to_string(),
+            read_timeout: Cell::new(None),
+            nonblocking: Cell::new(false),
+        })
+    }
+
+    /// Waits for the next datagram or stream from the server, returning its contents.
+    async fn receive(&self, size_limit: usize) -> io::Result<Vec<u8>> {
+        let mut datagram = pin!(self.connection.read_datagram());
+        let mut stream = pin!(self.connection.accept_uni());
+        let received = poll_fn(|cx| {
+            if let Poll::Ready(res) = datagram.as_mut().poll(cx) {
+                return Poll::Ready(Received::Datagram(res));
+            }
+            stream.as_mut().poll(cx).map(Received::Stream)
+        })
+        .await;
+        match received {
+            Received::Datagram(datagram) => Ok(datagram?.to_vec()),
+            Received::Stream(stream) => stream?
+                .read_to_end(size_limit)
+                .await
+                .map_err(io::Error::other),
+        }
+    }
+}
+
+enum Received<D, S> {
+    Datagram(D),
+    Stream(S),
+}
+
+impl Connection for QuicConnection {
+    fn new<A: ToSocketAddrs, B: ToSocketAddrs>(
+        local_address: A,
+        remote_address: B,
+    ) -> io::Result<Self> {
+        let remote_address = first_address(remote_address)?;
+        let config = TlsConnection::config(TlsConnection::webpki_roots())?;
+        Self::connect(
+            local_address,
+            remote_address,
+            &remote_address.ip().to_string(),
+            config,
+        )
+    }
+
+    fn send(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self
+            .connection
+            .max_datagram_size()
+            .is_some_and(|max| buf.len() <= max)
+        {
+            self.connection
+                .send_datagram(buf.to_vec().into())
+                .map_err(io::Error::other)?;
+            return Ok(buf.len());
+        }
+        self.runtime.block_on(async {
+            let mut stream = self.connection.open_uni().await?;
+            stream.write_all(buf).await?;
+            stream.finish().map_err(io::Error::other)
+        })?;
+        Ok(buf.len())
+    }
+
+    fn recv(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let timeout = if self.nonblocking.get() {
+            Some(Duration::ZERO)
+        } else {
+            self.read_timeout.get()
+        };
+        let packet = self.runtime.block_on(async {
+            let receive = self.receive(buf.len());
+            match timeout {
+                Some(timeout) => tokio::time::timeout(timeout, receive)
+                    .await
+                    .map_err(|_| io::Error::from(ErrorKind::WouldBlock))?,
+                None => receive.await,
+            }
+        })?;
+        copy_frame(&packet, buf)
+    }
+
+    fn set_read_timeout(&self, dur: Option<Duration>) -> io::Result<()> {
+        if dur == Some(Duration::ZERO) {
+            return Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                "Read timeout must not be zero",
+            ));
+        }
+        self.read_timeout.set(dur);
+        Ok(())
+    }
+
+    fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        self.nonblocking.set(nonblocking);
+        Ok(())
+    }
+
+    /// The clone shares the QUIC connection, so packets are received by whichever clone is
+    /// receiving first.
+    fn try_clone(&self) -> io::Result<Self> {
+        Ok(Self {
+            runtime: self.runtime.clone(),
+            endpoint: self.endpoint.clone(),
+            connection: self.connection.clone(),
+            config: self.config.clone(),
+            server_name: self.server_name.clone(),
+            read_timeout: self.read_timeout.clone(),
+            nonblocking: self.nonblocking.clone(),
+        })
+    }
+
+    /// Opens a new QUIC connection to ``remote_address`` from the same endpoint, with the same
+    /// configuration and server name.
+    fn connect<A: ToSocketAddrs>(&mut self, remote_address: A) -> io::Result<()> {
+        self.connection = handshake(
+            &self.runtime,
+            &self.endpoint,
+            &self.config,
+            remote_address,
+            &self.server_name,
+        )?;
+        Ok(())
+    }
+
+    fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.endpoint.local_addr()
+    }
+
+    /// Returns the server's current address, which may change as the connection migrates.
+    fn peer_addr(&self) -> io::Result<SocketAddr> {
+        Ok(self.connection.remote_address())
+    }
+}
+
+fn handshake<A: ToSocketAddrs>(
+    runtime: &Runtime,
+    endpoint: &Endpoint,
+    config: &ClientConfig,
+    remote_address: A,
+    server_name: &str,
+) -> io::Result<QuinnConnection> {
+    let _runtime = runtime.enter();
+    let connecting = endpoint
+        .connect_with(config.clone(), first_address(remote_address)?, server_name)
+        .map_err(io::Error::other)?;
+    runtime.block_on(connecting).map_err(io::Error::from)
+}
+
+fn first_address<A: ToSocketAddrs>(address: A) -> io::Result<SocketAddr> {
+    address
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| io::Error::new(ErrorKind::InvalidInput, "No address"))
+}
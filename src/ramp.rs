@@ -0,0 +1,71 @@
+use std::time::Duration;
+
+use crate::{Arg, OscMessage};
+
+/// A fade of a single ``Arg::Float`` parameter from one value to another, as a sequence of
+/// messages evenly spaced in time, e.g. a fader moving from ``0.0`` to ``0.75`` over 2 seconds at
+/// 30 updates a second. Send it with ``OscClient::send_ramp``, or iterate over ``steps`` to drive
+/// the timing some other way.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ramp {
+    address: String,
+    from: f32,
+    to: f32,
+    duration: Duration,
+    rate: f32,
+}
+
+impl Ramp {
+    /// Creates a ramp of ``address`` from ``from`` to ``to`` over ``duration``, sending ``rate``
+    /// messages a second. The first message is sent straight away with ``from``, and the last
+    /// after ``duration`` with exactly ``to``.
+    #[must_use]
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn new(address: impl ToString, from: f32, to: f32, duration: Duration, rate: f32) -> Self {
+        Self {
+            address: address.to_string(),
+            from,
+            to,
+            duration,
+            rate,
+        }
+    }
+
+    /// Returns the number of messages in the ramp, which is at least 2 (the start and end), or 1
+    /// if ``duration`` is zero or ``rate`` isn't positive.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        let intervals = (self.duration.as_secs_f32() * self.rate).ceil();
+        if intervals.is_nan() || intervals < 1.0 {
+            return 1;
+        }
+        // Saturates at usize::MAX for absurd rates
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let intervals = intervals as usize;
+        intervals.saturating_add(1)
+    }
+
+    /// Always ``false``, as a ramp has at least one message.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// Returns each message in the ramp with how long after the start it should be sent.
+    pub fn steps(&self) -> impl Iterator<Item = (Duration, OscMessage)> + '_ {
+        let intervals = self.len() - 1;
+        (0..=intervals).map(move |i| {
+            if intervals == 0 || i == intervals {
+                return (self.duration, self.message(self.to));
+            }
+            #[allow(clippy::cast_precision_loss)]
+            let progress = i as f32 / intervals as f32;
+            let value = self.from + (self.to - self.from) * progress;
+            (self.duration.mul_f32(progress), self.message(value))
+        })
+    }
+
+    fn message(&self, value: f32) -> OscMessage {
+        OscMessage::new(&self.address, vec![Arg::Float(value)])
+    }
+}
@@ -0,0 +1,117 @@
+use std::{
+    io::ErrorKind,
+    net::{SocketAddr, ToSocketAddrs, UdpSocket},
+    time::Duration,
+};
+
+use crate::{errors::Error, message_ref::OscMessageRef, OscMessage};
+
+/// A listen-only OSC client over UDP, for applications which only receive, e.g. meters from a
+/// mixer. Unlike ``OscClient``, it needs no remote address, and accepts messages from any device.
+/// To route messages to handlers instead, use an ``OscServer``.
+#[allow(clippy::module_name_repetitions)]
+pub struct OscReceiver {
+    socket: UdpSocket,
+    buffer: Vec<u8>,
+}
+
+impl OscReceiver {
+    /// Creates a new ``OscReceiver`` listening at ``local_address``. ``buffer_size`` dictates the
+    /// maximum size message that can be received, and ``read_timeout`` how long ``recv`` blocks
+    /// (forever if ``None``).
+    ///
+    /// # Errors
+    /// If the socket cannot be bound, or the read timeout cannot be set, this function will
+    /// return an ``Error::Socket``.
+    pub fn bind<A: ToSocketAddrs>(
+        local_address: A,
+        buffer_size: usize,
+        read_timeout: Option<Duration>,
+    ) -> Result<Self, Error> {
+        let socket = UdpSocket::bind(local_address).map_err(Error::Socket)?;
+        socket
+            .set_read_timeout(read_timeout)
+            .map_err(Error::Socket)?;
+        Ok(Self::from_socket(socket, buffer_size))
+    }
+
+    /// Creates an ``OscReceiver`` listening on an already bound ``socket``, e.g. one joined to a
+    /// multicast group. ``buffer_size`` is as for ``bind``.
+    #[must_use]
+    pub fn from_socket(socket: UdpSocket, buffer_size: usize) -> Self {
+        Self {
+            socket,
+            buffer: vec![0; buffer_size],
+        }
+    }
+
+    /// Waits to receive a message, and parses it into an ``OscMessage``.
+    ///
+    /// # Errors
+    /// Will return an ``Error::Socket`` if receiving fails, including an error of kind
+    /// ``io::ErrorKind::WouldBlock`` or ``io::ErrorKind::TimedOut`` if the read timeout passes.
+    /// Will also error if ``OscMessage::parse_bytes`` fails.
+    pub fn recv(&mut self) -> Result<OscMessage, Error> {
+        self.recv_from().map(|(msg, _)| msg)
+    }
+
+    /// Receives a message as ``recv`` does, along with the address of the device which sent it.
+    ///
+    /// # Errors
+    /// See ``recv``.
+    pub fn recv_from(&mut self) -> Result<(OscMessage, SocketAddr), Error> {
+        let (size, source) = self
+            .socket
+            .recv_from(&mut self.buffer)
+            .map_err(Error::Socket)?;
+        Ok((OscMessage::parse_bytes(&self.buffer[..size])?, source))
+    }
+
+    /// Receives a message as ``recv`` does, but parses it without copying into an
+    /// ``OscMessageRef`` borrowing the receiver's buffer.
+    ///
+    /// # Errors
+    /// See ``recv``.
+    pub fn recv_ref(&mut self) -> Result<(OscMessageRef<'_>, SocketAddr), Error> {
+        let (size, source) = self
+            .socket
+            .recv_from(&mut self.buffer)
+            .map_err(Error::Socket)?;
+        Ok((OscMessageRef::parse_bytes(&self.buffer[..size])?, source))
+    }
+
+    /// Returns a message which has already arrived, without waiting, or ``None`` if there is
+    /// none.
+    ///
+    /// # Errors
+    /// See ``recv``.
+    pub fn try_recv(&mut self) -> Result<Option<OscMessage>, Error> {
+        self.socket.set_nonblocking(true).map_err(Error::Socket)?;
+        let res = match self.recv() {
+            Ok(msg) => Ok(Some(msg)),
+            Err(Error::Socket(e)) if e.kind() == ErrorKind::WouldBlock => Ok(None),
+            Err(e) => Err(e),
+        };
+        self.socket.set_nonblocking(false).map_err(Error::Socket)?;
+        res
+    }
+
+    /// Sets how long ``recv`` blocks, or blocks forever if ``None``.
+    ///
+    /// # Errors
+    /// Will return an ``Error::Socket`` if the timeout can't be set, e.g. because it is zero.
+    pub fn set_read_timeout(&self, read_timeout: Option<Duration>) -> Result<(), Error> {
+        self.socket
+            .set_read_timeout(read_timeout)
+            .map_err(Error::Socket)
+    }
+
+    /// Returns the local address the receiver listens at, e.g. to learn which port was chosen
+    /// when binding to port 0.
+    ///
+    /// # Errors
+    /// Will return an ``Error::Socket`` if the socket can't report it.
+    pub fn local_addr(&self) -> Result<SocketAddr, Error> {
+        self.socket.local_addr().map_err(Error::Socket)
+    }
+}
@@ -0,0 +1,277 @@
+use std::{
+    fs::File,
+    io::{self, BufReader, BufWriter, ErrorKind, Read, Write},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    path::Path,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::{errors::Error, lock, OscPacket};
+
+/// The start of every recording, so a reader can tell it from some other file.
+const MAGIC: &[u8; 8] = b"#oscrec\0";
+
+/// Called with the exact bytes of a packet and the peer it came from or went to, if known. See
+/// ``OscClient::on_raw_send`` and ``OscServer::on_raw_recv``.
+pub type RawTap = Arc<dyn Fn(&[u8], Option<SocketAddr>) + Send + Sync>;
+
+/// Whether a recorded packet was received or sent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Received,
+    Sent,
+}
+
+/// A packet read back from a recording.
+#[derive(Debug, Clone)]
+pub struct RecordedPacket {
+    /// When the packet was received or sent, to the microsecond.
+    pub time: SystemTime,
+    pub direction: Direction,
+    /// Who the packet came from or went to, if the recorder knew.
+    pub peer: Option<SocketAddr>,
+    pub bytes: Vec<u8>,
+}
+
+impl RecordedPacket {
+    /// Parses the recorded bytes.
+    ///
+    /// # Errors
+    /// See ``OscPacket::parse_bytes``.
+    pub fn packet(&self) -> Result<OscPacket, Error> {
+        OscPacket::parse_bytes(&self.bytes)
+    }
+}
+
+/// Records every packet a client or server sends and receives, with a timestamp, e.g. to capture
+/// a rehearsal's control traffic for later analysis. Give one to ``OscClient::set_recorder`` or
+/// a server's ``with_recorder``; clones write to the same recording, so one can be shared by
+/// several.
+///
+/// Each packet is written as its time in microseconds since the Unix epoch (8 bytes), its
+/// direction (1 byte: 0 received, 1 sent), its peer (1 byte: 0 unknown, 4 or 6 for the IP
+/// version, followed by the address and a 2-byte port), its size (4 bytes), and then the packet,
+/// with numbers big-endian, after an 8-byte ``#oscrec`` header. ``Recording`` reads it back.
+///
+/// Clients and servers can't stop to report a failed write, so the recorder stops writing at the
+/// first failure and returns it from ``flush``.
+#[derive(Clone)]
+pub struct Recorder {
+    inner: Arc<Mutex<Inner>>,
+}
+
+struct Inner {
+    writer: Box<dyn Write + Send>,
+    failed: Option<io::Error>,
+}
+
+impl Recorder {
+    /// Records to ``writer``, writing the header straight away.
+    ///
+    /// # Errors
+    /// Will return an ``Error::Socket`` if the header can't be written.
+    pub fn new(mut writer: impl Write + Send + 'static) -> Result<Self, Error> {
+        writer.write_all(MAGIC).map_err(Error::Socket)?;
+        Ok(Self {
+            inner: Arc::new(Mutex::new(Inner {
+                writer: Box::new(writer),
+                failed: None,
+            })),
+        })
+    }
+
+    /// Records to a new file at ``path``, replacing any file already there.
+    ///
+    /// # Errors
+    /// Will return an ``Error::Socket`` if the file can't be created or written to.
+    pub fn create(path: impl AsRef<Path>) -> Result<Self, Error> {
+        Self::new(BufWriter::new(File::create(path).map_err(Error::Socket)?))
+    }
+
+    /// Records ``bytes`` as a packet received from, or sent to, ``peer``.
+    pub fn record(&self, direction: Direction, peer: Option<SocketAddr>, bytes: &[u8]) {
+        let mut inner = lock(&self.inner);
+        if inner.failed.is_some() {
+            return;
+        }
+        let res = write_packet(&mut inner.writer, direction, peer, bytes);
+        if let Err(e) = res {
+            inner.failed = Some(e);
+        }
+    }
+
+    /// Writes out anything buffered, e.g. before the recording is read.
+    ///
+    /// # Errors
+    /// Will return an ``Error::Socket`` if recording or flushing has failed.
+    pub fn flush(&self) -> Result<(), Error> {
+        let mut inner = lock(&self.inner);
+        if let Some(e) = &inner.failed {
+            return Err(Error::Socket(io::Error::new(e.kind(), e.to_string())));
+        }
+        inner.writer.flush().map_err(Error::Socket)
+    }
+}
+
+/// What a client or server tells about each packet it sends or receives: a ``Recorder`` and raw
+/// packet taps.
+#[cfg_attr(not(any(feature = "client", feature = "server")), allow(dead_code))]
+#[derive(Clone, Default)]
+pub(crate) struct Observers {
+    pub(crate) recorder: Option<Recorder>,
+    pub(crate) raw_send: Option<RawTap>,
+    pub(crate) raw_recv: Option<RawTap>,
+}
+
+#[cfg_attr(not(any(feature = "client", feature = "server")), allow(dead_code))]
+impl Observers {
+    pub(crate) fn sent(&self, bytes: &[u8], peer: Option<SocketAddr>) {
+        if let Some(recorder) = &self.recorder {
+            recorder.record(Direction::Sent, peer, bytes);
+        }
+        if let Some(tap) = &self.raw_send {
+            tap(bytes, peer);
+        }
+    }
+
+    pub(crate) fn received(&self, bytes: &[u8], peer: Option<SocketAddr>) {
+        if let Some(recorder) = &self.recorder {
+            recorder.record(Direction::Received, peer, bytes);
+        }
+        if let Some(tap) = &self.raw_recv {
+            tap(bytes, peer);
+        }
+    }
+}
+
+fn write_packet(
+    writer: &mut impl Write,
+    direction: Direction,
+    peer: Option<SocketAddr>,
+    bytes: &[u8],
+) -> io::Result<()> {
+    let micros = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_micros();
+    let micros = u64::try_from(micros).unwrap_or(u64::MAX);
+    let size = u32::try_from(bytes.len())
+        .map_err(|_| io::Error::new(ErrorKind::InvalidInput, "Packet too large to record"))?;
+    let mut header = Vec::with_capacity(32);
+    header.extend_from_slice(&micros.to_be_bytes());
+    header.push(match direction {
+        Direction::Received => 0,
+        Direction::Sent => 1,
+    });
+    match peer {
+        None => header.push(0),
+        Some(SocketAddr::V4(peer)) => {
+            header.push(4);
+            header.extend_from_slice(&peer.ip().octets());
+            header.extend_from_slice(&peer.port().to_be_bytes());
+        }
+        Some(SocketAddr::V6(peer)) => {
+            header.push(6);
+            header.extend_from_slice(&peer.ip().octets());
+            header.extend_from_slice(&peer.port().to_be_bytes());
+        }
+    }
+    header.extend_from_slice(&size.to_be_bytes());
+    writer.write_all(&header)?;
+    writer.write_all(bytes)
+}
+
+/// Reads back the packets written by a ``Recorder``, in the order they were recorded.
+pub struct Recording<R: Read> {
+    reader: R,
+    failed: bool,
+}
+
+impl<R: Read> Recording<R> {
+    /// Reads a recording from ``reader``, checking its header.
+    ///
+    /// # Errors
+    /// Will return an ``Error::Socket`` if reading fails, or ``Error::Malformed`` if ``reader``
+    /// doesn't hold a recording.
+    pub fn new(mut reader: R) -> Result<Self, Error> {
+        let mut magic = [0; 8];
+        reader.read_exact(&mut magic).map_err(Error::Socket)?;
+        if &magic != MAGIC {
+            return Err(Error::Malformed("OSC recording header".to_string()));
+        }
+        Ok(Self {
+            reader,
+            failed: false,
+        })
+    }
+
+    fn read_packet(&mut self) -> Result<Option<RecordedPacket>, Error> {
+        let mut time = [0; 8];
+        match self.reader.read_exact(&mut time) {
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+            res => res.map_err(Error::Socket)?,
+        }
+        let [direction, family] = self.read()?;
+        let direction = match direction {
+            0 => Direction::Received,
+            1 => Direction::Sent,
+            _ => return Err(Error::Malformed("OSC recording direction".to_string())),
+        };
+        let peer = match family {
+            0 => None,
+            4 => {
+                let ip: [u8; 4] = self.read()?;
+                let port = u16::from_be_bytes(self.read()?);
+                Some(SocketAddr::new(IpAddr::V4(Ipv4Addr::from(ip)), port))
+            }
+            6 => {
+                let ip: [u8; 16] = self.read()?;
+                let port = u16::from_be_bytes(self.read()?);
+                Some(SocketAddr::new(IpAddr::V6(Ipv6Addr::from(ip)), port))
+            }
+            _ => return Err(Error::Malformed("OSC recording peer".to_string())),
+        };
+        let size = u32::from_be_bytes(self.read()?) as usize;
+        let mut bytes = vec![0; size];
+        self.reader.read_exact(&mut bytes).map_err(Error::Socket)?;
+        Ok(Some(RecordedPacket {
+            time: UNIX_EPOCH + Duration::from_micros(u64::from_be_bytes(time)),
+            direction,
+            peer,
+            bytes,
+        }))
+    }
+
+    fn read<const N: usize>(&mut self) -> Result<[u8; N], Error> {
+        let mut bytes = [0; N];
+        self.reader.read_exact(&mut bytes).map_err(Error::Socket)?;
+        Ok(bytes)
+    }
+}
+
+impl Recording<BufReader<File>> {
+    /// Reads the recording in the file at ``path``.
+    ///
+    /// # Errors
+    /// See ``new``.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, Error> {
+        Self::new(BufReader::new(File::open(path).map_err(Error::Socket)?))
+    }
+}
+
+/// Yields each recorded packet, stopping after the first error. A recording cut off part way
+/// through a packet, e.g. because the program recording it was killed, ends with an
+/// ``Error::Socket`` of kind ``UnexpectedEof``.
+impl<R: Read> Iterator for Recording<R> {
+    type Item = Result<RecordedPacket, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let res = self.read_packet();
+        self.failed = res.is_err();
+        res.transpose()
+    }
+}
@@ -0,0 +1,131 @@
+use crate::{address::OscAddress, errors::Error, pattern, pattern::OscPattern};
+
+/// A table of routes, mapping OSC address patterns to handlers of type ``H``.
+///
+/// ``Router`` is not tied to a socket, so it can be used to dispatch messages received by a
+/// client (e.g. unsolicited meter updates) as well as by ``OscServer``.
+pub struct Router<H> {
+    routes: Vec<(OscPattern, H)>,
+}
+
+impl<H> Router<H> {
+    #[must_use]
+    pub fn new() -> Self {
+        Self { routes: Vec::new() }
+    }
+
+    /// Registers ``handler`` for messages sent to any address matching ``pattern``, e.g.
+    /// ``"/ch/*/fader"`` to handle the faders of every channel.
+    ///
+    /// # Errors
+    /// Will return ``Error::Address`` if ``pattern`` is not a valid ``OscPattern``, or
+    /// ``Error::DuplicateRoute`` if a route is already registered for it.
+    pub fn add_route<P>(mut self, pattern: P, handler: H) -> Result<Self, Error>
+    where
+        P: TryInto<OscPattern>,
+        Error: From<P::Error>,
+    {
+        self.insert_route(pattern, handler)?;
+        Ok(self)
+    }
+
+    /// Like ``add_route``, but registers the route in place.
+    ///
+    /// # Errors
+    /// Will return ``Error::Address`` if ``pattern`` is not a valid ``OscPattern``, or
+    /// ``Error::DuplicateRoute`` if a route is already registered for it.
+    pub fn insert_route<P>(&mut self, pattern: P, handler: H) -> Result<(), Error>
+    where
+        P: TryInto<OscPattern>,
+        Error: From<P::Error>,
+    {
+        let pattern = pattern.try_into()?;
+        if self.routes.iter().any(|(route, _)| *route == pattern) {
+            return Err(Error::DuplicateRoute(pattern.to_string()));
+        }
+        self.routes.push((pattern, handler));
+        Ok(())
+    }
+
+    /// Removes the route registered for ``pattern``, returning its handler if there was one.
+    pub fn remove_route(&mut self, pattern: &str) -> Option<H> {
+        let index = self
+            .routes
+            .iter()
+            .position(|(route, _)| route.as_str() == pattern)?;
+        Some(self.routes.remove(index).1)
+    }
+
+    /// Returns the routes matching a message sent to ``address``, along with the concrete address
+    /// each was matched at, in the order they were registered.
+    ///
+    /// ``address`` is usually a concrete address, which is matched against each route's pattern.
+    /// If it is itself an address pattern (e.g. ``/ch/*/mute``), it is matched against the routes
+    /// which are plain addresses instead. As such patterns come from whoever sent the message, one
+    /// with more than 32 wildcards or 64 ``{...}`` alternatives matches no route.
+    pub fn matching<'a>(&'a self, address: &'a str) -> impl Iterator<Item = (OscAddress, &'a H)> {
+        self.matching_routes(address)
+            .map(|(_, matched, handler)| (matched, handler))
+    }
+
+    /// Like ``matching``, but also returns the pattern each route was registered with.
+    pub fn matching_routes<'a>(
+        &'a self,
+        address: &'a str,
+    ) -> impl Iterator<Item = (&'a OscPattern, OscAddress, &'a H)> {
+        let concrete = OscAddress::new(address).ok();
+        let acceptable = concrete.is_some() || pattern::is_acceptable(address);
+        let routes = if acceptable { &self.routes[..] } else { &[] };
+        routes.iter().filter_map(move |(route, handler)| {
+            match_route(route, address, concrete.as_ref()).map(|matched| (route, matched, handler))
+        })
+    }
+
+    /// Like ``matching``, but gives mutable access to the handlers.
+    pub fn matching_mut<'a>(
+        &'a mut self,
+        address: &'a str,
+    ) -> impl Iterator<Item = (OscAddress, &'a mut H)> {
+        let concrete = OscAddress::new(address).ok();
+        let acceptable = concrete.is_some() || pattern::is_acceptable(address);
+        let routes = if acceptable {
+            &mut self.routes[..]
+        } else {
+            &mut []
+        };
+        routes.iter_mut().filter_map(move |(route, handler)| {
+            match_route(route, address, concrete.as_ref()).map(|matched| (matched, handler))
+        })
+    }
+
+    /// Returns every registered route, in the order they were registered.
+    pub fn routes(&self) -> impl Iterator<Item = (&OscPattern, &H)> {
+        self.routes.iter().map(|(route, handler)| (route, handler))
+    }
+
+    /// Consumes the router, returning every route, in the order they were registered.
+    pub fn into_routes(self) -> impl Iterator<Item = (OscPattern, H)> {
+        self.routes.into_iter()
+    }
+}
+
+/// Returns the concrete address at which ``route`` matches a message sent to ``address``, where
+/// ``concrete`` is ``address`` if it is a plain address rather than a pattern.
+fn match_route(
+    route: &OscPattern,
+    address: &str,
+    concrete: Option<&OscAddress>,
+) -> Option<OscAddress> {
+    match concrete {
+        Some(concrete) => route.matches(concrete).then(|| concrete.clone()),
+        None => OscAddress::new(route.as_str())
+            .ok()
+            .filter(|route| pattern::matches(address, route.as_str())),
+    }
+}
+
+impl<H> Default for Router<H> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
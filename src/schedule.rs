@@ -0,0 +1,116 @@
+use std::time::{Duration, SystemTime};
+
+use crate::{
+    bundle::OscBundle,
+    limit::{Limiter, RateLimit},
+    pattern::OscPattern,
+    time::OscTime,
+    OscPacket,
+};
+
+/// Bundles which arrived with a timetag in the future, and messages held back by rate limits,
+/// waiting to be dispatched. ``T`` is whatever the server needs to know to send the replies, such
+/// as the sender's address.
+pub(crate) struct Schedule<T> {
+    pending: Vec<(SystemTime, OscPacket, T)>,
+    limiter: Limiter<T>,
+}
+
+impl<T: Clone> Schedule<T> {
+    pub(crate) fn new() -> Self {
+        Self {
+            pending: Vec::new(),
+            limiter: Limiter::new(),
+        }
+    }
+
+    /// Schedules the bundles in ``packet`` whose timetag is in the future, and holds back the
+    /// messages ``limits`` apply to, returning whatever should be dispatched immediately.
+    pub(crate) fn defer(
+        &mut self,
+        packet: OscPacket,
+        origin: &T,
+        limits: &[(OscPattern, RateLimit)],
+    ) -> Option<OscPacket> {
+        let packet = self.split(packet, origin)?;
+        self.limiter.filter(packet, origin, limits)
+    }
+
+    /// Schedules the bundles in ``packet`` whose timetag is in the future. Bundles timetagged
+    /// ``OscTime::IMMEDIATE``, in the past, or at a time ``SystemTime`` can't represent are
+    /// dispatched immediately.
+    fn split(&mut self, packet: OscPacket, origin: &T) -> Option<OscPacket> {
+        let now = SystemTime::now();
+        match packet {
+            OscPacket::Message(_) => Some(packet),
+            OscPacket::Bundle(bundle) => {
+                if let Some(due) = due(bundle.timetag).filter(|due| *due > now) {
+                    self.pending
+                        .push((due, OscPacket::Bundle(bundle), origin.clone()));
+                    return None;
+                }
+                // Nested bundles may be timetagged later than the bundle containing them
+                let content: Vec<_> = bundle
+                    .content
+                    .into_iter()
+                    .filter_map(|packet| self.split(packet, origin))
+                    .collect();
+                (!content.is_empty())
+                    .then(|| OscPacket::Bundle(OscBundle::new(bundle.timetag, content)))
+            }
+        }
+    }
+
+    /// Returns how long until the next bundle or held message is due, or ``None`` if there are
+    /// none.
+    pub(crate) fn until_next(&self) -> Option<Duration> {
+        let next = self
+            .pending
+            .iter()
+            .map(|(due, _, _)| *due)
+            .chain(self.limiter.next_release())
+            .min()?;
+        Some(
+            next.duration_since(SystemTime::now())
+                .unwrap_or(Duration::ZERO),
+        )
+    }
+
+    /// Removes the bundles and held messages which are due, returning the parts of them to
+    /// dispatch now. Bundles are returned in the order they are due.
+    pub(crate) fn take_due(&mut self, limits: &[(OscPattern, RateLimit)]) -> Vec<(OscPacket, T)> {
+        let now = SystemTime::now();
+        let (mut due, pending) = std::mem::take(&mut self.pending)
+            .into_iter()
+            .partition(|(due, _, _)| *due <= now);
+        self.pending = pending;
+        due.sort_by_key(|(due, _, _)| *due);
+        let mut due: Vec<_> = due
+            .into_iter()
+            .filter_map(|(_, packet, origin)| {
+                self.defer(packet, &origin, limits)
+                    .map(|packet| (packet, origin))
+            })
+            .collect();
+        due.extend(
+            self.limiter
+                .take_released()
+                .into_iter()
+                .map(|(msg, origin)| (OscPacket::Message(msg), origin)),
+        );
+        due
+    }
+}
+
+impl<T: Clone> Default for Schedule<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn due(timetag: OscTime) -> Option<SystemTime> {
+    if timetag == OscTime::IMMEDIATE {
+        return None;
+    }
+    SystemTime::try_from(timetag).ok()
+}
@@ -0,0 +1,91 @@
+use crate::{
+    errors::Error,
+    namespace::Parameter,
+    pattern::{self, OscPattern},
+    write_type_tags, OscMessage,
+};
+
+/// The arguments expected at each of a set of address patterns, e.g. a single ``Arg::Float`` from
+/// 0 to 1 at ``/ch/*/mix/fader``. Messages are checked against the first pattern their address
+/// matches, and messages matching none are let through. Give it to a client with
+/// ``OscClient::set_schema`` to catch malformed messages before they're sent, or to a server with
+/// ``OscServer::with_schema`` to reject them as they arrive.
+///
+/// Signatures are described with a ``Parameter``, as for an ``OscNamespace``, though its access
+/// is ignored. ``T`` and ``F`` are interchangeable in type tags, as either may be sent for a
+/// boolean.
+#[derive(Debug, Clone, Default)]
+pub struct OscSchema {
+    signatures: Vec<(OscPattern, Parameter)>,
+}
+
+impl OscSchema {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Expects messages sent to addresses matching ``pattern`` to have the arguments described by
+    /// ``parameter``, unless an earlier pattern matches them.
+    ///
+    /// # Errors
+    /// Will return ``Error::Address`` if ``pattern`` is not a valid ``OscPattern``.
+    pub fn with_signature<P>(mut self, pattern: P, parameter: Parameter) -> Result<Self, Error>
+    where
+        P: TryInto<OscPattern>,
+        Error: From<P::Error>,
+    {
+        self.signatures.push((pattern.try_into()?, parameter));
+        Ok(self)
+    }
+
+    /// Returns the signature ``address`` is checked against, if any.
+    #[must_use]
+    pub fn signature(&self, address: &str) -> Option<&Parameter> {
+        self.signatures
+            .iter()
+            .find(|(signature, _)| pattern::matches(signature.as_str(), address))
+            .map(|(_, parameter)| parameter)
+    }
+
+    /// Checks ``msg`` against the signature for its address.
+    ///
+    /// # Errors
+    /// Will return ``Error::Schema`` if ``msg``'s argument types differ from the signature, or an
+    /// argument is outside its range.
+    pub fn check(&self, msg: &OscMessage) -> Result<(), Error> {
+        let Some(parameter) = self.signature(&msg.address) else {
+            return Ok(());
+        };
+        let mut tags = String::new();
+        for arg in &msg.args {
+            write_type_tags(arg, &mut tags);
+        }
+        if !same_tags(&tags, &parameter.type_tags) {
+            return Err(Error::Schema {
+                address: msg.address.clone(),
+                reason: format!(
+                    "expected type tags {:?}, found {tags:?}",
+                    parameter.type_tags
+                ),
+            });
+        }
+        for (index, (arg, range)) in msg.args.iter().zip(&parameter.ranges).enumerate() {
+            if let Some(range) = range {
+                if !range.contains(arg) {
+                    return Err(Error::Schema {
+                        address: msg.address.clone(),
+                        reason: format!("argument {index} is outside its range"),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns whether ``a`` and ``b`` are the same type tags, treating ``T`` and ``F`` as the same.
+fn same_tags(a: &str, b: &str) -> bool {
+    let boolean = |tag| if tag == 'F' { 'T' } else { tag };
+    a.len() == b.len() && a.chars().map(boolean).eq(b.chars().map(boolean))
+}
@@ -0,0 +1,100 @@
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{client::OscClient, errors::Error, sockets::Connection, OscMessage};
+
+/// Waits on several ``OscClient``s at once from a single thread, yielding each message with the
+/// id of the client which received it, e.g. for an application controlling a mixer, a lighting
+/// desk, and a media server.
+///
+/// The clients are polled in turn, without blocking, sleeping for the poll interval whenever none
+/// has a message ready. To mix transports, use ``OscClient<BoxedConnection>``.
+pub struct OscSelector<C: Connection> {
+    clients: Vec<(usize, OscClient<C>)>,
+    next_id: usize,
+    next_index: usize,
+    poll_interval: Duration,
+}
+
+impl<C: Connection> Default for OscSelector<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C: Connection> OscSelector<C> {
+    /// Creates a new ``OscSelector`` with no clients, polling every millisecond.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            clients: Vec::new(),
+            next_id: 0,
+            next_index: 0,
+            poll_interval: Duration::from_millis(1),
+        }
+    }
+
+    /// Adds ``client``, returning the id its messages will be yielded with.
+    pub fn add(&mut self, client: OscClient<C>) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.clients.push((id, client));
+        id
+    }
+
+    /// Removes and returns the client with ``id``, if it was added.
+    pub fn remove(&mut self, id: usize) -> Option<OscClient<C>> {
+        let index = self.clients.iter().position(|(i, _)| *i == id)?;
+        Some(self.clients.remove(index).1)
+    }
+
+    /// Returns the client with ``id``, e.g. to send to it.
+    pub fn get_mut(&mut self, id: usize) -> Option<&mut OscClient<C>> {
+        self.clients
+            .iter_mut()
+            .find_map(|(i, client)| (*i == id).then_some(client))
+    }
+
+    /// Sets how long ``select`` sleeps when no client has a message ready. Shorter intervals
+    /// lower latency at the cost of CPU time.
+    pub fn set_poll_interval(&mut self, poll_interval: Duration) {
+        self.poll_interval = poll_interval;
+    }
+
+    /// Waits for any client to receive a message, for up to ``timeout``, or forever if it is
+    /// ``None``. Returns the id of the client, and the message or the error it failed with, or
+    /// ``None`` if ``timeout`` passed first. Clients are polled in turn, starting after the last
+    /// one to yield, so a busy client can't starve the others.
+    pub fn select(
+        &mut self,
+        timeout: Option<Duration>,
+    ) -> Option<(usize, Result<OscMessage, Error>)> {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        loop {
+            let count = self.clients.len();
+            for offset in 0..count {
+                let index = (self.next_index + offset) % count;
+                let (id, client) = &mut self.clients[index];
+                let res = match client.try_recv() {
+                    Ok(None) => continue,
+                    Ok(Some(msg)) => Ok(msg),
+                    Err(e) => Err(e),
+                };
+                self.next_index = index + 1;
+                return Some((*id, res));
+            }
+
+            let mut sleep = self.poll_interval;
+            if let Some(deadline) = deadline {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                if remaining.is_zero() {
+                    return None;
+                }
+                sleep = sleep.min(remaining);
+            }
+            thread::sleep(sleep);
+        }
+    }
+}
@@ -0,0 +1,199 @@
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, ToSocketAddrs};
+
+#[cfg(feature = "udp")]
+use std::net::UdpSocket;
+
+use crate::{
+    bundle::OscBundle, errors::Error, sockets::Connection, time::OscTime, OscMessage, OscPacket,
+};
+
+/// An ``OscSender`` over UDP.
+#[cfg(feature = "udp")]
+pub type UdpOscSender = OscSender<UdpSocket>;
+
+/// A send-only OSC client, for applications which only transmit, e.g. to drive a lighting desk.
+/// Unlike ``OscClient``, it has no receive buffer or queue, and picks its own local address.
+#[allow(clippy::module_name_repetitions)]
+pub struct OscSender<C: Connection> {
+    connection: C,
+}
+
+impl<C: Connection> OscSender<C> {
+    /// Creates a new ``OscSender`` connected to ``remote_address``, from an ephemeral port on
+    /// every interface of the same IP version.
+    ///
+    /// # Errors
+    /// If ``remote_address`` doesn't resolve, or the connection cannot be made, this function will
+    /// return an ``Error::Socket``.
+    pub fn new<A: ToSocketAddrs>(remote_address: A) -> Result<Self, Error> {
+        let remote_address: Vec<_> = remote_address
+            .to_socket_addrs()
+            .map_err(Error::Socket)?
+            .collect();
+        let local_address = match remote_address.first() {
+            Some(SocketAddr::V6(_)) => SocketAddr::from((Ipv6Addr::UNSPECIFIED, 0)),
+            _ => SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0)),
+        };
+        let connection = C::new(local_address, &remote_address[..]).map_err(Error::Socket)?;
+        Ok(Self::from_connection(connection))
+    }
+
+    /// Creates an ``OscSender`` over an existing ``connection``.
+    #[must_use]
+    pub fn from_connection(connection: C) -> Self {
+        Self { connection }
+    }
+
+    /// Sends ``message``, returning the size of the packet sent.
+    ///
+    /// # Errors
+    /// Will return ``Err`` if ``message.build`` fails, or an ``Error::Socket`` if the connection
+    /// fails to send it.
+    pub fn send(&mut self, message: &OscMessage) -> Result<usize, Error> {
+        self.send_bytes(&message.build()?)
+    }
+
+    /// Sends ``messages`` together in one bundle, to be acted on at ``timetag``.
+    ///
+    /// # Errors
+    /// Will return ``Err`` if building the bundle fails (see ``OscBundle::build``), or an
+    /// ``Error::Socket`` if the connection fails to send it.
+    pub fn send_bundle(
+        &mut self,
+        messages: &[OscMessage],
+        timetag: OscTime,
+    ) -> Result<usize, Error> {
+        let content = messages.iter().cloned().map(OscPacket::Message).collect();
+        self.send_bytes(&OscBundle::new(timetag, content).build()?)
+    }
+
+    /// Sends raw bytes, e.g. to a device which doesn't understand standard OSC.
+    ///
+    /// # Errors
+    /// Will return an ``Error::Socket`` if sending the data fails.
+    pub fn send_bytes(&mut self, bytes: &[u8]) -> Result<usize, Error> {
+        self.connection.send(bytes).map_err(Error::Socket)
+    }
+
+    /// Returns the local address the sender sends from.
+    ///
+    /// # Errors
+    /// Will return an ``Error::Socket`` if the connection can't report it.
+    pub fn local_addr(&self) -> Result<SocketAddr, Error> {
+        self.connection.local_addr().map_err(Error::Socket)
+    }
+}
+
+/// A ``MultiSender`` over UDP.
+#[cfg(feature = "udp")]
+pub type UdpMultiSender = MultiSender<UdpSocket>;
+
+/// Sends each message to every one of a set of destinations, e.g. cue data which must reach a
+/// mixer, a lighting desk, and a recorder at once. Each destination has its own ``OscSender``, and
+/// each packet is only built once.
+///
+/// A destination failing doesn't stop the others being sent to: every destination is tried, and
+/// the first error is returned afterwards.
+pub struct MultiSender<C: Connection> {
+    destinations: Vec<(SocketAddr, OscSender<C>)>,
+}
+
+impl<C: Connection> MultiSender<C> {
+    /// Creates a ``MultiSender`` with no destinations.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            destinations: Vec::new(),
+        }
+    }
+
+    /// Adds ``address`` as a destination, as ``add_destination`` does.
+    ///
+    /// # Errors
+    /// See ``add_destination``.
+    pub fn with_destination<A: ToSocketAddrs>(mut self, address: A) -> Result<Self, Error> {
+        self.add_destination(address)?;
+        Ok(self)
+    }
+
+    /// Connects to ``address`` and adds it as a destination, returning the address it resolved
+    /// to. Adding a destination which is already present does nothing.
+    ///
+    /// # Errors
+    /// If ``address`` doesn't resolve, or the connection cannot be made, this function will
+    /// return an ``Error::Socket``.
+    pub fn add_destination<A: ToSocketAddrs>(&mut self, address: A) -> Result<SocketAddr, Error> {
+        let address = address
+            .to_socket_addrs()
+            .map_err(Error::Socket)?
+            .next()
+            .ok_or_else(|| {
+                Error::Socket(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    "Destination address resolved to nothing",
+                ))
+            })?;
+        if !self.destinations.iter().any(|(known, _)| *known == address) {
+            self.destinations.push((address, OscSender::new(address)?));
+        }
+        Ok(address)
+    }
+
+    /// Removes the destination ``address``, returning whether it was present.
+    pub fn remove_destination(&mut self, address: SocketAddr) -> bool {
+        let before = self.destinations.len();
+        self.destinations.retain(|(known, _)| *known != address);
+        self.destinations.len() != before
+    }
+
+    /// Returns the addresses of every destination, in the order they were added.
+    pub fn destinations(&self) -> impl Iterator<Item = SocketAddr> + '_ {
+        self.destinations.iter().map(|(address, _)| *address)
+    }
+
+    /// Sends ``message`` to every destination, returning the size of the packet sent.
+    ///
+    /// # Errors
+    /// Will return ``Err`` if ``message.build`` fails, or the first ``Error::Socket`` from sending
+    /// to a destination.
+    pub fn send(&mut self, message: &OscMessage) -> Result<usize, Error> {
+        self.send_bytes(&message.build()?)
+    }
+
+    /// Sends ``messages`` together in one bundle to every destination, to be acted on at
+    /// ``timetag``.
+    ///
+    /// # Errors
+    /// Will return ``Err`` if building the bundle fails (see ``OscBundle::build``), or the first
+    /// ``Error::Socket`` from sending to a destination.
+    pub fn send_bundle(
+        &mut self,
+        messages: &[OscMessage],
+        timetag: OscTime,
+    ) -> Result<usize, Error> {
+        let content = messages.iter().cloned().map(OscPacket::Message).collect();
+        self.send_bytes(&OscBundle::new(timetag, content).build()?)
+    }
+
+    /// Sends raw bytes to every destination.
+    ///
+    /// # Errors
+    /// Will return the first ``Error::Socket`` from sending to a destination.
+    pub fn send_bytes(&mut self, bytes: &[u8]) -> Result<usize, Error> {
+        let mut result = Ok(bytes.len());
+        for (_, sender) in &mut self.destinations {
+            if let Err(e) = sender.send_bytes(bytes) {
+                if result.is_ok() {
+                    result = Err(e);
+                }
+            }
+        }
+        result
+    }
+}
+
+impl<C: Connection> Default for MultiSender<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,125 @@
+use crate::{Arg, OscMessage};
+
+/// How many sequence numbers before the highest received are remembered, to tell a late message
+/// from a duplicate.
+const WINDOW: u32 = 64;
+
+/// Stamps outgoing messages with a sequence number, as an extra ``Arg::Int`` after their own
+/// arguments, so a ``SequenceTracker`` at the other end can spot messages lost, duplicated, or
+/// reordered on the way, e.g. to diagnose a lossy wireless link. Both ends must agree to use
+/// sequence numbers, as the receiver takes the last argument of every message to be one.
+///
+/// Numbers start at 0 and wrap around after ``u32::MAX``.
+#[derive(Debug, Clone, Default)]
+pub struct Sequencer {
+    next: u32,
+}
+
+impl Sequencer {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends the next sequence number to ``msg``.
+    pub fn stamp(&mut self, msg: &mut OscMessage) {
+        #[allow(clippy::cast_possible_wrap)]
+        msg.args.push(Arg::Int(self.next as i32));
+        self.next = self.next.wrapping_add(1);
+    }
+}
+
+/// What a received message's sequence number says about the link. See ``SequenceTracker``.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SequenceStatus {
+    /// The message was the next expected, or the first received.
+    InOrder,
+    /// ``missing`` messages were skipped before this one, and may have been lost.
+    Gap { missing: u32 },
+    /// The message arrived after a later one, filling an earlier gap.
+    Late,
+    /// The message was already received, e.g. resent by the network.
+    Duplicate,
+    /// The message didn't end with a sequence number, so wasn't checked.
+    Unsequenced,
+}
+
+/// Counts of what a ``SequenceTracker`` has seen.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SequenceStats {
+    /// Messages received with a sequence number, including duplicates.
+    pub received: u64,
+    pub duplicates: u64,
+    /// Messages skipped over, less those which arrived late.
+    pub lost: u64,
+    pub late: u64,
+}
+
+/// Checks the sequence numbers added by a ``Sequencer`` on received messages, removing them and
+/// reporting duplicates and gaps. Track each sender separately.
+///
+/// Only the last 64 sequence numbers are remembered, so a duplicate of an older message is taken
+/// to be late instead.
+#[derive(Debug, Clone, Default)]
+pub struct SequenceTracker {
+    /// The highest sequence number received, if any.
+    highest: Option<u32>,
+    /// Bit ``n`` is set if ``highest - n`` has been received.
+    seen: u64,
+    stats: SequenceStats,
+}
+
+impl SequenceTracker {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Removes the sequence number from the end of ``msg``, returning what it says about the
+    /// link. Messages which don't end with an ``Arg::Int`` are left as they are.
+    pub fn check(&mut self, msg: &mut OscMessage) -> SequenceStatus {
+        let Some(&Arg::Int(number)) = msg.args.last() else {
+            return SequenceStatus::Unsequenced;
+        };
+        msg.args.pop();
+        #[allow(clippy::cast_sign_loss)]
+        self.record(number as u32)
+    }
+
+    fn record(&mut self, number: u32) -> SequenceStatus {
+        self.stats.received += 1;
+        let Some(highest) = self.highest else {
+            self.highest = Some(number);
+            self.seen = 1;
+            return SequenceStatus::InOrder;
+        };
+        // Numbers less than half the range ahead are newer, so the sequence can wrap
+        let ahead = number.wrapping_sub(highest);
+        if ahead != 0 && ahead < u32::MAX / 2 {
+            self.highest = Some(number);
+            self.seen = self.seen.checked_shl(ahead).unwrap_or(0) | 1;
+            if ahead == 1 {
+                return SequenceStatus::InOrder;
+            }
+            self.stats.lost += u64::from(ahead - 1);
+            return SequenceStatus::Gap { missing: ahead - 1 };
+        }
+        let behind = highest.wrapping_sub(number);
+        if behind < WINDOW && self.seen & (1 << behind) != 0 {
+            self.stats.duplicates += 1;
+            return SequenceStatus::Duplicate;
+        }
+        if behind < WINDOW {
+            self.seen |= 1 << behind;
+        }
+        self.stats.lost = self.stats.lost.saturating_sub(1);
+        self.stats.late += 1;
+        SequenceStatus::Late
+    }
+
+    /// Returns the counts of what has been seen so far.
+    #[must_use]
+    pub fn stats(&self) -> SequenceStats {
+        self.stats
+    }
+}
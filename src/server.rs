@@ -1,11 +1,1825 @@
-// Forbidden characters in OSC addresses:
-// space
-// #
-// *
-// ,
-// /
-// ?
-// [
-// ]
-// {
-// }
+use std::{
+    collections::HashMap,
+    io::ErrorKind,
+    net::{SocketAddr, ToSocketAddrs},
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc::Sender,
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant, SystemTime},
+};
+
+#[cfg(feature = "tcp")]
+use std::net::{Shutdown, TcpListener, TcpStream};
+#[cfg(feature = "udp")]
+use std::{
+    io,
+    net::UdpSocket,
+    sync::{mpsc, OnceLock},
+};
+
+#[cfg(all(feature = "tokio", feature = "udp"))]
+pub use crate::async_server::{AsyncHandler, AsyncOscServer};
+#[cfg(feature = "oscquery")]
+use crate::oscquery::{OscQueryServer, OscTransport};
+#[cfg(feature = "tcp")]
+use crate::sockets::{Connection, FramedTcpStream};
+use crate::{
+    acl::AccessList,
+    address::OscAddress,
+    auth::{SharedSecretAuth, AUTH_ADDRESS},
+    cancel::CancelFlag,
+    errors::Error,
+    handler::IntoReplies,
+    limit::RateLimit,
+    lock,
+    namespace::{Access, OscNamespace},
+    parse::ParseOptions,
+    pattern::OscPattern,
+    pool::{BufferPool, PooledBuffer},
+    recording::Observers,
+    router::Router,
+    schedule::Schedule,
+    schema::OscSchema,
+    session::{Session, Sessions},
+    state::StateStore,
+    time::OscTime,
+    Arg, OscMessage, OscPacket,
+};
+
+/// Handles a message sent to a route, given the concrete address it was matched at and the
+/// sender's ``Session``, and returns the replies to send back to the sender, or an error to be
+/// turned into an ``ErrorReply``.
+///
+/// Handlers are closures, so they can capture whatever state they need (e.g. a model of a mixer).
+pub type Handler =
+    Box<dyn FnMut(&OscAddress, &OscMessage, &mut Session) -> Result<Vec<OscMessage>, Error> + Send>;
+
+/// Handles a message which matched no route, returning the replies to send back to the sender.
+pub type DefaultHandler = Box<dyn FnMut(&OscMessage) -> Vec<OscMessage> + Send>;
+
+/// Wraps the dispatch of every message. It is given the message and ``next``, which dispatches a
+/// message to the remaining middleware and then the routes, returning the replies. Middleware can
+/// rewrite the message, skip ``next`` entirely (e.g. to reject unauthenticated messages), or
+/// inspect the replies.
+pub type Middleware = Box<
+    dyn Fn(&OscMessage, &dyn Fn(&OscMessage) -> Vec<OscMessage>) -> Vec<OscMessage> + Send + Sync,
+>;
+
+/// Builds the reply sent when the handler for the given address fails, if any.
+pub type ErrorReply = Box<dyn Fn(&OscAddress, &Error) -> Option<OscMessage> + Send + Sync>;
+
+/// Called with the source and raw bytes of each packet which is dropped because it couldn't be
+/// parsed or was too large, and why.
+pub type ParseErrorHandler = Box<dyn Fn(SocketAddr, &[u8], &Error) + Send + Sync>;
+
+/// Called with the address and ``Session`` of a stream client as it connects or disconnects,
+/// e.g. to set up the state its handlers share, or clean up its subscriptions.
+pub type ConnectionHook = Box<dyn Fn(SocketAddr, &mut Session) + Send + Sync>;
+
+/// Defines the builder methods shared by every server, which configure its ``Dispatcher``, in
+/// the ``impl`` block of a server with a ``dispatcher`` field.
+macro_rules! dispatcher_builders {
+    () => {
+        /// Registers ``handler`` to be called for messages sent to any address matching
+        /// ``pattern``, e.g. ``"/ch/*/fader"``.
+        ///
+        /// ``handler`` can reply with any ``IntoReplies``: ``Some(args)`` to reply at the address
+        /// the message was received at, or one or more whole messages (e.g. answering ``/ping`` on
+        /// ``/pong``). It can also return a ``Result`` of any of these, in which case an error is
+        /// replied to with the server's ``ErrorReply``.
+        ///
+        /// # Errors
+        /// Will return ``Error::Address`` if ``pattern`` is not a valid ``OscPattern``, or
+        /// ``Error::DuplicateRoute`` if a route is already registered for it.
+        pub fn add_route<P, F, R>(
+            self,
+            pattern: P,
+            handler: F,
+        ) -> Result<Self, $crate::errors::Error>
+        where
+            P: TryInto<$crate::pattern::OscPattern>,
+            $crate::errors::Error: From<P::Error>,
+            F: FnMut(&$crate::address::OscAddress, &$crate::OscMessage) -> R + Send + 'static,
+            R: $crate::handler::IntoReplies,
+        {
+            $crate::lock(&self.dispatcher.router)
+                .insert_route(pattern, $crate::server::into_handler(handler))?;
+            Ok(self)
+        }
+
+        /// Registers ``handler`` like ``add_route``, but also gives it the sender's ``Session``, so
+        /// it can keep state for each sender (e.g. for an authentication handshake).
+        ///
+        /// # Errors
+        /// Will return ``Error::Address`` if ``pattern`` is not a valid ``OscPattern``, or
+        /// ``Error::DuplicateRoute`` if a route is already registered for it.
+        pub fn add_session_route<P, F, R>(
+            mut self,
+            pattern: P,
+            handler: F,
+        ) -> Result<Self, $crate::errors::Error>
+        where
+            P: TryInto<$crate::pattern::OscPattern>,
+            $crate::errors::Error: From<P::Error>,
+            F: FnMut(
+                    &$crate::address::OscAddress,
+                    &$crate::OscMessage,
+                    &mut $crate::session::Session,
+                ) -> R
+                + Send
+                + 'static,
+            R: $crate::handler::IntoReplies,
+        {
+            $crate::lock(&self.dispatcher.router)
+                .insert_route(pattern, $crate::server::into_session_handler(handler))?;
+            self.dispatcher.session_routes = true;
+            Ok(self)
+        }
+
+        /// Drops the ``Session`` of any sender which hasn't sent a packet for ``timeout``, 5
+        /// minutes by default, so a sender returning after that starts a new one. A connected
+        /// stream client's session instead lasts until it disconnects.
+        #[must_use]
+        pub fn with_session_timeout(mut self, timeout: ::std::time::Duration) -> Self {
+            self.dispatcher.sessions.idle_timeout = timeout;
+            self
+        }
+
+        /// Keeps sessions for at most ``max_sessions`` senders at once, 4096 by default, so a
+        /// flood of packets from spoofed addresses can't exhaust memory. Senders beyond it have no
+        /// session until others expire: their session routes are given a fresh one for each
+        /// message, and they can't authenticate.
+        #[must_use]
+        pub fn with_max_sessions(mut self, max_sessions: usize) -> Self {
+            self.dispatcher.sessions.capacity = max_sessions;
+            self
+        }
+
+        /// Registers routes which describe the server, to help debug which endpoints it exposes:
+        /// ``/osc/ping`` replies with the arguments it was sent, ``/osc/routes`` with the pattern
+        /// of every route as ``Arg::Str``s, and ``/osc/version`` with this crate's version.
+        ///
+        /// # Errors
+        /// Will return ``Error::DuplicateRoute`` if any of these routes is already registered.
+        pub fn with_introspection(self) -> Result<Self, $crate::errors::Error> {
+            self.dispatcher.add_introspection()?;
+            Ok(self)
+        }
+
+        /// Records the most recent arguments received at every address, which can then be read from
+        /// ``state``, e.g. to show a late-joining UI the current mix.
+        #[must_use]
+        pub fn with_state(mut self) -> Self {
+            self.dispatcher.record_state = true;
+            self
+        }
+
+        /// Registers ``/osc/clock``, which answers a timetag with that timetag, the time it was
+        /// received, and the time it was answered, so clients can estimate this machine's clock
+        /// with ``OscClient::estimate_clock`` and schedule bundles against it.
+        ///
+        /// # Errors
+        /// Will return ``Error::DuplicateRoute`` if ``/osc/clock`` is already registered.
+        pub fn with_clock_sync(self) -> Result<Self, $crate::errors::Error> {
+            self.dispatcher.add_clock_sync()?;
+            Ok(self)
+        }
+
+        /// Records state as ``with_state`` does, and registers ``/osc/state`` so clients which
+        /// connect late can ask for the current values. It replies with a message for each recorded
+        /// address matching any of the patterns it is sent as ``Arg::Str``s (or for every address,
+        /// if it is sent none), carrying that address's most recent arguments.
+        ///
+        /// # Errors
+        /// Will return ``Error::DuplicateRoute`` if ``/osc/state`` is already registered.
+        pub fn with_state_query(mut self) -> Result<Self, $crate::errors::Error> {
+            self.dispatcher.add_state_query()?;
+            Ok(self)
+        }
+
+        /// Returns the state recorded by ``with_state``, which is empty if it was not used. The
+        /// returned store keeps updating while the server runs.
+        #[must_use]
+        pub fn state(&self) -> $crate::state::StateStore {
+            self.dispatcher.state.clone()
+        }
+
+        /// Describes the addresses the server exposes, with the types and ranges of their
+        /// arguments, e.g. for editors to discover its parameters. Routes at plain addresses are
+        /// added to it automatically.
+        #[must_use]
+        pub fn with_namespace(mut self, namespace: $crate::namespace::OscNamespace) -> Self {
+            self.dispatcher.namespace = ::std::sync::Arc::new(namespace);
+            self
+        }
+
+        /// Returns the namespace given to ``with_namespace``, with a node for each route registered
+        /// at a plain address, rather than a pattern with wildcards.
+        #[must_use]
+        pub fn namespace(&self) -> $crate::namespace::OscNamespace {
+            self.dispatcher.namespace()
+        }
+
+        /// Registers ``handler`` for messages sent to any address matching ``pattern``, extracting
+        /// its arguments from the message, e.g. ``|ch: i32, level: f32| ...``.
+        ///
+        /// If the message's arguments don't match the handler's, it is not called and the
+        /// ``Error::Malformed`` describing the mismatch is replied to with the server's
+        /// ``ErrorReply``.
+        ///
+        /// # Errors
+        /// Will return ``Error::Address`` if ``pattern`` is not a valid ``OscPattern``, or
+        /// ``Error::DuplicateRoute`` if a route is already registered for it.
+        pub fn add_typed_route<P, Args>(
+            self,
+            pattern: P,
+            mut handler: impl $crate::handler::TypedHandler<Args>,
+        ) -> Result<Self, $crate::errors::Error>
+        where
+            P: TryInto<$crate::pattern::OscPattern>,
+            $crate::errors::Error: From<P::Error>,
+        {
+            self.add_route(
+                pattern,
+                move |address: &$crate::address::OscAddress, msg: &$crate::OscMessage| {
+                    handler.call(address, msg.args[..].to_vec())
+                },
+            )
+        }
+
+        /// Mounts ``service`` on the addresses matching ``pattern``, replying with its reply, or
+        /// with the server's ``ErrorReply`` if it fails. See ``OscService``.
+        ///
+        /// # Errors
+        /// Will return ``Error::Address`` if ``pattern`` is not a valid ``OscPattern``, or
+        /// ``Error::DuplicateRoute`` if a route is already registered for it.
+        pub fn add_service<P>(
+            self,
+            pattern: P,
+            mut service: impl $crate::service::OscService,
+        ) -> Result<Self, $crate::errors::Error>
+        where
+            P: TryInto<$crate::pattern::OscPattern>,
+            $crate::errors::Error: From<P::Error>,
+        {
+            self.add_route(
+                pattern,
+                move |_: &$crate::address::OscAddress, msg: &$crate::OscMessage| service.call(msg),
+            )
+        }
+
+        /// Mounts every route of ``router`` under each of ``prefixes``, e.g. a router of
+        /// ``/eq/...`` routes under ``/ch/01``, ``/ch/02`` and so on, so ``/ch/02/eq/gain`` is
+        /// handled by its ``/eq/gain`` route. Handlers are given the matched address with the
+        /// prefix stripped, while the message keeps its full address, and are shared between the
+        /// prefixes.
+        ///
+        /// A prefix may be a pattern, e.g. ``/ch/*``, as long as each of its parts matches a single
+        /// part of an address.
+        ///
+        /// # Errors
+        /// Will return ``Error::Address`` if a prefix joined with a route is not a valid
+        /// ``OscPattern``, or ``Error::DuplicateRoute`` if a route is already registered for it.
+        pub fn mount<I, S>(
+            mut self,
+            prefixes: I,
+            router: $crate::router::Router<$crate::server::Handler>,
+        ) -> Result<Self, $crate::errors::Error>
+        where
+            I: IntoIterator<Item = S>,
+            S: AsRef<str>,
+        {
+            self.dispatcher.mount(prefixes, router)?;
+            self.dispatcher.session_routes = true;
+            Ok(self)
+        }
+
+        /// Returns a ``ServerHandle`` which can add and remove routes while the server is running.
+        #[must_use]
+        pub fn handle(&self) -> $crate::server::ServerHandle {
+            self.dispatcher.handle()
+        }
+
+        /// Returns a snapshot of the server's metrics: packets and bytes received, parse failures,
+        /// hits per route, and bytes sent. Use ``handle`` to read them while the server is running.
+        #[must_use]
+        pub fn metrics(&self) -> $crate::server::ServerMetrics {
+            self.dispatcher.metrics.snapshot()
+        }
+
+        /// Sets ``handler`` to be called for any message whose address matches no route, e.g. to
+        /// log unknown addresses or forward them elsewhere. Without one, such messages are dropped.
+        pub fn set_default_handler<F, R>(&mut self, handler: F)
+        where
+            F: FnMut(&$crate::OscMessage) -> R + Send + 'static,
+            R: IntoIterator<Item = $crate::OscMessage>,
+        {
+            self.dispatcher.set_default_handler(handler);
+        }
+
+        /// Sets how the server replies when a handler fails. By default, it replies with a message
+        /// to ``/error`` containing the failed address and the error as ``Arg::Str``s. If
+        /// ``error_reply`` returns ``None``, nothing is sent.
+        #[must_use]
+        pub fn with_error_reply<F>(mut self, error_reply: F) -> Self
+        where
+            F: Fn(
+                    &$crate::address::OscAddress,
+                    &$crate::errors::Error,
+                ) -> Option<$crate::OscMessage>
+                + Send
+                + Sync
+                + 'static,
+        {
+            self.dispatcher.error_reply = Box::new(error_reply);
+            self
+        }
+
+        /// Restricts the routes matching ``pattern`` to ``access``, e.g. ``Access::ReadOnly`` for
+        /// meters. A message without arguments reads a route (a query), and one with arguments
+        /// writes it; messages a route doesn't permit aren't passed to its handler, and are replied
+        /// to with the server's ``ErrorReply`` for an ``Error::AccessDenied``. Routes matching no
+        /// pattern may be read and written. If several patterns match a route, the first added
+        /// applies.
+        ///
+        /// # Errors
+        /// Will return ``Error::Address`` if ``pattern`` is not a valid ``OscPattern``, or
+        /// ``Error::DuplicateRoute`` if an access level is already set for it.
+        pub fn with_access_level<P>(
+            mut self,
+            pattern: P,
+            access: $crate::namespace::Access,
+        ) -> Result<Self, $crate::errors::Error>
+        where
+            P: TryInto<$crate::pattern::OscPattern>,
+            $crate::errors::Error: From<P::Error>,
+        {
+            self.dispatcher
+                .access_levels
+                .insert_route(pattern, access)?;
+            Ok(self)
+        }
+
+        /// Only lets senders permitted by ``writers`` write to routes, e.g. only the operator
+        /// station, while others may still query them. Writes from anyone else are refused as for
+        /// ``with_access_level``. By default, every sender may write.
+        #[must_use]
+        pub fn with_write_access(mut self, writers: $crate::acl::AccessList) -> Self {
+            self.dispatcher.writers = writers;
+            self
+        }
+
+        /// Requires senders to authenticate with ``auth`` before their messages are handled,
+        /// registering the ``/auth`` route they authenticate with. See ``SharedSecretAuth``. A
+        /// stream client authenticates once per connection.
+        ///
+        /// # Errors
+        /// Will return ``Error::DuplicateRoute`` if ``/auth`` is already registered.
+        pub fn with_auth(
+            mut self,
+            auth: $crate::auth::SharedSecretAuth,
+        ) -> Result<Self, $crate::errors::Error> {
+            self.dispatcher.add_auth(auth)?;
+            Ok(self)
+        }
+
+        /// Rejects messages which don't match ``schema``, e.g. a fader level sent as a string,
+        /// replying with the server's ``ErrorReply`` for an ``Error::Schema`` instead of passing
+        /// them to the middleware and routes.
+        #[must_use]
+        pub fn with_schema(mut self, schema: $crate::schema::OscSchema) -> Self {
+            self.dispatcher.schema = Some(schema);
+            self
+        }
+
+        /// Parses packets with ``options``, e.g. ``ParseOptions::strict()`` on a network where
+        /// anyone can send packets. Packets which fail are dropped, and reported as
+        /// ``ServerEvent::ParseError``.
+        #[must_use]
+        pub fn with_parse_options(mut self, options: $crate::parse::ParseOptions) -> Self {
+            self.dispatcher.parse_options = options;
+            self
+        }
+
+        /// Encodes replies in buffers from ``pool``, e.g. to share one pool with other servers.
+        #[must_use]
+        pub fn with_buffer_pool(mut self, pool: $crate::pool::BufferPool) -> Self {
+            self.dispatcher.pool = pool;
+            self
+        }
+
+        /// Catches panics in route handlers if ``enabled``, as it is by default, so one faulty
+        /// handler doesn't take down the server. A panic is reported with the server's
+        /// ``ErrorReply`` for an ``Error::HandlerPanicked``, and the message's other routes are
+        /// still handled. A panic in the default handler is reported the same way, at the
+        /// message's address. If not ``enabled``, a panic unwinds out of ``start``.
+        #[must_use]
+        pub fn with_panic_isolation(mut self, enabled: bool) -> Self {
+            self.dispatcher.catch_panics = enabled;
+            self
+        }
+
+        /// Gives the server up to ``drain_timeout`` to finish once shut down with
+        /// ``ServerHandle::shutdown``, 5 seconds by default. Packets already received are still
+        /// dispatched, and bundles held until a timetag within it are dispatched when due, but
+        /// anything left after it is dropped, and stream clients still connected are
+        /// disconnected.
+        #[must_use]
+        pub fn with_drain_timeout(mut self, drain_timeout: ::std::time::Duration) -> Self {
+            self.dispatcher.drain_timeout = drain_timeout;
+            self
+        }
+
+        /// Calls ``handler`` with the source address, raw bytes, and error of every packet which
+        /// fails to parse or is larger than the maximum packet size, e.g. to log malformed traffic
+        /// from a misbehaving device. Such packets are otherwise dropped silently.
+        #[must_use]
+        pub fn on_parse_error<F>(mut self, handler: F) -> Self
+        where
+            F: Fn(::std::net::SocketAddr, &[u8], &$crate::errors::Error) + Send + Sync + 'static,
+        {
+            self.dispatcher.parse_error = Some(Box::new(handler));
+            self
+        }
+
+        /// Records every packet the server receives and every reply it sends with ``recorder``.
+        #[must_use]
+        pub fn with_recorder(mut self, recorder: $crate::recording::Recorder) -> Self {
+            self.dispatcher.observers.recorder = Some(recorder);
+            self
+        }
+
+        /// Calls ``tap`` with the exact bytes of every reply the server sends, once it's sent, and
+        /// where it was sent, e.g. for a protocol debugger. Replaces any earlier tap.
+        #[must_use]
+        pub fn on_raw_send<F>(mut self, tap: F) -> Self
+        where
+            F: Fn(&[u8], Option<::std::net::SocketAddr>) + Send + Sync + 'static,
+        {
+            self.dispatcher.observers.raw_send =
+                Some(::std::sync::Arc::new(tap) as $crate::recording::RawTap);
+            self
+        }
+
+        /// Calls ``tap`` with the exact bytes of every packet the server receives from a permitted
+        /// source, before they're parsed, and where they came from. Replaces any earlier tap.
+        #[must_use]
+        pub fn on_raw_recv<F>(mut self, tap: F) -> Self
+        where
+            F: Fn(&[u8], Option<::std::net::SocketAddr>) + Send + Sync + 'static,
+        {
+            self.dispatcher.observers.raw_recv =
+                Some(::std::sync::Arc::new(tap) as $crate::recording::RawTap);
+            self
+        }
+
+        /// Limits how often messages to addresses matching ``pattern`` are handled, e.g.
+        /// ``with_rate_limit("/ch/*/fader", RateLimit::Throttle(Duration::from_millis(10)))`` for a
+        /// controller which sends hundreds of fader moves a second. Messages held back by the limit
+        /// are handled later while the server runs, and a message held back replaces any held for
+        /// the same address, so only the latest value is handled. If several limits match an
+        /// address, the first added applies. Each stream client's messages are limited separately.
+        ///
+        /// # Errors
+        /// Will return ``Error::Address`` if ``pattern`` is not a valid ``OscPattern``.
+        pub fn with_rate_limit<P>(
+            mut self,
+            pattern: P,
+            limit: $crate::limit::RateLimit,
+        ) -> Result<Self, $crate::errors::Error>
+        where
+            P: TryInto<$crate::pattern::OscPattern>,
+            $crate::errors::Error: From<P::Error>,
+        {
+            self.dispatcher.limits.push((pattern.try_into()?, limit));
+            Ok(self)
+        }
+
+        /// Sends a ``ServerEvent`` to ``events`` for every message received, reply sent, packet
+        /// that fails to parse, and new sender, e.g. for a GUI to display live traffic. Events stop
+        /// being sent once the receiver is dropped, without affecting the server. New senders are
+        /// told apart by their sessions, so the server keeps them while it has a listener.
+        #[must_use]
+        pub fn with_events(
+            mut self,
+            events: ::std::sync::mpsc::Sender<$crate::server::ServerEvent>,
+        ) -> Self {
+            self.dispatcher.events = Some(events);
+            self
+        }
+
+        /// Adds ``middleware`` around the dispatch of every message, e.g.
+        /// ``with_middleware(|msg, next| next(msg))``. Middleware added first runs outermost.
+        #[must_use]
+        pub fn with_middleware<F>(mut self, middleware: F) -> Self
+        where
+            F: Fn(
+                    &$crate::OscMessage,
+                    &dyn Fn(&$crate::OscMessage) -> Vec<$crate::OscMessage>,
+                ) -> Vec<$crate::OscMessage>
+                + Send
+                + Sync
+                + 'static,
+        {
+            self.dispatcher.middleware.push(Box::new(middleware));
+            self
+        }
+    };
+}
+
+#[cfg(feature = "mio")]
+pub(crate) use dispatcher_builders;
+
+/// How often a running server checks whether it has been shut down with ``ServerHandle::shutdown``.
+pub(crate) const SHUTDOWN_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// How long a server keeps dispatching after being shut down, unless set with
+/// ``OscServer::with_drain_timeout``.
+const DEFAULT_DRAIN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How many clients a ``TcpOscServer`` serves at once, unless set with
+/// ``TcpOscServer::with_max_connections``.
+#[cfg(feature = "tcp")]
+const DEFAULT_MAX_CONNECTIONS: usize = 256;
+
+/// Where a UDP server sends the replies to messages for a route, set with
+/// ``OscServer::with_reply_policy``.
+#[cfg(feature = "udp")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplyTo {
+    /// The address the message came from, as by default.
+    Source,
+    /// A fixed address, e.g. a separate feedback port on the console.
+    Fixed(SocketAddr),
+    /// An address taken from the message's argument at this index: an ``Arg::Int`` is a port on
+    /// the sender's IP address, as many lighting consoles send, and an ``Arg::Str`` a whole
+    /// address, e.g. ``"10.0.0.5:8001"``. Replies go to the source if the argument is missing or
+    /// isn't an address.
+    Argument(usize),
+}
+
+#[cfg(feature = "udp")]
+impl ReplyTo {
+    fn destination(self, msg: &OscMessage, source: SocketAddr) -> SocketAddr {
+        match self {
+            ReplyTo::Source => None,
+            ReplyTo::Fixed(address) => Some(address),
+            ReplyTo::Argument(index) => match msg.args.get(index) {
+                Some(Arg::Int(port)) => u16::try_from(*port)
+                    .ok()
+                    .map(|port| SocketAddr::new(source.ip(), port)),
+                Some(Arg::Str(address)) => address.parse().ok(),
+                _ => None,
+            },
+        }
+        .unwrap_or(source)
+    }
+}
+
+/// Each handler is behind its own lock, so that different routes can be handled concurrently by
+/// ``start_threaded``.
+pub(crate) type SharedHandler = Arc<Mutex<Handler>>;
+
+#[cfg(feature = "udp")]
+#[allow(clippy::module_name_repetitions)]
+pub struct OscServer {
+    socket: UdpSocket,
+    dispatcher: Dispatcher,
+    buffer: Vec<u8>,
+    schedule: Schedule<SocketAddr>,
+    reply_policies: Vec<(OscPattern, ReplyTo)>,
+}
+
+#[cfg(feature = "udp")]
+impl OscServer {
+    /// Creates a new ``OscServer``, listening at ``address``. ``buffer_size`` dictates the maximum
+    /// size packet that the server can receive.
+    ///
+    /// The socket is bound with the platform's defaults, so whether ``[::]`` also accepts IPv4
+    /// clients depends on the platform. To choose, bind a socket with
+    /// ``ConnectionBuilder::with_only_v6`` and ``ConnectionBuilder::udp_listener``, which require
+    /// the ``socket2`` feature, and pass it to ``from_socket``.
+    ///
+    /// # Errors
+    /// If the socket cannot be bound, this function will return an ``Error::Socket``.
+    pub fn new<A: ToSocketAddrs>(address: A, buffer_size: usize) -> Result<Self, Error> {
+        let socket = UdpSocket::bind(address).map_err(Error::Socket)?;
+        Ok(Self::from_socket(socket, buffer_size))
+    }
+
+    /// Creates a new ``OscServer`` listening on an already bound ``socket``, e.g. one bound
+    /// dual-stack (see ``ConnectionBuilder::with_only_v6``) to accept both IPv4 and IPv6 clients.
+    #[must_use]
+    pub fn from_socket(socket: UdpSocket, buffer_size: usize) -> Self {
+        Self {
+            socket,
+            dispatcher: Dispatcher::new(),
+            buffer: vec![0; buffer_size],
+            schedule: Schedule::new(),
+            reply_policies: Vec::new(),
+        }
+    }
+
+    dispatcher_builders!();
+
+    /// Creates an ``OscQueryServer`` at ``address`` describing this server's namespace, so
+    /// editors can discover its parameters. Run it on another thread with ``start``.
+    ///
+    /// # Errors
+    /// Will return an ``Error::Socket`` if the listener cannot be bound, or this server's
+    /// address cannot be read.
+    #[cfg(feature = "oscquery")]
+    pub fn oscquery<A: ToSocketAddrs>(&self, address: A) -> Result<OscQueryServer, Error> {
+        OscQueryServer::new(
+            address,
+            self.handle(),
+            self.state(),
+            self.local_addr()?,
+            OscTransport::Udp,
+        )
+    }
+
+    /// Only accepts packets from sources permitted by ``access``, e.g. to only take commands
+    /// from the front-of-house machines. Other packets are dropped before they are parsed.
+    #[must_use]
+    pub fn with_access_list(mut self, access: AccessList) -> Self {
+        self.dispatcher.access = access;
+        self
+    }
+
+    /// Resizes the receive buffer to ``buffer_size`` bytes, the largest packet the server can
+    /// receive whole. Larger packets are truncated, and so usually fail to parse.
+    #[must_use]
+    pub fn with_buffer_size(mut self, buffer_size: usize) -> Self {
+        self.buffer.resize(buffer_size, 0);
+        self
+    }
+
+    /// Drops packets larger than ``max_packet_size`` bytes without parsing them, reporting them
+    /// as failing with ``Error::MessageTooLarge``. The receive buffer grows to one byte more than
+    /// this if it's smaller, so larger packets can be told apart from ones which just fit.
+    #[must_use]
+    pub fn with_max_packet_size(mut self, max_packet_size: usize) -> Self {
+        self.dispatcher.max_packet_size = Some(max_packet_size);
+        if self.buffer.len() <= max_packet_size {
+            self.buffer.resize(max_packet_size + 1, 0);
+        }
+        self
+    }
+
+    /// Sends the replies to messages at addresses matching ``pattern`` to ``reply_to`` rather than
+    /// back to the sender, e.g. ``with_reply_policy("/eos/*", ReplyTo::Argument(0))`` for a
+    /// console which says which port it listens on. If several policies match an address, the
+    /// first added applies.
+    ///
+    /// # Errors
+    /// Will return ``Error::Address`` if ``pattern`` is not a valid ``OscPattern``.
+    pub fn with_reply_policy<P>(mut self, pattern: P, reply_to: ReplyTo) -> Result<Self, Error>
+    where
+        P: TryInto<OscPattern>,
+        Error: From<P::Error>,
+    {
+        self.reply_policies.push((pattern.try_into()?, reply_to));
+        Ok(self)
+    }
+
+    /// Returns the address the server is listening at.
+    ///
+    /// # Errors
+    /// Will return an ``Error::Socket`` if the address cannot be read from the socket.
+    pub fn local_addr(&self) -> Result<SocketAddr, Error> {
+        self.socket.local_addr().map_err(Error::Socket)
+    }
+
+    /// Receives and dispatches packets until shut down with ``ServerHandle::shutdown``. Packets
+    /// that fail to parse are ignored.
+    ///
+    /// Message addresses are treated as OSC address patterns, so a message is dispatched to the
+    /// handler of every route it matches (e.g. ``/ch/*/mute`` reaches both ``/ch/01/mute`` and
+    /// ``/ch/02/mute``). Messages in bundles are dispatched in order.
+    ///
+    /// A bundle whose timetag is in the future is held until that time, then all of its messages
+    /// are dispatched together. Bundles timetagged ``OscTime::IMMEDIATE`` or in the past are
+    /// dispatched as soon as they arrive.
+    ///
+    /// Once shut down, the server stops receiving, dispatches held bundles which become due
+    /// within the drain timeout set with ``with_drain_timeout``, and returns ``Ok``. It can then
+    /// be started again.
+    ///
+    /// # Errors
+    /// Will return an ``Error::Socket`` if receiving a packet or sending a reply fails.
+    pub fn start(&mut self) -> Result<(), Error> {
+        self.socket.set_nonblocking(false).map_err(Error::Socket)?;
+        while !self.dispatcher.shutdown.is_cancelled() {
+            self.dispatch_due()?;
+            let timeout = poll_timeout(self.schedule.until_next());
+            if timeout.is_zero() {
+                continue;
+            }
+            self.socket
+                .set_read_timeout(Some(timeout))
+                .map_err(Error::Socket)?;
+            match self.socket.recv_from(&mut self.buffer) {
+                Err(e) if is_timeout(&e) => {}
+                received => self.handle_received(received)?,
+            }
+        }
+        let mut schedule = std::mem::take(&mut self.schedule);
+        let drained = self.dispatcher.drain(&mut schedule, |packet, source| {
+            self.dispatch(&packet, source)
+        });
+        self.dispatcher.shutdown.reset();
+        drained
+    }
+
+    /// Waits up to ``timeout`` to receive a packet and dispatches it as ``start`` does, so the
+    /// server can be driven from another event loop. A ``timeout`` of zero only handles a packet
+    /// which has already arrived. Bundles held until their timetag are dispatched by whichever
+    /// call is running when they become due, so call this at least that often.
+    ///
+    /// Returns whether a packet was received.
+    ///
+    /// # Errors
+    /// Will return an ``Error::Socket`` if receiving a packet or sending a reply fails.
+    pub fn step(&mut self, timeout: Duration) -> Result<bool, Error> {
+        self.dispatch_due()?;
+        let timeout = self
+            .schedule
+            .until_next()
+            .map_or(timeout, |next| next.min(timeout));
+        if timeout.is_zero() {
+            self.socket.set_nonblocking(true).map_err(Error::Socket)?;
+        } else {
+            self.socket.set_nonblocking(false).map_err(Error::Socket)?;
+            self.socket
+                .set_read_timeout(Some(timeout))
+                .map_err(Error::Socket)?;
+        }
+        let received = match self.socket.recv_from(&mut self.buffer) {
+            Err(e) if is_timeout(&e) => false,
+            received => self.handle_received(received).map(|()| true)?,
+        };
+        self.dispatch_due()?;
+        Ok(received)
+    }
+
+    /// Receives packets until shut down like ``start``, but hands them to a pool of ``n_workers``
+    /// threads (at least one) to be dispatched, so slow handlers don't hold up receiving packets.
+    ///
+    /// Different routes may be handled at the same time, but each handler is only called by one
+    /// thread at a time. Messages in a bundle are dispatched in order by one worker, but separate
+    /// packets may be handled out of order.
+    ///
+    /// Once shut down, the workers finish the packets already received, and any held bundles
+    /// which become due, until the drain timeout passes. Packets still queued then are dropped,
+    /// though handlers already running are waited for.
+    ///
+    /// # Errors
+    /// Will return an ``Error::Socket`` if receiving a packet fails. If a worker fails to build or
+    /// send a reply, the server stops as ``start`` does, returning that error once the other
+    /// workers have finished the packets they are handling.
+    pub fn start_threaded(&mut self, n_workers: usize) -> Result<(), Error> {
+        self.socket.set_nonblocking(false).map_err(Error::Socket)?;
+        let mut buffer = std::mem::take(&mut self.buffer);
+        let mut schedule = std::mem::take(&mut self.schedule);
+        let server = &*self;
+        let (sender, packets) = mpsc::channel::<(OscPacket, SocketAddr)>();
+        let packets = Mutex::new(packets);
+        // Set once shut down, after which workers drop what's left in the queue
+        let deadline = OnceLock::new();
+        // The first error a worker failed with, which stops the server
+        let failure = Mutex::new(None);
+
+        let result = thread::scope(|scope| {
+            let workers: Vec<_> = (0..n_workers.max(1))
+                .map(|_| {
+                    scope.spawn(|| loop {
+                        let Ok((packet, source)) = lock(&packets).recv() else {
+                            return;
+                        };
+                        if deadline
+                            .get()
+                            .is_some_and(|deadline| Instant::now() >= *deadline)
+                        {
+                            continue;
+                        }
+                        if let Err(e) = server.dispatch(&packet, source) {
+                            lock(&failure).get_or_insert(e);
+                            return;
+                        }
+                    })
+                })
+                .collect();
+
+            let mut result = 'receive: loop {
+                if let Some(e) = lock(&failure).take() {
+                    // The other workers drop the packets still queued
+                    let _ = deadline.set(Instant::now());
+                    break Err(e);
+                }
+                if server.dispatcher.shutdown.is_cancelled() {
+                    let _ = deadline.set(Instant::now() + server.dispatcher.drain_timeout);
+                    // Fails only if every worker has stopped, which is reported once they're joined
+                    let _ = server.dispatcher.drain(&mut schedule, |packet, source| {
+                        sender.send((packet, source)).map_err(drop)
+                    });
+                    break Ok(());
+                }
+                for due in schedule.take_due(&server.dispatcher.limits) {
+                    if sender.send(due).is_err() {
+                        // Every worker has stopped
+                        break 'receive Ok(());
+                    }
+                }
+                let timeout = poll_timeout(schedule.until_next());
+                if timeout.is_zero() {
+                    continue;
+                }
+                if let Err(e) = server.socket.set_read_timeout(Some(timeout)) {
+                    break Err(Error::Socket(e));
+                }
+                let (length, source) = match server.socket.recv_from(&mut buffer) {
+                    Ok(from) => from,
+                    Err(e) if is_timeout(&e) => continue,
+                    Err(e) => break Err(Error::Socket(e)),
+                };
+                if !server.dispatcher.access.permits(source.ip()) {
+                    continue;
+                }
+                let Some(packet) = server.dispatcher.parse(&buffer[..length], source) else {
+                    continue;
+                };
+                if let Some(packet) = schedule.defer(packet, &source, &server.dispatcher.limits) {
+                    if sender.send((packet, source)).is_err() {
+                        break Ok(());
+                    }
+                }
+            };
+            drop(sender);
+
+            for worker in workers {
+                if let Err(payload) = worker.join() {
+                    panic::resume_unwind(payload);
+                }
+            }
+            if let (Ok(()), Some(e)) = (&result, lock(&failure).take()) {
+                result = Err(e);
+            }
+            result
+        });
+        self.buffer = buffer;
+        self.schedule = schedule;
+        self.dispatcher.shutdown.reset();
+        result
+    }
+
+    fn handle_received(&mut self, received: io::Result<(usize, SocketAddr)>) -> Result<(), Error> {
+        let (length, source) = received.map_err(Error::Socket)?;
+        if !self.dispatcher.access.permits(source.ip()) {
+            return Ok(());
+        }
+        let Some(packet) = self.dispatcher.parse(&self.buffer[..length], source) else {
+            return Ok(());
+        };
+        if let Some(packet) = self
+            .schedule
+            .defer(packet, &source, &self.dispatcher.limits)
+        {
+            self.dispatch(&packet, source)?;
+        }
+        Ok(())
+    }
+
+    fn dispatch_due(&mut self) -> Result<(), Error> {
+        for (packet, source) in self.schedule.take_due(&self.dispatcher.limits) {
+            self.dispatch(&packet, source)?;
+        }
+        Ok(())
+    }
+
+    fn dispatch(&self, packet: &OscPacket, source: SocketAddr) -> Result<(), Error> {
+        let destination = match packet {
+            // Each message in a bundle may reply somewhere different
+            OscPacket::Bundle(bundle) if !self.reply_policies.is_empty() => {
+                for packet in &bundle.content {
+                    self.dispatch(packet, source)?;
+                }
+                return Ok(());
+            }
+            OscPacket::Bundle(_) => source,
+            OscPacket::Message(msg) => self
+                .reply_policies
+                .iter()
+                .find(|(pattern, _)| crate::pattern::matches(pattern.as_str(), &msg.address))
+                .map_or(source, |(_, reply_to)| reply_to.destination(msg, source)),
+        };
+        for reply in self.dispatcher.replies(packet, source) {
+            let bytes = self.dispatcher.build(&reply)?;
+            let sent = self
+                .socket
+                .send_to(&bytes, destination)
+                .map_err(Error::Socket)?;
+            self.dispatcher.sent(destination, reply, &bytes, sent);
+        }
+        Ok(())
+    }
+}
+
+/// An OSC server over TCP, accepting any number of clients at once. Packets use OSC 1.0 stream
+/// framing (see ``FramedTcpStream``), and replies are sent back on the stream the message arrived
+/// on.
+///
+/// Routes behave exactly as they do for ``OscServer``.
+#[cfg(feature = "tcp")]
+#[allow(clippy::module_name_repetitions)]
+pub struct TcpOscServer {
+    listener: TcpListener,
+    dispatcher: Dispatcher,
+    connections: Mutex<HashMap<SocketAddr, TcpStream>>,
+    max_connections: usize,
+    buffer_size: usize,
+}
+
+#[cfg(feature = "tcp")]
+impl TcpOscServer {
+    /// Creates a new ``TcpOscServer``, listening at ``address``. ``buffer_size`` dictates the
+    /// maximum size packet that the server can receive from each client.
+    ///
+    /// As with ``OscServer::new``, whether ``[::]`` also accepts IPv4 clients depends on the
+    /// platform. To choose, bind a listener with ``ConnectionBuilder::with_only_v6`` and
+    /// ``ConnectionBuilder::tcp_listener``, which require the ``socket2`` feature, and pass it to
+    /// ``from_listener``.
+    ///
+    /// # Errors
+    /// If the listener cannot be bound, this function will return an ``Error::Socket``.
+    pub fn new<A: ToSocketAddrs>(address: A, buffer_size: usize) -> Result<Self, Error> {
+        let listener = TcpListener::bind(address).map_err(Error::Socket)?;
+        Ok(Self::from_listener(listener, buffer_size))
+    }
+
+    /// Creates a new ``TcpOscServer`` accepting clients on an already bound ``listener``. See
+    /// ``OscServer::from_socket``.
+    #[must_use]
+    pub fn from_listener(listener: TcpListener, buffer_size: usize) -> Self {
+        Self {
+            listener,
+            dispatcher: Dispatcher::new(),
+            connections: Mutex::new(HashMap::new()),
+            max_connections: DEFAULT_MAX_CONNECTIONS,
+            buffer_size,
+        }
+    }
+
+    dispatcher_builders!();
+
+    /// See ``OscServer::oscquery``.
+    ///
+    /// # Errors
+    /// Will return an ``Error::Socket`` if the listener cannot be bound, or this server's
+    /// address cannot be read.
+    #[cfg(feature = "oscquery")]
+    pub fn oscquery<A: ToSocketAddrs>(&self, address: A) -> Result<OscQueryServer, Error> {
+        OscQueryServer::new(
+            address,
+            self.handle(),
+            self.state(),
+            self.local_addr()?,
+            OscTransport::Tcp,
+        )
+    }
+
+    /// Calls ``hook`` with the address and ``Session`` of each client as it connects, before any
+    /// of its packets are handled, e.g. to store a per-connection context for its handlers in
+    /// the session, or log client churn.
+    #[must_use]
+    pub fn on_connect<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(SocketAddr, &mut Session) + Send + Sync + 'static,
+    {
+        self.dispatcher.on_connect = Some(Box::new(hook));
+        self
+    }
+
+    /// Calls ``hook`` with the address and ``Session`` of each client once it has disconnected,
+    /// or been disconnected, before the session is dropped, e.g. to clean up its subscriptions.
+    #[must_use]
+    pub fn on_disconnect<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(SocketAddr, &mut Session) + Send + Sync + 'static,
+    {
+        self.dispatcher.on_disconnect = Some(Box::new(hook));
+        self
+    }
+
+    /// Only accepts clients permitted by ``access``. Other clients are disconnected as soon as
+    /// they are accepted.
+    #[must_use]
+    pub fn with_access_list(mut self, access: AccessList) -> Self {
+        self.dispatcher.access = access;
+        self
+    }
+
+    /// Serves at most ``max_connections`` clients at once, 256 by default, as each is handled on
+    /// its own thread. Clients connecting while the server is full are disconnected as soon as
+    /// they are accepted.
+    #[must_use]
+    pub fn with_max_connections(mut self, max_connections: usize) -> Self {
+        self.max_connections = max_connections;
+        self
+    }
+
+    /// Sets the size of each connection's receive buffer, the largest packet the server can
+    /// receive.
+    #[must_use]
+    pub fn with_buffer_size(mut self, buffer_size: usize) -> Self {
+        self.buffer_size = buffer_size;
+        self
+    }
+
+    /// Drops packets larger than ``max_packet_size`` bytes without parsing them, reporting them
+    /// as failing with ``Error::MessageTooLarge``.
+    #[must_use]
+    pub fn with_max_packet_size(mut self, max_packet_size: usize) -> Self {
+        self.dispatcher.max_packet_size = Some(max_packet_size);
+        self
+    }
+
+    /// Returns the address the server is listening at.
+    ///
+    /// # Errors
+    /// Will return an ``Error::Socket`` if the address cannot be read from the listener.
+    pub fn local_addr(&self) -> Result<SocketAddr, Error> {
+        self.listener.local_addr().map_err(Error::Socket)
+    }
+
+    /// Returns the addresses of the currently connected clients.
+    pub fn connections(&self) -> Vec<SocketAddr> {
+        lock(&self.connections).keys().copied().collect()
+    }
+
+    /// Accepts clients and dispatches their packets until shut down with
+    /// ``ServerHandle::shutdown``, handling each client on its own thread, up to the maximum set
+    /// with ``with_max_connections``. Packets that fail to parse are ignored. A client is
+    /// disconnected if its stream fails, it sends a packet larger than the buffer size (as soon as
+    /// the packet's length arrives), or a reply to it cannot be built or sent.
+    ///
+    /// Once shut down, the server stops accepting clients and receiving packets, finishes the
+    /// packets being handled, and dispatches held bundles which become due, until the drain
+    /// timeout set with ``with_drain_timeout`` passes. It then disconnects every client and
+    /// returns ``Ok``, after which it can be started again.
+    ///
+    /// # Errors
+    /// Will return an ``Error::Socket`` if accepting a client fails. Every client is disconnected
+    /// before returning.
+    pub fn start(&self) -> Result<(), Error> {
+        // Accepting is polled, so a shutdown is noticed without a client connecting
+        self.listener.set_nonblocking(true).map_err(Error::Socket)?;
+        thread::scope(|scope| {
+            let result = loop {
+                if self.dispatcher.shutdown.is_cancelled() {
+                    break Ok(());
+                }
+                let (stream, peer) = match self.listener.accept() {
+                    Ok(accepted) => accepted,
+                    Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                        thread::sleep(SHUTDOWN_POLL_INTERVAL);
+                        continue;
+                    }
+                    Err(e) => break Err(Error::Socket(e)),
+                };
+                if !self.dispatcher.access.permits(peer.ip())
+                    || lock(&self.connections).len() >= self.max_connections
+                {
+                    continue;
+                }
+                // Streams may inherit the listener's non-blocking mode
+                if stream.set_nonblocking(false).is_err() {
+                    continue;
+                }
+                let Ok(tracked) = stream.try_clone() else {
+                    continue;
+                };
+                lock(&self.connections).insert(peer, tracked);
+                scope.spawn(move || {
+                    self.dispatcher.connected(peer);
+                    self.serve(FramedTcpStream::from_stream(stream), peer);
+                    lock(&self.connections).remove(&peer);
+                    self.dispatcher.disconnected(peer);
+                });
+            };
+            if result.is_ok() {
+                let deadline = Instant::now() + self.dispatcher.drain_timeout;
+                while !lock(&self.connections).is_empty() && Instant::now() < deadline {
+                    thread::sleep(SHUTDOWN_POLL_INTERVAL);
+                }
+            }
+            for stream in lock(&self.connections).values() {
+                let _ = stream.shutdown(Shutdown::Both);
+            }
+            result
+        })?;
+        self.dispatcher.shutdown.reset();
+        Ok(())
+    }
+
+    fn serve(&self, mut stream: FramedTcpStream, peer: SocketAddr) {
+        let mut buffer = vec![0; self.buffer_size];
+        let mut schedule = Schedule::new();
+        while !self.dispatcher.shutdown.is_cancelled() {
+            for (packet, ()) in schedule.take_due(&self.dispatcher.limits) {
+                if !self.reply(&mut stream, &packet, peer) {
+                    return;
+                }
+            }
+            let timeout = poll_timeout(schedule.until_next());
+            if timeout.is_zero() {
+                continue;
+            }
+            if stream.set_read_timeout(Some(timeout)).is_err() {
+                return;
+            }
+            let length = match stream.recv(&mut buffer) {
+                Ok(length) => length,
+                Err(e) if is_timeout(&e) => continue,
+                Err(_) => return,
+            };
+            let Some(packet) = self.dispatcher.parse(&buffer[..length], peer) else {
+                continue;
+            };
+            if let Some(packet) = schedule.defer(packet, &(), &self.dispatcher.limits) {
+                if !self.reply(&mut stream, &packet, peer) {
+                    return;
+                }
+            }
+        }
+        let _ = self.dispatcher.drain(&mut schedule, |packet, ()| {
+            self.reply(&mut stream, &packet, peer)
+                .then_some(())
+                .ok_or(())
+        });
+    }
+
+    /// Dispatches ``packet`` and sends the replies, returning ``false`` if the client has gone.
+    fn reply(&self, stream: &mut FramedTcpStream, packet: &OscPacket, peer: SocketAddr) -> bool {
+        for reply in self.dispatcher.replies(packet, peer) {
+            let Ok(bytes) = self.dispatcher.build(&reply) else {
+                return false;
+            };
+            match stream.send(&bytes) {
+                Ok(sent) => self.dispatcher.sent(peer, reply, &bytes, sent),
+                Err(_) => return false,
+            }
+        }
+        true
+    }
+}
+
+/// The routes and handlers of a server, independent of its transport.
+pub(crate) struct Dispatcher {
+    pub(crate) router: Arc<Mutex<Router<SharedHandler>>>,
+    default_handler: Mutex<Option<DefaultHandler>>,
+    pub(crate) error_reply: ErrorReply,
+    pub(crate) middleware: Vec<Middleware>,
+    pub(crate) metrics: Arc<Metrics>,
+    pub(crate) access: AccessList,
+    pub(crate) parse_options: ParseOptions,
+    pub(crate) max_packet_size: Option<usize>,
+    pub(crate) parse_error: Option<ParseErrorHandler>,
+    pub(crate) pool: BufferPool,
+    pub(crate) sessions: Sessions,
+    /// Whether any route was registered which may use its sender's session.
+    pub(crate) session_routes: bool,
+    pub(crate) events: Option<Sender<ServerEvent>>,
+    pub(crate) limits: Vec<(OscPattern, RateLimit)>,
+    pub(crate) state: StateStore,
+    pub(crate) record_state: bool,
+    pub(crate) namespace: Arc<OscNamespace>,
+    pub(crate) observers: Observers,
+    pub(crate) schema: Option<OscSchema>,
+    pub(crate) auth: Option<SharedSecretAuth>,
+    pub(crate) access_levels: Router<Access>,
+    pub(crate) writers: AccessList,
+    pub(crate) on_connect: Option<ConnectionHook>,
+    pub(crate) on_disconnect: Option<ConnectionHook>,
+    pub(crate) shutdown: CancelFlag,
+    pub(crate) drain_timeout: Duration,
+    pub(crate) catch_panics: bool,
+}
+
+impl Dispatcher {
+    pub(crate) fn new() -> Self {
+        Self {
+            router: Arc::new(Mutex::new(Router::new())),
+            default_handler: Mutex::new(None),
+            error_reply: Box::new(|address, error| Some(default_error_reply(address, error))),
+            middleware: Vec::new(),
+            metrics: Arc::new(Metrics::default()),
+            access: AccessList::new(),
+            parse_options: ParseOptions::default(),
+            max_packet_size: None,
+            parse_error: None,
+            pool: BufferPool::default(),
+            sessions: Sessions::default(),
+            session_routes: false,
+            events: None,
+            limits: Vec::new(),
+            state: StateStore::new(),
+            record_state: false,
+            namespace: Arc::new(OscNamespace::new()),
+            observers: Observers::default(),
+            schema: None,
+            auth: None,
+            access_levels: Router::new(),
+            writers: AccessList::new(),
+            on_connect: None,
+            on_disconnect: None,
+            shutdown: CancelFlag::new(),
+            drain_timeout: DEFAULT_DRAIN_TIMEOUT,
+            catch_panics: true,
+        }
+    }
+
+    pub(crate) fn handle(&self) -> ServerHandle {
+        ServerHandle {
+            router: Arc::clone(&self.router),
+            metrics: Arc::clone(&self.metrics),
+            namespace: Arc::clone(&self.namespace),
+            shutdown: self.shutdown.clone(),
+        }
+    }
+
+    /// Once shut down, waits for the bundles held in ``schedule`` which become due within the
+    /// drain timeout and passes them to ``dispatch``, then drops the rest.
+    pub(crate) fn drain<T: Clone, E>(
+        &self,
+        schedule: &mut Schedule<T>,
+        mut dispatch: impl FnMut(OscPacket, T) -> Result<(), E>,
+    ) -> Result<(), E> {
+        let deadline = Instant::now() + self.drain_timeout;
+        let drained = loop {
+            if let Err(e) = schedule
+                .take_due(&self.limits)
+                .into_iter()
+                .try_for_each(|(packet, due)| dispatch(packet, due))
+            {
+                break Err(e);
+            }
+            match schedule.until_next() {
+                Some(next) if Instant::now() + next <= deadline => thread::sleep(next),
+                _ => break Ok(()),
+            }
+        };
+        *schedule = Schedule::new();
+        drained
+    }
+
+    pub(crate) fn namespace(&self) -> OscNamespace {
+        namespace(&self.namespace, &self.router)
+    }
+
+    pub(crate) fn set_default_handler<F, R>(&mut self, mut handler: F)
+    where
+        F: FnMut(&OscMessage) -> R + Send + 'static,
+        R: IntoIterator<Item = OscMessage>,
+    {
+        *lock(&self.default_handler) = Some(Box::new(move |msg: &OscMessage| {
+            handler(msg).into_iter().collect()
+        }));
+    }
+
+    /// Registers the introspection routes: ``/osc/ping`` replies with its arguments,
+    /// ``/osc/routes`` with the pattern of every route, and ``/osc/version`` with the crate's
+    /// version.
+    pub(crate) fn add_introspection(&self) -> Result<(), Error> {
+        // Weak, as the router owns this handler
+        let weak_router = Arc::downgrade(&self.router);
+        let mut router = lock(&self.router);
+        router.insert_route(
+            "/osc/ping",
+            into_handler(|_: &OscAddress, msg: &OscMessage| Some(msg.args[..].to_vec())),
+        )?;
+        router.insert_route(
+            "/osc/routes",
+            into_handler(move |_: &OscAddress, _: &OscMessage| -> Option<Vec<Arg>> {
+                let router = weak_router.upgrade()?;
+                let patterns = lock(&router)
+                    .routes()
+                    .map(|(pattern, _)| Arg::Str(pattern.to_string()))
+                    .collect();
+                Some(patterns)
+            }),
+        )?;
+        router.insert_route(
+            "/osc/version",
+            into_handler(|_: &OscAddress, _: &OscMessage| {
+                Some(vec![Arg::Str(env!("CARGO_PKG_VERSION").to_string())])
+            }),
+        )
+    }
+
+    /// Registers ``/osc/clock``, which replies to a timetag with it, followed by the times it was
+    /// received and answered.
+    pub(crate) fn add_clock_sync(&self) -> Result<(), Error> {
+        lock(&self.router).insert_route(
+            "/osc/clock",
+            into_handler(|_: &OscAddress, msg: &OscMessage| {
+                let received = OscTime::from(SystemTime::now());
+                let sent @ Arg::Time(_) = msg.args.first()? else {
+                    return None;
+                };
+                let answered = OscTime::from(SystemTime::now());
+                Some(vec![sent.clone(), Arg::Time(received), Arg::Time(answered)])
+            }),
+        )
+    }
+
+    /// Registers ``/auth``, which authenticates senders with ``auth``, and requires them to
+    /// before their other messages are dispatched.
+    pub(crate) fn add_auth(&mut self, auth: SharedSecretAuth) -> Result<(), Error> {
+        let login = auth.clone();
+        lock(&self.router).insert_route(
+            AUTH_ADDRESS,
+            into_session_handler(
+                move |_: &OscAddress, msg: &OscMessage, session: &mut Session| {
+                    login.login(msg, session)
+                },
+            ),
+        )?;
+        self.auth = Some(auth);
+        Ok(())
+    }
+
+    /// Registers each route of ``router`` under each of ``prefixes``, stripping the prefix from
+    /// the address its handler is given.
+    pub(crate) fn mount<I, S>(&self, prefixes: I, router: Router<Handler>) -> Result<(), Error>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let shared: Vec<_> = router
+            .into_routes()
+            .map(|(pattern, handler)| (pattern, Arc::new(Mutex::new(handler))))
+            .collect();
+        let mut own_router = lock(&self.router);
+        for prefix in prefixes {
+            let prefix = prefix.as_ref().trim_end_matches('/');
+            let depth = prefix.split('/').skip(1).count();
+            for (pattern, handler) in &shared {
+                let handler = Arc::clone(handler);
+                let mounted: Handler = Box::new(
+                    move |address: &OscAddress, msg: &OscMessage, session: &mut Session| {
+                        let stripped = OscAddress::from_segments(address.segments().skip(depth))?;
+                        lock(&handler)(&stripped, msg, session)
+                    },
+                );
+                own_router.insert_route(
+                    format!("{prefix}{pattern}").as_str(),
+                    Arc::new(Mutex::new(mounted)),
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Records every message's arguments in ``state``, and registers ``/osc/state``, which replies
+    /// with the recorded values of the addresses matching the patterns it is sent as
+    /// ``Arg::Str``s (or of every address, if it is sent none).
+    pub(crate) fn add_state_query(&mut self) -> Result<(), Error> {
+        self.record_state = true;
+        let state = self.state.clone();
+        lock(&self.router).insert_route(
+            "/osc/state",
+            into_handler(move |_: &OscAddress, msg: &OscMessage| {
+                if msg.args.is_empty() {
+                    return Ok(state.messages());
+                }
+                let mut replies = Vec::new();
+                for arg in &msg.args {
+                    let pattern = OscPattern::new(String::try_from(arg.clone())?)?;
+                    replies.extend(state.matching(&pattern));
+                }
+                Ok::<_, Error>(replies)
+            }),
+        )
+    }
+
+    /// Parses a packet received from ``source``, recording it in the metrics. Returns ``None`` if
+    /// it is invalid or too large.
+    pub(crate) fn parse(&self, bytes: &[u8], source: SocketAddr) -> Option<OscPacket> {
+        self.metrics.received(bytes.len());
+        self.observers.received(bytes, Some(source));
+        // Keeps the sender's session alive, and reports it if it's new
+        self.session(source, false);
+        let res = match self.max_packet_size {
+            Some(limit) if bytes.len() > limit => Err(Error::MessageTooLarge {
+                size: bytes.len(),
+                limit,
+            }),
+            _ => OscPacket::parse_bytes_with(bytes, &self.parse_options),
+        };
+        match res {
+            Ok(packet) => Some(packet),
+            Err(error) => {
+                self.metrics.parse_failures.fetch_add(1, Ordering::Relaxed);
+                if let Some(parse_error) = &self.parse_error {
+                    parse_error(source, bytes, &error);
+                }
+                self.emit(|| ServerEvent::ParseError { source, error });
+                None
+            }
+        }
+    }
+
+    /// Calls the ``on_connect`` hook, if any, for a stream client which has connected from
+    /// ``peer``, starting its session.
+    #[cfg_attr(not(feature = "tcp"), allow(dead_code))]
+    pub(crate) fn connected(&self, peer: SocketAddr) {
+        let session = self.session(peer, true);
+        if let (Some(on_connect), Some(session)) = (&self.on_connect, session) {
+            on_connect(peer, &mut lock(&session));
+        }
+    }
+
+    /// Calls the ``on_disconnect`` hook, if any, for a stream client at ``peer`` which has
+    /// disconnected, then ends its session.
+    #[cfg_attr(not(feature = "tcp"), allow(dead_code))]
+    pub(crate) fn disconnected(&self, peer: SocketAddr) {
+        if let Some(on_disconnect) = &self.on_disconnect {
+            if let Some(session) = self.session(peer, true) {
+                on_disconnect(peer, &mut lock(&session));
+            }
+        }
+        self.sessions.remove(peer);
+    }
+
+    /// Encodes ``reply`` in a buffer from the pool.
+    pub(crate) fn build(&self, reply: &OscMessage) -> Result<PooledBuffer<'_>, Error> {
+        let mut bytes = self.pool.take();
+        reply.build_into(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// Records that ``reply`` was sent to ``destination`` as ``bytes``, taking ``size`` bytes.
+    pub(crate) fn sent(
+        &self,
+        destination: SocketAddr,
+        reply: OscMessage,
+        bytes: &[u8],
+        size: usize,
+    ) {
+        self.metrics.sent(size);
+        self.observers.sent(bytes, Some(destination));
+        self.emit(|| ServerEvent::ReplySent {
+            destination,
+            message: reply,
+        });
+    }
+
+    /// Returns ``source``'s session, starting one and reporting the new sender if it has none, or
+    /// ``None`` if the server doesn't need sessions or already has as many as it may keep.
+    /// ``connected`` marks ``source`` as a connected stream client.
+    fn session(&self, source: SocketAddr, connected: bool) -> Option<Arc<Mutex<Session>>> {
+        let needed = self.session_routes
+            || self.auth.is_some()
+            || self.on_connect.is_some()
+            || self.on_disconnect.is_some()
+            || self.events.is_some();
+        if !needed {
+            return None;
+        }
+        let (session, new) = self.sessions.touch(source, connected)?;
+        if new {
+            self.emit(|| ServerEvent::ClientSeen { source });
+        }
+        Some(session)
+    }
+
+    /// Sends an event to the listener, if there is one. The event is only built if it is needed.
+    fn emit(&self, event: impl FnOnce() -> ServerEvent) {
+        if let Some(events) = &self.events {
+            // The listener going away shouldn't stop the server
+            let _ = events.send(event());
+        }
+    }
+
+    /// Dispatches ``packet``, sent by ``source``, to its handlers, returning their replies.
+    pub(crate) fn replies(&self, packet: &OscPacket, source: SocketAddr) -> Vec<OscMessage> {
+        match packet {
+            OscPacket::Message(msg) => {
+                if let Some(auth) = &self.auth {
+                    let authenticated = self
+                        .session(source, false)
+                        .is_some_and(|session| auth.is_authenticated(&lock(&session)));
+                    if msg.address != AUTH_ADDRESS && !authenticated {
+                        return auth.rejection().into_iter().collect();
+                    }
+                }
+                if let Some(Err(error)) = self.schema.as_ref().map(|schema| schema.check(msg)) {
+                    return OscAddress::new(&msg.address)
+                        .ok()
+                        .and_then(|address| (self.error_reply)(&address, &error))
+                        .into_iter()
+                        .collect();
+                }
+                // Writes the sender may not make shouldn't change the recorded state either
+                if self.record_state
+                    && OscAddress::new(&msg.address)
+                        .is_ok_and(|address| self.permits(&address, msg, source))
+                {
+                    self.state.record(msg);
+                }
+                self.emit(|| ServerEvent::MessageReceived {
+                    source,
+                    message: msg.clone(),
+                });
+                self.through_middleware(0, msg, source)
+            }
+            OscPacket::Bundle(bundle) => bundle
+                .content
+                .iter()
+                .flat_map(|packet| self.replies(packet, source))
+                .collect(),
+        }
+    }
+
+    /// Passes ``msg`` through the middleware from ``index`` onwards, then to the routes.
+    fn through_middleware(
+        &self,
+        index: usize,
+        msg: &OscMessage,
+        source: SocketAddr,
+    ) -> Vec<OscMessage> {
+        match self.middleware.get(index) {
+            Some(middleware) => {
+                middleware(msg, &|msg| self.through_middleware(index + 1, msg, source))
+            }
+            None => self.route(msg, source),
+        }
+    }
+
+    /// Returns whether ``source`` may send ``msg`` to the route matched at ``address``. A message
+    /// without arguments reads the parameter there, and one with arguments writes it.
+    fn permits(&self, address: &OscAddress, msg: &OscMessage, source: SocketAddr) -> bool {
+        let level = self
+            .access_levels
+            .matching(address.as_str())
+            .next()
+            .map_or(Access::ReadWrite, |(_, level)| *level);
+        if msg.args.is_empty() {
+            matches!(level, Access::ReadOnly | Access::ReadWrite)
+        } else {
+            matches!(level, Access::WriteOnly | Access::ReadWrite)
+                && self.writers.permits(source.ip())
+        }
+    }
+
+    fn route(&self, msg: &OscMessage, source: SocketAddr) -> Vec<OscMessage> {
+        // Release the routes before calling handlers, so they can be changed meanwhile
+        let handlers: Vec<_> = lock(&self.router)
+            .matching_routes(&msg.address)
+            .map(|(route, address, handler)| {
+                self.metrics.hit(route);
+                (address, Arc::clone(handler))
+            })
+            .collect();
+        let mut replies = Vec::new();
+        let shared = self.session(source, false);
+        let mut guard = shared.as_deref().map(lock);
+        let mut fresh;
+        let session = if let Some(session) = guard.as_deref_mut() {
+            session
+        } else {
+            fresh = Session::new(source);
+            &mut fresh
+        };
+        for (address, handler) in &handlers {
+            if !self.permits(address, msg, source) {
+                let error = Error::AccessDenied(address.to_string());
+                replies.extend((self.error_reply)(address, &error));
+                continue;
+            }
+            let mut handler = lock(handler);
+            let result = if self.catch_panics {
+                // The handler's state may be left inconsistent, but that's no worse than the
+                // server stopping, and the panic is reported
+                panic::catch_unwind(AssertUnwindSafe(|| (handler)(address, msg, session)))
+                    .unwrap_or_else(|payload| Err(Error::HandlerPanicked(panic_message(&*payload))))
+            } else {
+                (handler)(address, msg, session)
+            };
+            match result {
+                Ok(mut handler_replies) => replies.append(&mut handler_replies),
+                Err(e) => replies.extend((self.error_reply)(address, &e)),
+            }
+        }
+        if handlers.is_empty() {
+            if let Some(handler) = &mut *lock(&self.default_handler) {
+                replies = if self.catch_panics {
+                    panic::catch_unwind(AssertUnwindSafe(|| handler(msg))).unwrap_or_else(
+                        |payload| {
+                            let error = Error::HandlerPanicked(panic_message(&*payload));
+                            OscAddress::new(&msg.address)
+                                .ok()
+                                .and_then(|address| (self.error_reply)(&address, &error))
+                                .into_iter()
+                                .collect()
+                        },
+                    )
+                } else {
+                    handler(msg)
+                };
+            }
+        }
+        replies
+    }
+}
+
+/// A handle to the routes of an ``OscServer`` or ``TcpOscServer``, which can be used to add and
+/// remove routes from other threads while the server is running, e.g. as plugins are loaded, and
+/// to shut it down.
+#[derive(Clone)]
+pub struct ServerHandle {
+    router: Arc<Mutex<Router<SharedHandler>>>,
+    metrics: Arc<Metrics>,
+    namespace: Arc<OscNamespace>,
+    shutdown: CancelFlag,
+}
+
+impl ServerHandle {
+    /// Registers ``handler`` for messages sent to any address matching ``pattern``, as with
+    /// ``OscServer::add_route``.
+    ///
+    /// # Errors
+    /// Will return ``Error::Address`` if ``pattern`` is not a valid ``OscPattern``, or
+    /// ``Error::DuplicateRoute`` if a route is already registered for it.
+    pub fn add_route<P, F, R>(&self, pattern: P, handler: F) -> Result<(), Error>
+    where
+        P: TryInto<OscPattern>,
+        Error: From<P::Error>,
+        F: FnMut(&OscAddress, &OscMessage) -> R + Send + 'static,
+        R: IntoReplies,
+    {
+        lock(&self.router).insert_route(pattern, into_handler(handler))
+    }
+
+    /// Returns a snapshot of the server's metrics.
+    #[must_use]
+    pub fn metrics(&self) -> ServerMetrics {
+        self.metrics.snapshot()
+    }
+
+    /// Removes the route registered for ``pattern``. Returns whether there was one.
+    #[must_use]
+    pub fn remove_route(&self, pattern: &str) -> bool {
+        lock(&self.router).remove_route(pattern).is_some()
+    }
+
+    /// Returns the server's namespace, including any routes added since it started.
+    #[must_use]
+    pub fn namespace(&self) -> OscNamespace {
+        namespace(&self.namespace, &self.router)
+    }
+
+    /// Asks the server to stop, e.g. when the application quits. Its ``start`` notices within
+    /// about 50 milliseconds, drains as described there, and returns ``Ok``. If the server isn't
+    /// running, the next ``start`` returns as soon as it has drained.
+    pub fn shutdown(&self) {
+        self.shutdown.cancel();
+    }
+}
+
+/// Returns the message a panic was raised with, if it was a string.
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(ToString::to_string)
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
+
+/// Returns how long a server should wait for a packet, given how long until its next held bundle
+/// is due, so that it still notices being shut down promptly.
+pub(crate) fn poll_timeout(next: Option<Duration>) -> Duration {
+    next.map_or(SHUTDOWN_POLL_INTERVAL, |next| {
+        next.min(SHUTDOWN_POLL_INTERVAL)
+    })
+}
+
+/// A snapshot of a server's counters, e.g. for a monitoring dashboard.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[allow(clippy::module_name_repetitions)]
+pub struct ServerMetrics {
+    /// Packets received, whether or not they could be parsed.
+    pub packets_received: u64,
+    /// Received packets which could not be parsed.
+    pub parse_failures: u64,
+    /// Number of messages dispatched to each route, by the pattern it was registered with.
+    pub route_hits: HashMap<String, u64>,
+    pub bytes_received: u64,
+    pub bytes_sent: u64,
+}
+
+/// Something a server did, sent to the channel given to ``OscServer::with_events`` so that a
+/// frontend can display live traffic.
+#[derive(Debug)]
+#[allow(clippy::module_name_repetitions)]
+pub enum ServerEvent {
+    /// A message is about to be dispatched. Messages in a bundle held until its timetag are sent
+    /// when it becomes due.
+    MessageReceived {
+        source: SocketAddr,
+        message: OscMessage,
+    },
+    /// A packet from ``source`` could not be parsed.
+    ParseError { source: SocketAddr, error: Error },
+    /// A reply was sent, or for ``MioOscServer``'s TCP clients, queued to be sent.
+    ReplySent {
+        destination: SocketAddr,
+        message: OscMessage,
+    },
+    /// A packet arrived from a sender with no session, either because it is new, or because its
+    /// session expired or its TCP connection closed. A TCP client is reported as it connects.
+    /// Only sent while the server can keep a session for the sender.
+    ClientSeen { source: SocketAddr },
+}
+
+/// The live counters behind ``ServerMetrics``, shared between a server and its handles.
+#[derive(Default)]
+pub(crate) struct Metrics {
+    packets_received: AtomicU64,
+    parse_failures: AtomicU64,
+    route_hits: Mutex<HashMap<String, u64>>,
+    bytes_received: AtomicU64,
+    bytes_sent: AtomicU64,
+}
+
+impl Metrics {
+    fn received(&self, bytes: usize) {
+        self.packets_received.fetch_add(1, Ordering::Relaxed);
+        self.bytes_received
+            .fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn sent(&self, bytes: usize) {
+        self.bytes_sent.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    fn hit(&self, route: &OscPattern) {
+        *lock(&self.route_hits).entry(route.to_string()).or_default() += 1;
+    }
+
+    pub(crate) fn snapshot(&self) -> ServerMetrics {
+        ServerMetrics {
+            packets_received: self.packets_received.load(Ordering::Relaxed),
+            parse_failures: self.parse_failures.load(Ordering::Relaxed),
+            route_hits: lock(&self.route_hits).clone(),
+            bytes_received: self.bytes_received.load(Ordering::Relaxed),
+            bytes_sent: self.bytes_sent.load(Ordering::Relaxed),
+        }
+    }
+}
+
+pub(crate) fn into_handler<F, R>(mut handler: F) -> SharedHandler
+where
+    F: FnMut(&OscAddress, &OscMessage) -> R + Send + 'static,
+    R: IntoReplies,
+{
+    into_session_handler(
+        move |address: &OscAddress, msg: &OscMessage, _: &mut Session| handler(address, msg),
+    )
+}
+
+pub(crate) fn into_session_handler<F, R>(mut handler: F) -> SharedHandler
+where
+    F: FnMut(&OscAddress, &OscMessage, &mut Session) -> R + Send + 'static,
+    R: IntoReplies,
+{
+    Arc::new(Mutex::new(Box::new(
+        move |address: &OscAddress, msg: &OscMessage, session: &mut Session| {
+            handler(address, msg, session).into_replies(address)
+        },
+    )))
+}
+
+/// Returns ``described``, with a node added for each route at a plain address.
+fn namespace(described: &OscNamespace, router: &Mutex<Router<SharedHandler>>) -> OscNamespace {
+    let mut namespace = described.clone();
+    namespace.add_routes(lock(router).routes().map(|(pattern, _)| pattern));
+    namespace
+}
+
+/// Returns whether ``error`` is a read timing out, which is reported differently across platforms.
+pub(crate) fn is_timeout(error: &std::io::Error) -> bool {
+    matches!(error.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut)
+}
+
+pub(crate) fn default_error_reply(address: &OscAddress, error: &Error) -> OscMessage {
+    OscMessage::new(
+        "/error",
+        vec![Arg::Str(address.to_string()), Arg::Str(error.to_string())],
+    )
+}
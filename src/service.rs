@@ -0,0 +1,131 @@
+use crate::{errors::Error, OscMessage};
+
+/// Handles a message, returning the reply to send back, if any. Services can be composed with the
+/// combinators here, and tested by calling them directly, without any sockets, then mounted on a
+/// server with ``OscServer::add_service``.
+///
+/// Implemented for ``FnMut(&OscMessage) -> Result<Option<OscMessage>, Error>`` closures.
+pub trait OscService: Send + 'static {
+    /// Handles ``msg``, returning the reply to it, if any.
+    ///
+    /// # Errors
+    /// Will return the service's error, if it failed. Servers reply with their ``ErrorReply``.
+    fn call(&mut self, msg: &OscMessage) -> Result<Option<OscMessage>, Error>;
+
+    /// Passes the reply of ``self`` to ``next``, replying with whatever ``next`` does, e.g. to
+    /// validate a message before applying it. If ``self`` doesn't reply, neither does this.
+    fn and_then<S: OscService>(self, next: S) -> AndThen<Self, S>
+    where
+        Self: Sized,
+    {
+        AndThen(self, next)
+    }
+
+    /// Passes the message to ``fallback`` if ``self`` doesn't reply to it.
+    fn or<S: OscService>(self, fallback: S) -> Or<Self, S>
+    where
+        Self: Sized,
+    {
+        Or(self, fallback)
+    }
+
+    /// Passes the message to ``fallback`` if ``self`` fails, e.g. to try a cache before a slower
+    /// source. ``self``'s error is discarded.
+    fn or_else<S: OscService>(self, fallback: S) -> OrElse<Self, S>
+    where
+        Self: Sized,
+    {
+        OrElse(self, fallback)
+    }
+
+    /// Only calls ``self`` for messages ``predicate`` returns ``true`` for, not replying to the
+    /// rest.
+    fn filter<P>(self, predicate: P) -> Filter<Self, P>
+    where
+        Self: Sized,
+        P: FnMut(&OscMessage) -> bool + Send + 'static,
+    {
+        Filter(self, predicate)
+    }
+
+    /// Transforms each reply of ``self`` with ``f``, e.g. to move it to another address.
+    fn map_reply<F>(self, f: F) -> MapReply<Self, F>
+    where
+        Self: Sized,
+        F: FnMut(OscMessage) -> OscMessage + Send + 'static,
+    {
+        MapReply(self, f)
+    }
+}
+
+impl<F> OscService for F
+where
+    F: FnMut(&OscMessage) -> Result<Option<OscMessage>, Error> + Send + 'static,
+{
+    fn call(&mut self, msg: &OscMessage) -> Result<Option<OscMessage>, Error> {
+        self(msg)
+    }
+}
+
+/// See ``OscService::and_then``.
+pub struct AndThen<A, B>(A, B);
+
+impl<A: OscService, B: OscService> OscService for AndThen<A, B> {
+    fn call(&mut self, msg: &OscMessage) -> Result<Option<OscMessage>, Error> {
+        match self.0.call(msg)? {
+            Some(reply) => self.1.call(&reply),
+            None => Ok(None),
+        }
+    }
+}
+
+/// See ``OscService::or``.
+pub struct Or<A, B>(A, B);
+
+impl<A: OscService, B: OscService> OscService for Or<A, B> {
+    fn call(&mut self, msg: &OscMessage) -> Result<Option<OscMessage>, Error> {
+        match self.0.call(msg)? {
+            Some(reply) => Ok(Some(reply)),
+            None => self.1.call(msg),
+        }
+    }
+}
+
+/// See ``OscService::or_else``.
+pub struct OrElse<A, B>(A, B);
+
+impl<A: OscService, B: OscService> OscService for OrElse<A, B> {
+    fn call(&mut self, msg: &OscMessage) -> Result<Option<OscMessage>, Error> {
+        self.0.call(msg).or_else(|_| self.1.call(msg))
+    }
+}
+
+/// See ``OscService::filter``.
+pub struct Filter<S, P>(S, P);
+
+impl<S, P> OscService for Filter<S, P>
+where
+    S: OscService,
+    P: FnMut(&OscMessage) -> bool + Send + 'static,
+{
+    fn call(&mut self, msg: &OscMessage) -> Result<Option<OscMessage>, Error> {
+        if (self.1)(msg) {
+            self.0.call(msg)
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+/// See ``OscService::map_reply``.
+pub struct MapReply<S, F>(S, F);
+
+impl<S, F> OscService for MapReply<S, F>
+where
+    S: OscService,
+    F: FnMut(OscMessage) -> OscMessage + Send + 'static,
+{
+    fn call(&mut self, msg: &OscMessage) -> Result<Option<OscMessage>, Error> {
+        Ok(self.0.call(msg)?.map(&mut self.1))
+    }
+}
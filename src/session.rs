@@ -0,0 +1,161 @@
+use std::{
+    any::{Any, TypeId},
+    collections::{hash_map, HashMap},
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use crate::lock;
+
+/// State a server keeps for one sender, created when its first packet arrives. Handlers
+/// registered with ``add_session_route`` can store any values in it, one per type, e.g. whether
+/// the sender has authenticated or which addresses it has subscribed to.
+///
+/// Sessions are only kept by servers which need them: those with session or mounted routes,
+/// authentication, connection hooks, or an events listener. Senders beyond the maximum set with
+/// ``with_max_sessions`` are given a fresh session for each message instead.
+pub struct Session {
+    peer: SocketAddr,
+    created: Instant,
+    values: HashMap<TypeId, Box<dyn Any + Send>>,
+}
+
+impl Session {
+    pub(crate) fn new(peer: SocketAddr) -> Self {
+        Self {
+            peer,
+            created: Instant::now(),
+            values: HashMap::new(),
+        }
+    }
+
+    /// Returns the address of the sender this session belongs to.
+    #[must_use]
+    pub fn peer(&self) -> SocketAddr {
+        self.peer
+    }
+
+    /// Returns when the session was created.
+    #[must_use]
+    pub fn created(&self) -> Instant {
+        self.created
+    }
+
+    #[must_use]
+    pub fn get<T: Any + Send>(&self) -> Option<&T> {
+        self.values.get(&TypeId::of::<T>())?.downcast_ref()
+    }
+
+    pub fn get_mut<T: Any + Send>(&mut self) -> Option<&mut T> {
+        self.values.get_mut(&TypeId::of::<T>())?.downcast_mut()
+    }
+
+    /// Stores ``value``, returning the ``T`` it replaced, if any.
+    pub fn insert<T: Any + Send>(&mut self, value: T) -> Option<T> {
+        self.values
+            .insert(TypeId::of::<T>(), Box::new(value))
+            .and_then(|old| old.downcast().ok())
+            .map(|old| *old)
+    }
+
+    pub fn remove<T: Any + Send>(&mut self) -> Option<T> {
+        self.values
+            .remove(&TypeId::of::<T>())
+            .and_then(|old| old.downcast().ok())
+            .map(|old| *old)
+    }
+}
+
+/// How long a sender's session lasts after its last packet, unless set with
+/// ``OscServer::with_session_timeout``.
+const DEFAULT_IDLE_TIMEOUT: Duration = Duration::from_mins(5);
+
+/// How many senders may have a session at once, unless set with ``OscServer::with_max_sessions``.
+const DEFAULT_MAX_SESSIONS: usize = 4096;
+
+/// Every sender's ``Session``, each behind its own lock so different senders can be handled
+/// concurrently.
+pub(crate) struct Sessions {
+    table: Mutex<Table>,
+    pub(crate) idle_timeout: Duration,
+    pub(crate) capacity: usize,
+}
+
+struct Table {
+    entries: HashMap<SocketAddr, Entry>,
+    /// When expired sessions are next dropped, or ``None`` if they never expire.
+    next_sweep: Option<Instant>,
+}
+
+struct Entry {
+    last_seen: Instant,
+    /// Whether the sender is a connected stream client, whose session lasts until it disconnects.
+    connected: bool,
+    session: Arc<Mutex<Session>>,
+}
+
+impl Default for Sessions {
+    fn default() -> Self {
+        Self {
+            table: Mutex::new(Table {
+                entries: HashMap::new(),
+                next_sweep: Some(Instant::now()),
+            }),
+            idle_timeout: DEFAULT_IDLE_TIMEOUT,
+            capacity: DEFAULT_MAX_SESSIONS,
+        }
+    }
+}
+
+impl Sessions {
+    /// Returns ``peer``'s session, and whether it was just started, marking it as active. A new
+    /// session is started if it had none, or it has been idle for longer than the timeout, unless
+    /// the maximum number of senders already have one, in which case this returns ``None``.
+    /// ``connected`` marks ``peer`` as a connected stream client.
+    pub(crate) fn touch(
+        &self,
+        peer: SocketAddr,
+        connected: bool,
+    ) -> Option<(Arc<Mutex<Session>>, bool)> {
+        let now = Instant::now();
+        let timeout = self.idle_timeout;
+        let mut table = lock(&self.table);
+        // Expired sessions are replaced as their sender returns, so they only need dropping
+        // occasionally to make room for others
+        if table.next_sweep.is_some_and(|at| now >= at) {
+            table.entries.retain(|_, entry| {
+                entry.connected || now.duration_since(entry.last_seen) <= timeout
+            });
+            table.next_sweep = now.checked_add(timeout);
+        }
+        let full = table.entries.len() >= self.capacity;
+        match table.entries.entry(peer) {
+            hash_map::Entry::Occupied(mut occupied) => {
+                let entry = occupied.get_mut();
+                let expired = !entry.connected && now.duration_since(entry.last_seen) > timeout;
+                if expired {
+                    entry.session = Arc::new(Mutex::new(Session::new(peer)));
+                }
+                entry.last_seen = now;
+                entry.connected |= connected;
+                Some((Arc::clone(&entry.session), expired))
+            }
+            hash_map::Entry::Vacant(_) if full => None,
+            hash_map::Entry::Vacant(vacant) => {
+                let session = Arc::new(Mutex::new(Session::new(peer)));
+                vacant.insert(Entry {
+                    last_seen: now,
+                    connected,
+                    session: Arc::clone(&session),
+                });
+                Some((session, true))
+            }
+        }
+    }
+
+    #[cfg_attr(not(feature = "tcp"), allow(dead_code))]
+    pub(crate) fn remove(&self, peer: SocketAddr) {
+        lock(&self.table).entries.remove(&peer);
+    }
+}
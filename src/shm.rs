@@ -0,0 +1,348 @@
+use std::{
+    cell::Cell,
+    fs::{File, OpenOptions},
+    io::{self, ErrorKind},
+    net::ToSocketAddrs,
+    path::Path,
+    ptr,
+    sync::atomic::{AtomicU64, Ordering},
+    thread,
+    time::{Duration, Instant},
+};
+
+use memmap2::MmapMut;
+
+use crate::sockets::Connection;
+
+/// Marks a file as holding a pair of rings, ``OSCR``.
+const MAGIC: u32 = 0x4f53_4352;
+/// Where the first ring starts, after the magic number and capacity.
+const HEADER_LEN: usize = 64;
+/// Where a ring's data starts, after its write and read positions, which are on separate cache
+/// lines so the two processes don't contend for them.
+const RING_HEADER_LEN: usize = 128;
+/// How many times a blocking ``recv`` checks for data before yielding to other threads.
+const SPIN_LIMIT: u32 = 1000;
+
+/// A ``Connection`` to another process on the same machine over a pair of ring buffers in shared
+/// memory, one for each direction, e.g. between a DAW plugin and its companion app. Sending and
+/// receiving only copy the packet in or out of the mapping, without a system call, so packets
+/// arrive within microseconds.
+///
+/// One process creates the rings in a file with ``create``, preferably on a memory-backed file
+/// system such as ``/dev/shm``, and the other maps the same file with ``open``. Each ring has a
+/// single sender and receiver, so the connection can't be cloned. ``send`` fails with
+/// ``io::ErrorKind::WouldBlock`` if there isn't room for the packet, as the receiver has fallen
+/// behind, and a blocking ``recv`` spins, then yields, until a packet arrives or the read timeout
+/// passes.
+pub struct ShmConnection {
+    // Kept alive for ``base``
+    _mmap: MmapMut,
+    base: *mut u8,
+    capacity: usize,
+    /// Where the ring this end sends on starts.
+    outgoing: usize,
+    /// Where the ring this end receives on starts.
+    incoming: usize,
+    read_timeout: Cell<Option<Duration>>,
+    nonblocking: Cell<bool>,
+}
+
+// The mapping is owned by the connection, and only reached through ``base`` by its methods
+unsafe impl Send for ShmConnection {}
+
+impl ShmConnection {
+    /// Creates the rings in the file at ``path``, each holding up to ``capacity`` bytes of
+    /// packets (plus 4 bytes for the length of each), replacing anything already there. The
+    /// other process should then ``open`` it.
+    ///
+    /// # Errors
+    /// Will return Err if the file cannot be created or mapped, or with kind
+    /// ``io::ErrorKind::InvalidInput`` if ``capacity`` is zero or over ``u32::MAX``.
+    pub fn create(path: impl AsRef<Path>, capacity: usize) -> io::Result<Self> {
+        let header = u32::try_from(capacity)
+            .ok()
+            .filter(|&capacity| capacity > 0)
+            .ok_or_else(|| {
+                io::Error::new(
+                    ErrorKind::InvalidInput,
+                    format!(
+                        "Ring capacity {capacity} isn't from 1 to {} bytes",
+                        u32::MAX
+                    ),
+                )
+            })?;
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)?;
+        file.set_len(file_len(capacity) as u64)?;
+        let mut mmap = map(&file)?;
+        mmap[4..8].copy_from_slice(&header.to_le_bytes());
+        // Written last, so ``open`` can't see a half-initialised file as valid
+        mmap[..4].copy_from_slice(&MAGIC.to_le_bytes());
+        Ok(Self::from_mmap(mmap, capacity, false))
+    }
+
+    /// Maps the rings in the file at ``path``, created by another process with ``create``.
+    ///
+    /// # Errors
+    /// Will return Err if the file cannot be opened or mapped, or with kind
+    /// ``io::ErrorKind::InvalidData`` if it doesn't hold rings.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = OpenOptions::new().read(true).write(true).open(path)?;
+        let mmap = map(&file)?;
+        let invalid = || io::Error::new(ErrorKind::InvalidData, "File doesn't hold OSC rings");
+        let header = mmap.get(..HEADER_LEN).ok_or_else(invalid)?;
+        if header[..4] != MAGIC.to_le_bytes() {
+            return Err(invalid());
+        }
+        let capacity = u32::from_le_bytes([header[4], header[5], header[6], header[7]]) as usize;
+        if capacity == 0 || mmap.len() < file_len(capacity) {
+            return Err(invalid());
+        }
+        Ok(Self::from_mmap(mmap, capacity, true))
+    }
+
+    /// Returns how many bytes of packets each ring holds.
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    fn from_mmap(mut mmap: MmapMut, capacity: usize, opened: bool) -> Self {
+        let first = HEADER_LEN;
+        let second = HEADER_LEN + ring_len(capacity);
+        let (outgoing, incoming) = if opened {
+            (second, first)
+        } else {
+            (first, second)
+        };
+        Self {
+            base: mmap.as_mut_ptr(),
+            _mmap: mmap,
+            capacity,
+            outgoing,
+            incoming,
+            read_timeout: Cell::new(None),
+            nonblocking: Cell::new(false),
+        }
+    }
+
+    /// Returns the write and read positions of the ring at ``ring``, which only increase.
+    #[allow(clippy::cast_ptr_alignment)]
+    fn positions(&self, ring: usize) -> (&AtomicU64, &AtomicU64) {
+        // Both are within the mapping, and 8-byte aligned as the mapping is page aligned
+        unsafe {
+            (
+                &*self.base.add(ring).cast::<AtomicU64>(),
+                &*self
+                    .base
+                    .add(ring + RING_HEADER_LEN / 2)
+                    .cast::<AtomicU64>(),
+            )
+        }
+    }
+
+    /// Returns where ``position`` is in a ring's data.
+    fn offset(&self, position: u64) -> usize {
+        // Less than the capacity, so it fits
+        #[allow(clippy::cast_possible_truncation)]
+        let offset = (position % self.capacity as u64) as usize;
+        offset
+    }
+
+    /// Copies ``bytes`` into the ring at ``ring``, starting ``position`` bytes in and wrapping
+    /// around its end.
+    fn write_at(&mut self, ring: usize, position: u64, bytes: &[u8]) {
+        let offset = self.offset(position);
+        let first = bytes.len().min(self.capacity - offset);
+        let data = ring + RING_HEADER_LEN;
+        // The reader won't touch this space until the write position passes it
+        unsafe {
+            ptr::copy_nonoverlapping(bytes.as_ptr(), self.base.add(data + offset), first);
+            ptr::copy_nonoverlapping(
+                bytes[first..].as_ptr(),
+                self.base.add(data),
+                bytes.len() - first,
+            );
+        }
+    }
+
+    /// Copies ``buf.len()`` bytes out of the ring at ``ring``, starting ``position`` bytes in
+    /// and wrapping around its end.
+    fn read_at(&self, ring: usize, position: u64, buf: &mut [u8]) {
+        let offset = self.offset(position);
+        let first = buf.len().min(self.capacity - offset);
+        let data = ring + RING_HEADER_LEN;
+        // The writer won't touch this space until the read position passes it
+        unsafe {
+            ptr::copy_nonoverlapping(self.base.add(data + offset), buf.as_mut_ptr(), first);
+            ptr::copy_nonoverlapping(
+                self.base.add(data),
+                buf[first..].as_mut_ptr(),
+                buf.len() - first,
+            );
+        }
+    }
+
+    /// Copies the oldest packet into ``buf``, returning its size and where the next packet
+    /// starts.
+    fn read_packet(&self, buf: &mut [u8]) -> io::Result<(usize, u64)> {
+        let (write, read) = self.positions(self.incoming);
+        let deadline = self
+            .read_timeout
+            .get()
+            .map(|timeout| Instant::now() + timeout);
+        let mut spins = 0;
+        let tail = read.load(Ordering::Relaxed);
+        let head = loop {
+            let head = write.load(Ordering::Acquire);
+            if head != tail {
+                break head;
+            }
+            if self.nonblocking.get() {
+                return Err(ErrorKind::WouldBlock.into());
+            }
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                return Err(io::Error::new(
+                    ErrorKind::TimedOut,
+                    "No packet arrived before the read timeout",
+                ));
+            }
+            if spins < SPIN_LIMIT {
+                spins += 1;
+                std::hint::spin_loop();
+            } else {
+                thread::yield_now();
+            }
+        };
+        // The positions and length are written by the other process, so are checked before
+        // anything is copied, to keep copies within the ring
+        let available = head
+            .checked_sub(tail)
+            .filter(|&available| available >= 4 && available <= self.capacity as u64)
+            .ok_or_else(inconsistent)?;
+        let mut length = [0; 4];
+        self.read_at(self.incoming, tail, &mut length);
+        let length = u32::from_le_bytes(length) as usize;
+        if length + 4 > self.capacity || 4 + length as u64 > available {
+            return Err(inconsistent());
+        }
+        let Some(packet) = buf.get_mut(..length) else {
+            return Err(io::Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "Received packet of {length} bytes, larger than the {} byte buffer",
+                    buf.len()
+                ),
+            ));
+        };
+        self.read_at(self.incoming, tail + 4, packet);
+        Ok((length, tail + 4 + length as u64))
+    }
+}
+
+impl Connection for ShmConnection {
+    /// Shared-memory rings can't be opened by address, so this always returns an error. Use
+    /// ``ShmConnection::create`` or ``open``, and ``OscClient::from_connection``.
+    fn new<A: ToSocketAddrs, B: ToSocketAddrs>(_: A, _: B) -> io::Result<Self> {
+        Err(io::Error::new(
+            ErrorKind::Unsupported,
+            "Shared-memory rings can't be opened by address",
+        ))
+    }
+
+    fn send(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let frame_len = buf.len() + 4;
+        let length = u32::try_from(buf.len())
+            .ok()
+            .filter(|_| frame_len <= self.capacity)
+            .ok_or_else(|| {
+                io::Error::new(
+                    ErrorKind::InvalidInput,
+                    format!(
+                        "Packet of {} bytes doesn't fit in the {} byte ring",
+                        buf.len(),
+                        self.capacity
+                    ),
+                )
+            })?;
+        let (write, read) = self.positions(self.outgoing);
+        let head = write.load(Ordering::Relaxed);
+        let free = head
+            .checked_sub(read.load(Ordering::Acquire))
+            .and_then(|used| (self.capacity as u64).checked_sub(used))
+            .ok_or_else(inconsistent)?;
+        if free < frame_len as u64 {
+            return Err(io::Error::new(
+                ErrorKind::WouldBlock,
+                "The ring is full, as the receiver has fallen behind",
+            ));
+        }
+        self.write_at(self.outgoing, head, &length.to_le_bytes());
+        self.write_at(self.outgoing, head + 4, buf);
+        self.positions(self.outgoing)
+            .0
+            .store(head + frame_len as u64, Ordering::Release);
+        Ok(buf.len())
+    }
+
+    fn recv(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let (length, next) = self.read_packet(buf)?;
+        self.positions(self.incoming)
+            .1
+            .store(next, Ordering::Release);
+        Ok(length)
+    }
+
+    fn set_read_timeout(&self, dur: Option<Duration>) -> io::Result<()> {
+        self.read_timeout.set(dur);
+        Ok(())
+    }
+
+    fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        self.nonblocking.set(nonblocking);
+        Ok(())
+    }
+
+    /// Each ring has a single sender and receiver, so this always returns an error of kind
+    /// ``io::ErrorKind::Unsupported``.
+    fn try_clone(&self) -> io::Result<Self> {
+        Err(io::Error::new(
+            ErrorKind::Unsupported,
+            "Shared-memory rings have a single sender and receiver",
+        ))
+    }
+
+    fn peek(&self, buf: &mut [u8]) -> io::Result<usize> {
+        self.read_packet(buf).map(|(length, _)| length)
+    }
+}
+
+/// The error for a ring whose positions or packet lengths don't add up, e.g. as the other
+/// process wrote garbage over it.
+fn inconsistent() -> io::Error {
+    io::Error::new(
+        ErrorKind::InvalidData,
+        "The ring's positions are inconsistent",
+    )
+}
+
+fn map(file: &File) -> io::Result<MmapMut> {
+    // Other processes may change the rings while they're mapped, so they're only reached
+    // through atomics and raw copies
+    unsafe { MmapMut::map_mut(file) }
+}
+
+/// Returns the size of one ring holding ``capacity`` bytes, rounded up to a cache line.
+fn ring_len(capacity: usize) -> usize {
+    RING_HEADER_LEN + capacity.next_multiple_of(64)
+}
+
+/// Returns the size of a file holding two rings of ``capacity`` bytes.
+fn file_len(capacity: usize) -> usize {
+    HEADER_LEN + 2 * ring_len(capacity)
+}
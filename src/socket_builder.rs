@@ -0,0 +1,221 @@
+#[cfg(feature = "udp")]
+use std::net::UdpSocket;
+#[cfg(feature = "tcp")]
+use std::net::{TcpListener, TcpStream};
+use std::{
+    io,
+    net::{SocketAddr, ToSocketAddrs},
+};
+
+use socket2::{Domain, Protocol, Socket, Type};
+
+#[cfg(feature = "tcp")]
+use crate::sockets::FramedTcpStream;
+
+/// Opens connections with socket options set before they connect, which ``Connection::new``
+/// leaves at the system defaults, e.g.
+/// ``ConnectionBuilder::new().with_reuse_address(true).udp("0.0.0.0:10024", remote)``. Give the
+/// connection to ``OscClient::from_connection``.
+#[derive(Debug, Clone, Default)]
+pub struct ConnectionBuilder {
+    reuse_address: bool,
+    reuse_port: bool,
+    send_buffer_size: Option<usize>,
+    recv_buffer_size: Option<usize>,
+    ttl: Option<u32>,
+    only_v6: Option<bool>,
+    device: Option<String>,
+}
+
+impl ConnectionBuilder {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets ``SO_REUSEADDR``, allowing the local address to be bound while an earlier socket's
+    /// connections on it are closing.
+    #[must_use]
+    pub fn with_reuse_address(mut self, reuse: bool) -> Self {
+        self.reuse_address = reuse;
+        self
+    }
+
+    /// Sets ``SO_REUSEPORT``, allowing several sockets to bind the same local address, where the
+    /// platform supports it. It is ignored elsewhere.
+    #[must_use]
+    pub fn with_reuse_port(mut self, reuse: bool) -> Self {
+        self.reuse_port = reuse;
+        self
+    }
+
+    /// Sets the size of the kernel's send buffer, in bytes.
+    #[must_use]
+    pub fn with_send_buffer_size(mut self, size: usize) -> Self {
+        self.send_buffer_size = Some(size);
+        self
+    }
+
+    /// Sets the size of the kernel's receive buffer, in bytes, e.g. so bursts of meter updates
+    /// aren't dropped.
+    #[must_use]
+    pub fn with_recv_buffer_size(mut self, size: usize) -> Self {
+        self.recv_buffer_size = Some(size);
+        self
+    }
+
+    /// Sets the time-to-live (or, for IPv6, the hop limit) of outgoing packets.
+    #[must_use]
+    pub fn with_ttl(mut self, ttl: u32) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Sets ``IPV6_V6ONLY`` on IPv6 sockets. ``false`` makes a socket bound to ``[::]`` dual-stack,
+    /// accepting IPv4 traffic too (as IPv4-mapped addresses), whatever the platform's default.
+    /// It is ignored for IPv4 sockets.
+    #[must_use]
+    pub fn with_only_v6(mut self, only_v6: bool) -> Self {
+        self.only_v6 = Some(only_v6);
+        self
+    }
+
+    /// Binds the socket to the network interface named ``device``, e.g. ``"eth1"``, so traffic
+    /// only uses that interface. Only supported on Linux, Android and Fuchsia.
+    #[must_use]
+    pub fn with_device(mut self, device: impl Into<String>) -> Self {
+        self.device = Some(device.into());
+        self
+    }
+
+    /// Opens a ``UdpSocket`` bound to ``local_address`` and connected to ``remote_address``.
+    ///
+    /// # Errors
+    /// Will return ``Err`` if an option can't be set, or binding or connecting fails.
+    #[cfg(feature = "udp")]
+    pub fn udp<A: ToSocketAddrs, B: ToSocketAddrs>(
+        &self,
+        local_address: A,
+        remote_address: B,
+    ) -> io::Result<UdpSocket> {
+        let socket = self.bind(local_address, Type::DGRAM, Protocol::UDP)?;
+        let socket = UdpSocket::from(socket);
+        socket.connect(remote_address)?;
+        Ok(socket)
+    }
+
+    /// Opens a ``UdpSocket`` bound to ``local_address``, without connecting it, e.g. for
+    /// ``OscServer::from_socket``.
+    ///
+    /// # Errors
+    /// Will return ``Err`` if an option can't be set, or binding fails.
+    #[cfg(feature = "udp")]
+    pub fn udp_listener<A: ToSocketAddrs>(&self, local_address: A) -> io::Result<UdpSocket> {
+        self.bind(local_address, Type::DGRAM, Protocol::UDP)
+            .map(UdpSocket::from)
+    }
+
+    /// Opens a ``TcpListener`` bound to ``local_address``, e.g. for
+    /// ``TcpOscServer::from_listener``.
+    ///
+    /// # Errors
+    /// Will return ``Err`` if an option can't be set, or binding or listening fails.
+    #[cfg(feature = "tcp")]
+    pub fn tcp_listener<A: ToSocketAddrs>(&self, local_address: A) -> io::Result<TcpListener> {
+        let socket = self.bind(local_address, Type::STREAM, Protocol::TCP)?;
+        socket.listen(128)?;
+        Ok(socket.into())
+    }
+
+    /// Opens a ``TcpStream`` bound to ``local_address`` and connected to ``remote_address``.
+    ///
+    /// # Errors
+    /// Will return ``Err`` if an option can't be set, or binding or connecting fails.
+    #[cfg(feature = "tcp")]
+    pub fn tcp<A: ToSocketAddrs, B: ToSocketAddrs>(
+        &self,
+        local_address: A,
+        remote_address: B,
+    ) -> io::Result<TcpStream> {
+        let socket = self.bind(local_address, Type::STREAM, Protocol::TCP)?;
+        socket.connect(&first_address(remote_address)?.into())?;
+        Ok(socket.into())
+    }
+
+    /// Like ``tcp``, but using OSC 1.0 length-prefix framing (see ``FramedTcpStream``).
+    ///
+    /// # Errors
+    /// See ``tcp``.
+    #[cfg(feature = "tcp")]
+    pub fn framed_tcp<A: ToSocketAddrs, B: ToSocketAddrs>(
+        &self,
+        local_address: A,
+        remote_address: B,
+    ) -> io::Result<FramedTcpStream> {
+        self.tcp(local_address, remote_address)
+            .map(FramedTcpStream::from_stream)
+    }
+
+    fn bind<A: ToSocketAddrs>(
+        &self,
+        local_address: A,
+        ty: Type,
+        protocol: Protocol,
+    ) -> io::Result<Socket> {
+        let local_address = first_address(local_address)?;
+        let socket = Socket::new(Domain::for_address(local_address), ty, Some(protocol))?;
+        socket.set_reuse_address(self.reuse_address)?;
+        #[cfg(all(
+            unix,
+            not(any(
+                target_os = "solaris",
+                target_os = "illumos",
+                target_os = "cygwin",
+                target_os = "nuttx"
+            ))
+        ))]
+        if self.reuse_port {
+            socket.set_reuse_port(true)?;
+        }
+        if let Some(size) = self.send_buffer_size {
+            socket.set_send_buffer_size(size)?;
+        }
+        if let Some(size) = self.recv_buffer_size {
+            socket.set_recv_buffer_size(size)?;
+        }
+        if let Some(ttl) = self.ttl {
+            match local_address {
+                SocketAddr::V4(_) => socket.set_ttl_v4(ttl)?,
+                SocketAddr::V6(_) => socket.set_unicast_hops_v6(ttl)?,
+            }
+        }
+        if let (Some(only_v6), SocketAddr::V6(_)) = (self.only_v6, local_address) {
+            socket.set_only_v6(only_v6)?;
+        }
+        if let Some(device) = &self.device {
+            bind_device(&socket, device)?;
+        }
+        socket.bind(&local_address.into())?;
+        Ok(socket)
+    }
+}
+
+#[cfg(any(target_os = "android", target_os = "fuchsia", target_os = "linux"))]
+fn bind_device(socket: &Socket, device: &str) -> io::Result<()> {
+    socket.bind_device(Some(device.as_bytes()))
+}
+
+#[cfg(not(any(target_os = "android", target_os = "fuchsia", target_os = "linux")))]
+fn bind_device(_: &Socket, _: &str) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "Binding to a device is not supported on this platform",
+    ))
+}
+
+fn first_address<A: ToSocketAddrs>(address: A) -> io::Result<SocketAddr> {
+    address
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "No address"))
+}
@@ -1,9 +1,15 @@
 use std::{
-    io::{Read, Write},
-    net::{TcpStream, ToSocketAddrs, UdpSocket},
+    collections::VecDeque,
+    io::{ErrorKind, Read, Stdin, Stdout, Write},
+    net::{SocketAddr, ToSocketAddrs},
     time::Duration,
 };
 
+#[cfg(feature = "tcp")]
+use std::net::TcpStream;
+#[cfg(feature = "udp")]
+use std::net::UdpSocket;
+
 pub trait Connection
 where
     Self: Sized,
@@ -44,8 +50,108 @@ where
     /// Failure depends on platform. Some platforms do not implement socket cloning (e.g. WASI/WASM).
     /// Different platforms may generate different errors.
     fn try_clone(&self) -> std::io::Result<Self>;
+    /// Points the ``impl Connection`` at ``remote_address`` instead, reconnecting if needed.
+    ///
+    /// # Errors
+    /// Will return Err if connecting fails. By default, returns an error of kind
+    /// ``io::ErrorKind::Unsupported``.
+    fn connect<A: ToSocketAddrs>(&mut self, remote_address: A) -> std::io::Result<()> {
+        let _ = remote_address;
+        Err(std::io::Error::new(
+            ErrorKind::Unsupported,
+            "Connection can't change its remote address",
+        ))
+    }
+    /// Returns the local address of the ``impl Connection``, e.g. to learn which port was
+    /// assigned when binding to port 0.
+    ///
+    /// # Errors
+    /// Will return Err if the address can't be found. By default, returns an error of kind
+    /// ``io::ErrorKind::Unsupported``.
+    fn local_addr(&self) -> std::io::Result<SocketAddr> {
+        Err(std::io::Error::new(
+            ErrorKind::Unsupported,
+            "Connection has no local address",
+        ))
+    }
+    /// Returns the remote address the ``impl Connection`` is connected to.
+    ///
+    /// # Errors
+    /// Will return Err if it isn't connected. By default, returns an error of kind
+    /// ``io::ErrorKind::Unsupported``.
+    fn peer_addr(&self) -> std::io::Result<SocketAddr> {
+        Err(std::io::Error::new(
+            ErrorKind::Unsupported,
+            "Connection has no remote address",
+        ))
+    }
+    /// Receives data into ``buf`` as ``recv`` does, but leaves it to be received again, e.g. to
+    /// find the size of a datagram before receiving it. Only packet-based connections can peek.
+    ///
+    /// # Errors
+    /// As for ``recv``. By default, returns an error of kind ``io::ErrorKind::Unsupported``.
+    fn peek(&self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let _ = buf;
+        Err(std::io::Error::new(
+            ErrorKind::Unsupported,
+            "Connection can't peek",
+        ))
+    }
+    /// Receives data into ``buf`` as ``recv`` does, along with the address it came from, if the
+    /// connection can tell, e.g. for an unconnected UDP socket receiving from several devices.
+    ///
+    /// # Errors
+    /// As for ``recv``. By default, receives with ``recv`` and returns no address.
+    fn recv_with_source(&mut self, buf: &mut [u8]) -> std::io::Result<(usize, Option<SocketAddr>)> {
+        Ok((self.recv(buf)?, None))
+    }
+    /// Splits the ``impl Connection`` into a half which only sends and a half which only
+    /// receives, so that one thread can send while another blocks receiving. The receiving half
+    /// keeps any partially received packet.
+    ///
+    /// # Errors
+    /// The halves share the underlying connection using ``try_clone``, so this fails if it does.
+    fn split(self) -> std::io::Result<(SendHalf<Self>, RecvHalf<Self>)> {
+        Ok((SendHalf(self.try_clone()?), RecvHalf(self)))
+    }
+}
+
+/// The sending half of a ``Connection``, from ``Connection::split``.
+pub struct SendHalf<C>(C);
+
+/// The receiving half of a ``Connection``, from ``Connection::split``.
+pub struct RecvHalf<C>(C);
+
+impl<C: Connection> SendHalf<C> {
+    /// See ``Connection::send``.
+    ///
+    /// # Errors
+    /// If sending data fails, return Err
+    pub fn send(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.send(buf)
+    }
+}
+
+impl<C: Connection> RecvHalf<C> {
+    /// See ``Connection::recv``.
+    ///
+    /// # Errors
+    /// If there is no data to receive, return ``Err(io::Error.kind() == ErrorKind::WouldBlock)``.
+    /// If it fails for any other reason, ``Err`` also.
+    pub fn recv(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.0.recv(buf)
+    }
+
+    /// See ``Connection::set_read_timeout``.
+    ///
+    /// # Errors
+    /// Will return Err if the read timeout could not be set.
+    pub fn set_read_timeout(&self, dur: Option<Duration>) -> std::io::Result<()> {
+        self.0.set_read_timeout(dur)
+    }
 }
 
+#[cfg(feature = "udp")]
 impl Connection for UdpSocket {
     fn new<A: ToSocketAddrs, B: ToSocketAddrs>(
         local_address: A,
@@ -75,7 +181,33 @@ impl Connection for UdpSocket {
     fn try_clone(&self) -> std::io::Result<Self> {
         UdpSocket::try_clone(self)
     }
+
+    /// Keeps the socket, and so the local address, connecting it to ``remote_address``.
+    fn connect<A: ToSocketAddrs>(&mut self, remote_address: A) -> std::io::Result<()> {
+        UdpSocket::connect(self, remote_address)
+    }
+
+    fn local_addr(&self) -> std::io::Result<SocketAddr> {
+        UdpSocket::local_addr(self)
+    }
+
+    fn peek(&self, buf: &mut [u8]) -> std::io::Result<usize> {
+        UdpSocket::peek(self, buf)
+    }
+
+    fn recv_with_source(&mut self, buf: &mut [u8]) -> std::io::Result<(usize, Option<SocketAddr>)> {
+        let (size, source) = UdpSocket::recv_from(self, buf)?;
+        Ok((size, Some(source)))
+    }
+
+    fn peer_addr(&self) -> std::io::Result<SocketAddr> {
+        UdpSocket::peer_addr(self)
+    }
 }
+
+/// Raw ``TcpStream``s write packets back-to-back with no framing, so the receiving side has no way
+/// to find message boundaries. Prefer ``FramedTcpStream`` unless the peer expects exactly this.
+#[cfg(feature = "tcp")]
 impl Connection for TcpStream {
     fn new<A: ToSocketAddrs, B: ToSocketAddrs>(_: A, remote_address: B) -> std::io::Result<Self> {
         TcpStream::connect(remote_address)
@@ -100,4 +232,578 @@ impl Connection for TcpStream {
     fn try_clone(&self) -> std::io::Result<Self> {
         TcpStream::try_clone(self)
     }
+
+    /// Replaces the stream with a new one connected to ``remote_address``. Socket options such as
+    /// the read timeout are not carried over.
+    fn connect<A: ToSocketAddrs>(&mut self, remote_address: A) -> std::io::Result<()> {
+        *self = TcpStream::connect(remote_address)?;
+        Ok(())
+    }
+
+    fn local_addr(&self) -> std::io::Result<SocketAddr> {
+        TcpStream::local_addr(self)
+    }
+
+    fn peer_addr(&self) -> std::io::Result<SocketAddr> {
+        TcpStream::peer_addr(self)
+    }
+}
+
+/// The object-safe part of ``Connection``, implemented for every ``Connection``, which
+/// ``BoxedConnection`` uses to erase the connection's type.
+pub trait DynConnection: Send {
+    /// See ``Connection::send``.
+    ///
+    /// # Errors
+    /// If sending data fails, return Err
+    fn send(&mut self, buf: &[u8]) -> std::io::Result<usize>;
+    /// See ``Connection::recv``.
+    ///
+    /// # Errors
+    /// If receiving data fails, return Err
+    fn recv(&mut self, buf: &mut [u8]) -> std::io::Result<usize>;
+    /// See ``Connection::set_read_timeout``.
+    ///
+    /// # Errors
+    /// Will return Err if the read timeout could not be set.
+    fn set_read_timeout(&self, dur: Option<Duration>) -> std::io::Result<()>;
+    /// See ``Connection::set_nonblocking``.
+    ///
+    /// # Errors
+    /// Will return Err if the mode could not be changed.
+    fn set_nonblocking(&self, nonblocking: bool) -> std::io::Result<()>;
+    /// See ``Connection::try_clone``.
+    ///
+    /// # Errors
+    /// Will return Err if the connection could not be cloned.
+    fn try_clone_boxed(&self) -> std::io::Result<Box<dyn DynConnection>>;
+    /// See ``Connection::connect``.
+    ///
+    /// # Errors
+    /// Will return Err if connecting fails.
+    fn connect_to(&mut self, remote_address: &[SocketAddr]) -> std::io::Result<()>;
+    /// See ``Connection::local_addr``.
+    ///
+    /// # Errors
+    /// Will return Err if the address can't be found.
+    fn local_addr(&self) -> std::io::Result<SocketAddr>;
+    /// See ``Connection::peer_addr``.
+    ///
+    /// # Errors
+    /// Will return Err if it isn't connected.
+    fn peer_addr(&self) -> std::io::Result<SocketAddr>;
+    /// See ``Connection::peek``.
+    ///
+    /// # Errors
+    /// Will return Err if peeking fails, or is unsupported.
+    fn peek(&self, buf: &mut [u8]) -> std::io::Result<usize>;
+    /// See ``Connection::recv_with_source``.
+    ///
+    /// # Errors
+    /// If receiving data fails, return Err
+    fn recv_with_source(&mut self, buf: &mut [u8]) -> std::io::Result<(usize, Option<SocketAddr>)>;
+}
+
+impl<C: Connection + Send + 'static> DynConnection for C {
+    fn send(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        Connection::send(self, buf)
+    }
+
+    fn recv(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        Connection::recv(self, buf)
+    }
+
+    fn set_read_timeout(&self, dur: Option<Duration>) -> std::io::Result<()> {
+        Connection::set_read_timeout(self, dur)
+    }
+
+    fn set_nonblocking(&self, nonblocking: bool) -> std::io::Result<()> {
+        Connection::set_nonblocking(self, nonblocking)
+    }
+
+    fn try_clone_boxed(&self) -> std::io::Result<Box<dyn DynConnection>> {
+        Ok(Box::new(Connection::try_clone(self)?))
+    }
+
+    fn connect_to(&mut self, remote_address: &[SocketAddr]) -> std::io::Result<()> {
+        Connection::connect(self, remote_address)
+    }
+
+    fn local_addr(&self) -> std::io::Result<SocketAddr> {
+        Connection::local_addr(self)
+    }
+
+    fn peer_addr(&self) -> std::io::Result<SocketAddr> {
+        Connection::peer_addr(self)
+    }
+
+    fn peek(&self, buf: &mut [u8]) -> std::io::Result<usize> {
+        Connection::peek(self, buf)
+    }
+
+    fn recv_with_source(&mut self, buf: &mut [u8]) -> std::io::Result<(usize, Option<SocketAddr>)> {
+        Connection::recv_with_source(self, buf)
+    }
+}
+
+/// Any ``Connection``, with its type erased, so that the transport can be chosen at runtime and
+/// an ``OscClient<BoxedConnection>`` stored in one field whichever it is, e.g.
+/// ``OscClient::from_connection(BoxedConnection::wrap(stream), 1024, None)``.
+///
+/// ``BoxedConnection`` doesn't know which transport to open, so its ``Connection::new`` always
+/// returns an error of kind ``io::ErrorKind::Unsupported``. Wrap a connection opened with its own
+/// ``Connection::new`` instead.
+pub struct BoxedConnection(Box<dyn DynConnection>);
+
+impl BoxedConnection {
+    #[must_use]
+    pub fn wrap<C: Connection + Send + 'static>(connection: C) -> Self {
+        Self(Box::new(connection))
+    }
+}
+
+impl Connection for BoxedConnection {
+    fn new<A: ToSocketAddrs, B: ToSocketAddrs>(_: A, _: B) -> std::io::Result<Self> {
+        Err(std::io::Error::new(
+            ErrorKind::Unsupported,
+            "BoxedConnection can't choose a transport, so wrap an existing connection",
+        ))
+    }
+
+    fn send(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.send(buf)
+    }
+
+    fn recv(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.0.recv(buf)
+    }
+
+    fn set_read_timeout(&self, dur: Option<Duration>) -> std::io::Result<()> {
+        self.0.set_read_timeout(dur)
+    }
+
+    fn set_nonblocking(&self, nonblocking: bool) -> std::io::Result<()> {
+        self.0.set_nonblocking(nonblocking)
+    }
+
+    fn try_clone(&self) -> std::io::Result<Self> {
+        self.0.try_clone_boxed().map(Self)
+    }
+
+    fn connect<A: ToSocketAddrs>(&mut self, remote_address: A) -> std::io::Result<()> {
+        let remote_address: Vec<_> = remote_address.to_socket_addrs()?.collect();
+        self.0.connect_to(&remote_address)
+    }
+
+    fn local_addr(&self) -> std::io::Result<SocketAddr> {
+        self.0.local_addr()
+    }
+
+    fn peer_addr(&self) -> std::io::Result<SocketAddr> {
+        self.0.peer_addr()
+    }
+
+    fn peek(&self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.0.peek(buf)
+    }
+
+    fn recv_with_source(&mut self, buf: &mut [u8]) -> std::io::Result<(usize, Option<SocketAddr>)> {
+        self.0.recv_with_source(buf)
+    }
+}
+
+/// A ``TcpStream`` using OSC 1.0 stream framing: every packet is preceded by its length as a
+/// 4-byte big-endian integer. Partial reads are buffered until a whole packet has arrived, so each
+/// ``recv`` yields exactly one packet. A packet too large for the buffer given to ``recv`` fails
+/// with ``ErrorKind::InvalidData`` as soon as its length arrives, without being buffered, and the
+/// stream should then be dropped.
+#[cfg(feature = "tcp")]
+pub struct FramedTcpStream {
+    stream: TcpStream,
+    pending: Vec<u8>,
+}
+
+#[cfg(feature = "tcp")]
+impl FramedTcpStream {
+    /// Wraps an already connected ``TcpStream``.
+    #[must_use]
+    pub fn from_stream(stream: TcpStream) -> Self {
+        Self {
+            stream,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Returns the underlying ``TcpStream``, discarding any partially received packet.
+    #[must_use]
+    pub fn into_inner(self) -> TcpStream {
+        self.stream
+    }
+
+    fn take_frame(&mut self, buf: &mut [u8]) -> std::io::Result<Option<usize>> {
+        take_frame(&mut self.pending, buf.len())?
+            .map(|frame| copy_frame(&frame, buf))
+            .transpose()
+    }
+}
+
+#[cfg(feature = "tcp")]
+impl Connection for FramedTcpStream {
+    fn new<A: ToSocketAddrs, B: ToSocketAddrs>(_: A, remote_address: B) -> std::io::Result<Self> {
+        TcpStream::connect(remote_address).map(Self::from_stream)
+    }
+
+    fn send(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.stream.write_all(&frame(buf)?)?;
+        Ok(buf.len())
+    }
+
+    fn recv(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let mut chunk = [0; 4096];
+        loop {
+            if let Some(length) = self.take_frame(buf)? {
+                return Ok(length);
+            }
+            match self.stream.read(&mut chunk)? {
+                0 => {
+                    return Err(std::io::Error::new(
+                        ErrorKind::UnexpectedEof,
+                        "Connection closed by peer",
+                    ))
+                }
+                n => self.pending.extend_from_slice(&chunk[..n]),
+            }
+        }
+    }
+
+    fn set_read_timeout(&self, dur: Option<Duration>) -> std::io::Result<()> {
+        self.stream.set_read_timeout(dur)
+    }
+
+    fn set_nonblocking(&self, nonblocking: bool) -> std::io::Result<()> {
+        self.stream.set_nonblocking(nonblocking)
+    }
+
+    /// The clone shares the socket but not any partially received packet.
+    fn try_clone(&self) -> std::io::Result<Self> {
+        self.stream.try_clone().map(Self::from_stream)
+    }
+
+    /// Replaces the stream with a new one connected to ``remote_address``, discarding any
+    /// partially received packet. Socket options such as the read timeout are not carried over.
+    fn connect<A: ToSocketAddrs>(&mut self, remote_address: A) -> std::io::Result<()> {
+        *self = Self::from_stream(TcpStream::connect(remote_address)?);
+        Ok(())
+    }
+
+    fn local_addr(&self) -> std::io::Result<SocketAddr> {
+        self.stream.local_addr()
+    }
+
+    fn peer_addr(&self) -> std::io::Result<SocketAddr> {
+        self.stream.peer_addr()
+    }
+}
+
+const SLIP_END: u8 = 0xC0;
+const SLIP_ESC: u8 = 0xDB;
+const SLIP_ESC_END: u8 = 0xDC;
+const SLIP_ESC_ESC: u8 = 0xDD;
+
+/// Wraps a byte stream ``Connection`` (such as a raw ``TcpStream``) in SLIP (RFC 1055) framing,
+/// as OSC 1.1 requires of stream transports: each packet is sent between ``END`` bytes, with
+/// ``END`` and ``ESC`` bytes within it escaped. Partial reads are buffered until a whole packet
+/// has arrived, so each ``recv`` yields exactly one packet.
+pub struct SlipFramed<T> {
+    inner: T,
+    frame: Vec<u8>,
+    escaping: bool,
+    ready: VecDeque<Vec<u8>>,
+}
+
+impl<T> SlipFramed<T> {
+    /// Wraps an already connected ``inner``.
+    #[must_use]
+    pub fn from_stream(inner: T) -> Self {
+        Self {
+            inner,
+            frame: Vec::new(),
+            escaping: false,
+            ready: VecDeque::new(),
+        }
+    }
+
+    /// Returns the underlying stream, discarding any partially received packet.
+    #[must_use]
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    /// Decodes ``bytes``, queueing each packet they complete.
+    fn decode(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            match (self.escaping, byte) {
+                (false, SLIP_END) => {
+                    // Empty frames are the double END between packets.
+                    if !self.frame.is_empty() {
+                        self.ready.push_back(std::mem::take(&mut self.frame));
+                    }
+                }
+                (false, SLIP_ESC) => self.escaping = true,
+                (true, SLIP_ESC_END) => {
+                    self.frame.push(SLIP_END);
+                    self.escaping = false;
+                }
+                (true, SLIP_ESC_ESC) => {
+                    self.frame.push(SLIP_ESC);
+                    self.escaping = false;
+                }
+                // A protocol violation, so keep the byte as is.
+                (_, byte) => {
+                    self.frame.push(byte);
+                    self.escaping = false;
+                }
+            }
+        }
+    }
+}
+
+/// Encodes ``packet`` as a double-ended SLIP frame.
+pub(crate) fn slip_encode(packet: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(packet.len() + 2);
+    frame.push(SLIP_END);
+    for &byte in packet {
+        match byte {
+            SLIP_END => frame.extend_from_slice(&[SLIP_ESC, SLIP_ESC_END]),
+            SLIP_ESC => frame.extend_from_slice(&[SLIP_ESC, SLIP_ESC_ESC]),
+            _ => frame.push(byte),
+        }
+    }
+    frame.push(SLIP_END);
+    frame
+}
+
+/// Decodes a SLIP ``frame`` with its ``END`` bytes already removed.
+#[cfg_attr(not(feature = "tokio"), allow(dead_code))]
+pub(crate) fn slip_decode(frame: &[u8]) -> Vec<u8> {
+    let mut packet = Vec::with_capacity(frame.len());
+    let mut escaping = false;
+    for &byte in frame {
+        match (escaping, byte) {
+            (false, SLIP_ESC) => escaping = true,
+            (true, SLIP_ESC_END) => {
+                packet.push(SLIP_END);
+                escaping = false;
+            }
+            (true, SLIP_ESC_ESC) => {
+                packet.push(SLIP_ESC);
+                escaping = false;
+            }
+            // A protocol violation, so keep the byte as is.
+            (_, byte) => {
+                packet.push(byte);
+                escaping = false;
+            }
+        }
+    }
+    packet
+}
+
+/// Returns where the SLIP ``END`` byte ending the first frame in ``bytes`` is, if any.
+#[cfg_attr(not(feature = "tokio"), allow(dead_code))]
+pub(crate) fn slip_end(bytes: &[u8]) -> Option<usize> {
+    bytes.iter().position(|&byte| byte == SLIP_END)
+}
+
+impl<T: Connection> Connection for SlipFramed<T> {
+    fn new<A: ToSocketAddrs, B: ToSocketAddrs>(
+        local_address: A,
+        remote_address: B,
+    ) -> std::io::Result<Self> {
+        T::new(local_address, remote_address).map(Self::from_stream)
+    }
+
+    fn send(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let frame = slip_encode(buf);
+        let mut rest = &frame[..];
+        while !rest.is_empty() {
+            match self.inner.send(rest)? {
+                0 => return Err(ErrorKind::WriteZero.into()),
+                n => rest = &rest[n..],
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn recv(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let mut chunk = [0; 4096];
+        loop {
+            if let Some(frame) = self.ready.pop_front() {
+                return copy_frame(&frame, buf);
+            }
+            match self.inner.recv(&mut chunk)? {
+                0 => {
+                    return Err(std::io::Error::new(
+                        ErrorKind::UnexpectedEof,
+                        "Connection closed by peer",
+                    ))
+                }
+                n => self.decode(&chunk[..n]),
+            }
+        }
+    }
+
+    fn set_read_timeout(&self, dur: Option<Duration>) -> std::io::Result<()> {
+        self.inner.set_read_timeout(dur)
+    }
+
+    fn set_nonblocking(&self, nonblocking: bool) -> std::io::Result<()> {
+        self.inner.set_nonblocking(nonblocking)
+    }
+
+    /// The clone shares the stream but not any partially received packet.
+    fn try_clone(&self) -> std::io::Result<Self> {
+        self.inner.try_clone().map(Self::from_stream)
+    }
+
+    /// Discards any partially received packet.
+    fn connect<A: ToSocketAddrs>(&mut self, remote_address: A) -> std::io::Result<()> {
+        self.inner.connect(remote_address)?;
+        self.frame.clear();
+        self.escaping = false;
+        self.ready.clear();
+        Ok(())
+    }
+
+    fn local_addr(&self) -> std::io::Result<SocketAddr> {
+        self.inner.local_addr()
+    }
+
+    fn peer_addr(&self) -> std::io::Result<SocketAddr> {
+        self.inner.peer_addr()
+    }
+}
+
+/// A byte stream ``Connection`` over a reader and a writer, such as a child process's stdout and
+/// stdin, for piping OSC between processes. It has no framing of its own, so wrap it in
+/// ``SlipFramed``, e.g. ``SlipFramed::from_stream(Pipe::stdio())``, and give that to
+/// ``OscClient::from_connection``.
+///
+/// Pipes have no addresses, read timeouts, or non-blocking mode, and can't be cloned, so those
+/// methods return errors of kind ``io::ErrorKind::Unsupported`` (except for clearing the read
+/// timeout or non-blocking mode, which succeeds). As a result, calls which wait until a deadline or only take
+/// messages which have already arrived, such as ``wait_for`` and ``try_iter``, fail.
+pub struct Pipe<R, W> {
+    reader: R,
+    writer: W,
+}
+
+impl<R: Read, W: Write> Pipe<R, W> {
+    #[must_use]
+    pub fn new(reader: R, writer: W) -> Self {
+        Self { reader, writer }
+    }
+
+    /// Returns the reader and writer.
+    #[must_use]
+    pub fn into_inner(self) -> (R, W) {
+        (self.reader, self.writer)
+    }
+}
+
+impl Pipe<Stdin, Stdout> {
+    /// Creates a ``Pipe`` reading from this process's stdin and writing to its stdout, e.g. for
+    /// a server launched by inetd.
+    #[must_use]
+    pub fn stdio() -> Self {
+        Self::new(std::io::stdin(), std::io::stdout())
+    }
+}
+
+fn unsupported(what: &str) -> std::io::Error {
+    std::io::Error::new(
+        ErrorKind::Unsupported,
+        format!("Pipes don't support {what}"),
+    )
+}
+
+impl<R: Read, W: Write> Connection for Pipe<R, W> {
+    /// Pipes can't be opened by address, so this always returns an error.
+    fn new<A: ToSocketAddrs, B: ToSocketAddrs>(_: A, _: B) -> std::io::Result<Self> {
+        Err(unsupported("addresses"))
+    }
+
+    fn send(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let size = self.writer.write(buf)?;
+        self.writer.flush()?;
+        Ok(size)
+    }
+
+    fn recv(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.reader.read(buf)
+    }
+
+    fn set_read_timeout(&self, dur: Option<Duration>) -> std::io::Result<()> {
+        match dur {
+            Some(_) => Err(unsupported("read timeouts")),
+            None => Ok(()),
+        }
+    }
+
+    fn set_nonblocking(&self, nonblocking: bool) -> std::io::Result<()> {
+        if nonblocking {
+            return Err(unsupported("non-blocking mode"));
+        }
+        Ok(())
+    }
+
+    fn try_clone(&self) -> std::io::Result<Self> {
+        Err(unsupported("cloning"))
+    }
+}
+
+/// Prefixes ``packet`` with its length, as OSC 1.0 stream framing requires.
+#[cfg_attr(not(feature = "tcp"), allow(dead_code))]
+pub(crate) fn frame(packet: &[u8]) -> std::io::Result<Vec<u8>> {
+    let length = u32::try_from(packet.len())
+        .map_err(|_| std::io::Error::new(ErrorKind::InvalidInput, "Packet too large to frame"))?;
+    let mut frame = length.to_be_bytes().to_vec();
+    frame.extend_from_slice(packet);
+    Ok(frame)
+}
+
+/// Removes the first whole length-prefixed packet from ``pending``, if one has fully arrived.
+///
+/// Fails with ``ErrorKind::InvalidData`` as soon as the length of a packet larger than ``limit``
+/// bytes has arrived, so a peer can't make the stream buffer a huge packet which couldn't be
+/// received anyway. The packet is left in ``pending``, so the stream should be dropped.
+#[cfg_attr(not(feature = "tcp"), allow(dead_code))]
+pub(crate) fn take_frame(pending: &mut Vec<u8>, limit: usize) -> std::io::Result<Option<Vec<u8>>> {
+    let Some(header) = pending.first_chunk::<4>() else {
+        return Ok(None);
+    };
+    let length = u32::from_be_bytes(*header) as usize;
+    if length > limit {
+        return Err(too_large(length, limit));
+    }
+    if pending.len() < 4 + length {
+        return Ok(None);
+    }
+    Ok(Some(pending.drain(..4 + length).skip(4).collect()))
+}
+
+/// Copies a received ``frame`` into the start of ``buf``, returning its length.
+pub(crate) fn copy_frame(frame: &[u8], buf: &mut [u8]) -> std::io::Result<usize> {
+    let Some(start) = buf.get_mut(..frame.len()) else {
+        return Err(too_large(frame.len(), buf.len()));
+    };
+    start.copy_from_slice(frame);
+    Ok(frame.len())
+}
+
+fn too_large(size: usize, limit: usize) -> std::io::Error {
+    std::io::Error::new(
+        ErrorKind::InvalidData,
+        format!("Received packet of {size} bytes, larger than the {limit} byte buffer"),
+    )
 }
@@ -0,0 +1,70 @@
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+};
+
+use crate::{address::OscAddress, lock, pattern::OscPattern, Arg, OscMessage};
+
+/// The most recent arguments received at each address, recorded by a server ``with_state``, e.g.
+/// to show the current position of every fader. Clones share the same values, so a store can be
+/// read from other threads while the server is running.
+#[derive(Clone, Default)]
+pub struct StateStore {
+    values: Arc<Mutex<BTreeMap<String, Vec<Arg>>>>,
+}
+
+impl StateStore {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the most recent arguments received at ``address``, if any have been.
+    #[must_use]
+    pub fn get(&self, address: &str) -> Option<Vec<Arg>> {
+        lock(&self.values).get(address).cloned()
+    }
+
+    /// Sets the arguments for ``address``, as if a message had been received there, e.g. to seed
+    /// the initial values a state query replies with.
+    pub fn insert(&self, address: &OscAddress, args: Vec<Arg>) {
+        lock(&self.values).insert(address.to_string(), args);
+    }
+
+    /// Returns the addresses with recorded values, in order.
+    #[must_use]
+    pub fn addresses(&self) -> Vec<String> {
+        lock(&self.values).keys().cloned().collect()
+    }
+
+    /// Returns a message for every address with a recorded value, carrying its most recent
+    /// arguments, in address order.
+    #[must_use]
+    pub fn messages(&self) -> Vec<OscMessage> {
+        lock(&self.values)
+            .iter()
+            .map(|(address, args)| OscMessage::new(address.as_str(), args.clone()))
+            .collect()
+    }
+
+    /// Returns a message for each address matching ``pattern``, carrying its most recent
+    /// arguments, in address order.
+    #[must_use]
+    pub fn matching(&self, pattern: &OscPattern) -> Vec<OscMessage> {
+        lock(&self.values)
+            .iter()
+            .filter(|(address, _)| crate::pattern::matches(pattern.as_str(), address))
+            .map(|(address, args)| OscMessage::new(address.as_str(), args.clone()))
+            .collect()
+    }
+
+    /// Records ``msg``'s arguments, unless its address is a pattern or one of the server's own
+    /// ``/osc/`` routes.
+    #[cfg_attr(not(feature = "server"), allow(dead_code))]
+    pub(crate) fn record(&self, msg: &OscMessage) {
+        if msg.address.starts_with("/osc/") || OscAddress::new(msg.address.as_str()).is_err() {
+            return;
+        }
+        lock(&self.values).insert(msg.address.clone(), msg.args[..].to_vec());
+    }
+}
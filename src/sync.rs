@@ -0,0 +1,24 @@
+// The locks and channels the client's background threads share state through. With the
+// ``parking_lot`` feature they come from ``parking_lot`` and ``crossbeam-channel``, which wake
+// waiting threads with less jitter at high message rates than ``std``'s.
+
+#[cfg(feature = "parking_lot")]
+pub(crate) use crossbeam_channel::{
+    unbounded as channel, Receiver, RecvTimeoutError, Sender, TryRecvError,
+};
+#[cfg(feature = "parking_lot")]
+pub(crate) use parking_lot::{Mutex, MutexGuard};
+#[cfg(not(feature = "parking_lot"))]
+pub(crate) use std::sync::{
+    mpsc::{channel, Receiver, RecvTimeoutError, Sender, TryRecvError},
+    Mutex,
+};
+
+/// Locks ``mutex``, ignoring poisoning as ``crate::lock`` does.
+#[cfg(feature = "parking_lot")]
+pub(crate) fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock()
+}
+
+#[cfg(not(feature = "parking_lot"))]
+pub(crate) use crate::lock;
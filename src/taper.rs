@@ -0,0 +1,91 @@
+use crate::Normalized;
+
+/// The curve a mixer uses to map a fader's normalised ``0.0..=1.0`` position, as sent in an
+/// ``Arg::Float``, to a level in dB. Read a position with ``msg.arg::<Normalized>(0)?`` and
+/// convert it with ``to_db``, or send a level with ``from_db``.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Taper {
+    /// The four linear segments of Behringer's X32 and X-Air (and Midas M32 and MR) faders, from
+    /// -90 dB (off) at 0.0 through -60, -30 and -10 dB to +10 dB at 1.0. The consoles themselves
+    /// round positions to 1024 steps.
+    X32,
+    /// Linear in dB from ``min_db`` just above 0.0 to ``max_db`` at 1.0, with 0.0 off, as a
+    /// generic logarithmic fader.
+    Log { min_db: f32, max_db: f32 },
+    /// The position is the linear gain itself, so 1.0 is 0 dB and 0.5 about -6 dB.
+    Gain,
+}
+
+/// The X32's fader segments, as the position each starts at, the dB at that position, and dB per
+/// unit of position.
+const X32_SEGMENTS: [(f32, f32, f32); 4] = [
+    (0.5, -10.0, 40.0),
+    (0.25, -30.0, 80.0),
+    (0.0625, -60.0, 160.0),
+    (0.0, -90.0, 480.0),
+];
+
+impl Taper {
+    /// Returns the level in dB of a fader at ``position``, or ``f32::NEG_INFINITY`` if it is
+    /// off.
+    #[must_use]
+    pub fn to_db(self, position: Normalized) -> f32 {
+        let position = position.get();
+        match self {
+            Taper::X32 => {
+                if position == 0.0 {
+                    return f32::NEG_INFINITY;
+                }
+                let (start, db, slope) = X32_SEGMENTS
+                    .into_iter()
+                    .find(|(start, _, _)| position >= *start)
+                    .unwrap_or(X32_SEGMENTS[3]);
+                db + (position - start) * slope
+            }
+            Taper::Log { min_db, max_db } => {
+                if position == 0.0 {
+                    return f32::NEG_INFINITY;
+                }
+                min_db + (max_db - min_db) * position
+            }
+            Taper::Gain => gain_to_db(position),
+        }
+    }
+
+    /// Returns the position of a fader at ``db``, clamped to the fader's range, so levels below
+    /// its lowest are off. ``NaN`` is treated as off.
+    #[must_use]
+    pub fn from_db(self, db: f32) -> Normalized {
+        let position = match self {
+            Taper::X32 => X32_SEGMENTS
+                .into_iter()
+                .find(|(_, start_db, _)| db >= *start_db)
+                .map_or(0.0, |(start, start_db, slope)| {
+                    start + (db - start_db) / slope
+                }),
+            Taper::Log { min_db, max_db } => {
+                if db < min_db {
+                    0.0
+                } else {
+                    (db - min_db) / (max_db - min_db)
+                }
+            }
+            Taper::Gain => db_to_gain(db),
+        };
+        // Clamped into range, with NaN (e.g. from a zero-width Log taper) as 0.0
+        Normalized::new(position.clamp(0.0, 1.0)).unwrap_or_default()
+    }
+}
+
+/// Converts a linear gain to dB, e.g. 0.5 to about -6 dB. A gain of 0.0 is
+/// ``f32::NEG_INFINITY``.
+#[must_use]
+pub fn gain_to_db(gain: f32) -> f32 {
+    20.0 * gain.log10()
+}
+
+/// Converts a level in dB to a linear gain, e.g. -6 dB to about 0.5.
+#[must_use]
+pub fn db_to_gain(db: f32) -> f32 {
+    10.0_f32.powf(db / 20.0)
+}
@@ -0,0 +1,69 @@
+use std::ops::Range;
+
+use crate::{errors::Error, write_arg, write_string, write_type_tags, Arg, OscMessage};
+
+/// An ``OscMessage`` encoded once, whose arguments can be patched in place before each send,
+/// e.g. for sending ``/ch/01/mix/fader`` many times a second with only the level changing. Send
+/// it with ``OscClient::send_bytes(template.bytes())``.
+#[derive(Debug, Clone)]
+pub struct OscMessageTemplate {
+    bytes: Vec<u8>,
+    /// The byte ranges of each argument's type tags and data.
+    args: Vec<(Range<usize>, Range<usize>)>,
+}
+
+impl OscMessageTemplate {
+    /// Encodes ``message``, whose arguments are the initial values.
+    ///
+    /// # Errors
+    /// Will return ``Err`` if ``message.build`` fails.
+    pub fn new(message: &OscMessage) -> Result<Self, Error> {
+        let bytes = message.build()?;
+        let mut tags = ",".to_string();
+        let mut data_start = 0;
+        let mut args = Vec::with_capacity(message.args.len());
+        for arg in &message.args {
+            let tags_start = tags.len();
+            write_type_tags(arg, &mut tags);
+            let data_len = write_arg(arg)?.len();
+            args.push((tags_start..tags.len(), data_start..data_start + data_len));
+            data_start += data_len;
+        }
+
+        // Offset the ranges by the address, and the type tags for the data.
+        let address_len = write_string(&message.address).len();
+        let data_offset = address_len + write_string(&tags).len();
+        for (tags, data) in &mut args {
+            *tags = tags.start + address_len..tags.end + address_len;
+            *data = data.start + data_offset..data.end + data_offset;
+        }
+        Ok(Self { bytes, args })
+    }
+
+    /// Replaces the argument at ``index`` with ``arg``, rewriting only its bytes. ``arg`` must
+    /// encode to the same size as the argument it replaces, as any numeric argument of the same
+    /// type, any ``Arg::Bool``, or a string padded to the same length does.
+    ///
+    /// # Errors
+    /// Will return ``Error::TemplateArg`` if there is no argument at ``index``, or ``arg``
+    /// encodes to a different size. Will also return ``Err`` if encoding ``arg`` fails.
+    pub fn set(&mut self, index: usize, arg: impl Into<Arg>) -> Result<(), Error> {
+        let arg = arg.into();
+        let (tags, data) = self.args.get(index).ok_or(Error::TemplateArg(index))?;
+        let mut arg_tags = String::new();
+        write_type_tags(&arg, &mut arg_tags);
+        let arg_data = write_arg(&arg)?;
+        if arg_tags.len() != tags.len() || arg_data.len() != data.len() {
+            return Err(Error::TemplateArg(index));
+        }
+        self.bytes[tags.clone()].copy_from_slice(arg_tags.as_bytes());
+        self.bytes[data.clone()].copy_from_slice(&arg_data);
+        Ok(())
+    }
+
+    /// Returns the encoded message, as ``OscMessage::build`` would.
+    #[must_use]
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+}
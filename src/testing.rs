@@ -0,0 +1,559 @@
+use std::{
+    collections::VecDeque,
+    io::ErrorKind,
+    net::{SocketAddr, ToSocketAddrs, UdpSocket},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Condvar, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+#[cfg(all(feature = "client", feature = "server", feature = "udp"))]
+use std::thread::JoinHandle;
+
+#[cfg(all(feature = "client", feature = "server", feature = "udp"))]
+use crate::{
+    client::OscClient,
+    server::{OscServer, ServerHandle},
+};
+use crate::{
+    errors::Error, lock, pattern::OscPattern, sockets::Connection, Args, OscMessage, OscPacket,
+};
+
+/// The faults a ``SimConnection`` injects into one direction of traffic. Probabilities are in
+/// ``0.0..=1.0`` and are rolled independently for every packet.
+#[derive(Debug, Clone, Default)]
+pub struct Faults {
+    /// Chance that a packet is silently dropped.
+    pub loss: f64,
+    /// Chance that a packet is delivered twice.
+    pub duplication: f64,
+    /// Chance that a packet is held back and delivered after the one following it.
+    pub reordering: f64,
+    /// Delay added before every packet is delivered.
+    pub latency: Duration,
+}
+
+/// Wraps any ``Connection`` and injects packet loss, duplication, reordering, and latency into
+/// its traffic, for exercising timeouts and retry logic without a real lossy network.
+///
+/// Faults are decided by a pseudo-random generator seeded at construction, so a given seed and
+/// sequence of calls always produces the same faults.
+pub struct SimConnection<C: Connection> {
+    inner: C,
+    send_faults: Faults,
+    recv_faults: Faults,
+    rng_state: u64,
+    held_send: Option<Vec<u8>>,
+    held_recv: Option<Vec<u8>>,
+    ready: VecDeque<Vec<u8>>,
+}
+
+impl<C: Connection> SimConnection<C> {
+    /// Wraps ``inner`` without any faults. Use ``with_send_faults`` and ``with_recv_faults`` to
+    /// configure them.
+    #[must_use]
+    pub fn wrap(inner: C, seed: u64) -> Self {
+        Self {
+            inner,
+            send_faults: Faults::default(),
+            recv_faults: Faults::default(),
+            rng_state: seed,
+            held_send: None,
+            held_recv: None,
+            ready: VecDeque::new(),
+        }
+    }
+
+    /// Sets the faults injected into outgoing packets.
+    #[must_use]
+    pub fn with_send_faults(mut self, faults: Faults) -> Self {
+        self.send_faults = faults;
+        self
+    }
+
+    /// Sets the faults injected into incoming packets.
+    #[must_use]
+    pub fn with_recv_faults(mut self, faults: Faults) -> Self {
+        self.recv_faults = faults;
+        self
+    }
+
+    /// Returns the wrapped connection.
+    pub fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    // splitmix64, so that any seed (including 0) gives a usable sequence.
+    #[allow(clippy::cast_precision_loss)]
+    fn chance(&mut self, probability: f64) -> bool {
+        if probability <= 0.0 {
+            return false;
+        }
+        self.rng_state = self.rng_state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.rng_state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        ((z >> 11) as f64 / (1u64 << 53) as f64) < probability
+    }
+}
+
+fn copy_packet(packet: &[u8], buf: &mut [u8]) -> usize {
+    let length = packet.len().min(buf.len());
+    buf[..length].copy_from_slice(&packet[..length]);
+    length
+}
+
+impl<C: Connection> Connection for SimConnection<C> {
+    fn new<A: ToSocketAddrs, B: ToSocketAddrs>(
+        local_address: A,
+        remote_address: B,
+    ) -> std::io::Result<Self> {
+        C::new(local_address, remote_address).map(|inner| Self::wrap(inner, 0))
+    }
+
+    /// Always reports the whole of ``buf`` as sent, even if the packet was dropped or held back.
+    fn send(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        thread::sleep(self.send_faults.latency);
+        if self.chance(self.send_faults.loss) {
+            return Ok(buf.len());
+        }
+        if self.held_send.is_none() && self.chance(self.send_faults.reordering) {
+            self.held_send = Some(buf.to_vec());
+            return Ok(buf.len());
+        }
+
+        self.inner.send(buf)?;
+        if self.chance(self.send_faults.duplication) {
+            self.inner.send(buf)?;
+        }
+        if let Some(held) = self.held_send.take() {
+            self.inner.send(&held)?;
+        }
+        Ok(buf.len())
+    }
+
+    /// A packet held back for reordering is delivered once the packet after it arrives, or when
+    /// the inner connection has nothing more to give.
+    fn recv(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if let Some(packet) = self.ready.pop_front() {
+            return Ok(copy_packet(&packet, buf));
+        }
+
+        loop {
+            let length = match self.inner.recv(buf) {
+                Ok(length) => length,
+                Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+                    return match self.held_recv.take() {
+                        Some(held) => Ok(copy_packet(&held, buf)),
+                        None => Err(e),
+                    };
+                }
+                Err(e) => return Err(e),
+            };
+
+            if self.chance(self.recv_faults.loss) {
+                continue;
+            }
+            if self.held_recv.is_none() && self.chance(self.recv_faults.reordering) {
+                self.held_recv = Some(buf[..length].to_vec());
+                continue;
+            }
+
+            if self.chance(self.recv_faults.duplication) {
+                self.ready.push_back(buf[..length].to_vec());
+            }
+            if let Some(held) = self.held_recv.take() {
+                self.ready.push_back(held);
+            }
+            thread::sleep(self.recv_faults.latency);
+            return Ok(length);
+        }
+    }
+
+    fn set_read_timeout(&self, dur: Option<Duration>) -> std::io::Result<()> {
+        self.inner.set_read_timeout(dur)
+    }
+
+    fn set_nonblocking(&self, nonblocking: bool) -> std::io::Result<()> {
+        self.inner.set_nonblocking(nonblocking)
+    }
+
+    /// The clone injects the same faults, continuing from the current generator state, but does
+    /// not share held back or duplicated packets.
+    fn try_clone(&self) -> std::io::Result<Self> {
+        Ok(Self {
+            inner: self.inner.try_clone()?,
+            send_faults: self.send_faults.clone(),
+            recv_faults: self.recv_faults.clone(),
+            rng_state: self.rng_state,
+            held_send: None,
+            held_recv: None,
+            ready: VecDeque::new(),
+        })
+    }
+
+    /// Held back and duplicated packets from the old remote are discarded.
+    fn connect<A: ToSocketAddrs>(&mut self, remote_address: A) -> std::io::Result<()> {
+        self.inner.connect(remote_address)?;
+        self.held_send = None;
+        self.held_recv = None;
+        self.ready.clear();
+        Ok(())
+    }
+
+    fn local_addr(&self) -> std::io::Result<SocketAddr> {
+        self.inner.local_addr()
+    }
+
+    fn peer_addr(&self) -> std::io::Result<SocketAddr> {
+        self.inner.peer_addr()
+    }
+}
+
+/// How long the mock server's thread waits for a packet before checking whether it's been
+/// dropped.
+const MOCK_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// A UDP server on an ephemeral localhost port which replies to requests with canned responses
+/// and records every message it receives, for testing a device driver without the device.
+///
+/// Each request is answered by the first rule which matches it, added with ``respond``,
+/// ``respond_to``, or, with the ``json`` feature, ``load_json``. Requests no rule matches are
+/// only recorded. Messages in a bundle are handled one by one. The server stops when dropped.
+pub struct MockOscServer {
+    address: SocketAddr,
+    state: Arc<(Mutex<MockState>, Condvar)>,
+    stopped: Arc<AtomicBool>,
+}
+
+#[derive(Default)]
+struct MockState {
+    rules: Vec<MockRule>,
+    received: Vec<OscMessage>,
+}
+
+struct MockRule {
+    pattern: OscPattern,
+    /// The arguments a request must have, if any particular ones.
+    args: Option<Args>,
+    replies: Vec<OscMessage>,
+}
+
+impl MockRule {
+    fn matches(&self, request: &OscMessage) -> bool {
+        let address = request.address.as_str().try_into();
+        if !address.is_ok_and(|address| self.pattern.matches(&address)) {
+            return false;
+        }
+        // ``Arg`` can't be compared directly, as a custom argument may hold anything
+        self.args.as_ref().is_none_or(|args| {
+            let expected = OscMessage::new(&request.address, args.clone()).build();
+            matches!((expected, request.build()), (Ok(a), Ok(b)) if a == b)
+        })
+    }
+}
+
+impl MockOscServer {
+    /// Starts the server on a free port on ``127.0.0.1``. See ``local_addr``.
+    ///
+    /// # Errors
+    /// Will return an ``Error::Socket`` if the socket can't be bound.
+    pub fn start() -> Result<Self, Error> {
+        let socket = UdpSocket::bind("127.0.0.1:0").map_err(Error::Socket)?;
+        socket
+            .set_read_timeout(Some(MOCK_POLL_INTERVAL))
+            .map_err(Error::Socket)?;
+        let address = socket.local_addr().map_err(Error::Socket)?;
+        let state: Arc<(Mutex<MockState>, Condvar)> = Arc::default();
+        let stopped = Arc::new(AtomicBool::new(false));
+        let (thread_state, thread_stopped) = (state.clone(), stopped.clone());
+        thread::spawn(move || serve(&socket, &thread_state, &thread_stopped));
+        Ok(Self {
+            address,
+            state,
+            stopped,
+        })
+    }
+
+    /// Returns the address to send requests to.
+    #[must_use]
+    pub fn local_addr(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// Replies with ``replies`` to every request to an address matching ``pattern``.
+    ///
+    /// # Errors
+    /// Will return ``Error::Address`` if ``pattern`` is not a valid ``OscPattern``.
+    pub fn respond<P>(
+        &self,
+        pattern: P,
+        replies: impl IntoIterator<Item = OscMessage>,
+    ) -> Result<(), Error>
+    where
+        P: TryInto<OscPattern>,
+        Error: From<P::Error>,
+    {
+        self.add_rule(MockRule {
+            pattern: pattern.try_into()?,
+            args: None,
+            replies: replies.into_iter().collect(),
+        });
+        Ok(())
+    }
+
+    /// Replies with ``replies`` to requests to ``request``'s address with exactly its arguments.
+    ///
+    /// # Errors
+    /// Will return ``Error::Address`` if ``request``'s address is not a valid ``OscPattern``.
+    pub fn respond_to(
+        &self,
+        request: OscMessage,
+        replies: impl IntoIterator<Item = OscMessage>,
+    ) -> Result<(), Error> {
+        self.add_rule(MockRule {
+            pattern: OscPattern::new(&request.address)?,
+            args: Some(request.args),
+            replies: replies.into_iter().collect(),
+        });
+        Ok(())
+    }
+
+    /// Adds the rules in ``json``, an array of objects with an ``address`` pattern, optionally
+    /// the ``args`` a request must have, and the ``replies`` to send, as messages in the form
+    /// ``OscMessage::to_json`` writes, e.g.
+    /// ``[{"address": "/status", "replies": [{"address": "/status", "args": []}]}]``.
+    ///
+    /// # Errors
+    /// Will return ``Error::Malformed`` if ``json`` isn't in that form, or ``Error::Address`` if
+    /// an address is not a valid ``OscPattern``.
+    #[cfg(feature = "json")]
+    pub fn load_json(&self, json: &str) -> Result<(), Error> {
+        #[derive(serde::Deserialize)]
+        struct ScriptRule {
+            address: String,
+            #[serde(default)]
+            args: Option<Args>,
+            #[serde(default)]
+            replies: Vec<OscMessage>,
+        }
+
+        let rules: Vec<ScriptRule> = serde_json::from_str(json)
+            .map_err(|e| Error::Malformed(format!("Mock server script ({e})")))?;
+        for rule in rules {
+            self.add_rule(MockRule {
+                pattern: OscPattern::new(&rule.address)?,
+                args: rule.args,
+                replies: rule.replies,
+            });
+        }
+        Ok(())
+    }
+
+    /// Adds the rules in the JSON file at ``path``. See ``load_json``.
+    ///
+    /// # Errors
+    /// Will return an ``Error::Socket`` if the file can't be read, or see ``load_json``.
+    #[cfg(feature = "json")]
+    pub fn load_file(&self, path: impl AsRef<std::path::Path>) -> Result<(), Error> {
+        self.load_json(&std::fs::read_to_string(path).map_err(Error::Socket)?)
+    }
+
+    fn add_rule(&self, rule: MockRule) {
+        lock(&self.state.0).rules.push(rule);
+    }
+
+    /// Returns every message received so far, in the order they arrived.
+    #[must_use]
+    pub fn received(&self) -> Vec<OscMessage> {
+        lock(&self.state.0).received.clone()
+    }
+
+    /// Waits until at least ``count`` messages have been received, or ``timeout`` passes,
+    /// returning every message received so far. UDP gives no guarantee a request arrives before
+    /// its send returns, so use this rather than ``received`` to check what was sent.
+    #[must_use]
+    pub fn wait_received(&self, count: usize, timeout: Duration) -> Vec<OscMessage> {
+        let deadline = Instant::now() + timeout;
+        let (state, arrived) = &*self.state;
+        let mut state = lock(state);
+        while state.received.len() < count {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                break;
+            }
+            state = match arrived.wait_timeout(state, remaining) {
+                Ok((state, _)) => state,
+                Err(poisoned) => poisoned.into_inner().0,
+            };
+        }
+        state.received.clone()
+    }
+
+    /// Forgets the messages received so far.
+    pub fn clear_received(&self) {
+        lock(&self.state.0).received.clear();
+    }
+}
+
+impl Drop for MockOscServer {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Receives requests on ``socket``, recording and replying to them, until ``stopped`` is set.
+fn serve(socket: &UdpSocket, state: &(Mutex<MockState>, Condvar), stopped: &AtomicBool) {
+    let (state, arrived) = state;
+    let mut buffer = vec![0; 65536];
+    while !stopped.load(Ordering::Relaxed) {
+        let (size, source) = match socket.recv_from(&mut buffer) {
+            Ok(received) => received,
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => continue,
+            Err(_) => return,
+        };
+        let Ok(packet) = OscPacket::parse_bytes(&buffer[..size]) else {
+            continue;
+        };
+        let mut requests = Vec::new();
+        flatten(packet, &mut requests);
+        for request in requests {
+            let replies = {
+                let mut state = lock(state);
+                let replies = state
+                    .rules
+                    .iter()
+                    .find(|rule| rule.matches(&request))
+                    .map(|rule| rule.replies.clone())
+                    .unwrap_or_default();
+                state.received.push(request);
+                replies
+            };
+            arrived.notify_all();
+            for reply in replies {
+                if let Ok(bytes) = reply.build() {
+                    let _ = socket.send_to(&bytes, source);
+                }
+            }
+        }
+    }
+}
+
+fn flatten(packet: OscPacket, messages: &mut Vec<OscMessage>) {
+    match packet {
+        OscPacket::Message(message) => messages.push(message),
+        OscPacket::Bundle(bundle) => {
+            for packet in bundle.content {
+                flatten(packet, messages);
+            }
+        }
+    }
+}
+
+/// A running ``OscServer`` on an ephemeral localhost port, with an ``OscClient`` connected to it,
+/// for integration tests of a server's routes in a few lines, e.g.
+/// ``OscTestPair::start(|server| server.add_route("/ping", |_, _| Some(vec![])))``, as this
+/// crate's own tests of sessions, access control and handler panics do. The server runs on its
+/// own thread, and is stopped when the pair is dropped.
+#[cfg(all(feature = "client", feature = "server", feature = "udp"))]
+pub struct OscTestPair {
+    client: OscClient<UdpSocket>,
+    address: SocketAddr,
+    handle: ServerHandle,
+    stopped: Arc<AtomicBool>,
+    thread: Option<JoinHandle<Result<(), Error>>>,
+}
+
+#[cfg(all(feature = "client", feature = "server", feature = "udp"))]
+impl OscTestPair {
+    /// Binds a server to a free port on ``127.0.0.1``, registers its routes with ``configure``,
+    /// and starts it, connecting a client to it with a 1 second timeout.
+    ///
+    /// # Errors
+    /// Will return an ``Error::Socket`` if a socket can't be bound, or any error from
+    /// ``configure``.
+    pub fn start<F>(configure: F) -> Result<Self, Error>
+    where
+        F: FnOnce(OscServer) -> Result<OscServer, Error>,
+    {
+        Self::from_server(configure(OscServer::new("127.0.0.1:0", 65536)?)?)
+    }
+
+    /// Starts ``server``, which should be bound to a localhost address, and connects a client to
+    /// it with a 1 second timeout.
+    ///
+    /// # Errors
+    /// Will return an ``Error::Socket`` if the client's socket can't be bound.
+    pub fn from_server(mut server: OscServer) -> Result<Self, Error> {
+        let address = server.local_addr()?;
+        let handle = server.handle();
+        let client = OscClient::new("127.0.0.1:0", address, 65536, Some(Duration::from_secs(1)))?;
+        let stopped = Arc::new(AtomicBool::new(false));
+        let thread_stopped = stopped.clone();
+        let thread = thread::spawn(move || {
+            while !thread_stopped.load(Ordering::Relaxed) {
+                server.step(MOCK_POLL_INTERVAL)?;
+            }
+            Ok(())
+        });
+        Ok(Self {
+            client,
+            address,
+            handle,
+            stopped,
+            thread: Some(thread),
+        })
+    }
+
+    /// Returns the client connected to the server.
+    pub fn client(&mut self) -> &mut OscClient<UdpSocket> {
+        &mut self.client
+    }
+
+    /// Returns the address the server is bound to.
+    #[must_use]
+    pub fn server_addr(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// Returns a ``ServerHandle`` to change the server's routes while it runs.
+    #[must_use]
+    pub fn handle(&self) -> ServerHandle {
+        self.handle.clone()
+    }
+
+    /// Stops the server and waits for its thread to finish, returning the error which stopped it
+    /// early, if any.
+    ///
+    /// # Errors
+    /// Will return any error from ``OscServer::step``, e.g. if sending a reply failed.
+    ///
+    /// # Panics
+    /// Will resume the panic of a handler which panicked.
+    pub fn stop(mut self) -> Result<(), Error> {
+        self.join()
+    }
+
+    fn join(&mut self) -> Result<(), Error> {
+        self.stopped.store(true, Ordering::Relaxed);
+        match self.thread.take().map(JoinHandle::join) {
+            Some(Ok(result)) => result,
+            Some(Err(payload)) => std::panic::resume_unwind(payload),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(all(feature = "client", feature = "server", feature = "udp"))]
+impl Drop for OscTestPair {
+    fn drop(&mut self) {
+        // A panicking handler has already failed the test
+        if !thread::panicking() {
+            let _ = self.join();
+        }
+    }
+}
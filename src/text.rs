@@ -0,0 +1,242 @@
+use std::{
+    fmt::{self, Display, Write},
+    str::{Chars, FromStr},
+};
+
+use crate::{copy_blob, errors::Error, Arg, Args, OscMessage, Rgba};
+
+/// Writes a message in an ``oscsend``-like text form: the address, then each argument's type tag
+/// followed by its value, e.g. ``/ch/01/fader f 0.75``. Strings are quoted if they're empty or
+/// hold whitespace, quotes, or backslashes. Blobs and unknown arguments are written as hex, as
+/// are colors (``rrggbbaa``) and MIDI messages (port, status, and data bytes). Timetags are
+/// written as the 64-bit NTP timestamp. Arrays are written as ``[``, their arguments, then
+/// ``]``. Arguments without a value (``T``, ``F``, ``N`` and ``I``) are just their tag.
+impl Display for OscMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_str(&self.address, f)?;
+        for arg in &self.args {
+            f.write_char(' ')?;
+            write_arg(arg, f)?;
+        }
+        Ok(())
+    }
+}
+
+fn write_arg(arg: &Arg, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match arg {
+        Arg::Int(i) => write!(f, "i {i}"),
+        Arg::Float(x) => write!(f, "f {x}"),
+        Arg::Str(s) => {
+            f.write_str("s ")?;
+            write_str(s, f)
+        }
+        Arg::Blob(b) => {
+            f.write_str("b ")?;
+            write_hex(b, f)
+        }
+        Arg::Time(t) => write!(f, "t {}", u64::from(*t)),
+        Arg::Int64(h) => write!(f, "h {h}"),
+        Arg::Double(d) => write!(f, "d {d}"),
+        Arg::Bool(true) => f.write_char('T'),
+        Arg::Bool(false) => f.write_char('F'),
+        Arg::Nil => f.write_char('N'),
+        Arg::Infinitum => f.write_char('I'),
+        Arg::Char(c) => {
+            f.write_str("c ")?;
+            write_str(c.encode_utf8(&mut [0; 4]), f)
+        }
+        Arg::Color(color) => {
+            f.write_str("r ")?;
+            write_hex(&<[u8; 4]>::from(*color), f)
+        }
+        Arg::Midi {
+            port,
+            status,
+            data1,
+            data2,
+        } => {
+            f.write_str("m ")?;
+            write_hex(&[*port, *status, *data1, *data2], f)
+        }
+        Arg::Array(items) => {
+            f.write_char('[')?;
+            for item in items {
+                f.write_char(' ')?;
+                write_arg(item, f)?;
+            }
+            f.write_str(" ]")
+        }
+        Arg::Symbol(s) => {
+            f.write_str("S ")?;
+            write_str(s, f)
+        }
+        Arg::Unknown(tag, data) => {
+            write!(f, "{tag} ")?;
+            write_hex(data, f)
+        }
+        // Written as the data it's sent with, so it reads back as an ``Arg::Unknown``
+        Arg::Custom(value) => match value.encode() {
+            Ok(data) => {
+                write!(f, "{} ", value.tag())?;
+                write_hex(&data, f)
+            }
+            Err(e) => write!(f, "{} <{e}>", value.tag()),
+        },
+    }
+}
+
+fn write_str(s: &str, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    if !s.is_empty() && !s.contains(|c: char| c.is_whitespace() || matches!(c, '"' | '\\')) {
+        return f.write_str(s);
+    }
+    f.write_char('"')?;
+    for c in s.chars() {
+        if matches!(c, '"' | '\\') {
+            f.write_char('\\')?;
+        }
+        f.write_char(c)?;
+    }
+    f.write_char('"')
+}
+
+fn write_hex(data: &[u8], f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    if data.is_empty() {
+        return f.write_str("\"\"");
+    }
+    data.iter().try_for_each(|b| write!(f, "{b:02x}"))
+}
+
+/// Reads a message from the text form its ``Display`` implementation writes, e.g.
+/// ``"/ch/01/fader f 0.75".parse::<OscMessage>()``. Tags this crate doesn't know are read as
+/// ``Arg::Unknown`` with hex data.
+impl FromStr for OscMessage {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut tokens = Tokens(s.chars());
+        let address = tokens
+            .next()?
+            .ok_or_else(|| Error::Malformed("Empty OSC text message".to_string()))?;
+        let mut args = Args::new();
+        while let Some(tag) = tokens.next()? {
+            args.push(read_arg(&tag, &mut tokens)?);
+        }
+        Ok(Self::new(address, args))
+    }
+}
+
+fn read_arg(tag: &str, tokens: &mut Tokens) -> Result<Arg, Error> {
+    let mut chars = tag.chars();
+    let (Some(tag), None) = (chars.next(), chars.next()) else {
+        return Err(Error::Malformed(format!("OSC text type tag {tag:?}")));
+    };
+    let mut value = || {
+        tokens
+            .next()?
+            .ok_or_else(|| Error::Malformed(format!("OSC text argument {tag} with no value")))
+    };
+    Ok(match tag {
+        'i' => Arg::Int(parse_number(tag, &value()?)?),
+        'f' => Arg::Float(parse_number(tag, &value()?)?),
+        's' => Arg::Str(value()?),
+        'b' => Arg::Blob(copy_blob(&read_hex(tag, &value()?)?)),
+        't' => Arg::Time(parse_number::<u64>(tag, &value()?)?.into()),
+        'h' => Arg::Int64(parse_number(tag, &value()?)?),
+        'd' => Arg::Double(parse_number(tag, &value()?)?),
+        'T' => Arg::Bool(true),
+        'F' => Arg::Bool(false),
+        'N' => Arg::Nil,
+        'I' => Arg::Infinitum,
+        'c' => Arg::Char(parse_number(tag, &value()?)?),
+        'r' => Arg::Color(Rgba::from(read_hex_array(tag, &value()?)?)),
+        'm' => {
+            let [port, status, data1, data2] = read_hex_array(tag, &value()?)?;
+            Arg::Midi {
+                port,
+                status,
+                data1,
+                data2,
+            }
+        }
+        '[' => {
+            let mut items = Vec::new();
+            loop {
+                let item = tokens
+                    .next()?
+                    .ok_or_else(|| Error::Malformed("Unclosed OSC text array".to_string()))?;
+                if item == "]" {
+                    break Arg::Array(items);
+                }
+                items.push(read_arg(&item, tokens)?);
+            }
+        }
+        'S' => Arg::Symbol(value()?),
+        ']' | ',' => return Err(Error::Malformed(format!("OSC text type tag {tag:?}"))),
+        tag => Arg::Unknown(tag, read_hex(tag, &value()?)?),
+    })
+}
+
+fn parse_number<T: FromStr>(tag: char, value: &str) -> Result<T, Error> {
+    value
+        .parse()
+        .map_err(|_| Error::Malformed(format!("OSC text argument {tag} {value:?}")))
+}
+
+fn read_hex(tag: char, value: &str) -> Result<Vec<u8>, Error> {
+    let malformed = || Error::Malformed(format!("OSC text argument {tag} {value:?}"));
+    if !value.len().is_multiple_of(2) {
+        return Err(malformed());
+    }
+    (0..value.len())
+        .step_by(2)
+        .map(|i| {
+            value
+                .get(i..i + 2)
+                .and_then(|byte| u8::from_str_radix(byte, 16).ok())
+                .ok_or_else(malformed)
+        })
+        .collect()
+}
+
+fn read_hex_array(tag: char, value: &str) -> Result<[u8; 4], Error> {
+    read_hex(tag, value)?
+        .try_into()
+        .map_err(|_| Error::Malformed(format!("OSC text argument {tag} {value:?}")))
+}
+
+/// Splits text into whitespace-separated tokens, reading quoted tokens as ``Display`` writes
+/// them.
+struct Tokens<'a>(Chars<'a>);
+
+impl Tokens<'_> {
+    fn next(&mut self) -> Result<Option<String>, Error> {
+        let mut token = String::new();
+        let mut chars = self.0.by_ref().skip_while(|c| c.is_whitespace()).peekable();
+        match chars.peek() {
+            None => return Ok(None),
+            Some('"') => {
+                chars.next();
+                loop {
+                    match chars.next() {
+                        Some('"') => return Ok(Some(token)),
+                        Some('\\') => token.extend(chars.next()),
+                        Some(c) => token.push(c),
+                        None => {
+                            return Err(Error::Malformed(
+                                "Unterminated OSC text string".to_string(),
+                            ))
+                        }
+                    }
+                }
+            }
+            Some(_) => {}
+        }
+        for c in chars {
+            if c.is_whitespace() {
+                break;
+            }
+            token.push(c);
+        }
+        Ok(Some(token))
+    }
+}
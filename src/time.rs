@@ -0,0 +1,168 @@
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::errors::Error;
+
+/// Seconds between the NTP epoch (1900-01-01) and the Unix epoch (1970-01-01).
+const NTP_UNIX_OFFSET: u64 = 2_208_988_800;
+const NANOS_PER_SECOND: u64 = 1_000_000_000;
+
+/// An OSC timetag: a 64-bit NTP timestamp, counting seconds (and fractions of a second) since
+/// midnight on January 1st 1900.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OscTime {
+    pub seconds: u32,
+    pub fractional: u32,
+}
+
+impl OscTime {
+    /// The special timetag meaning "immediately", with 63 zero bits followed by a one.
+    pub const IMMEDIATE: Self = Self {
+        seconds: 0,
+        fractional: 1,
+    };
+
+    #[must_use]
+    pub fn new(seconds: u32, fractional: u32) -> Self {
+        Self {
+            seconds,
+            fractional,
+        }
+    }
+
+    /// Returns the timetag ``delay`` from now, e.g. to schedule a bundle.
+    #[must_use]
+    pub fn from_now(delay: Duration) -> Self {
+        (SystemTime::now() + delay).into()
+    }
+}
+
+impl From<u64> for OscTime {
+    fn from(value: u64) -> Self {
+        #[allow(clippy::cast_possible_truncation)]
+        Self {
+            seconds: (value >> 32) as u32,
+            fractional: value as u32,
+        }
+    }
+}
+
+impl From<OscTime> for u64 {
+    fn from(value: OscTime) -> Self {
+        (u64::from(value.seconds) << 32) | u64::from(value.fractional)
+    }
+}
+
+/// Times outside the 1900-2036 NTP era wrap around, as they do on the wire.
+impl From<SystemTime> for OscTime {
+    #[allow(clippy::cast_possible_truncation)]
+    fn from(value: SystemTime) -> Self {
+        let (seconds, nanos) = match value.duration_since(UNIX_EPOCH) {
+            Ok(since) => (
+                NTP_UNIX_OFFSET.wrapping_add(since.as_secs()),
+                since.subsec_nanos(),
+            ),
+            Err(e) => {
+                let before = e.duration();
+                let mut seconds = NTP_UNIX_OFFSET.wrapping_sub(before.as_secs());
+                let mut nanos = before.subsec_nanos();
+                if nanos > 0 {
+                    seconds = seconds.wrapping_sub(1);
+                    nanos = 1_000_000_000 - nanos;
+                }
+                (seconds, nanos)
+            }
+        };
+        Self {
+            seconds: seconds as u32,
+            fractional: ((u64::from(nanos) << 32) / NANOS_PER_SECOND) as u32,
+        }
+    }
+}
+
+/// An ``Instant`` has no epoch, so it's placed by its distance from ``Instant::now()``, counted
+/// from ``SystemTime::now()``. Changes to the system clock after the ``Instant`` was taken aren't
+/// accounted for.
+impl From<Instant> for OscTime {
+    fn from(value: Instant) -> Self {
+        let (system_now, now) = (SystemTime::now(), Instant::now());
+        if value >= now {
+            (system_now + (value - now)).into()
+        } else {
+            (system_now - (now - value)).into()
+        }
+    }
+}
+
+/// Timetags are interpreted in the 1900-2036 NTP era.
+///
+/// # Errors
+/// Will return ``Error::TimeRange`` if the platform's ``SystemTime`` cannot represent the time.
+impl TryFrom<OscTime> for SystemTime {
+    type Error = Error;
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn try_from(value: OscTime) -> Result<Self, Self::Error> {
+        let nanos = ((u64::from(value.fractional) * NANOS_PER_SECOND) >> 32) as u32;
+        let since_ntp_epoch = Duration::new(u64::from(value.seconds), nanos);
+        UNIX_EPOCH
+            .checked_sub(Duration::from_secs(NTP_UNIX_OFFSET))
+            .and_then(|ntp_epoch| ntp_epoch.checked_add(since_ntp_epoch))
+            .ok_or(Error::TimeRange(value))
+    }
+}
+
+/// An estimate of a remote device's clock relative to ours, from an NTP-style exchange of
+/// timetags, so bundles can be scheduled against the device's clock rather than ours. See
+/// ``OscClient::estimate_clock``.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClockEstimate {
+    /// How long the exchange took, less the time the remote spent answering.
+    pub round_trip: Duration,
+    /// How far the remote clock is ahead of ours, in nanoseconds (negative if it's behind).
+    pub offset_nanos: i64,
+}
+
+impl ClockEstimate {
+    /// Computes the estimate from one exchange: we sent at ``sent``, the remote received at
+    /// ``remote_received`` and replied at ``remote_sent`` by its clock, and the reply arrived at
+    /// ``received``. Assumes the network delay is the same both ways.
+    #[must_use]
+    pub fn from_exchange(
+        sent: OscTime,
+        remote_received: OscTime,
+        remote_sent: OscTime,
+        received: OscTime,
+    ) -> Self {
+        let outbound = signed_nanos(remote_received, sent);
+        let inbound = signed_nanos(remote_sent, received);
+        let round_trip = signed_nanos(received, sent) - signed_nanos(remote_sent, remote_received);
+        Self {
+            round_trip: Duration::from_nanos(u64::try_from(round_trip).unwrap_or(0)),
+            offset_nanos: i64::midpoint(outbound, inbound),
+        }
+    }
+
+    /// Returns the remote clock's timetag for ``local``, a time by our clock.
+    #[must_use]
+    pub fn to_remote(&self, local: OscTime) -> OscTime {
+        let offset = (i128::from(self.offset_nanos) << 32) / i128::from(NANOS_PER_SECOND);
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        OscTime::from(u64::from(local).wrapping_add(offset as i64 as u64))
+    }
+
+    /// Returns the remote clock's timetag ``delay`` from now, e.g. to schedule a bundle on it.
+    #[must_use]
+    pub fn from_now(&self, delay: Duration) -> OscTime {
+        self.to_remote(OscTime::from_now(delay))
+    }
+}
+
+/// Returns ``a - b`` in nanoseconds, assuming they are less than half an NTP era apart.
+fn signed_nanos(a: OscTime, b: OscTime) -> i64 {
+    #[allow(clippy::cast_possible_wrap)]
+    let diff = u64::from(a).wrapping_sub(u64::from(b)) as i64;
+    #[allow(clippy::cast_possible_truncation)]
+    let nanos = ((i128::from(diff) * i128::from(NANOS_PER_SECOND)) >> 32) as i64;
+    nanos
+}
@@ -0,0 +1,157 @@
+use std::{
+    io::{self, ErrorKind, Read, Write},
+    net::{SocketAddr, TcpStream, ToSocketAddrs},
+    sync::Arc,
+    time::Duration,
+};
+
+use rustls::{pki_types::ServerName, ClientConfig, ClientConnection, RootCertStore, StreamOwned};
+
+use crate::sockets::{copy_frame, frame, take_frame, Connection};
+
+/// A TLS-encrypted ``TcpStream``, using OSC 1.0 length-prefix framing within the encrypted stream
+/// (see ``FramedTcpStream``), for sending OSC over untrusted networks.
+///
+/// ``Connection::new`` verifies the server's certificate against the web PKI roots, for the
+/// remote IP address. To trust other certificates (e.g. a device's self-signed one), verify a
+/// host name, or authenticate with a client certificate, build a ``ClientConfig`` (see
+/// ``TlsConnection::config``), ``connect`` with it, and give the connection to
+/// ``OscClient::from_connection``.
+pub struct TlsConnection {
+    stream: StreamOwned<ClientConnection, TcpStream>,
+    pending: Vec<u8>,
+    server_name: ServerName<'static>,
+    config: Arc<ClientConfig>,
+}
+
+impl TlsConnection {
+    /// Connects to ``remote_address``, verifying that its certificate is valid for
+    /// ``server_name`` (a host name or IP address) according to ``config``. The handshake happens
+    /// on the first send or receive.
+    ///
+    /// # Errors
+    /// Will return ``Err`` if ``server_name`` is invalid, or connecting fails.
+    pub fn connect<A: ToSocketAddrs>(
+        remote_address: A,
+        server_name: &str,
+        config: Arc<ClientConfig>,
+    ) -> io::Result<Self> {
+        let server_name = ServerName::try_from(server_name.to_string())
+            .map_err(|e| io::Error::new(ErrorKind::InvalidInput, e))?;
+        Self::connect_to(remote_address, server_name, config)
+    }
+
+    /// Creates a ``ClientConfig`` trusting the certificates in ``roots``, without a client
+    /// certificate.
+    ///
+    /// # Errors
+    /// Will return ``Err`` if the default TLS versions are unsupported.
+    pub fn config(roots: RootCertStore) -> io::Result<Arc<ClientConfig>> {
+        let provider = Arc::new(rustls::crypto::ring::default_provider());
+        let config = ClientConfig::builder_with_provider(provider)
+            .with_safe_default_protocol_versions()
+            .map_err(io::Error::other)?
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+        Ok(Arc::new(config))
+    }
+
+    /// Returns the root certificates trusted by web browsers, from ``webpki-roots``.
+    #[must_use]
+    pub fn webpki_roots() -> RootCertStore {
+        RootCertStore {
+            roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+        }
+    }
+
+    fn connect_to<A: ToSocketAddrs>(
+        remote_address: A,
+        server_name: ServerName<'static>,
+        config: Arc<ClientConfig>,
+    ) -> io::Result<Self> {
+        let connection =
+            ClientConnection::new(config.clone(), server_name.clone()).map_err(io::Error::other)?;
+        let socket = TcpStream::connect(remote_address)?;
+        Ok(Self {
+            stream: StreamOwned::new(connection, socket),
+            pending: Vec::new(),
+            server_name,
+            config,
+        })
+    }
+}
+
+impl Connection for TlsConnection {
+    fn new<A: ToSocketAddrs, B: ToSocketAddrs>(_: A, remote_address: B) -> io::Result<Self> {
+        let remote_address: Vec<SocketAddr> = remote_address.to_socket_addrs()?.collect();
+        let ip = remote_address
+            .first()
+            .ok_or_else(|| io::Error::new(ErrorKind::InvalidInput, "No remote address"))?
+            .ip();
+        let config = Self::config(Self::webpki_roots())?;
+        Self::connect_to(
+            &remote_address[..],
+            ServerName::IpAddress(ip.into()),
+            config,
+        )
+    }
+
+    fn send(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.stream.write_all(&frame(buf)?)?;
+        self.stream.flush()?;
+        Ok(buf.len())
+    }
+
+    fn recv(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut chunk = [0; 4096];
+        loop {
+            if let Some(frame) = take_frame(&mut self.pending, buf.len())? {
+                return copy_frame(&frame, buf);
+            }
+            match self.stream.read(&mut chunk)? {
+                0 => {
+                    return Err(io::Error::new(
+                        ErrorKind::UnexpectedEof,
+                        "Connection closed by peer",
+                    ))
+                }
+                n => self.pending.extend_from_slice(&chunk[..n]),
+            }
+        }
+    }
+
+    fn set_read_timeout(&self, dur: Option<Duration>) -> io::Result<()> {
+        self.stream.sock.set_read_timeout(dur)
+    }
+
+    fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        self.stream.sock.set_nonblocking(nonblocking)
+    }
+
+    /// TLS sessions can't be shared, so this always returns an error of kind
+    /// ``io::ErrorKind::Unsupported``.
+    fn try_clone(&self) -> io::Result<Self> {
+        Err(io::Error::new(
+            ErrorKind::Unsupported,
+            "TLS connections can't be cloned",
+        ))
+    }
+
+    /// Opens a new TLS session to ``remote_address``, with the same configuration and server name.
+    fn connect<A: ToSocketAddrs>(&mut self, remote_address: A) -> io::Result<()> {
+        *self = Self::connect_to(
+            remote_address,
+            self.server_name.clone(),
+            self.config.clone(),
+        )?;
+        Ok(())
+    }
+
+    fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.stream.sock.local_addr()
+    }
+
+    fn peer_addr(&self) -> io::Result<SocketAddr> {
+        self.stream.sock.peer_addr()
+    }
+}
@@ -0,0 +1,73 @@
+#![cfg(all(
+    feature = "testing",
+    feature = "client",
+    feature = "server",
+    feature = "udp"
+))]
+
+use std::net::{IpAddr, Ipv4Addr};
+
+use osc::{
+    acl::{AccessList, IpRange},
+    namespace::Access,
+    server::OscServer,
+    state::StateStore,
+    testing::OscTestPair,
+    Arg, OscMessage,
+};
+
+/// Starts a server recording state, with a route at ``/level`` configured by ``configure``, and
+/// returns it with its state.
+fn recording(configure: impl FnOnce(OscServer) -> OscServer) -> (OscTestPair, StateStore) {
+    let mut state = None;
+    let pair = OscTestPair::start(|server| {
+        let server = configure(server.with_state()).add_route("/level", |_, _| Some(vec![]))?;
+        state = Some(server.state());
+        Ok(server)
+    })
+    .unwrap();
+    (pair, state.unwrap())
+}
+
+#[test]
+fn permitted_writes_are_recorded() {
+    let (mut pair, state) = recording(|server| server);
+    pair.client()
+        .call(&OscMessage::new("/level", vec![Arg::Float(0.5)]))
+        .unwrap();
+    assert_eq!(state.addresses(), vec!["/level".to_string()]);
+}
+
+#[test]
+fn read_only_routes_refuse_writes() {
+    let (mut pair, state) = recording(|server| {
+        server
+            .with_access_level("/level", Access::ReadOnly)
+            .unwrap()
+    });
+    let reply = pair
+        .client()
+        .call_expecting(&OscMessage::new("/level", vec![Arg::Float(0.5)]), "/error")
+        .unwrap();
+    assert_eq!(
+        String::try_from(reply.args[1].clone()).unwrap(),
+        "Access to /level denied"
+    );
+    assert!(state.get("/level").is_none());
+}
+
+#[test]
+fn writes_from_other_senders_are_refused() {
+    let operator = IpRange::from(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)));
+    let (mut pair, state) =
+        recording(|server| server.with_write_access(AccessList::new().allow(operator)));
+    let reply = pair
+        .client()
+        .call_expecting(&OscMessage::new("/level", vec![Arg::Float(0.5)]), "/error")
+        .unwrap();
+    assert_eq!(
+        String::try_from(reply.args[1].clone()).unwrap(),
+        "Access to /level denied"
+    );
+    assert!(state.get("/level").is_none());
+}
@@ -0,0 +1,70 @@
+#![cfg(feature = "chunking")]
+
+use std::{thread, time::Duration};
+
+use osc::{
+    chunk::{split, ChunkAssembler},
+    errors::Error,
+};
+
+#[test]
+fn chunks_are_reassembled() {
+    let blob: Vec<u8> = (0..=255).collect();
+    let mut assembler = ChunkAssembler::new(Duration::from_secs(1));
+    let mut chunks = split("/firmware", 1, &blob, 64).unwrap();
+    chunks.reverse();
+    let last = chunks.pop().unwrap();
+    for chunk in &chunks {
+        assert!(assembler.push(chunk).unwrap().is_none());
+    }
+    assert_eq!(
+        assembler.push(&last).unwrap(),
+        Some(("/firmware".to_string(), blob))
+    );
+    assert_eq!(assembler.pending(), 0);
+}
+
+#[test]
+fn transfers_beyond_the_maximum_length_are_dropped() {
+    let mut assembler = ChunkAssembler::new(Duration::from_secs(1)).with_max_len(100);
+    let chunks = split("/firmware", 1, &[0; 256], 64).unwrap();
+    let error = chunks
+        .iter()
+        .find_map(|chunk| assembler.push(chunk).err())
+        .unwrap();
+    assert!(matches!(error, Error::TransferTooLarge { limit: 100, .. }));
+    assert!(!error.is_parse());
+    assert_eq!(assembler.pending(), 0);
+}
+
+#[test]
+fn stale_transfers_make_room_for_new_ones() {
+    let mut assembler = ChunkAssembler::new(Duration::from_secs(1)).with_max_transfers(2);
+    let transfers: Vec<_> = (0..3)
+        .map(|id| split("/firmware", id, &[0; 256], 64).unwrap())
+        .collect();
+    for chunks in &transfers {
+        assembler.push(&chunks[0]).unwrap();
+        thread::sleep(Duration::from_millis(1));
+    }
+    assert_eq!(assembler.pending(), 2);
+    // The first transfer was dropped, so its other chunks start it again rather than finish it
+    for chunk in &transfers[0][1..] {
+        assert!(assembler.push(chunk).unwrap().is_none());
+    }
+    for chunk in &transfers[2][1..] {
+        assembler.push(chunk).unwrap();
+    }
+    assert_eq!(assembler.pending(), 1);
+}
+
+#[test]
+fn idle_transfers_expire() {
+    let mut assembler = ChunkAssembler::new(Duration::from_millis(20));
+    let first = split("/firmware", 1, &[0; 256], 64).unwrap();
+    let second = split("/firmware", 2, &[0; 256], 64).unwrap();
+    assembler.push(&first[0]).unwrap();
+    thread::sleep(Duration::from_millis(40));
+    assembler.push(&second[0]).unwrap();
+    assert_eq!(assembler.pending(), 1);
+}
@@ -0,0 +1,37 @@
+#![cfg(all(feature = "client", feature = "udp"))]
+
+use std::{
+    io::ErrorKind,
+    net::UdpSocket,
+    time::{Duration, Instant},
+};
+
+use osc::{client::OscClient, errors::Error};
+
+fn client(read_timeout: Duration) -> OscClient<UdpSocket> {
+    OscClient::new("127.0.0.1:0", "127.0.0.1:9", 1024, Some(read_timeout)).unwrap()
+}
+
+#[test]
+fn try_recv_leaves_blocking_clients_blocking() {
+    let mut client = client(Duration::from_millis(200));
+    assert!(client.try_recv().unwrap().is_none());
+    assert_eq!(client.try_iter().unwrap().count(), 0);
+    let start = Instant::now();
+    assert!(client.recv().is_err());
+    assert!(start.elapsed() >= Duration::from_millis(150));
+}
+
+#[test]
+fn try_recv_leaves_nonblocking_clients_nonblocking() {
+    let mut client = client(Duration::from_secs(5));
+    client.set_nonblocking(true).unwrap();
+    assert!(client.try_recv().unwrap().is_none());
+    assert_eq!(client.try_iter().unwrap().count(), 0);
+    let start = Instant::now();
+    let Err(Error::Socket(error)) = client.recv() else {
+        panic!("received a message from nowhere");
+    };
+    assert_eq!(error.kind(), ErrorKind::WouldBlock);
+    assert!(start.elapsed() < Duration::from_secs(1));
+}
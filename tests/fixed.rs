@@ -0,0 +1,26 @@
+use osc::{
+    errors::Error,
+    fixed::{FixedArg, OscMessageFixed},
+};
+
+#[test]
+fn values_beyond_the_capacity_are_refused() {
+    assert!(matches!(
+        OscMessageFixed::<1, 8>::new("/too/long/address"),
+        Err(Error::CapacityExceeded {
+            size: 17,
+            capacity: 8
+        })
+    ));
+    let mut msg = OscMessageFixed::<1, 8>::new("/fader").unwrap();
+    msg.push(FixedArg::Float(0.5)).unwrap();
+    let error = msg.push(FixedArg::Float(0.5)).unwrap_err();
+    assert!(matches!(
+        error,
+        Error::CapacityExceeded {
+            size: 2,
+            capacity: 1
+        }
+    ));
+    assert!(!error.is_parse());
+}
@@ -0,0 +1,47 @@
+#![cfg(all(
+    feature = "testing",
+    feature = "client",
+    feature = "server",
+    feature = "udp"
+))]
+
+use osc::{testing::OscTestPair, OscMessage};
+
+fn panic_reply(pair: &mut OscTestPair, address: &str) -> String {
+    let reply = pair
+        .client()
+        .call_expecting(&OscMessage::new(address, vec![]), "/error")
+        .unwrap();
+    String::try_from(reply.args[1].clone()).unwrap()
+}
+
+#[test]
+fn route_panics_are_reported() {
+    let mut pair = OscTestPair::start(|server| {
+        server
+            .add_route("/boom", |_, _| -> Option<Vec<osc::Arg>> { panic!("boom") })?
+            .add_route("/ping", |_, _| Some(vec![]))
+    })
+    .unwrap();
+    assert_eq!(panic_reply(&mut pair, "/boom"), "Handler panicked: boom");
+    pair.client()
+        .call(&OscMessage::new("/ping", vec![]))
+        .unwrap();
+}
+
+#[test]
+fn default_handler_panics_are_reported() {
+    let mut pair = OscTestPair::start(|mut server| {
+        server.set_default_handler(|_| -> Vec<OscMessage> { panic!("unknown") });
+        Ok(server)
+    })
+    .unwrap();
+    assert_eq!(
+        panic_reply(&mut pair, "/nowhere"),
+        "Handler panicked: unknown"
+    );
+    assert_eq!(
+        panic_reply(&mut pair, "/nowhere"),
+        "Handler panicked: unknown"
+    );
+}
@@ -0,0 +1,48 @@
+use osc::{errors::Error, parse::ParseOptions, Arg, OscMessage, OscPacket};
+
+fn parse(args: Vec<Arg>, options: &ParseOptions) -> Error {
+    let bytes = OscMessage::new("/limits", args).build().unwrap();
+    let Err(error) = OscPacket::parse_bytes_with(&bytes, options) else {
+        panic!("parsed despite exceeding a limit");
+    };
+    assert!(matches!(error, Error::Parse(_)));
+    assert!(error.is_parse());
+    error
+}
+
+#[test]
+fn limit_errors_are_wrapped_with_where_they_occurred() {
+    let options = ParseOptions {
+        max_args: Some(3),
+        max_string_len: Some(8),
+        max_blob_len: Some(16),
+        max_depth: Some(1),
+        ..ParseOptions::default()
+    };
+    let error = parse(vec![Arg::Str("x".repeat(32))], &options);
+    assert!(matches!(
+        error.cause(),
+        Error::StringTooLong {
+            length: 32,
+            limit: 8
+        }
+    ));
+    let error = parse(vec![Arg::from(vec![0_u8; 64])], &options);
+    assert!(matches!(
+        error.cause(),
+        Error::BlobTooLarge {
+            size: 64,
+            limit: 16
+        }
+    ));
+    let error = parse(vec![Arg::Int(1); 4], &options);
+    assert!(matches!(
+        error.cause(),
+        Error::TooManyArgs { count: 4, limit: 3 }
+    ));
+    let error = parse(
+        vec![Arg::Array(vec![Arg::Array(vec![Arg::Int(1)])])],
+        &options,
+    );
+    assert!(matches!(error.cause(), Error::NestingTooDeep { limit: 1 }));
+}
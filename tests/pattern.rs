@@ -0,0 +1,39 @@
+use osc::{address::OscAddress, pattern::OscPattern, router::Router};
+
+fn matches(pattern: &str, address: &str) -> bool {
+    OscPattern::new(pattern)
+        .unwrap()
+        .matches(&OscAddress::new(address).unwrap())
+}
+
+#[test]
+fn wildcards_match() {
+    assert!(matches("/ch/*/mix", "/ch/01/mix"));
+    assert!(matches("/ch/0?/mix", "/ch/01/mix"));
+    assert!(matches("/ch/[0-1][!2]/mix", "/ch/01/mix"));
+    assert!(matches("/ch/{01,02}/mute", "/ch/02/mute"));
+    assert!(matches("/a*b*c", "/abbbc"));
+    assert!(!matches("/ch/*/mix", "/ch/01/mix/on"));
+    assert!(!matches("/ch/[2-9]1", "/ch/01"));
+    assert!(!matches("/ch/{01,02", "/ch/01"));
+    assert!(!matches("/a*b", "/aaac"));
+}
+
+#[test]
+fn many_stars_match_quickly() {
+    // Exponential with a backtracking matcher
+    let pattern = format!("/{}b", "*a".repeat(30));
+    let address = format!("/{}", "a".repeat(36));
+    assert!(!matches(&pattern, &address));
+    assert!(matches(&pattern, &format!("{address}b")));
+}
+
+#[test]
+fn incoming_patterns_are_capped() {
+    let router = Router::new().add_route("/a", ()).unwrap();
+    assert_eq!(router.matching("/{a,b}").count(), 1);
+    assert_eq!(router.matching(&format!("/{}", "*".repeat(32))).count(), 1);
+    assert_eq!(router.matching(&format!("/{}", "*".repeat(33))).count(), 0);
+    let alternatives = vec!["a"; 70].join(",");
+    assert_eq!(router.matching(&format!("/{{{alternatives}}}")).count(), 0);
+}
@@ -0,0 +1,65 @@
+#![cfg(all(
+    feature = "testing",
+    feature = "client",
+    feature = "server",
+    feature = "udp"
+))]
+
+use std::{thread, time::Duration};
+
+use osc::{
+    address::OscAddress, client::OscClient, server::OscServer, session::Session,
+    testing::OscTestPair, Arg, OscMessage,
+};
+
+/// Starts a server whose ``/count`` route replies with how many messages the sender's session
+/// has seen.
+fn counting(configure: impl FnOnce(OscServer) -> OscServer) -> OscTestPair {
+    OscTestPair::start(|server| {
+        configure(server).add_session_route(
+            "/count",
+            |_: &OscAddress, _: &OscMessage, session: &mut Session| {
+                let count = session.get::<i32>().copied().unwrap_or(0) + 1;
+                session.insert(count);
+                Some(vec![Arg::Int(count)])
+            },
+        )
+    })
+    .unwrap()
+}
+
+fn count(client: &mut OscClient<std::net::UdpSocket>) -> i32 {
+    let reply = client.call(&OscMessage::new("/count", vec![])).unwrap();
+    i32::try_from(reply.args[0].clone()).unwrap()
+}
+
+#[test]
+fn sessions_last_between_messages() {
+    let mut pair = counting(|server| server);
+    assert_eq!(count(pair.client()), 1);
+    assert_eq!(count(pair.client()), 2);
+}
+
+#[test]
+fn idle_sessions_expire() {
+    let mut pair = counting(|server| server.with_session_timeout(Duration::from_millis(50)));
+    assert_eq!(count(pair.client()), 1);
+    thread::sleep(Duration::from_millis(100));
+    assert_eq!(count(pair.client()), 1);
+}
+
+#[test]
+fn senders_beyond_the_maximum_get_fresh_sessions() {
+    let mut pair = counting(|server| server.with_max_sessions(1));
+    assert_eq!(count(pair.client()), 1);
+    let mut other = OscClient::new(
+        "127.0.0.1:0",
+        pair.server_addr(),
+        1024,
+        Some(Duration::from_secs(1)),
+    )
+    .unwrap();
+    assert_eq!(count(&mut other), 1);
+    assert_eq!(count(&mut other), 1);
+    assert_eq!(count(pair.client()), 2);
+}
@@ -0,0 +1,86 @@
+#![cfg(all(
+    feature = "socket2",
+    feature = "client",
+    feature = "server",
+    feature = "tcp",
+    feature = "udp"
+))]
+
+use std::{thread, time::Duration};
+
+use osc::{
+    client::OscClient,
+    server::{OscServer, TcpOscServer},
+    socket_builder::ConnectionBuilder,
+    sockets::Connection,
+    Arg, OscMessage,
+};
+
+const TIMEOUT: Option<Duration> = Some(Duration::from_secs(2));
+
+fn ping<C: Connection>(connection: C) {
+    let mut client = OscClient::from_connection(connection, 1024, TIMEOUT).unwrap();
+    let reply = client.call(&OscMessage::new("/ping", vec![])).unwrap();
+    assert_eq!(i32::try_from(reply.args[0].clone()).unwrap(), 1);
+}
+
+/// Starts a UDP server on a socket bound by ``builder``, pings it from a client bound to
+/// ``client_address``, then stops it.
+fn udp_round_trip(builder: &ConnectionBuilder, server_address: &str, client_address: &str) {
+    let mut server = OscServer::from_socket(builder.udp_listener(server_address).unwrap(), 1024)
+        .add_route("/ping", |_, _| Some(vec![Arg::Int(1)]))
+        .unwrap();
+    let port = server.local_addr().unwrap().port();
+    let handle = server.handle();
+    thread::scope(|scope| {
+        scope.spawn(|| server.start().unwrap());
+        let remote = (client_address, port);
+        ping(
+            ConnectionBuilder::new()
+                .udp((client_address, 0), remote)
+                .unwrap(),
+        );
+        handle.shutdown();
+    });
+}
+
+/// As ``udp_round_trip``, over TCP.
+fn tcp_round_trip(builder: &ConnectionBuilder, server_address: &str, client_address: &str) {
+    let server = TcpOscServer::from_listener(builder.tcp_listener(server_address).unwrap(), 1024)
+        .add_route("/ping", |_, _| Some(vec![Arg::Int(1)]))
+        .unwrap();
+    let port = server.local_addr().unwrap().port();
+    let handle = server.handle();
+    thread::scope(|scope| {
+        scope.spawn(|| server.start().unwrap());
+        let remote = (client_address, port);
+        ping(
+            ConnectionBuilder::new()
+                .framed_tcp((client_address, 0), remote)
+                .unwrap(),
+        );
+        handle.shutdown();
+    });
+}
+
+#[test]
+fn udp_over_ipv6_loopback() {
+    udp_round_trip(&ConnectionBuilder::new(), "[::1]:0", "::1");
+}
+
+#[test]
+fn tcp_over_ipv6_loopback() {
+    tcp_round_trip(&ConnectionBuilder::new(), "[::1]:0", "::1");
+}
+
+#[test]
+fn dual_stack_udp_accepts_ipv4_clients() {
+    let builder = ConnectionBuilder::new().with_only_v6(false);
+    udp_round_trip(&builder, "[::]:0", "127.0.0.1");
+}
+
+#[test]
+fn dual_stack_tcp_accepts_ipv4_clients() {
+    let builder = ConnectionBuilder::new().with_only_v6(false);
+    tcp_round_trip(&builder, "[::]:0", "127.0.0.1");
+}
@@ -0,0 +1,41 @@
+#![cfg(all(feature = "server", feature = "tcp"))]
+
+use std::{
+    io::{ErrorKind, Read},
+    net::TcpStream,
+    thread,
+    time::Duration,
+};
+
+use osc::server::TcpOscServer;
+
+#[test]
+fn clients_beyond_the_maximum_are_disconnected() {
+    let server = TcpOscServer::new("127.0.0.1:0", 1024)
+        .unwrap()
+        .with_max_connections(1);
+    let address = server.local_addr().unwrap();
+    let handle = server.handle();
+    thread::scope(|scope| {
+        scope.spawn(|| server.start().unwrap());
+        let mut first = TcpStream::connect(address).unwrap();
+        while server.connections().is_empty() {
+            thread::sleep(Duration::from_millis(10));
+        }
+        let mut second = TcpStream::connect(address).unwrap();
+        second
+            .set_read_timeout(Some(Duration::from_secs(2)))
+            .unwrap();
+        assert_eq!(second.read(&mut [0; 1]).unwrap(), 0);
+
+        first
+            .set_read_timeout(Some(Duration::from_millis(100)))
+            .unwrap();
+        let still_open = first.read(&mut [0; 1]).unwrap_err();
+        assert!(matches!(
+            still_open.kind(),
+            ErrorKind::WouldBlock | ErrorKind::TimedOut
+        ));
+        handle.shutdown();
+    });
+}
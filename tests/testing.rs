@@ -0,0 +1,69 @@
+#![cfg(all(
+    feature = "testing",
+    feature = "client",
+    feature = "server",
+    feature = "udp"
+))]
+
+use std::{net::UdpSocket, time::Duration};
+
+use osc::{
+    client::OscClient,
+    testing::{MockOscServer, OscTestPair},
+    Arg, OscMessage,
+};
+
+#[test]
+fn test_pairs_serve_routes_added_while_running() {
+    let mut pair =
+        OscTestPair::start(|server| server.add_route("/ping", |_, _| Some(vec![]))).unwrap();
+    pair.client()
+        .call(&OscMessage::new("/ping", vec![]))
+        .unwrap();
+    pair.handle()
+        .add_route("/echo", |_, message: &OscMessage| {
+            Some(message.args.to_vec())
+        })
+        .unwrap();
+    let reply = pair
+        .client()
+        .call(&OscMessage::new("/echo", vec![Arg::Int(7)]))
+        .unwrap();
+    assert_eq!(i32::try_from(reply.args[0].clone()).unwrap(), 7);
+    pair.stop().unwrap();
+}
+
+#[test]
+fn dropping_a_test_pair_frees_its_port() {
+    let pair = OscTestPair::start(Ok).unwrap();
+    let address = pair.server_addr();
+    drop(pair);
+    UdpSocket::bind(address).unwrap();
+}
+
+#[test]
+fn mock_servers_reply_and_record_requests() {
+    let mock = MockOscServer::start().unwrap();
+    mock.respond(
+        "/status",
+        [OscMessage::new("/status", vec![Arg::Str("ok".into())])],
+    )
+    .unwrap();
+    let mut client: OscClient<UdpSocket> = OscClient::new(
+        "127.0.0.1:0",
+        mock.local_addr(),
+        1024,
+        Some(Duration::from_secs(1)),
+    )
+    .unwrap();
+    let reply = client.call(&OscMessage::new("/status", vec![])).unwrap();
+    assert_eq!(String::try_from(reply.args[0].clone()).unwrap(), "ok");
+
+    client.send(&OscMessage::new("/unknown", vec![])).unwrap();
+    let received = mock.wait_received(2, Duration::from_secs(1));
+    let addresses: Vec<_> = received
+        .iter()
+        .map(|message| message.address.as_str())
+        .collect();
+    assert_eq!(addresses, ["/status", "/unknown"]);
+}
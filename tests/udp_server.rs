@@ -0,0 +1,36 @@
+#![cfg(all(feature = "server", feature = "udp"))]
+
+use std::{
+    net::UdpSocket,
+    sync::mpsc,
+    thread,
+    time::{Duration, Instant},
+};
+
+use osc::{
+    errors::Error,
+    server::{OscServer, ReplyTo},
+    OscMessage,
+};
+
+#[test]
+fn failing_workers_stop_the_server() {
+    // Replies can't be sent to an IPv6 address from an IPv4 socket
+    let mut server = OscServer::new("127.0.0.1:0", 1024)
+        .unwrap()
+        .add_route("/ping", |_, _| Some(vec![]))
+        .unwrap()
+        .with_reply_policy("/ping", ReplyTo::Fixed("[::1]:9".parse().unwrap()))
+        .unwrap();
+    let address = server.local_addr().unwrap();
+    let (done, stopped) = mpsc::channel();
+    thread::spawn(move || done.send(server.start_threaded(2)));
+
+    let started = Instant::now();
+    let client = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let ping = OscMessage::new("/ping", vec![]).build().unwrap();
+    client.send_to(&ping, address).unwrap();
+    let result = stopped.recv_timeout(Duration::from_secs(2)).unwrap();
+    assert!(matches!(result, Err(Error::Socket(_))));
+    assert!(started.elapsed() < Duration::from_secs(1));
+}